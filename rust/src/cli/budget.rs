@@ -0,0 +1,229 @@
+//! Budget command implementation
+//!
+//! Checks the monthly/weekly budgets from the settings against spend from the
+//! local logs and the recorded usage history, with month/week-end forecasts.
+
+use chrono::{NaiveDate, Utc};
+use clap::Args;
+
+use super::usage::{render_progress_bar, OutputFormat};
+use crate::core::{
+    budget_spend_since, evaluate_budgets, format_currency, BudgetScope, BudgetStatus, ProviderId, SpendHistory,
+    UsageHistoryStore, BUDGET_MOVING_AVERAGE_DAYS,
+};
+use crate::settings::Settings;
+
+/// Arguments for the budget command
+#[derive(Args, Debug, Default)]
+pub struct BudgetArgs {
+    /// Only show this provider's budgets and the total budgets
+    #[arg(short, long)]
+    pub provider: Option<String>,
+
+    /// Output format: text or json
+    #[arg(short, long, default_value = "text")]
+    pub format: OutputFormat,
+
+    /// Shorthand for --format json
+    #[arg(long)]
+    pub json: bool,
+
+    /// Disable ANSI colors in text output
+    #[arg(long = "no-color")]
+    pub no_color: bool,
+
+    /// Pretty-print JSON output
+    #[arg(long)]
+    pub pretty: bool,
+}
+
+/// Run the budget command
+pub async fn run(args: BudgetArgs) -> anyhow::Result<()> {
+    let format = if args.json { OutputFormat::Json } else { args.format };
+    let use_color = !args.no_color && is_terminal();
+    let provider = match args.provider.as_deref() {
+        Some(name) => Some(
+            ProviderId::from_cli_name(name)
+                .ok_or_else(|| anyhow::anyhow!("Unknown provider: '{}'. Use --help to see available providers.", name))?,
+        ),
+        None => None,
+    };
+
+    let settings = Settings::load();
+    for name in settings.budgets.unknown_providers() {
+        tracing::warn!("Ignoring budget for unknown provider '{}'", name);
+    }
+    let converter = settings.currency_converter();
+    let today = Utc::now().date_naive();
+
+    let statuses = if settings.budgets.is_empty() {
+        Vec::new()
+    } else {
+        let providers = settings.budgets.spend_providers(&settings.get_enabled_provider_ids());
+        let history = match UsageHistoryStore::open_default() {
+            Ok(store) => Some(store),
+            Err(e) => {
+                tracing::warn!("Usage history unavailable: {}", e);
+                None
+            }
+        };
+        let spend = tokio::task::spawn_blocking(move || {
+            SpendHistory::collect(&providers, history.as_ref(), &converter, budget_spend_since(today), today)
+        })
+        .await?;
+        evaluate_budgets(&settings.budgets, &spend, today)
+            .into_iter()
+            .filter(|status| {
+                provider.is_none_or(|id| status.scope == BudgetScope::Total || status.scope == BudgetScope::Provider(id))
+            })
+            .collect()
+    };
+
+    match format {
+        OutputFormat::Text => {
+            if settings.budgets.is_empty() {
+                print_setup_hint();
+            } else {
+                print_text(&statuses, &settings.budgets.alert_thresholds, today, use_color);
+            }
+        }
+        OutputFormat::Json => {
+            let payload = serde_json::json!({
+                "date": today,
+                "currency": "USD",
+                "alert_thresholds": settings.budgets.alert_thresholds,
+                "budgets": statuses
+                    .iter()
+                    .map(|status| budget_json(status, &settings.budgets.alert_thresholds))
+                    .collect::<Vec<_>>(),
+            });
+            let output = if args.pretty {
+                serde_json::to_string_pretty(&payload)?
+            } else {
+                serde_json::to_string(&payload)?
+            };
+            println!("{}", output);
+        }
+    }
+
+    Ok(())
+}
+
+/// One budget as JSON
+fn budget_json(status: &BudgetStatus, thresholds: &[f64]) -> serde_json::Value {
+    serde_json::json!({
+        "scope": status.scope.key(),
+        "period": status.period.as_str(),
+        "start": status.start,
+        "end": status.end,
+        "limit_usd": status.limit_usd,
+        "spent_usd": status.spent_usd,
+        "percent": status.percent(),
+        "forecast": status.forecast,
+        "alert_threshold": status.reached_threshold(thresholds),
+    })
+}
+
+/// Print each budget as a bar with spend and forecasts
+fn print_text(statuses: &[BudgetStatus], thresholds: &[f64], today: NaiveDate, use_color: bool) {
+    let title = format!("Budgets ({})", today);
+    if use_color {
+        println!("\x1b[1m{}\x1b[0m", title);
+    } else {
+        println!("{}", title);
+    }
+
+    if statuses.is_empty() {
+        println!("  No budgets for this provider");
+        return;
+    }
+
+    for status in statuses {
+        for line in budget_lines(status, thresholds, use_color) {
+            println!("{}", line);
+        }
+    }
+}
+
+/// Text lines for one budget
+fn budget_lines(status: &BudgetStatus, thresholds: &[f64], use_color: bool) -> Vec<String> {
+    let usd = |value: f64| format_currency(value, "USD");
+    let heading = format!("{} · {}", status.scope.display_name(), status.period.as_str());
+    let mut spent = format!("{} of {} ({:.0}%)", usd(status.spent_usd), usd(status.limit_usd), status.percent());
+    if use_color && status.reached_threshold(thresholds).is_some() {
+        let color = if status.percent() >= 100.0 { "\x1b[31m" } else { "\x1b[33m" };
+        spent = format!("{}{}\x1b[0m", color, spent);
+    }
+    vec![
+        format!(
+            "  {:<24} {}  {}",
+            heading,
+            render_progress_bar(status.percent().min(100.0), 20, use_color),
+            spent
+        ),
+        format!(
+            "  {:<24} Forecast {} (linear) · {} ({}-day average) by {}",
+            "",
+            usd(status.forecast.linear_usd),
+            usd(status.forecast.moving_average_usd),
+            BUDGET_MOVING_AVERAGE_DAYS,
+            status.end
+        ),
+    ]
+}
+
+/// Explain how to configure budgets
+fn print_setup_hint() {
+    println!("No budgets configured. Add them to settings.json, for example:");
+    println!();
+    println!(r#"  "budgets": {{"#);
+    println!(r#"    "total": {{ "monthly": 300 }},"#);
+    println!(r#"    "providers": {{ "claude": {{ "weekly": 40 }} }},"#);
+    println!(r#"    "alert_thresholds": [50, 80, 100]"#);
+    println!("  }}");
+    if let Some(path) = Settings::settings_path() {
+        println!();
+        println!("Settings file: {}", path.display());
+    }
+}
+
+fn is_terminal() -> bool {
+    use std::io::IsTerminal;
+    std::io::stdout().is_terminal()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{BudgetForecast, BudgetPeriod};
+
+    #[test]
+    fn test_budget_output() {
+        let status = BudgetStatus {
+            scope: BudgetScope::Provider(ProviderId::Claude),
+            period: BudgetPeriod::Weekly,
+            limit_usd: 40.0,
+            spent_usd: 34.0,
+            start: NaiveDate::from_ymd_opt(2026, 1, 12).unwrap(),
+            end: NaiveDate::from_ymd_opt(2026, 1, 18).unwrap(),
+            forecast: BudgetForecast {
+                linear_usd: 47.6,
+                moving_average_usd: 43.5,
+            },
+        };
+        let thresholds = [50.0, 80.0, 100.0];
+
+        let lines = budget_lines(&status, &thresholds, false);
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("  Claude · weekly"));
+        assert!(lines[0].ends_with("$34.00 of $40.00 (85%)"));
+        assert!(lines[1].contains("Forecast $47.60 (linear) · $43.50 (7-day average) by 2026-01-18"));
+
+        let json = budget_json(&status, &thresholds);
+        assert_eq!(json["scope"], "claude");
+        assert_eq!(json["period"], "weekly");
+        assert_eq!(json["end"], "2026-01-18");
+        assert_eq!(json["alert_threshold"], 80.0);
+        assert_eq!(json["forecast"]["linear_usd"], 47.6);
+    }
+}
@@ -1,23 +1,40 @@
 //! Cost command implementation
 //!
-//! Scans local JSONL logs to calculate token costs for Codex and Claude.
+//! Scans the agents' local session logs (Codex, Claude, Gemini CLI, OpenCode)
+//! to calculate token costs.
+//! Parsed usage is cached per file, so repeated runs only read new log lines.
+//! `codexbar cost blocks` rebuilds Claude's 5-hour session blocks, and
+//! `codexbar cost --follow` streams updates as the logs change, and
+//! `codexbar cost --analytics` reports how well the prompt cache is used.
 
-use clap::Args;
+use chrono::{DateTime, Duration, Utc};
+use clap::{Args, Subcommand};
+use std::collections::HashMap;
+use std::path::PathBuf;
 
+use super::history::{csv_escape, parse_since};
 use super::usage::{OutputFormat, ProviderSelection};
-use crate::core::ProviderId;
-use crate::cost_scanner::{CostScanner, CostSummary};
+use crate::core::{
+    build_session_blocks, max_completed_block_tokens, session_token_limit_from_env, CacheEfficiency, CostAnalytics, CostGroupBy,
+    CostSummary, CostUsageCache, CostUsageDayRange, CostUsageGroup, CostUsagePricing, CostWatcher,
+    CurrencyConverter, JsonlScanner, local_log_providers, local_log_scanner, PricingFile, ProviderId, SessionBlock,
+    SESSION_LIMIT_LOOKBACK_DAYS,
+};
+use crate::settings::Settings;
 
 /// Arguments for the cost command
 #[derive(Args, Debug, Default)]
 pub struct CostArgs {
+    #[command(subcommand)]
+    pub command: Option<CostCommand>,
+
     /// Provider to query (codex, claude, cursor, gemini, copilot, all, both)
     #[arg(short, long)]
     pub provider: Option<String>,
 
-    /// Output format: text or json
+    /// Output format: text, json or csv
     #[arg(short, long, default_value = "text")]
-    pub format: OutputFormat,
+    pub format: CostFormat,
 
     /// Shorthand for --format json
     #[arg(long)]
@@ -34,103 +51,450 @@ pub struct CostArgs {
     /// Number of days to scan (default: 30)
     #[arg(short, long, default_value = "30")]
     pub days: u32,
+
+    /// First day to report: a date (YYYY-MM-DD) or a duration ago like 7d or 2w (overrides --days)
+    #[arg(long)]
+    pub since: Option<String>,
+
+    /// Last day to report (inclusive): a date or a duration ago (default: today)
+    #[arg(long)]
+    pub until: Option<String>,
+
+    /// Group costs by project, model, day or session (repeat or comma-separate)
+    #[arg(long = "group-by", value_delimiter = ',')]
+    pub group_by: Vec<CostGroupBy>,
+
+    /// Currency to report costs in (default: reporting currency from settings)
+    #[arg(long)]
+    pub currency: Option<String>,
+
+    /// Pricing file to merge over the built-in prices (default: pricing.json in the config directory)
+    #[arg(long)]
+    pub pricing: Option<PathBuf>,
+
+    /// Keep running and print a line (or JSON object) whenever new usage is logged
+    #[arg(long)]
+    pub follow: bool,
+
+    /// Report cache efficiency per model and day: cache hit ratio, cache read/write
+    /// cost, output/input ratio and what caching saved
+    #[arg(long)]
+    pub analytics: bool,
 }
 
-/// Run the cost command
-pub async fn run(args: CostArgs) -> anyhow::Result<()> {
-    let format = if args.json {
-        OutputFormat::Json
+#[derive(Subcommand, Debug)]
+pub enum CostCommand {
+    /// Show Claude's 5-hour session blocks rebuilt from the local logs
+    Blocks(BlocksArgs),
+}
+
+/// Arguments for `cost blocks`
+#[derive(Args, Debug, Default)]
+pub struct BlocksArgs {
+    /// Number of days to show (default: 2)
+    #[arg(short, long, default_value = "2")]
+    pub days: u32,
+
+    /// Only show the active block
+    #[arg(long)]
+    pub active: bool,
+
+    /// Tokens per block for the usage percentage (default: CODEXBAR_CLAUDE_SESSION_TOKEN_LIMIT,
+    /// else the largest block of the last week)
+    #[arg(long = "token-limit")]
+    pub token_limit: Option<i64>,
+
+    /// Output format: text or json
+    #[arg(short, long, default_value = "text")]
+    pub format: OutputFormat,
+
+    /// Shorthand for --format json
+    #[arg(long)]
+    pub json: bool,
+
+    /// Disable ANSI colors in text output
+    #[arg(long = "no-color")]
+    pub no_color: bool,
+
+    /// Pretty-print JSON output
+    #[arg(long)]
+    pub pretty: bool,
+
+    /// Pricing file to merge over the built-in prices
+    #[arg(long)]
+    pub pricing: Option<PathBuf>,
+}
+
+/// Output format for the cost command
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CostFormat {
+    #[default]
+    Text,
+    Json,
+    Csv,
+}
+
+impl std::str::FromStr for CostFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(CostFormat::Text),
+            "json" => Ok(CostFormat::Json),
+            "csv" => Ok(CostFormat::Csv),
+            _ => Err(format!("Invalid format: {}. Use 'text', 'json' or 'csv'", s)),
+        }
+    }
+}
+
+/// Days covered by a report
+struct ReportPeriod {
+    range: CostUsageDayRange,
+    days: u32,
+    /// Heading suffix, e.g. "last 30 days" or "2026-01-01 to 2026-01-31"
+    label: String,
+}
+
+/// Resolve `--since`/`--until`/`--days` into a day range
+fn report_period(args: &CostArgs, now: DateTime<Utc>) -> anyhow::Result<ReportPeriod> {
+    let today = now.date_naive();
+    let until = match args.until.as_deref() {
+        Some(value) => parse_since(value, now)?.date_naive(),
+        None => today,
+    };
+    let since = match args.since.as_deref() {
+        Some(value) => parse_since(value, now)?.date_naive(),
+        None => until - Duration::days(args.days.max(1) as i64 - 1),
+    };
+    if since > until {
+        anyhow::bail!("--since ({}) is after --until ({})", since, until);
+    }
+
+    let days = (until - since).num_days() as u32 + 1;
+    let label = if args.since.is_none() && args.until.is_none() {
+        format!("last {} days", days)
     } else {
-        args.format
+        format!("{} to {}", since, until)
     };
+    Ok(ReportPeriod {
+        range: CostUsageDayRange::new(since, until),
+        days,
+        label,
+    })
+}
+
+/// Run the cost command
+pub async fn run(args: CostArgs) -> anyhow::Result<()> {
+    if let Some(CostCommand::Blocks(blocks_args)) = args.command {
+        return run_blocks(blocks_args).await;
+    }
+
+    let format = if args.json { CostFormat::Json } else { args.format };
 
     let providers = ProviderSelection::from_arg(args.provider.as_deref())?;
     let use_color = !args.no_color && is_terminal();
-    let scanner = CostScanner::new(args.days);
+    let period = report_period(&args, Utc::now())?;
+    if args.follow {
+        if format == CostFormat::Csv {
+            anyhow::bail!("--follow supports text and json output");
+        }
+        if period.range.until_key < CostUsageDayRange::day_key(Utc::now().date_naive()) {
+            anyhow::bail!("--follow needs a period that includes today");
+        }
+    }
+    if args.analytics && (args.follow || !args.group_by.is_empty()) {
+        anyhow::bail!("--analytics cannot be combined with --follow or --group-by");
+    }
+
+    if let Some(path) = &args.pricing {
+        CostUsagePricing::use_pricing_file(path)
+            .map_err(|e| anyhow::anyhow!("Failed to load pricing file {}: {}", path.display(), e))?;
+    }
+
+    let settings = Settings::load();
+    let reporting = args.currency.as_deref().unwrap_or(&settings.reporting_currency);
+    let converter = CurrencyConverter::new(reporting, &settings.exchange_rates);
+    if converter.rate(converter.reporting_currency()).is_none() {
+        anyhow::bail!(
+            "No exchange rate for '{}'. Add one to exchange_rates in settings.",
+            converter.reporting_currency()
+        );
+    }
 
     tracing::debug!(
-        "Running cost command: providers={:?}, format={:?}, days={}",
+        "Running cost command: providers={:?}, format={:?}, period={}, group_by={:?}",
         providers.as_list(),
         format,
-        args.days
+        period.label,
+        args.group_by
     );
 
-    // Collect cost data for requested providers
-    let mut results: Vec<CostResult> = Vec::new();
+    let results = cost_results(&providers.as_list(), &period.range, &args.group_by, args.analytics);
 
-    for provider in providers.as_list() {
-        match provider {
-            ProviderId::Codex => {
-                let summary = scanner.scan_codex();
-                results.push(CostResult {
-                    provider: provider.cli_name().to_string(),
-                    display_name: provider.display_name().to_string(),
-                    summary,
-                    supported: true,
-                });
-            }
-            ProviderId::Claude => {
-                let summary = scanner.scan_claude();
-                results.push(CostResult {
+    warn_unpriced_models(&results, args.pricing.as_deref());
+
+    match format {
+        CostFormat::Text => {
+            print_text_output(&results, &args.group_by, &converter, use_color, &period);
+        }
+        CostFormat::Json => {
+            print_json_output(&results, &args.group_by, &converter, args.pretty, &period)?;
+        }
+        CostFormat::Csv if args.analytics => {
+            print!("{}", format_analytics_csv(&results, &converter));
+        }
+        CostFormat::Csv => {
+            print!("{}", format_csv(&results, &args.group_by, &converter));
+        }
+    }
+
+    if args.follow {
+        let local: Vec<ProviderId> = providers
+            .as_list()
+            .into_iter()
+            .filter(|&p| local_log_scanner(p).is_some())
+            .collect();
+        if local.is_empty() {
+            anyhow::bail!("--follow needs a provider with local logs ({})", local_log_provider_names());
+        }
+        return tokio::task::spawn_blocking(move || follow(&args, &local, &converter, format, use_color)).await?;
+    }
+
+    Ok(())
+}
+
+/// Cost of each provider over `range` from its local logs; providers without
+/// local logs are marked unsupported
+pub fn cost_results(
+    providers: &[ProviderId],
+    range: &CostUsageDayRange,
+    group_by: &[CostGroupBy],
+    analytics: bool,
+) -> Vec<CostResult> {
+    providers
+        .iter()
+        .map(|&provider| {
+            if local_log_scanner(provider).is_some() {
+                let cache = JsonlScanner::load_and_refresh(provider, range, None);
+                CostResult {
                     provider: provider.cli_name().to_string(),
                     display_name: provider.display_name().to_string(),
-                    summary,
+                    summary: cache.summary(range),
+                    groups: cache.grouped(range, group_by),
+                    unpriced_models: cache.unpriced_models(provider, range),
+                    analytics: analytics.then(|| CostAnalytics::from_cache(&cache, provider, range)),
                     supported: true,
-                });
-            }
-            _ => {
+                }
+            } else {
                 // Other providers don't have local logs to scan
-                results.push(CostResult {
+                CostResult {
                     provider: provider.cli_name().to_string(),
                     display_name: provider.display_name().to_string(),
                     summary: CostSummary::default(),
+                    groups: Vec::new(),
+                    unpriced_models: Vec::new(),
+                    analytics: None,
                     supported: false,
-                });
+                }
             }
-        }
+        })
+        .collect()
+}
+
+/// Today's cost in USD from a provider's local logs, or `None` for providers
+/// without logs on this machine
+pub async fn today_cost_usd(provider: ProviderId) -> Option<f64> {
+    if local_log_scanner(provider).is_none() || JsonlScanner::default_log_roots(provider).is_empty() {
+        return None;
     }
+    tokio::task::spawn_blocking(move || {
+        let today = CostUsageDayRange::last_days(1);
+        JsonlScanner::load_and_refresh(provider, &today, None).summary(&today).total_cost_usd
+    })
+    .await
+    .ok()
+}
 
-    match format {
-        OutputFormat::Text => {
-            print_text_output(&results, use_color, args.days);
+/// Providers with local logs, for messages
+fn local_log_provider_names() -> String {
+    local_log_providers()
+        .iter()
+        .map(|provider| provider.display_name())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Today's usage of a provider, to report what each update added
+#[derive(Debug, Clone, PartialEq)]
+struct FollowTotals {
+    day: String,
+    cost_usd: f64,
+    tokens: u64,
+}
+
+impl FollowTotals {
+    fn from_cache(cache: &CostUsageCache, now: DateTime<Utc>) -> Self {
+        let today = now.date_naive();
+        let summary = cache.summary(&CostUsageDayRange::new(today, today));
+        Self {
+            day: CostUsageDayRange::day_key(today),
+            cost_usd: summary.total_cost_usd,
+            tokens: summary.input_tokens + summary.output_tokens + summary.cached_tokens,
         }
-        OutputFormat::Json => {
-            print_json_output(&results, args.pretty, args.days)?;
+    }
+
+    /// Cost added since `previous` (all of today's cost after midnight)
+    fn delta_usd(&self, previous: Option<&FollowTotals>) -> f64 {
+        match previous.filter(|p| p.day == self.day) {
+            Some(previous) => self.cost_usd - previous.cost_usd,
+            None => self.cost_usd,
         }
     }
+}
 
-    Ok(())
+/// Watch the local logs and print each update until interrupted (`--follow`)
+fn follow(
+    args: &CostArgs,
+    providers: &[ProviderId],
+    converter: &CurrencyConverter,
+    format: CostFormat,
+    use_color: bool,
+) -> anyhow::Result<()> {
+    let period = report_period(args, Utc::now())?;
+    let mut watcher = CostWatcher::new(providers, &period.range)
+        .map_err(|e| anyhow::anyhow!("Failed to watch the session logs: {}", e))?;
+    if watcher.providers().is_empty() {
+        anyhow::bail!("No session log directories found to watch");
+    }
+
+    let now = Utc::now();
+    let mut last: HashMap<ProviderId, FollowTotals> = watcher
+        .providers()
+        .into_iter()
+        .filter_map(|p| watcher.cache(p).map(|cache| (p, FollowTotals::from_cache(cache, now))))
+        .collect();
+    if format == CostFormat::Text {
+        eprintln!("Watching for new usage (Ctrl+C to stop)...");
+    }
+
+    loop {
+        let updated = watcher.wait(std::time::Duration::from_secs(60));
+        if updated.is_empty() {
+            continue;
+        }
+        // "Last N days" moves with the clock
+        let now = Utc::now();
+        let period = report_period(args, now)?;
+        for provider in updated {
+            let Some(cache) = watcher.cache(provider) else {
+                continue;
+            };
+            let totals = FollowTotals::from_cache(cache, now);
+            let previous = last.insert(provider, totals.clone());
+            if previous.as_ref() == Some(&totals) {
+                continue;
+            }
+            let delta_usd = totals.delta_usd(previous.as_ref());
+            let period_usd = cache.summary(&period.range).total_cost_usd;
+            match format {
+                CostFormat::Json => println!(
+                    "{}",
+                    serde_json::json!({
+                        "timestamp": now.to_rfc3339(),
+                        "provider": provider.cli_name(),
+                        "currency": converter.reporting_currency(),
+                        "added": to_reporting(converter, delta_usd),
+                        "today": to_reporting(converter, totals.cost_usd),
+                        "today_tokens": totals.tokens,
+                        "period_total": to_reporting(converter, period_usd),
+                        "period": {
+                            "start": CostUsageDayRange::parse_day_key(&period.range.since_key).map(|d| d.to_string()),
+                            "end": CostUsageDayRange::parse_day_key(&period.range.until_key).map(|d| d.to_string())
+                        }
+                    })
+                ),
+                _ => {
+                    let added = format!("+{}", converter.format(to_reporting(converter, delta_usd)));
+                    let added = if use_color { format!("\x1b[32m{}\x1b[0m", added) } else { added };
+                    println!(
+                        "{}  {:<7} {}  today {} · {} {}",
+                        now.with_timezone(&chrono::Local).format("%H:%M:%S"),
+                        provider.display_name(),
+                        added,
+                        converter.format(to_reporting(converter, totals.cost_usd)),
+                        period.label,
+                        converter.format(to_reporting(converter, period_usd)),
+                    );
+                }
+            }
+        }
+    }
 }
 
 /// Cost result for a provider
-struct CostResult {
+pub struct CostResult {
     provider: String,
     display_name: String,
     summary: CostSummary,
+    /// Usage per `--group-by` key combination (a single total row without it)
+    groups: Vec<CostUsageGroup>,
+    /// Models used in the period that have no price
+    unpriced_models: Vec<String>,
+    /// Cache efficiency (`--analytics`)
+    analytics: Option<CostAnalytics>,
     supported: bool,
 }
 
+/// Tell the user (on stderr) which models were counted as $0
+fn warn_unpriced_models(results: &[CostResult], pricing: Option<&std::path::Path>) {
+    let pricing_path = pricing
+        .map(|path| path.to_path_buf())
+        .or_else(PricingFile::default_path)
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|| "pricing.json".to_string());
+    for result in results.iter().filter(|r| !r.unpriced_models.is_empty()) {
+        eprintln!(
+            "Warning: no price for {} model(s) {} (counted as $0). Add them to {}.",
+            result.display_name,
+            result.unpriced_models.join(", "),
+            pricing_path
+        );
+    }
+}
+
+/// Convert a USD amount from the local scanners into the reporting currency
+fn to_reporting(converter: &CurrencyConverter, usd: f64) -> f64 {
+    converter.convert(usd, "USD").unwrap_or(usd)
+}
+
 /// Print text output
-fn print_text_output(results: &[CostResult], use_color: bool, days: u32) {
+fn print_text_output(
+    results: &[CostResult],
+    group_by: &[CostGroupBy],
+    converter: &CurrencyConverter,
+    use_color: bool,
+    period: &ReportPeriod,
+) {
     for (i, result) in results.iter().enumerate() {
         if use_color {
-            println!("\x1b[1m{} Cost (last {} days)\x1b[0m", result.display_name, days);
+            println!("\x1b[1m{} Cost ({})\x1b[0m", result.display_name, period.label);
         } else {
-            println!("{} Cost (last {} days)", result.display_name, days);
+            println!("{} Cost ({})", result.display_name, period.label);
         }
 
         if !result.supported {
             println!("  Local cost scanning not available for this provider");
-            println!("  (Only Codex and Claude have local logs)");
+            println!("  (Local logs: {})", local_log_provider_names());
         } else if result.summary.sessions_count == 0 {
             println!("  No usage data found");
             println!("  Check that you have used {} locally", result.display_name);
         } else {
             // Total cost
+            let total = result.summary.format_total(converter);
             if use_color {
-                println!("  Total:    \x1b[32m{}\x1b[0m", result.summary.format_total());
+                println!("  Total:    \x1b[32m{}\x1b[0m", total);
             } else {
-                println!("  Total:    {}", result.summary.format_total());
+                println!("  Total:    {}", total);
             }
 
             // Token breakdown
@@ -144,13 +508,19 @@ fn print_text_output(results: &[CostResult], use_color: bool, days: u32) {
             // Sessions
             println!("  Sessions: {}", result.summary.sessions_count);
 
-            // Cost by model
-            if !result.summary.by_model.is_empty() {
+            if let Some(analytics) = &result.analytics {
+                println!();
+                print_analytics(analytics, converter, use_color);
+            } else if !group_by.is_empty() {
+                println!();
+                print_group_table(&result.groups, group_by, converter, use_color);
+            } else if !result.summary.by_model.is_empty() {
+                // Cost by model
                 println!("  By model:");
                 let mut models: Vec<_> = result.summary.by_model.iter().collect();
                 models.sort_by(|a, b| b.1.partial_cmp(a.1).unwrap_or(std::cmp::Ordering::Equal));
                 for (model, cost) in models {
-                    println!("    {}: ${:.2}", model, cost);
+                    println!("    {}: {}", model, converter.format(to_reporting(converter, *cost)));
                 }
             }
         }
@@ -159,11 +529,149 @@ fn print_text_output(results: &[CostResult], use_color: bool, days: u32) {
             println!();
         }
     }
+
+    // Combined total when more than one provider has data
+    let with_data: Vec<&CostResult> = results
+        .iter()
+        .filter(|r| r.supported && r.summary.sessions_count > 0)
+        .collect();
+    if with_data.len() > 1 {
+        let total: f64 = with_data
+            .iter()
+            .map(|r| to_reporting(converter, r.summary.total_cost_usd))
+            .sum();
+        println!();
+        if use_color {
+            println!("\x1b[1mTotal (all providers):\x1b[0m {}", converter.format(total));
+        } else {
+            println!("Total (all providers): {}", converter.format(total));
+        }
+    }
+}
+
+/// Print grouped usage as an aligned table, most expensive first (or
+/// chronologically when grouped by day)
+fn print_group_table(
+    groups: &[CostUsageGroup],
+    group_by: &[CostGroupBy],
+    converter: &CurrencyConverter,
+    use_color: bool,
+) {
+    let mut rows: Vec<&CostUsageGroup> = groups.iter().collect();
+    if !group_by.contains(&CostGroupBy::Day) {
+        rows.sort_by(|a, b| {
+            b.tokens
+                .cost_usd
+                .partial_cmp(&a.tokens.cost_usd)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+    }
+
+    let mut header: Vec<String> = group_by.iter().map(|g| g.as_str().to_uppercase()).collect();
+    header.extend(["INPUT", "OUTPUT", "CACHED", "COST"].map(String::from));
+    let mut table: Vec<Vec<String>> = vec![header];
+    for group in rows {
+        let mut row = group.keys.clone();
+        row.push(format_number(group.tokens.input.max(0) as u64));
+        row.push(format_number(group.tokens.output.max(0) as u64));
+        row.push(format_number((group.tokens.cache_read + group.tokens.cache_creation).max(0) as u64));
+        row.push(converter.format(to_reporting(converter, group.tokens.cost_usd)));
+        table.push(row);
+    }
+    print_table(&table, group_by.len(), use_color);
+}
+
+/// Print rows aligned in columns: the first `key_columns` left-aligned, the
+/// rest right-aligned, with the first row as a (dimmed) header
+fn print_table(table: &[Vec<String>], key_columns: usize, use_color: bool) {
+    let columns = table[0].len();
+    let widths: Vec<usize> = (0..columns)
+        .map(|c| table.iter().map(|row| row[c].chars().count()).max().unwrap_or(0))
+        .collect();
+
+    for (i, row) in table.iter().enumerate() {
+        let cells: Vec<String> = row
+            .iter()
+            .enumerate()
+            .map(|(c, cell)| {
+                if c < key_columns {
+                    format!("{:<width$}", cell, width = widths[c])
+                } else {
+                    format!("{:>width$}", cell, width = widths[c])
+                }
+            })
+            .collect();
+        let line = cells.join("  ");
+        if i == 0 && use_color {
+            println!("  \x1b[2m{}\x1b[0m", line.trim_end());
+        } else {
+            println!("  {}", line.trim_end());
+        }
+    }
+}
+
+/// Print cache efficiency in total, per model and per day
+fn print_analytics(analytics: &CostAnalytics, converter: &CurrencyConverter, use_color: bool) {
+    let money = |usd: f64| converter.format(to_reporting(converter, usd));
+    println!(
+        "  Cache:    {} of input from cache, saved {} vs. uncached",
+        format_ratio(analytics.total.cache_hit_ratio()),
+        money(analytics.total.savings_usd())
+    );
+    for (dimension, rows) in [("MODEL", &analytics.by_model), ("DAY", &analytics.by_day)] {
+        println!();
+        let mut table = vec![[dimension, "HIT", "READ COST", "WRITE COST", "OUT/IN", "SAVED"].map(String::from).to_vec()];
+        for (key, efficiency) in rows {
+            table.push(vec![
+                key.clone(),
+                format_ratio(efficiency.cache_hit_ratio()),
+                money(efficiency.cache_read_usd),
+                money(efficiency.cache_write_usd),
+                efficiency
+                    .output_input_ratio()
+                    .map(|ratio| format!("{:.3}", ratio))
+                    .unwrap_or_else(|| "-".to_string()),
+                money(efficiency.savings_usd()),
+            ]);
+        }
+        print_table(&table, 1, use_color);
+    }
+}
+
+/// A 0-1 ratio as a whole percentage
+fn format_ratio(ratio: Option<f64>) -> String {
+    ratio
+        .map(|ratio| format!("{:.0}%", ratio * 100.0))
+        .unwrap_or_else(|| "-".to_string())
 }
 
 /// Print JSON output
-fn print_json_output(results: &[CostResult], pretty: bool, days: u32) -> anyhow::Result<()> {
-    let payloads: Vec<serde_json::Value> = results
+fn print_json_output(
+    results: &[CostResult],
+    group_by: &[CostGroupBy],
+    converter: &CurrencyConverter,
+    pretty: bool,
+    period: &ReportPeriod,
+) -> anyhow::Result<()> {
+    let payloads = cost_json(results, group_by, converter, period.days);
+    let output = if pretty {
+        serde_json::to_string_pretty(&payloads)?
+    } else {
+        serde_json::to_string(&payloads)?
+    };
+    println!("{}", output);
+
+    Ok(())
+}
+
+/// Cost results as in `cost --json`
+pub fn cost_json(
+    results: &[CostResult],
+    group_by: &[CostGroupBy],
+    converter: &CurrencyConverter,
+    days: u32,
+) -> Vec<serde_json::Value> {
+    results
         .iter()
         .map(|r| {
             if !r.supported {
@@ -173,13 +681,14 @@ fn print_json_output(results: &[CostResult], pretty: bool, days: u32) -> anyhow:
                     "error": "Local cost scanning not available for this provider"
                 })
             } else {
-                serde_json::json!({
+                let mut payload = serde_json::json!({
                     "provider": r.provider,
                     "supported": true,
                     "days_scanned": days,
                     "cost": {
                         "total_usd": r.summary.total_cost_usd,
-                        "currency": "USD"
+                        "total": to_reporting(converter, r.summary.total_cost_usd),
+                        "currency": converter.reporting_currency()
                     },
                     "tokens": {
                         "input": r.summary.input_tokens,
@@ -188,23 +697,346 @@ fn print_json_output(results: &[CostResult], pretty: bool, days: u32) -> anyhow:
                     },
                     "sessions_count": r.summary.sessions_count,
                     "by_model": r.summary.by_model,
+                    "unpriced_models": r.unpriced_models,
                     "period": {
                         "start": r.summary.period_start.map(|d| d.to_string()),
                         "end": r.summary.period_end.map(|d| d.to_string())
                     }
-                })
+                });
+                if !group_by.is_empty() {
+                    payload["group_by"] = serde_json::json!(group_by.iter().map(|g| g.as_str()).collect::<Vec<_>>());
+                    payload["groups"] = r
+                        .groups
+                        .iter()
+                        .map(|group| group_json(group, group_by, converter))
+                        .collect();
+                }
+                if let Some(analytics) = &r.analytics {
+                    let rows = |rows: &[(String, CacheEfficiency)], dimension: &str| -> Vec<serde_json::Value> {
+                        rows.iter()
+                            .map(|(key, efficiency)| {
+                                let mut value = efficiency_json(efficiency, converter);
+                                value[dimension] = serde_json::json!(key);
+                                value
+                            })
+                            .collect()
+                    };
+                    payload["analytics"] = serde_json::json!({
+                        "total": efficiency_json(&analytics.total, converter),
+                        "by_model": rows(&analytics.by_model, "model"),
+                        "by_day": rows(&analytics.by_day, "day"),
+                    });
+                }
+                payload
             }
         })
+        .collect()
+}
+
+/// One group as JSON: its keys by name plus tokens and cost
+fn group_json(group: &CostUsageGroup, group_by: &[CostGroupBy], converter: &CurrencyConverter) -> serde_json::Value {
+    let mut value = serde_json::json!({
+        "tokens": {
+            "input": group.tokens.input,
+            "output": group.tokens.output,
+            "cache_read": group.tokens.cache_read,
+            "cache_creation": group.tokens.cache_creation
+        },
+        "cost_usd": group.tokens.cost_usd,
+        "cost": to_reporting(converter, group.tokens.cost_usd)
+    });
+    for (dimension, key) in group_by.iter().zip(&group.keys) {
+        value[dimension.as_str()] = serde_json::json!(key);
+    }
+    value
+}
+
+/// Cache efficiency as JSON, costs in USD and the reporting currency
+fn efficiency_json(efficiency: &CacheEfficiency, converter: &CurrencyConverter) -> serde_json::Value {
+    serde_json::json!({
+        "tokens": {
+            "input": efficiency.tokens.input,
+            "output": efficiency.tokens.output,
+            "cache_read": efficiency.tokens.cache_read,
+            "cache_creation": efficiency.tokens.cache_creation
+        },
+        "cache_hit_ratio": efficiency.cache_hit_ratio(),
+        "output_input_ratio": efficiency.output_input_ratio(),
+        "cache_read_cost_usd": efficiency.cache_read_usd,
+        "cache_write_cost_usd": efficiency.cache_write_usd,
+        "uncached_cost_usd": efficiency.uncached_cost_usd,
+        "saved_usd": efficiency.savings_usd(),
+        "saved": to_reporting(converter, efficiency.savings_usd())
+    })
+}
+
+/// Format cache efficiency as CSV, one row per provider and model or day
+fn format_analytics_csv(results: &[CostResult], converter: &CurrencyConverter) -> String {
+    let mut out = [
+        "provider",
+        "dimension",
+        "key",
+        "input_tokens",
+        "output_tokens",
+        "cache_read_tokens",
+        "cache_creation_tokens",
+        "cache_hit_ratio",
+        "output_input_ratio",
+        "cache_read_cost_usd",
+        "cache_write_cost_usd",
+        "saved_usd",
+        "saved",
+        "currency",
+    ]
+    .join(",");
+    out.push('\n');
+
+    let ratio = |value: Option<f64>| value.map(|v| format!("{:.4}", v)).unwrap_or_default();
+    for result in results {
+        let Some(analytics) = &result.analytics else {
+            continue;
+        };
+        let rows = analytics
+            .by_model
+            .iter()
+            .map(|row| ("model", row))
+            .chain(analytics.by_day.iter().map(|row| ("day", row)));
+        for (dimension, (key, efficiency)) in rows {
+            let fields = [
+                result.provider.clone(),
+                dimension.to_string(),
+                key.clone(),
+                efficiency.tokens.input.to_string(),
+                efficiency.tokens.output.to_string(),
+                efficiency.tokens.cache_read.to_string(),
+                efficiency.tokens.cache_creation.to_string(),
+                ratio(efficiency.cache_hit_ratio()),
+                ratio(efficiency.output_input_ratio()),
+                format!("{:.6}", efficiency.cache_read_usd),
+                format!("{:.6}", efficiency.cache_write_usd),
+                format!("{:.6}", efficiency.savings_usd()),
+                format!("{:.6}", to_reporting(converter, efficiency.savings_usd())),
+                converter.reporting_currency().to_string(),
+            ];
+            let row: Vec<String> = fields.iter().map(|f| csv_escape(f)).collect();
+            out.push_str(&row.join(","));
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
+/// Format grouped usage as CSV, one row per provider and group
+fn format_csv(results: &[CostResult], group_by: &[CostGroupBy], converter: &CurrencyConverter) -> String {
+    let mut header = vec!["provider"];
+    header.extend(group_by.iter().map(|g| g.as_str()));
+    header.extend([
+        "input_tokens",
+        "output_tokens",
+        "cache_read_tokens",
+        "cache_creation_tokens",
+        "cost_usd",
+        "cost",
+        "currency",
+    ]);
+    let mut out = header.join(",");
+    out.push('\n');
+
+    for result in results.iter().filter(|r| r.supported) {
+        for group in &result.groups {
+            let mut fields = vec![result.provider.clone()];
+            fields.extend(group.keys.iter().cloned());
+            fields.extend([
+                group.tokens.input.to_string(),
+                group.tokens.output.to_string(),
+                group.tokens.cache_read.to_string(),
+                group.tokens.cache_creation.to_string(),
+                format!("{:.6}", group.tokens.cost_usd),
+                format!("{:.6}", to_reporting(converter, group.tokens.cost_usd)),
+                converter.reporting_currency().to_string(),
+            ]);
+            let row: Vec<String> = fields.iter().map(|f| csv_escape(f)).collect();
+            out.push_str(&row.join(","));
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
+/// Run `cost blocks`
+async fn run_blocks(args: BlocksArgs) -> anyhow::Result<()> {
+    let format = if args.json { OutputFormat::Json } else { args.format };
+    let use_color = !args.no_color && is_terminal();
+
+    if let Some(path) = &args.pricing {
+        CostUsagePricing::use_pricing_file(path)
+            .map_err(|e| anyhow::anyhow!("Failed to load pricing file {}: {}", path.display(), e))?;
+    }
+
+    let settings = Settings::load();
+    let converter = CurrencyConverter::new(&settings.reporting_currency, &settings.exchange_rates);
+
+    // Scan at least a week back so the limit estimate has history to go on
+    let now = Utc::now();
+    let lookback = (args.days.max(1) as i64).max(SESSION_LIMIT_LOOKBACK_DAYS);
+    let since = now - Duration::days(lookback);
+    let entries = JsonlScanner::claude_usage_entries(&JsonlScanner::default_claude_projects_roots(), since);
+    let all_blocks = build_session_blocks(&entries, now);
+
+    let limit = args
+        .token_limit
+        .filter(|limit| *limit > 0)
+        .or_else(session_token_limit_from_env)
+        .or_else(|| max_completed_block_tokens(&all_blocks));
+
+    let shown_since = now - Duration::days(args.days.max(1) as i64);
+    let blocks: Vec<&SessionBlock> = all_blocks
+        .iter()
+        .filter(|block| block.end > shown_since)
+        .filter(|block| !args.active || block.is_active)
         .collect();
 
-    let output = if pretty {
-        serde_json::to_string_pretty(&payloads)?
+    match format {
+        OutputFormat::Text => print_blocks_text(&blocks, limit, &converter, use_color, args.days, now),
+        OutputFormat::Json => {
+            let payload = serde_json::json!({
+                "provider": "claude",
+                "token_limit": limit,
+                "blocks": blocks.iter().map(|block| block_json(block, limit, now)).collect::<Vec<_>>(),
+            });
+            let output = if args.pretty {
+                serde_json::to_string_pretty(&payload)?
+            } else {
+                serde_json::to_string(&payload)?
+            };
+            println!("{}", output);
+        }
+    }
+
+    Ok(())
+}
+
+/// One session block as JSON
+fn block_json(block: &SessionBlock, limit: Option<i64>, now: DateTime<Utc>) -> serde_json::Value {
+    serde_json::json!({
+        "start": block.start,
+        "end": block.end,
+        "first_entry": block.first_entry,
+        "last_entry": block.last_entry,
+        "is_active": block.is_active,
+        "entries": block.entries,
+        "tokens": {
+            "input": block.tokens.input,
+            "output": block.tokens.output,
+            "cache_read": block.tokens.cache_read,
+            "cache_creation": block.tokens.cache_creation,
+            "total": block.total_tokens()
+        },
+        "cost_usd": block.tokens.cost_usd,
+        "models": block.models,
+        "used_percent": limit.map(|limit| block_percent(block.total_tokens(), limit)),
+        "burn_rate": block.burn_rate(),
+        "projection": block.projection(now)
+    })
+}
+
+/// Share of the block limit, in percent
+fn block_percent(tokens: i64, limit: i64) -> f64 {
+    tokens as f64 / limit.max(1) as f64 * 100.0
+}
+
+/// Print session blocks as a table followed by details of the active block
+fn print_blocks_text(
+    blocks: &[&SessionBlock],
+    limit: Option<i64>,
+    converter: &CurrencyConverter,
+    use_color: bool,
+    days: u32,
+    now: DateTime<Utc>,
+) {
+    let title = format!("Claude Session Blocks (last {} days)", days);
+    if use_color {
+        println!("\x1b[1m{}\x1b[0m", title);
     } else {
-        serde_json::to_string(&payloads)?
+        println!("{}", title);
+    }
+
+    if blocks.is_empty() {
+        println!("  No Claude usage found");
+        return;
+    }
+
+    let local = |ts: DateTime<Utc>| ts.with_timezone(&chrono::Local);
+    println!(
+        "  {:<16}  {:<5}  {:>12}  {:>10}  {:>6}  MODELS",
+        "START", "END", "TOKENS", "COST", "LIMIT"
+    );
+    for block in blocks {
+        let percent = limit
+            .map(|limit| format!("{:.0}%", block_percent(block.total_tokens(), limit)))
+            .unwrap_or_else(|| "-".to_string());
+        let line = format!(
+            "  {:<16}  {:<5}  {:>12}  {:>10}  {:>6}  {}",
+            local(block.start).format("%Y-%m-%d %H:%M"),
+            local(block.end).format("%H:%M"),
+            format_number(block.total_tokens().max(0) as u64),
+            converter.format(to_reporting(converter, block.tokens.cost_usd)),
+            percent,
+            block.models.join(", ")
+        );
+        if block.is_active && use_color {
+            println!("\x1b[32m{}\x1b[0m", line);
+        } else {
+            println!("{}", line);
+        }
+    }
+
+    let Some(active) = blocks.iter().find(|block| block.is_active) else {
+        println!();
+        println!("  No active block");
+        return;
     };
-    println!("{}", output);
 
-    Ok(())
+    let remaining = (active.end - now).num_minutes().max(0);
+    println!();
+    println!(
+        "  Active block: {} - {} ({}h {}m left)",
+        local(active.start).format("%H:%M"),
+        local(active.end).format("%H:%M"),
+        remaining / 60,
+        remaining % 60
+    );
+    match limit {
+        Some(limit) => println!(
+            "  Tokens:       {} of {} ({:.0}%)",
+            format_number(active.total_tokens().max(0) as u64),
+            format_number(limit.max(0) as u64),
+            block_percent(active.total_tokens(), limit)
+        ),
+        None => println!("  Tokens:       {}", format_number(active.total_tokens().max(0) as u64)),
+    }
+    println!("  Cost:         {}", converter.format(to_reporting(converter, active.tokens.cost_usd)));
+    if let Some(rate) = active.burn_rate() {
+        println!(
+            "  Burn rate:    {} tokens/min, {}/h",
+            format_number(rate.tokens_per_minute.round() as u64),
+            converter.format(to_reporting(converter, rate.cost_per_hour_usd))
+        );
+    }
+    if let Some(projection) = active.projection(now) {
+        let percent = limit
+            .map(|limit| format!(" ({:.0}%)", block_percent(projection.total_tokens, limit)))
+            .unwrap_or_default();
+        println!(
+            "  Projected:    {} tokens{}, {} by {}",
+            format_number(projection.total_tokens.max(0) as u64),
+            percent,
+            converter.format(to_reporting(converter, projection.cost_usd)),
+            local(active.end).format("%H:%M")
+        );
+    }
 }
 
 /// Format a number with commas
@@ -226,3 +1058,120 @@ fn is_terminal() -> bool {
     use std::io::IsTerminal;
     std::io::stdout().is_terminal()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::CostUsageTokens;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_follow_totals_delta() {
+        let totals = |day: &str, cost_usd: f64| FollowTotals {
+            day: day.to_string(),
+            cost_usd,
+            tokens: 0,
+        };
+        let current = totals("2026-01-15", 1.5);
+        assert_eq!(current.delta_usd(None), 1.5);
+        assert!((current.delta_usd(Some(&totals("2026-01-15", 1.25))) - 0.25).abs() < 1e-9);
+        // After midnight everything logged today is new
+        assert_eq!(current.delta_usd(Some(&totals("2026-01-14", 9.0))), 1.5);
+    }
+
+    #[test]
+    fn test_report_period() {
+        let now = Utc.with_ymd_and_hms(2026, 1, 31, 12, 0, 0).unwrap();
+        let args = |since: Option<&str>, until: Option<&str>| CostArgs {
+            days: 30,
+            since: since.map(str::to_string),
+            until: until.map(str::to_string),
+            ..CostArgs::default()
+        };
+
+        let default = report_period(&args(None, None), now).unwrap();
+        assert_eq!(default.range.since_key, "2026-01-02");
+        assert_eq!(default.range.until_key, "2026-01-31");
+        assert_eq!(default.label, "last 30 days");
+
+        let explicit = report_period(&args(Some("2026-01-10"), Some("2026-01-20")), now).unwrap();
+        assert_eq!(explicit.days, 11);
+        assert_eq!(explicit.label, "2026-01-10 to 2026-01-20");
+
+        let relative = report_period(&args(Some("7d"), None), now).unwrap();
+        assert_eq!(relative.range.since_key, "2026-01-24");
+
+        assert!(report_period(&args(Some("2026-02-01"), None), now).is_err());
+    }
+
+    #[test]
+    fn test_format_csv_groups() {
+        let converter = CurrencyConverter::new("USD", &Default::default());
+        let result = CostResult {
+            provider: "claude".to_string(),
+            display_name: "Claude".to_string(),
+            summary: CostSummary::default(),
+            groups: vec![CostUsageGroup {
+                keys: vec!["-home-dev-client,a".to_string(), "2026-01-14".to_string()],
+                tokens: CostUsageTokens {
+                    input: 100,
+                    cache_read: 20,
+                    cache_creation: 10,
+                    output: 50,
+                    cost_usd: 0.25,
+                },
+            }],
+            unpriced_models: Vec::new(),
+            analytics: None,
+            supported: true,
+        };
+
+        let csv = format_csv(&[result], &[CostGroupBy::Project, CostGroupBy::Day], &converter);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "provider,project,day,input_tokens,output_tokens,cache_read_tokens,cache_creation_tokens,cost_usd,cost,currency"
+        );
+        assert_eq!(
+            lines[1],
+            "claude,\"-home-dev-client,a\",2026-01-14,100,50,20,10,0.250000,0.250000,USD"
+        );
+    }
+
+    #[test]
+    fn test_format_analytics_csv() {
+        let converter = CurrencyConverter::new("USD", &Default::default());
+        let tokens = CostUsageTokens {
+            input: 100_000,
+            cache_read: 900_000,
+            cache_creation: 0,
+            output: 10_000,
+            cost_usd: 0.0,
+        };
+        let efficiency = CacheEfficiency::from_tokens(ProviderId::Claude, "claude-sonnet-4-5", &tokens);
+        let result = CostResult {
+            provider: "claude".to_string(),
+            display_name: "Claude".to_string(),
+            summary: CostSummary::default(),
+            groups: Vec::new(),
+            unpriced_models: Vec::new(),
+            analytics: Some(CostAnalytics {
+                by_model: vec![("claude-sonnet-4-5".to_string(), efficiency)],
+                by_day: vec![("2026-01-14".to_string(), efficiency)],
+                total: efficiency,
+            }),
+            supported: true,
+        };
+
+        let csv = format_analytics_csv(&[result], &converter);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("provider,dimension,key,input_tokens"));
+        // 900K reads at $0.30 instead of $3 per million
+        assert_eq!(
+            lines[1],
+            "claude,model,claude-sonnet-4-5,100000,10000,900000,0,0.9000,0.0100,0.270000,0.000000,2.430000,2.430000,USD"
+        );
+        assert!(lines[2].starts_with("claude,day,2026-01-14,"));
+    }
+}
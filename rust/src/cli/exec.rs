@@ -0,0 +1,435 @@
+//! Exec command implementation
+//!
+//! Wraps an agent run: checks the provider's usage first and refuses, warns
+//! or asks when the most restrictive window is over a threshold, keeps the
+//! terminal title updated with fresh usage while the agent runs, and reports
+//! the usage change and the cost of the run from the local logs afterwards.
+//! Nothing but the title is written while the agent owns the terminal.
+
+use std::io::{IsTerminal, Write};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use clap::Args;
+
+use super::usage::{fetch_usage, provider_fetch_context};
+use crate::core::{
+    format_currency, local_log_scanner, CostSummary, CostUsageDayRange, FetchContext, JsonlScanner, NamedRateWindow,
+    ProviderId, TokenAccountStore, UsageSnapshot,
+};
+use crate::host::{CommandOptions, CommandRunner};
+use crate::settings::Settings;
+
+/// What to do when usage is over the threshold before the run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LimitAction {
+    /// Ask for confirmation (refuse without a terminal to ask on)
+    #[default]
+    Ask,
+    /// Print a warning and run anyway
+    Warn,
+    /// Do not run the command
+    Refuse,
+}
+
+impl std::str::FromStr for LimitAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ask" => Ok(LimitAction::Ask),
+            "warn" => Ok(LimitAction::Warn),
+            "refuse" => Ok(LimitAction::Refuse),
+            _ => Err(format!("Invalid action: {}. Use 'ask', 'warn' or 'refuse'", s)),
+        }
+    }
+}
+
+/// Arguments for the exec command
+#[derive(Args, Debug)]
+pub struct ExecArgs {
+    /// Provider whose usage to check (default: the command name, e.g. claude or codex)
+    #[arg(short, long)]
+    pub provider: Option<String>,
+
+    /// Percent used of the most restrictive window at which --on-limit applies
+    #[arg(short, long, default_value = "90")]
+    pub threshold: f64,
+
+    /// When over the threshold: ask, warn or refuse
+    #[arg(long = "on-limit", default_value = "ask")]
+    pub on_limit: LimitAction,
+
+    /// Seconds between usage checks while the command runs
+    #[arg(long, default_value = "120", value_parser = clap::value_parser!(u64).range(10..))]
+    pub poll: u64,
+
+    /// Do not show usage in the terminal title while the command runs
+    #[arg(long = "no-status-line")]
+    pub no_status_line: bool,
+
+    /// Web fetch timeout in seconds
+    #[arg(long = "web-timeout", default_value = "30")]
+    pub web_timeout: u64,
+
+    /// The command to run, after `--`
+    #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true, value_name = "COMMAND")]
+    pub command: Vec<String>,
+}
+
+/// Run the exec command, returning the command's exit code
+pub async fn run(args: ExecArgs) -> anyhow::Result<i32> {
+    let provider = resolve_provider(args.provider.as_deref(), &args.command)?;
+    let settings = Settings::load();
+    let accounts = TokenAccountStore::new().load().unwrap_or_default();
+    let account = accounts.get(&provider).and_then(|data| data.active_account());
+    let base_ctx = FetchContext {
+        web_timeout: args.web_timeout,
+        ..FetchContext::default()
+    };
+    let ctx = provider_fetch_context(provider, &settings, account, &base_ctx);
+    let name = provider.display_name();
+
+    // Check usage before starting
+    let before = match fetch_usage(provider, ctx.clone(), false).await.result {
+        Ok(result) => {
+            eprintln!("codexbar: {}", usage_summary(name, &result.usage));
+            if let Some(window) = over_threshold(&result.usage, args.threshold) {
+                let message = format!(
+                    "{} {} window is {:.0}% used (threshold {:.0}%)",
+                    name, window.label, window.window.used_percent, args.threshold
+                );
+                match args.on_limit {
+                    LimitAction::Warn => eprintln!("codexbar: warning: {}", message),
+                    LimitAction::Refuse => anyhow::bail!("{}; not starting", message),
+                    LimitAction::Ask => {
+                        if !confirm(&format!("codexbar: {}. Start anyway? [y/N] ", message)).await {
+                            anyhow::bail!("{}; not starting", message);
+                        }
+                    }
+                }
+            }
+            Some(result.usage)
+        }
+        Err(e) => {
+            eprintln!("codexbar: could not check {} usage: {}", name, e);
+            None
+        }
+    };
+
+    let cost_before = cost_summary(provider).await;
+    let started = Instant::now();
+
+    let options = CommandOptions {
+        extra_args: args.command[1..].to_vec(),
+        ..CommandOptions::default()
+    };
+    let mut child = CommandRunner::new()
+        .spawn_attached(&args.command[0], &options)
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+
+    // The agent handles Ctrl-C itself; keep this process alive for the report
+    let interrupts = tokio::spawn(async {
+        while tokio::signal::ctrl_c().await.is_ok() {}
+    });
+    let title = !args.no_status_line && std::io::stderr().is_terminal();
+    let terminal = TerminalGuard::take(title);
+    let warning = Arc::new(Mutex::new(None));
+    let poller = tokio::spawn(poll_usage(
+        provider,
+        ctx.clone(),
+        Duration::from_secs(args.poll),
+        args.threshold,
+        title,
+        Arc::clone(&warning),
+    ));
+
+    let status = tokio::task::spawn_blocking(move || child.wait()).await;
+    poller.abort();
+    interrupts.abort();
+    drop(terminal);
+    let status = status??;
+
+    // Report what the run used
+    let elapsed = started.elapsed();
+    eprintln!();
+    eprintln!("codexbar: {} ran for {}", args.command[0], format_elapsed(elapsed));
+    if let Some(signal) = killed_by_signal(&status) {
+        eprintln!("codexbar: {} was killed by signal {}", args.command[0], signal);
+    }
+    if let Some(message) = warning.lock().ok().and_then(|mut held| held.take()) {
+        eprintln!("codexbar: warning: {}", message);
+    }
+    if let Ok(result) = fetch_usage(provider, ctx, false).await.result {
+        for line in usage_delta_lines(before.as_ref(), &result.usage) {
+            eprintln!("codexbar: {}", line);
+        }
+    }
+    if let (Some(before), Some(after)) = (cost_before, cost_summary(provider).await) {
+        eprintln!("codexbar: {}", cost_delta_line(&before, &after));
+    }
+
+    Ok(exit_code(&status))
+}
+
+/// Terminal state held while the agent owns the screen: stderr logging is
+/// muted and the title saved, both undone on drop so an early return cannot
+/// leave them behind
+struct TerminalGuard {
+    title: bool,
+}
+
+impl TerminalGuard {
+    fn take(title: bool) -> Self {
+        if title {
+            // Save the current title so it can be restored afterwards
+            eprint!("\x1b[22;2t");
+        }
+        // Log lines would garble the agent's full-screen UI; warnings wait for the report
+        crate::logging::set_stderr_muted(true);
+        Self { title }
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        crate::logging::set_stderr_muted(false);
+        if self.title {
+            // Restore the title saved before the run
+            eprint!("\x1b[23;2t");
+        }
+    }
+}
+
+/// The signal that killed the command, if any
+#[cfg(unix)]
+fn killed_by_signal(status: &std::process::ExitStatus) -> Option<i32> {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal()
+}
+
+#[cfg(not(unix))]
+fn killed_by_signal(_status: &std::process::ExitStatus) -> Option<i32> {
+    None
+}
+
+/// Exit code to pass through: the command's own, or 128 + signal like a
+/// shell when it was killed
+fn exit_code(status: &std::process::ExitStatus) -> i32 {
+    match killed_by_signal(status) {
+        Some(signal) => 128 + signal,
+        None => status.code().unwrap_or(1),
+    }
+}
+
+/// The provider named by `--provider`, else the one the command is named after
+fn resolve_provider(arg: Option<&str>, command: &[String]) -> anyhow::Result<ProviderId> {
+    if let Some(name) = arg {
+        return ProviderId::from_cli_name(name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown provider: '{}'. Use --help to see available providers.", name));
+    }
+    command
+        .first()
+        .and_then(|program| std::path::Path::new(program).file_stem())
+        .and_then(|stem| ProviderId::from_cli_name(&stem.to_string_lossy().to_lowercase()))
+        .ok_or_else(|| anyhow::anyhow!("Pass --provider to say whose usage to check for this command"))
+}
+
+/// The most restrictive window when it is at or over `threshold` percent
+fn over_threshold(usage: &UsageSnapshot, threshold: f64) -> Option<&NamedRateWindow> {
+    let most = usage.most_restrictive();
+    if most.used_percent < threshold {
+        return None;
+    }
+    usage.windows.iter().find(|named| std::ptr::eq(&named.window, most))
+}
+
+/// Ask a yes/no question on the terminal; no terminal means no
+async fn confirm(prompt: &str) -> bool {
+    if !std::io::stdin().is_terminal() {
+        return false;
+    }
+    eprint!("{}", prompt);
+    let _ = std::io::stderr().flush();
+    tokio::task::spawn_blocking(|| {
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer).is_ok() && matches!(answer.trim(), "y" | "Y" | "yes")
+    })
+    .await
+    .unwrap_or(false)
+}
+
+/// Re-check usage every `interval`, showing it in the terminal title. The
+/// first threshold crossing is flagged in the title and held in `warning`
+/// for the report after the run.
+async fn poll_usage(
+    provider: ProviderId,
+    ctx: FetchContext,
+    interval: Duration,
+    threshold: f64,
+    title: bool,
+    warning: Arc<Mutex<Option<String>>>,
+) {
+    let name = provider.display_name();
+    loop {
+        tokio::time::sleep(interval).await;
+        let Ok(result) = fetch_usage(provider, ctx.clone(), false).await.result else {
+            continue;
+        };
+        let over = over_threshold(&result.usage, threshold);
+        if title {
+            eprint!("\x1b]2;{}\x07", title_text(name, &result.usage, over.is_some(), threshold));
+        }
+        if let (Some(window), Ok(mut held)) = (over, warning.lock()) {
+            held.get_or_insert_with(|| {
+                format!(
+                    "{} {} window reached {:.0}% used during the run (threshold {:.0}%)",
+                    name, window.label, window.window.used_percent, threshold
+                )
+            });
+        }
+    }
+}
+
+/// Terminal title while the agent runs, flagged when over the threshold
+fn title_text(name: &str, usage: &UsageSnapshot, over: bool, threshold: f64) -> String {
+    let summary = usage_summary(name, usage);
+    if over {
+        format!("⚠ {} (over {:.0}%)", summary, threshold)
+    } else {
+        summary
+    }
+}
+
+/// Today's cost summary from the provider's local logs, covering the days
+/// around now so a run past midnight is counted whole
+async fn cost_summary(provider: ProviderId) -> Option<CostSummary> {
+    local_log_scanner(provider)?;
+    tokio::task::spawn_blocking(move || {
+        let today = Utc::now().date_naive();
+        let range = CostUsageDayRange::new(today - chrono::Duration::days(1), today + chrono::Duration::days(1));
+        JsonlScanner::load_and_refresh(provider, &range, None).summary(&range)
+    })
+    .await
+    .ok()
+}
+
+/// One line of every window, e.g. "Claude: Session 31% (resets in 2h 14m) · Weekly 62%"
+fn usage_summary(name: &str, usage: &UsageSnapshot) -> String {
+    let windows: Vec<String> = usage
+        .windows
+        .iter()
+        .map(|named| match named.window.format_countdown() {
+            Some(countdown) => format!("{} {:.0}% (resets in {})", named.label, named.window.used_percent, countdown),
+            None => format!("{} {:.0}%", named.label, named.window.used_percent),
+        })
+        .collect();
+    format!("{}: {}", name, windows.join(" · "))
+}
+
+/// How each window moved during the run
+fn usage_delta_lines(before: Option<&UsageSnapshot>, after: &UsageSnapshot) -> Vec<String> {
+    after
+        .windows
+        .iter()
+        .map(|named| {
+            let now = named.window.used_percent;
+            let previous = before
+                .and_then(|usage| usage.window(&named.id))
+                .map(|previous| previous.window.used_percent);
+            match previous {
+                Some(previous) if now < previous => {
+                    format!("{:<10} {:.0}% → {:.0}% (window reset)", named.label, previous, now)
+                }
+                Some(previous) => format!("{:<10} {:.0}% → {:.0}% ({:+.0})", named.label, previous, now, now - previous),
+                None => format!("{:<10} {:.0}%", named.label, now),
+            }
+        })
+        .collect()
+}
+
+/// Tokens and cost logged between two summaries. Other sessions of the same
+/// agent running meanwhile are counted too.
+fn cost_delta_line(before: &CostSummary, after: &CostSummary) -> String {
+    format!(
+        "Cost {} ({} input, {} output, {} cached tokens)",
+        format_currency((after.total_cost_usd - before.total_cost_usd).max(0.0), "USD"),
+        after.input_tokens.saturating_sub(before.input_tokens),
+        after.output_tokens.saturating_sub(before.output_tokens),
+        after.cached_tokens.saturating_sub(before.cached_tokens),
+    )
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    match (secs / 3600, secs / 60 % 60, secs % 60) {
+        (0, 0, s) => format!("{}s", s),
+        (0, m, s) => format!("{}m {}s", m, s),
+        (h, m, _) => format!("{}h {}m", h, m),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::RateWindow;
+
+    #[test]
+    fn test_limits_and_deltas() {
+        let before = UsageSnapshot::new(RateWindow::new(31.0)).with_secondary(RateWindow::new(88.0));
+        assert!(over_threshold(&before, 90.0).is_none());
+        let window = over_threshold(&before, 85.0).unwrap();
+        assert_eq!(window.id, NamedRateWindow::SECONDARY_ID);
+
+        let after = UsageSnapshot::new(RateWindow::new(5.0)).with_secondary(RateWindow::new(91.0));
+        let lines = usage_delta_lines(Some(&before), &after);
+        assert_eq!(lines[0], format!("{:<10} 31% → 5% (window reset)", after.windows[0].label));
+        assert_eq!(lines[1], format!("{:<10} 88% → 91% (+3)", after.windows[1].label));
+        assert_eq!(usage_delta_lines(None, &after)[1], format!("{:<10} 91%", after.windows[1].label));
+
+        let before = CostSummary {
+            total_cost_usd: 1.0,
+            input_tokens: 100,
+            output_tokens: 10,
+            cached_tokens: 1000,
+            ..CostSummary::default()
+        };
+        let after = CostSummary {
+            total_cost_usd: 3.5,
+            input_tokens: 350,
+            output_tokens: 60,
+            cached_tokens: 9000,
+            ..CostSummary::default()
+        };
+        assert_eq!(cost_delta_line(&before, &after), "Cost $2.50 (250 input, 50 output, 8000 cached tokens)");
+    }
+
+    #[test]
+    fn test_title_text() {
+        let usage = UsageSnapshot::new(RateWindow::new(31.0)).with_secondary(RateWindow::new(92.0));
+        let summary = usage_summary("Claude", &usage);
+        assert_eq!(title_text("Claude", &usage, false, 90.0), summary);
+        assert_eq!(title_text("Claude", &usage, true, 90.0), format!("⚠ {} (over 90%)", summary));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_exit_code() {
+        use std::os::unix::process::ExitStatusExt;
+        assert_eq!(exit_code(&std::process::ExitStatus::from_raw(3 << 8)), 3);
+        let killed = std::process::ExitStatus::from_raw(9);
+        assert_eq!(killed_by_signal(&killed), Some(9));
+        assert_eq!(exit_code(&killed), 137);
+    }
+
+    #[test]
+    fn test_resolve_provider() {
+        let command = |program: &str| vec![program.to_string(), "--resume".to_string()];
+        assert_eq!(resolve_provider(None, &command("claude")).unwrap(), ProviderId::Claude);
+        assert_eq!(resolve_provider(None, &command("/usr/local/bin/codex")).unwrap(), ProviderId::Codex);
+        assert_eq!(resolve_provider(Some("gemini"), &command("npx")).unwrap(), ProviderId::Gemini);
+        assert!(resolve_provider(None, &command("npx")).is_err());
+        assert!(resolve_provider(Some("nope"), &command("claude")).is_err());
+    }
+}
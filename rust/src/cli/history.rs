@@ -0,0 +1,362 @@
+//! History command implementation
+//!
+//! Reads usage snapshots recorded by the tray app from the local history database.
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use clap::Args;
+
+use crate::core::{ProviderId, UsageHistoryEntry, UsageHistoryStore};
+
+/// Arguments for the history command
+#[derive(Args, Debug)]
+pub struct HistoryArgs {
+    /// Provider to show (claude, codex, ..., or all)
+    #[arg(short, long)]
+    pub provider: Option<String>,
+
+    /// How far back to look: a duration like 30m, 24h, 7d, 2w or a date (YYYY-MM-DD)
+    #[arg(short, long, default_value = "7d")]
+    pub since: String,
+
+    /// Output format: text, json or csv
+    #[arg(short, long, default_value = "text")]
+    pub format: HistoryFormat,
+
+    /// Shorthand for --format json
+    #[arg(long)]
+    pub json: bool,
+
+    /// Pretty-print JSON output
+    #[arg(long)]
+    pub pretty: bool,
+
+    /// Disable ANSI colors in text output
+    #[arg(long = "no-color")]
+    pub no_color: bool,
+}
+
+/// Output format for the history command
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HistoryFormat {
+    #[default]
+    Text,
+    Json,
+    Csv,
+}
+
+impl std::str::FromStr for HistoryFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(HistoryFormat::Text),
+            "json" => Ok(HistoryFormat::Json),
+            "csv" => Ok(HistoryFormat::Csv),
+            _ => Err(format!("Invalid format: {}. Use 'text', 'json' or 'csv'", s)),
+        }
+    }
+}
+
+/// Run the history command
+pub async fn run(args: HistoryArgs) -> anyhow::Result<()> {
+    let format = if args.json { HistoryFormat::Json } else { args.format };
+
+    let provider = match args.provider.as_deref().map(str::to_lowercase).as_deref() {
+        None | Some("all") => None,
+        Some(name) => Some(ProviderId::from_cli_name(name).ok_or_else(|| {
+            anyhow::anyhow!("Unknown provider: '{}'. Use --help to see available providers.", name)
+        })?),
+    };
+    let since = parse_since(&args.since, Utc::now())?;
+
+    let store = UsageHistoryStore::open_default()?;
+    let entries = store.query(provider, since)?;
+
+    tracing::debug!(
+        "Running history command: provider={:?}, since={}, entries={}",
+        provider,
+        since,
+        entries.len()
+    );
+
+    match format {
+        HistoryFormat::Text => {
+            let use_color = !args.no_color && is_terminal();
+            print_text_output(&entries, provider, since, use_color);
+        }
+        HistoryFormat::Json => {
+            let output = if args.pretty {
+                serde_json::to_string_pretty(&entries)?
+            } else {
+                serde_json::to_string(&entries)?
+            };
+            println!("{}", output);
+        }
+        HistoryFormat::Csv => {
+            print!("{}", format_csv(&entries));
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse a `--since` value into an absolute cutoff
+pub(super) fn parse_since(value: &str, now: DateTime<Utc>) -> anyhow::Result<DateTime<Utc>> {
+    let value = value.trim();
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        if let Some(dt) = date.and_hms_opt(0, 0, 0) {
+            return Ok(dt.and_utc());
+        }
+    }
+
+    let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid time value: '{}'. Use e.g. 24h, 7d or 2026-01-31", value))?;
+
+    let duration = match unit {
+        "m" | "min" => Duration::minutes(amount),
+        "h" => Duration::hours(amount),
+        "d" | "" => Duration::days(amount),
+        "w" => Duration::weeks(amount),
+        _ => anyhow::bail!("Invalid time unit: '{}'. Use m, h, d or w", unit),
+    };
+
+    Ok(now - duration)
+}
+
+/// Print text output, grouped by provider
+fn print_text_output(
+    entries: &[UsageHistoryEntry],
+    provider: Option<ProviderId>,
+    since: DateTime<Utc>,
+    use_color: bool,
+) {
+    let since_local = since.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M");
+
+    if entries.is_empty() {
+        let name = provider.map(|p| p.display_name()).unwrap_or("any provider");
+        println!("No usage history for {} since {}", name, since_local);
+        println!("  History is recorded while the tray app refreshes providers");
+        return;
+    }
+
+    let mut providers: Vec<ProviderId> = Vec::new();
+    for entry in entries {
+        if !providers.contains(&entry.provider) {
+            providers.push(entry.provider);
+        }
+    }
+
+    for (i, id) in providers.iter().enumerate() {
+        let rows: Vec<&UsageHistoryEntry> = entries.iter().filter(|e| e.provider == *id).collect();
+
+        let header = format!("{} history since {} ({} snapshots)", id.display_name(), since_local, rows.len());
+        if use_color {
+            println!("\x1b[1m{}\x1b[0m", header);
+        } else {
+            println!("{}", header);
+        }
+
+        // Change per window between the first and last snapshot, plus the peak
+        if let (Some(first), Some(last)) = (rows.first(), rows.last()) {
+            for window in &last.windows {
+                let start = first.window(&window.id).map(|w| w.used_percent);
+                let peak = rows
+                    .iter()
+                    .filter_map(|e| e.window(&window.id))
+                    .map(|w| w.used_percent)
+                    .fold(f64::MIN, f64::max);
+                match start {
+                    Some(start) => println!(
+                        "  {:<10} {:>5.1}% → {:>5.1}% ({:+.1}%), peak {:.1}%",
+                        window.label,
+                        start,
+                        window.used_percent,
+                        window.used_percent - start,
+                        peak
+                    ),
+                    None => println!(
+                        "  {:<10} {:>5.1}%, peak {:.1}%",
+                        window.label,
+                        window.used_percent,
+                        peak
+                    ),
+                }
+            }
+        }
+
+        for entry in &rows {
+            let windows: Vec<String> = entry
+                .windows
+                .iter()
+                .map(|w| format!("{} {:.1}%", w.label, w.used_percent))
+                .collect();
+            let mut line = format!(
+                "  {}  {}",
+                entry.captured_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"),
+                windows.join("  ")
+            );
+            if let Some(ref cost) = entry.cost {
+                line.push_str(&format!("  Cost {}", cost.format_used()));
+                if let Some(limit) = cost.format_limit() {
+                    line.push_str(&format!("/{}", limit));
+                }
+            }
+            if use_color {
+                line.push_str(&format!("  \x1b[2m[{}]\x1b[0m", entry.source_label));
+            } else {
+                line.push_str(&format!("  [{}]", entry.source_label));
+            }
+            println!("{}", line);
+        }
+
+        if i < providers.len() - 1 {
+            println!();
+        }
+    }
+}
+
+/// Format entries as CSV, one row per rate window
+fn format_csv(entries: &[UsageHistoryEntry]) -> String {
+    let mut out = String::from(
+        "captured_at,provider,source,account,window,label,kind,used_percent,window_minutes,resets_at,cost_used,cost_limit,currency\n",
+    );
+
+    for entry in entries {
+        let account = entry.account_email.as_deref().unwrap_or_default();
+        let (cost_used, cost_limit, currency) = match entry.cost {
+            Some(ref cost) => (
+                cost.used.to_string(),
+                cost.limit.map(|l| l.to_string()).unwrap_or_default(),
+                cost.currency_code.clone(),
+            ),
+            None => (String::new(), String::new(), String::new()),
+        };
+
+        for window in &entry.windows {
+            let fields = [
+                entry.captured_at.to_rfc3339(),
+                entry.provider.cli_name().to_string(),
+                entry.source_label.clone(),
+                account.to_string(),
+                window.id.clone(),
+                window.label.clone(),
+                window.kind.as_str().to_string(),
+                format!("{:.2}", window.used_percent),
+                window.window_minutes.map(|m| m.to_string()).unwrap_or_default(),
+                window.resets_at.map(|t| t.to_rfc3339()).unwrap_or_default(),
+                cost_used.clone(),
+                cost_limit.clone(),
+                currency.clone(),
+            ];
+            let row: Vec<String> = fields.iter().map(|f| csv_escape(f)).collect();
+            out.push_str(&row.join(","));
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
+/// Quote a CSV field when it contains separators, quotes or newlines
+pub(super) fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Check if stdout is a terminal
+fn is_terminal() -> bool {
+    use std::io::IsTerminal;
+    std::io::stdout().is_terminal()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{CostSnapshot, RateWindowKind, UsageHistoryWindow};
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_parse_since_durations() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 10, 12, 0, 0).unwrap();
+        assert_eq!(parse_since("7d", now).unwrap(), now - Duration::days(7));
+        assert_eq!(parse_since("24h", now).unwrap(), now - Duration::hours(24));
+        assert_eq!(parse_since("30m", now).unwrap(), now - Duration::minutes(30));
+        assert_eq!(parse_since("2w", now).unwrap(), now - Duration::weeks(2));
+        assert_eq!(parse_since("3", now).unwrap(), now - Duration::days(3));
+    }
+
+    #[test]
+    fn test_parse_since_dates() {
+        let now = Utc::now();
+        assert_eq!(
+            parse_since("2026-01-31", now).unwrap(),
+            Utc.with_ymd_and_hms(2026, 1, 31, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            parse_since("2026-01-31T08:30:00Z", now).unwrap(),
+            Utc.with_ymd_and_hms(2026, 1, 31, 8, 30, 0).unwrap()
+        );
+        assert!(parse_since("7y", now).is_err());
+        assert!(parse_since("soon", now).is_err());
+    }
+
+    #[test]
+    fn test_format_csv_one_row_per_window() {
+        let entry = UsageHistoryEntry {
+            provider: ProviderId::Claude,
+            captured_at: Utc.with_ymd_and_hms(2026, 3, 10, 12, 0, 0).unwrap(),
+            source_label: "oauth".to_string(),
+            account_email: Some("dev@example.com".to_string()),
+            account_organization: None,
+            login_method: Some("Claude Max, Team".to_string()),
+            windows: vec![
+                UsageHistoryWindow {
+                    id: "primary".to_string(),
+                    label: "Session".to_string(),
+                    kind: RateWindowKind::Session,
+                    used_percent: 42.0,
+                    window_minutes: Some(300),
+                    resets_at: None,
+                    reset_description: None,
+                },
+                UsageHistoryWindow {
+                    id: "secondary".to_string(),
+                    label: "Weekly".to_string(),
+                    kind: RateWindowKind::Weekly,
+                    used_percent: 61.5,
+                    window_minutes: Some(10080),
+                    resets_at: None,
+                    reset_description: None,
+                },
+            ],
+            cost: Some(CostSnapshot::new(3.5, "USD", "Monthly").with_limit(100.0)),
+        };
+
+        let csv = format_csv(&[entry]);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("captured_at,provider,"));
+        assert_eq!(
+            lines[1],
+            "2026-03-10T12:00:00+00:00,claude,oauth,dev@example.com,primary,Session,session,42.00,300,,3.5,100,USD"
+        );
+        assert!(lines[2].contains(",secondary,Weekly,weekly,61.50,10080,"));
+    }
+
+    #[test]
+    fn test_csv_escape() {
+        assert_eq!(csv_escape("plain"), "plain");
+        assert_eq!(csv_escape("a,b"), "\"a,b\"");
+        assert_eq!(csv_escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
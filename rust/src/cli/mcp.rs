@@ -0,0 +1,398 @@
+//! MCP command implementation
+//!
+//! Speaks the Model Context Protocol over stdio (newline-delimited JSON-RPC
+//! 2.0) so agents can check their own quota. Tools: `get_usage`, `get_cost`,
+//! `get_status` and `recommend_provider`, backed by the provider fetchers
+//! and the local cost scanner. Logs go to stderr; stdout carries only
+//! protocol messages.
+
+use std::time::Duration;
+
+use clap::Args;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
+
+use super::cost::{cost_json, cost_results};
+use super::pick::{rank, track_error_rates, Candidate};
+use super::usage::{
+    fetch_usage, provider_fetch_context, status_json, usage_error_json, usage_json, ProviderSelection, UsageFetch,
+};
+use crate::core::{
+    local_log_scanner, CostGroupBy, CostUsageDayRange, CurrencyConverter, FetchContext, ProviderId, TokenAccountStore,
+};
+use crate::settings::Settings;
+use crate::status::fetch_provider_status;
+
+/// Newest protocol revision this server speaks
+const PROTOCOL_VERSION: &str = "2025-06-18";
+
+/// Revisions accepted from clients
+const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &["2024-11-05", "2025-03-26", "2025-06-18"];
+
+/// Longest wait for a status page
+const STATUS_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Days covered by `get_cost` without `days`
+const DEFAULT_COST_DAYS: u32 = 30;
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Arguments for the mcp command
+#[derive(Args, Debug)]
+pub struct McpArgs {
+    /// Web fetch timeout in seconds
+    #[arg(long = "web-timeout", default_value = "30")]
+    pub web_timeout: u64,
+}
+
+/// What the tools share
+struct McpServer {
+    settings: Settings,
+    converter: CurrencyConverter,
+    base_ctx: FetchContext,
+}
+
+/// Run the mcp command
+pub async fn run(args: McpArgs) -> anyhow::Result<()> {
+    let settings = Settings::load();
+    let server = std::sync::Arc::new(McpServer {
+        converter: settings.currency_converter(),
+        settings,
+        base_ctx: FetchContext {
+            web_timeout: args.web_timeout,
+            ..FetchContext::default()
+        },
+    });
+
+    // Requests are handled concurrently; one writer keeps responses whole
+    let (tx, mut rx) = mpsc::unbounded_channel::<Value>();
+    let writer = tokio::spawn(async move {
+        let mut stdout = tokio::io::stdout();
+        while let Some(message) = rx.recv().await {
+            let mut line = message.to_string();
+            line.push('\n');
+            if stdout.write_all(line.as_bytes()).await.is_err() || stdout.flush().await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let server = server.clone();
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            if let Some(response) = server.handle_line(&line).await {
+                let _ = tx.send(response);
+            }
+        });
+    }
+
+    // Stdin closed: let in-flight requests finish, then stop
+    drop(tx);
+    let _ = writer.await;
+    Ok(())
+}
+
+impl McpServer {
+    /// Handle one incoming line; notifications get no response
+    async fn handle_line(&self, line: &str) -> Option<Value> {
+        let message: Value = match serde_json::from_str(line) {
+            Ok(message) => message,
+            Err(e) => return Some(error_response(Value::Null, PARSE_ERROR, &format!("Parse error: {}", e))),
+        };
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            // Responses to requests we never send, or garbage
+            return message
+                .get("id")
+                .map(|id| error_response(id.clone(), INVALID_REQUEST, "Invalid request"));
+        };
+        let id = message.get("id")?.clone();
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        let result = match method {
+            "initialize" => Ok(initialize_result(&params)),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": tool_definitions() })),
+            "tools/call" => self.call_tool(&params).await,
+            _ => Err((METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+        };
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => error_response(id, code, &message),
+        })
+    }
+
+    /// `tools/call`: run a tool; its failures are reported in the result
+    async fn call_tool(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let name = params
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or((INVALID_PARAMS, "Missing tool name".to_string()))?;
+        let arguments = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+
+        let output = match name {
+            "get_usage" => self.get_usage(&arguments).await,
+            "get_cost" => self.get_cost(&arguments).await,
+            "get_status" => self.get_status(&arguments).await,
+            "recommend_provider" => self.recommend_provider().await,
+            _ => return Err((INVALID_PARAMS, format!("Unknown tool: {}", name))),
+        };
+        Ok(match output {
+            Ok(value) => json!({
+                "content": [{ "type": "text", "text": serde_json::to_string_pretty(&value).unwrap_or_default() }],
+                "isError": false,
+            }),
+            Err(error) => json!({
+                "content": [{ "type": "text", "text": error }],
+                "isError": true,
+            }),
+        })
+    }
+
+    /// Fetch several providers at once, each signed in as its active token account
+    async fn fetch(&self, providers: &[ProviderId], fetch_status: bool) -> Vec<UsageFetch> {
+        let accounts = TokenAccountStore::new().load().unwrap_or_default();
+        let fetches = providers.iter().map(|&id| {
+            let account = accounts.get(&id).and_then(|data| data.active_account());
+            let ctx = provider_fetch_context(id, &self.settings, account, &self.base_ctx);
+            fetch_usage(id, ctx, fetch_status)
+        });
+        futures::future::join_all(fetches).await
+    }
+
+    /// `get_usage(provider?)`: the same JSON as `usage --json`
+    async fn get_usage(&self, arguments: &Value) -> Result<Value, String> {
+        let providers = match optional_str(arguments, "provider")? {
+            Some(name) => ProviderSelection::from_arg(Some(name))
+                .map_err(|e| e.to_string())?
+                .as_list(),
+            None => self.settings.get_enabled_provider_ids(),
+        };
+        let results: Vec<Value> = self
+            .fetch(&providers, false)
+            .await
+            .iter()
+            .map(|fetch| match &fetch.result {
+                Ok(result) => usage_json(fetch.provider, result, None, &self.converter),
+                Err(e) => usage_error_json(fetch.provider, e, &fetch.attempts),
+            })
+            .collect();
+        Ok(Value::Array(results))
+    }
+
+    /// `get_cost(days, group_by, provider?)`: local log costs as `cost --json`
+    async fn get_cost(&self, arguments: &Value) -> Result<Value, String> {
+        let days = match arguments.get("days") {
+            None | Some(Value::Null) => DEFAULT_COST_DAYS,
+            Some(value) => value
+                .as_u64()
+                .filter(|days| (1..=3650).contains(days))
+                .ok_or("days must be a whole number from 1 to 3650")? as u32,
+        };
+        let group_by: Vec<CostGroupBy> = match arguments.get("group_by") {
+            None | Some(Value::Null) => Vec::new(),
+            Some(Value::String(groups)) => groups
+                .split(',')
+                .filter(|group| !group.trim().is_empty())
+                .map(str::parse)
+                .collect::<Result<_, _>>()?,
+            Some(Value::Array(groups)) => groups
+                .iter()
+                .map(|group| group.as_str().ok_or("group_by entries must be strings")?.parse())
+                .collect::<Result<_, _>>()?,
+            Some(_) => return Err("group_by must be a list of project, model, day or session".to_string()),
+        };
+        let providers = match optional_str(arguments, "provider")? {
+            Some(name) => ProviderSelection::from_arg(Some(name))
+                .map_err(|e| e.to_string())?
+                .as_list(),
+            None => self
+                .settings
+                .get_enabled_provider_ids()
+                .into_iter()
+                .filter(|&id| local_log_scanner(id).is_some())
+                .collect(),
+        };
+
+        let converter = self.converter.clone();
+        let payload = tokio::task::spawn_blocking(move || {
+            let range = CostUsageDayRange::last_days(days);
+            cost_json(&cost_results(&providers, &range, &group_by, false), &group_by, &converter, days)
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+        Ok(Value::Array(payload))
+    }
+
+    /// `get_status(provider)`: the provider's status page
+    async fn get_status(&self, arguments: &Value) -> Result<Value, String> {
+        let name = optional_str(arguments, "provider")?.ok_or("provider is required")?;
+        let id = ProviderId::from_cli_name(name).ok_or_else(|| format!("Unknown provider: '{}'", name))?;
+        let status = tokio::time::timeout(STATUS_TIMEOUT, fetch_provider_status(id.cli_name()))
+            .await
+            .ok()
+            .flatten();
+        Ok(json!({
+            "provider": id.cli_name(),
+            "status": status.as_ref().map(status_json),
+        }))
+    }
+
+    /// `recommend_provider()`: the enabled provider with the most headroom,
+    /// ranked as in `pick --json`
+    async fn recommend_provider(&self) -> Result<Value, String> {
+        let providers = self.settings.get_enabled_provider_ids();
+        let fetches = self.fetch(&providers, true).await;
+        let now = chrono::Utc::now();
+        let candidates = rank(&fetches, &track_error_rates(&fetches, now), now);
+        let best = candidates.first().filter(|candidate| candidate.is_eligible());
+
+        Ok(json!({
+            "provider": best.map(|candidate| candidate.provider.cli_name()),
+            "candidates": candidates.iter().map(Candidate::to_json).collect::<Vec<_>>(),
+        }))
+    }
+}
+
+/// A string argument, if present
+fn optional_str<'a>(arguments: &'a Value, key: &str) -> Result<Option<&'a str>, String> {
+    match arguments.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(value)) => Ok(Some(value.as_str())),
+        Some(_) => Err(format!("{} must be a string", key)),
+    }
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+/// `initialize`: agree on a protocol revision and announce the tools
+fn initialize_result(params: &Value) -> Value {
+    let requested = params.get("protocolVersion").and_then(Value::as_str);
+    let version = requested
+        .filter(|version| SUPPORTED_PROTOCOL_VERSIONS.contains(version))
+        .unwrap_or(PROTOCOL_VERSION);
+    json!({
+        "protocolVersion": version,
+        "capabilities": { "tools": {} },
+        "serverInfo": { "name": "codexbar", "version": env!("CARGO_PKG_VERSION") },
+        "instructions": "Check AI provider rate limits, local token costs and status pages before starting long agent runs.",
+    })
+}
+
+/// Tool names, descriptions and input schemas
+fn tool_definitions() -> Value {
+    let provider = json!({
+        "type": "string",
+        "description": "Provider id, e.g. claude, codex, cursor, gemini; 'all' for every provider",
+    });
+    json!([
+        {
+            "name": "get_usage",
+            "description": "Current rate-limit windows (percent used, reset time), credits and spend per provider. Without a provider, every enabled provider.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "provider": provider },
+            },
+        },
+        {
+            "name": "get_cost",
+            "description": "Token usage and cost in USD from the local agent logs (Codex, Claude, Gemini CLI, OpenCode), optionally grouped.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "days": { "type": "integer", "minimum": 1, "maximum": 3650, "description": "Days to cover, ending today (default 30)" },
+                    "group_by": {
+                        "type": "array",
+                        "items": { "type": "string", "enum": ["project", "model", "day", "session"] },
+                        "description": "Break the cost down by these dimensions",
+                    },
+                    "provider": provider,
+                },
+            },
+        },
+        {
+            "name": "get_status",
+            "description": "Status page level (operational, degraded, partial, major) and description for a provider.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "provider": provider },
+                "required": ["provider"],
+            },
+        },
+        {
+            "name": "recommend_provider",
+            "description": "The enabled provider with the most headroom, ranked by quota left in the most restrictive window, time to reset, usage pace, status page health and recent fetch errors. Providers with errors, exhausted windows or a major outage are never picked.",
+            "inputSchema": { "type": "object", "properties": {} },
+        },
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server() -> McpServer {
+        McpServer {
+            settings: Settings::default(),
+            converter: CurrencyConverter::new("USD", &std::collections::HashMap::new()),
+            base_ctx: FetchContext::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_protocol_messages() {
+        let server = server();
+
+        let response = server
+            .handle_line(r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2024-11-05"}}"#)
+            .await
+            .unwrap();
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"]["protocolVersion"], "2024-11-05");
+        assert_eq!(response["result"]["serverInfo"]["name"], "codexbar");
+
+        assert!(server
+            .handle_line(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#)
+            .await
+            .is_none());
+
+        let response = server
+            .handle_line(r#"{"jsonrpc":"2.0","id":"a","method":"tools/list"}"#)
+            .await
+            .unwrap();
+        let names: Vec<&str> = response["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["get_usage", "get_cost", "get_status", "recommend_provider"]);
+
+        let response = server
+            .handle_line(r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"get_cost","arguments":{"group_by":["week"]}}}"#)
+            .await
+            .unwrap();
+        assert_eq!(response["result"]["isError"], true);
+
+        let response = server
+            .handle_line(r#"{"jsonrpc":"2.0","id":3,"method":"resources/list"}"#)
+            .await
+            .unwrap();
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+
+        let response = server.handle_line("{not json").await.unwrap();
+        assert_eq!(response["error"]["code"], PARSE_ERROR);
+        assert_eq!(response["id"], Value::Null);
+    }
+}
@@ -3,6 +3,13 @@
 //! Matches the original CodexBar CLI structure:
 //! - `codexbar` - defaults to usage command
 //! - `codexbar cost` - print local token cost usage
+//! - `codexbar budget` - check spend against the configured budgets
+//! - `codexbar history` - print recorded usage history
+//! - `codexbar watch` - full-screen terminal dashboard
+//! - `codexbar serve` - headless refresh loop with an HTTP API and metrics
+//! - `codexbar mcp` - MCP server over stdio for agents
+//! - `codexbar exec` - run an agent with usage checks before, during and after
+//! - `codexbar pick` - recommend the provider with the most headroom
 //! - `codexbar menubar` - launch the menu bar GUI app
 //! - `codexbar autostart` - manage Windows auto-start
 
@@ -10,10 +17,17 @@
 
 pub mod account;
 pub mod autostart;
+pub mod budget;
 pub mod config;
 pub mod cost;
+pub mod exec;
+pub mod history;
+pub mod mcp;
+pub mod pick;
+pub mod serve;
 pub mod tty_runner;
 pub mod usage;
+pub mod watch;
 
 use clap::{Parser, Subcommand};
 
@@ -106,6 +120,27 @@ pub enum Commands {
     /// Print local token cost usage (Claude + Codex) without web/CLI access
     Cost(cost::CostArgs),
 
+    /// Check spend against the monthly/weekly budgets from the settings
+    Budget(budget::BudgetArgs),
+
+    /// Print recorded usage history as text, JSON or CSV
+    History(history::HistoryArgs),
+
+    /// Full-screen terminal dashboard that refreshes on an interval
+    Watch(watch::WatchArgs),
+
+    /// Refresh in the background and serve usage, cost, status and metrics over HTTP
+    Serve(serve::ServeArgs),
+
+    /// Run an MCP server over stdio with usage, cost and status tools
+    Mcp(mcp::McpArgs),
+
+    /// Run an agent command with usage checks before, during and after it
+    Exec(exec::ExecArgs),
+
+    /// Recommend the provider with the most quota headroom
+    Pick(pick::PickArgs),
+
     /// Launch the menu bar GUI application
     Menubar,
 
@@ -0,0 +1,443 @@
+//! Pick command implementation
+//!
+//! Ranks interchangeable providers by how much they have left: headroom in
+//! the most restrictive window, time to its reset, usage pace, status page
+//! health and the recent fetch error rate from the usage history. Prints the
+//! best candidate, or the whole ranking as JSON for scripts.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+use clap::Args;
+use serde_json::{json, Value};
+
+use super::usage::{fetch_usage, provider_fetch_context, OutputFormat, ProviderSelection, UsageFetch};
+use crate::core::{FetchContext, PaceStage, ProviderId, TokenAccountStore, UsageHistoryStore, UsagePace};
+use crate::settings::Settings;
+use crate::status::StatusLevel;
+
+/// How far back fetch failures count towards the error rate
+const ERROR_RATE_HOURS: i64 = 24;
+
+/// Arguments for the pick command
+#[derive(Args, Debug)]
+pub struct PickArgs {
+    /// Providers to choose from, comma-separated (e.g. codex,claude,gemini,cursor; default: the enabled providers)
+    #[arg(short, long, value_delimiter = ',')]
+    pub provider: Vec<String>,
+
+    /// Output format: text or json
+    #[arg(short, long, default_value = "text")]
+    pub format: OutputFormat,
+
+    /// Shorthand for --format json
+    #[arg(long)]
+    pub json: bool,
+
+    /// Disable ANSI colors in text output
+    #[arg(long = "no-color")]
+    pub no_color: bool,
+
+    /// Pretty-print JSON output
+    #[arg(long)]
+    pub pretty: bool,
+
+    /// Skip the provider status pages
+    #[arg(long = "no-status")]
+    pub no_status: bool,
+
+    /// Web fetch timeout in seconds
+    #[arg(long = "web-timeout", default_value = "30")]
+    pub web_timeout: u64,
+}
+
+/// One ranked provider
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub provider: ProviderId,
+    /// Higher is better; only meaningful between eligible candidates
+    pub score: f64,
+    /// Label of the most restrictive window
+    pub window: Option<String>,
+    pub used_percent: Option<f64>,
+    pub resets_at: Option<DateTime<Utc>>,
+    pub pace: Option<PaceStage>,
+    pub status: Option<StatusLevel>,
+    /// Share of recent fetches that failed
+    pub error_rate: Option<f64>,
+    /// Why the provider can't be picked, if it can't
+    pub skipped: Option<String>,
+    /// What went into the score, e.g. "65% left in Weekly"
+    pub reasons: Vec<String>,
+}
+
+impl Candidate {
+    pub fn is_eligible(&self) -> bool {
+        self.skipped.is_none()
+    }
+
+    /// The candidate as in `pick --json`
+    pub fn to_json(&self) -> Value {
+        json!({
+            "provider": self.provider.cli_name(),
+            "eligible": self.is_eligible(),
+            "score": self.is_eligible().then(|| (self.score * 10.0).round() / 10.0),
+            "window": self.window,
+            "used_percent": self.used_percent,
+            "resets_at": self.resets_at,
+            "pace": self.pace.map(|stage| stage.label()),
+            "status": self.status.map(|level| format!("{:?}", level).to_lowercase()),
+            "error_rate": self.error_rate,
+            "skipped": self.skipped,
+            "reasons": self.reasons,
+        })
+    }
+}
+
+/// Run the pick command
+pub async fn run(args: PickArgs) -> anyhow::Result<()> {
+    let format = if args.json { OutputFormat::Json } else { args.format };
+    let use_color = !args.no_color && is_terminal();
+
+    let settings = Settings::load();
+    let mut providers = Vec::new();
+    for name in args.provider.iter().map(|name| name.trim()).filter(|name| !name.is_empty()) {
+        for id in ProviderSelection::from_arg(Some(name))?.as_list() {
+            if !providers.contains(&id) {
+                providers.push(id);
+            }
+        }
+    }
+    if providers.is_empty() {
+        providers = settings.get_enabled_provider_ids();
+    }
+    if providers.is_empty() {
+        anyhow::bail!("No providers enabled. Enable some in settings or pass --provider.");
+    }
+
+    let base_ctx = FetchContext {
+        web_timeout: args.web_timeout,
+        ..FetchContext::default()
+    };
+    let accounts = TokenAccountStore::new().load().unwrap_or_default();
+    let fetches = futures::future::join_all(providers.iter().map(|&id| {
+        let account = accounts.get(&id).and_then(|data| data.active_account());
+        fetch_usage(id, provider_fetch_context(id, &settings, account, &base_ctx), !args.no_status)
+    }))
+    .await;
+
+    let now = Utc::now();
+    let error_rates = track_error_rates(&fetches, now);
+    let candidates = rank(&fetches, &error_rates, now);
+    let best = candidates.first().filter(|candidate| candidate.is_eligible());
+
+    match format {
+        OutputFormat::Text => print_text(&candidates, use_color),
+        OutputFormat::Json => {
+            let payload = json!({
+                "provider": best.map(|candidate| candidate.provider.cli_name()),
+                "candidates": candidates.iter().map(Candidate::to_json).collect::<Vec<_>>(),
+            });
+            let output = if args.pretty {
+                serde_json::to_string_pretty(&payload)?
+            } else {
+                serde_json::to_string(&payload)?
+            };
+            println!("{}", output);
+        }
+    }
+
+    if best.is_none() {
+        anyhow::bail!("No provider can be picked: each one failed to fetch, is out of quota or has a major outage");
+    }
+    Ok(())
+}
+
+/// Recent error rates of the fetched providers from the usage history, then
+/// record these fetches so later picks see them too
+pub fn track_error_rates(fetches: &[UsageFetch], now: DateTime<Utc>) -> HashMap<ProviderId, f64> {
+    let store = match UsageHistoryStore::open_default() {
+        Ok(store) => store,
+        Err(e) => {
+            tracing::warn!("Usage history unavailable: {}", e);
+            return HashMap::new();
+        }
+    };
+
+    let since = now - Duration::hours(ERROR_RATE_HOURS);
+    let mut rates = HashMap::new();
+    for fetch in fetches {
+        match store.error_rate(fetch.provider, since) {
+            Ok(Some(rate)) => {
+                rates.insert(fetch.provider, rate);
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Failed to read error rate for {}: {}", fetch.provider.cli_name(), e),
+        }
+        if let Ok(result) = &fetch.result {
+            if let Err(e) = store.record(fetch.provider, result) {
+                tracing::warn!("Failed to record usage history for {}: {}", fetch.provider.cli_name(), e);
+            }
+        }
+        if let Err(e) = store.record_fetch_outcome(fetch.provider, fetch.result.is_ok(), now) {
+            tracing::warn!("Failed to record fetch outcome for {}: {}", fetch.provider.cli_name(), e);
+        }
+    }
+    rates
+}
+
+/// Score every fetch and sort best first, with providers that can't be
+/// picked (failed fetches, exhausted windows, major outages) last
+pub fn rank(fetches: &[UsageFetch], error_rates: &HashMap<ProviderId, f64>, now: DateTime<Utc>) -> Vec<Candidate> {
+    let mut candidates: Vec<Candidate> = fetches
+        .iter()
+        .map(|fetch| score(fetch, error_rates.get(&fetch.provider).copied(), now))
+        .collect();
+    candidates.sort_by(|a, b| {
+        b.is_eligible()
+            .cmp(&a.is_eligible())
+            .then_with(|| b.score.total_cmp(&a.score))
+    });
+    candidates
+}
+
+/// Score one provider. The base is the headroom left in its most restrictive
+/// window; a reset coming up soon gives back part of what's used, the pace
+/// nudges the score up or down, and status problems and recent fetch
+/// failures take points off.
+fn score(fetch: &UsageFetch, error_rate: Option<f64>, now: DateTime<Utc>) -> Candidate {
+    let status = fetch.status.as_ref().map(|status| status.level);
+    let mut candidate = Candidate {
+        provider: fetch.provider,
+        score: 0.0,
+        window: None,
+        used_percent: None,
+        resets_at: None,
+        pace: None,
+        status,
+        error_rate,
+        skipped: None,
+        reasons: Vec::new(),
+    };
+
+    let result = match &fetch.result {
+        Ok(result) => result,
+        Err(e) => {
+            candidate.skipped = Some(e.clone());
+            return candidate;
+        }
+    };
+
+    let usage = &result.usage;
+    let window = usage.most_restrictive();
+    let named = usage.windows.iter().find(|named| std::ptr::eq(&named.window, window));
+    let label = named.map_or("Usage", |named| named.label.as_str());
+    let used = window.used_percent.clamp(0.0, 100.0);
+    candidate.window = Some(label.to_string());
+    candidate.used_percent = Some(window.used_percent);
+    candidate.resets_at = window.resets_at;
+
+    if window.is_exhausted() {
+        candidate.skipped = Some(format!("{} limit reached", label));
+        return candidate;
+    }
+    if status == Some(StatusLevel::Major) {
+        candidate.skipped = Some(StatusLevel::Major.description().to_string());
+        return candidate;
+    }
+
+    let mut score = 100.0 - used;
+    candidate.reasons.push(format!("{:.0}% left in {}", 100.0 - used, label));
+
+    if let Some(resets_at) = window.resets_at {
+        // Up to half of the used share comes back when the reset is due now,
+        // fading out for resets a day or more away
+        let hours = ((resets_at - now).num_seconds() as f64 / 3600.0).max(0.0);
+        let soon = 1.0 - (hours / 24.0).min(1.0);
+        if soon > 0.0 {
+            score += used * soon * 0.5;
+        }
+        if let Some(countdown) = window.format_countdown() {
+            candidate.reasons.push(format!("resets in {}", countdown));
+        }
+    }
+
+    if let Some(pace) = named.and_then(|named| UsagePace::for_window(named, Some(now))) {
+        score += match pace.stage {
+            PaceStage::FarAhead => -15.0,
+            PaceStage::Ahead => -10.0,
+            PaceStage::SlightlyAhead => -5.0,
+            PaceStage::OnTrack => 0.0,
+            PaceStage::SlightlyBehind => 2.0,
+            PaceStage::Behind => 5.0,
+            PaceStage::FarBehind => 8.0,
+        };
+        if !pace.will_last_to_reset && pace.eta_seconds.is_some() {
+            score -= 10.0;
+        }
+        candidate.pace = Some(pace.stage);
+        candidate.reasons.push(pace.format_status());
+    }
+
+    match status {
+        Some(StatusLevel::Degraded) => score -= 10.0,
+        Some(StatusLevel::Partial) => score -= 25.0,
+        _ => {}
+    }
+    if let Some(level) = status.filter(|level| !matches!(level, StatusLevel::Operational | StatusLevel::Unknown)) {
+        candidate.reasons.push(level.description().to_string());
+    }
+
+    if let Some(rate) = error_rate.filter(|rate| *rate > 0.0) {
+        score -= rate * 30.0;
+        candidate.reasons.push(format!("{:.0}% of recent fetches failed", rate * 100.0));
+    }
+
+    candidate.score = score;
+    candidate
+}
+
+/// Print the pick and the ranking behind it
+fn print_text(candidates: &[Candidate], use_color: bool) {
+    let bold = |text: &str| {
+        if use_color {
+            format!("\x1b[1m{}\x1b[0m", text)
+        } else {
+            text.to_string()
+        }
+    };
+
+    match candidates.first().filter(|candidate| candidate.is_eligible()) {
+        Some(best) => println!("{} {}", bold("Pick:"), bold(best.provider.display_name())),
+        None => println!("{} none", bold("Pick:")),
+    }
+    println!();
+
+    let name_width = candidates
+        .iter()
+        .map(|candidate| candidate.provider.display_name().len())
+        .max()
+        .unwrap_or(0);
+    let mut rank = 0;
+    for candidate in candidates {
+        let name = candidate.provider.display_name();
+        if candidate.is_eligible() {
+            rank += 1;
+            println!(
+                "  {}. {:<width$}  score {:>3.0}  {}",
+                rank,
+                name,
+                candidate.score,
+                candidate.reasons.join(" · "),
+                width = name_width
+            );
+        } else {
+            println!(
+                "  -  {:<width$}  skipped    {}",
+                name,
+                candidate.skipped.as_deref().unwrap_or_default(),
+                width = name_width
+            );
+        }
+    }
+}
+
+fn is_terminal() -> bool {
+    use std::io::IsTerminal;
+    std::io::stdout().is_terminal()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{NamedRateWindow, ProviderFetchResult, RateWindow, UsageSnapshot};
+    use crate::status::ProviderStatus as StatusInfo;
+
+    fn fetch(provider: ProviderId, usage: UsageSnapshot, level: StatusLevel) -> UsageFetch {
+        UsageFetch {
+            provider,
+            result: Ok(ProviderFetchResult::new(usage, "test")),
+            attempts: Vec::new(),
+            status: Some(StatusInfo {
+                level,
+                ..StatusInfo::default()
+            }),
+            duration: std::time::Duration::ZERO,
+            transient: false,
+        }
+    }
+
+    fn session(used: f64, resets_in: Duration, now: DateTime<Utc>) -> UsageSnapshot {
+        UsageSnapshot::new(RateWindow::with_details(used, Some(300), Some(now + resets_in), None))
+    }
+
+    #[test]
+    fn test_rank_prefers_headroom_and_skips_unusable() {
+        let now = Utc::now();
+        let failed = UsageFetch {
+            result: Err("Not logged in".to_string()),
+            ..fetch(ProviderId::Gemini, UsageSnapshot::new(RateWindow::new(0.0)), StatusLevel::Operational)
+        };
+        let fetches = vec![
+            fetch(ProviderId::Claude, UsageSnapshot::new(RateWindow::new(60.0)), StatusLevel::Operational),
+            fetch(ProviderId::Codex, UsageSnapshot::new(RateWindow::new(20.0)), StatusLevel::Major),
+            fetch(ProviderId::Cursor, UsageSnapshot::new(RateWindow::new(40.0)), StatusLevel::Operational),
+            fetch(ProviderId::Copilot, UsageSnapshot::new(RateWindow::new(100.0)), StatusLevel::Operational),
+            failed,
+        ];
+
+        let ranked = rank(&fetches, &HashMap::new(), now);
+        let order: Vec<ProviderId> = ranked.iter().map(|c| c.provider).collect();
+        assert_eq!(&order[..2], &[ProviderId::Cursor, ProviderId::Claude]);
+        assert!(ranked[..2].iter().all(Candidate::is_eligible));
+        assert!(ranked[2..].iter().all(|c| !c.is_eligible()));
+        assert_eq!(ranked[0].reasons[0], "60% left in Session");
+
+        let gemini = ranked.iter().find(|c| c.provider == ProviderId::Gemini).unwrap();
+        assert_eq!(gemini.skipped.as_deref(), Some("Not logged in"));
+        let copilot = ranked.iter().find(|c| c.provider == ProviderId::Copilot).unwrap();
+        assert_eq!(copilot.skipped.as_deref(), Some("Session limit reached"));
+
+        let json = ranked[0].to_json();
+        assert_eq!(json["provider"], "cursor");
+        assert_eq!(json["eligible"], true);
+        assert_eq!(json["score"], 60.0);
+    }
+
+    #[test]
+    fn test_rank_uses_most_restrictive_window_reset_status_and_errors() {
+        let now = Utc::now();
+
+        // The weekly window binds, not the session
+        let usage = session(10.0, Duration::hours(4), now).with_window(NamedRateWindow::new(
+            "secondary",
+            "Weekly",
+            crate::core::RateWindowKind::Weekly,
+            RateWindow::with_details(70.0, Some(10080), Some(now + Duration::days(6)), None),
+        ));
+        let ranked = rank(&[fetch(ProviderId::Codex, usage, StatusLevel::Operational)], &HashMap::new(), now);
+        assert_eq!(ranked[0].window.as_deref(), Some("Weekly"));
+        assert_eq!(ranked[0].pace, Some(PaceStage::FarAhead));
+        assert!(ranked[0].score < 30.0);
+
+        // A near reset outweighs a little more headroom elsewhere
+        let fetches = vec![
+            fetch(ProviderId::Claude, session(80.0, Duration::minutes(10), now), StatusLevel::Operational),
+            fetch(ProviderId::Codex, session(60.0, Duration::days(2), now), StatusLevel::Operational),
+        ];
+        assert_eq!(rank(&fetches, &HashMap::new(), now)[0].provider, ProviderId::Claude);
+
+        // Status trouble and failing fetches cost points
+        let fetches = vec![
+            fetch(ProviderId::Claude, UsageSnapshot::new(RateWindow::new(30.0)), StatusLevel::Partial),
+            fetch(ProviderId::Codex, UsageSnapshot::new(RateWindow::new(40.0)), StatusLevel::Operational),
+            fetch(ProviderId::Cursor, UsageSnapshot::new(RateWindow::new(35.0)), StatusLevel::Operational),
+        ];
+        let error_rates = HashMap::from([(ProviderId::Cursor, 0.5)]);
+        let ranked = rank(&fetches, &error_rates, now);
+        let order: Vec<ProviderId> = ranked.iter().map(|c| c.provider).collect();
+        assert_eq!(order, vec![ProviderId::Codex, ProviderId::Cursor, ProviderId::Claude]);
+        assert!(ranked[1].reasons.contains(&"50% of recent fetches failed".to_string()));
+        assert!(ranked[2].reasons.contains(&"Partial Outage".to_string()));
+    }
+}
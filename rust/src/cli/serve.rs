@@ -0,0 +1,578 @@
+//! Serve command implementation
+//!
+//! Runs the refresh loop without a UI and serves the latest results over
+//! HTTP: `/v1/usage` (the same JSON as `usage --json`), `/v1/cost`,
+//! `/v1/status` and `/metrics` in the Prometheus text format.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::extract::{Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use clap::Args;
+use serde::Deserialize;
+
+use super::cost::{cost_json, cost_results, today_cost_usd};
+use super::usage::{
+    fetch_usage, provider_fetch_context, status_json, usage_error_json, usage_json, ProviderSelection, UsageFetch,
+};
+use crate::core::{
+    local_log_scanner, CostGroupBy, CostUsageDayRange, CurrencyConverter, FetchContext, ProviderId, RefreshScheduler,
+    TokenAccountStore, UsageHistoryStore,
+};
+use crate::settings::Settings;
+use crate::status::StatusLevel;
+
+/// Refresh interval for providers set to manual refresh in the settings,
+/// which a headless loop has no other way to refresh
+const DEFAULT_INTERVAL_SECS: u64 = 300;
+
+/// How often the loop checks for providers that are due
+const TICK: Duration = Duration::from_secs(1);
+
+/// Days covered by `/v1/cost` without `?days=`
+const DEFAULT_COST_DAYS: u32 = 30;
+
+/// Arguments for the serve command
+#[derive(Args, Debug)]
+pub struct ServeArgs {
+    /// Address to listen on (use 0.0.0.0:PORT to accept remote connections)
+    #[arg(long, default_value = "127.0.0.1:9876")]
+    pub listen: SocketAddr,
+
+    /// Provider to refresh (codex, claude, ..., all, both; default: the enabled providers)
+    #[arg(short, long)]
+    pub provider: Option<String>,
+
+    /// Seconds between refreshes (default: the refresh intervals from the settings)
+    #[arg(short, long, value_parser = clap::value_parser!(u64).range(10..))]
+    pub interval: Option<u64>,
+
+    /// Skip the provider status pages
+    #[arg(long = "no-status")]
+    pub no_status: bool,
+
+    /// Web fetch timeout in seconds
+    #[arg(long = "web-timeout", default_value = "60")]
+    pub web_timeout: u64,
+}
+
+/// Latest results and counters of one provider
+#[derive(Default)]
+struct ProviderState {
+    fetch: Option<UsageFetch>,
+    /// Active token account label when the fetch started
+    account: Option<String>,
+    /// Signed-in email from the last successful fetch
+    email: Option<String>,
+    /// Today's cost from the local logs in USD
+    cost_today_usd: Option<f64>,
+    fetches: u64,
+    errors: u64,
+    updated_at: Option<DateTime<Utc>>,
+}
+
+impl ProviderState {
+    /// The `account` label: the token account, else the last known
+    /// signed-in email, so a failed fetch doesn't change the label
+    fn account_label(&self) -> String {
+        self.account.clone().or_else(|| self.email.clone()).unwrap_or_default()
+    }
+}
+
+/// State shared by the refresh loop and the HTTP handlers
+struct ServeState {
+    providers: Vec<ProviderId>,
+    entries: Mutex<HashMap<ProviderId, ProviderState>>,
+    scheduler: Mutex<RefreshScheduler>,
+    history: Option<Mutex<UsageHistoryStore>>,
+    converter: CurrencyConverter,
+}
+
+impl ServeState {
+    /// Providers with their state, in the configured order
+    fn snapshot<T>(&self, f: impl Fn(ProviderId, &ProviderState) -> Option<T>) -> Vec<T> {
+        let Ok(entries) = self.entries.lock() else {
+            return Vec::new();
+        };
+        self.providers
+            .iter()
+            .filter_map(|id| entries.get(id).and_then(|state| f(*id, state)))
+            .collect()
+    }
+}
+
+/// Run the serve command
+pub async fn run(args: ServeArgs) -> anyhow::Result<()> {
+    let settings = Settings::load();
+    let providers = match args.provider.as_deref() {
+        Some(arg) => ProviderSelection::from_arg(Some(arg))?.as_list(),
+        None => settings.get_enabled_provider_ids(),
+    };
+    if providers.is_empty() {
+        anyhow::bail!("No providers enabled. Enable some in settings or pass --provider.");
+    }
+
+    let mut scheduler = RefreshScheduler::new();
+    scheduler.configure(
+        providers.iter().map(|&id| {
+            let mut policy = settings.refresh_policy(id);
+            policy.interval_secs = match args.interval {
+                Some(secs) => secs,
+                None if policy.interval_secs == 0 => DEFAULT_INTERVAL_SECS,
+                None => policy.interval_secs,
+            };
+            (id, policy)
+        }),
+        Utc::now(),
+    );
+    let history = match UsageHistoryStore::open_default() {
+        Ok(store) => Some(Mutex::new(store)),
+        Err(e) => {
+            tracing::warn!("Usage history unavailable: {}", e);
+            None
+        }
+    };
+
+    let state = Arc::new(ServeState {
+        entries: Mutex::new(providers.iter().map(|&id| (id, ProviderState::default())).collect()),
+        providers,
+        scheduler: Mutex::new(scheduler),
+        history,
+        converter: settings.currency_converter(),
+    });
+    let base_ctx = FetchContext {
+        web_timeout: args.web_timeout,
+        ..FetchContext::default()
+    };
+    tokio::spawn(refresh_loop(state.clone(), settings, base_ctx, !args.no_status));
+
+    let app = Router::new()
+        .route("/v1/usage", get(get_usage))
+        .route("/v1/cost", get(get_cost))
+        .route("/v1/status", get(get_status))
+        .route("/metrics", get(get_metrics))
+        .with_state(state);
+    let listener = tokio::net::TcpListener::bind(args.listen)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to listen on {}: {}", args.listen, e))?;
+    eprintln!("Serving on http://{} (/v1/usage, /v1/cost, /v1/status, /metrics)", args.listen);
+
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+    Ok(())
+}
+
+/// Fetch every provider the scheduler reports as due, forever
+async fn refresh_loop(state: Arc<ServeState>, settings: Settings, base_ctx: FetchContext, fetch_status: bool) {
+    loop {
+        let due = match state.scheduler.lock() {
+            Ok(mut scheduler) => {
+                let due = scheduler.due(Utc::now());
+                scheduler.mark_started(&due);
+                due
+            }
+            Err(_) => Vec::new(),
+        };
+        if !due.is_empty() {
+            if let Some(cutoff) = settings.history_cutoff(Utc::now()) {
+                if let Some(store) = state.history.as_ref().and_then(|h| h.lock().ok()) {
+                    if let Err(e) = store.prune_before(cutoff) {
+                        tracing::warn!("Failed to prune usage history: {}", e);
+                    }
+                }
+            }
+            // Re-read the accounts so `codexbar account` switches apply
+            let accounts = TokenAccountStore::new().load().unwrap_or_default();
+            for id in due {
+                let account = accounts.get(&id).and_then(|data| data.active_account());
+                let ctx = provider_fetch_context(id, &settings, account, &base_ctx);
+                let label = account.map(|account| account.label.clone());
+                tokio::spawn(refresh_provider(state.clone(), id, ctx, label, fetch_status));
+            }
+        }
+        tokio::time::sleep(TICK).await;
+    }
+}
+
+/// Fetch one provider and record the result
+async fn refresh_provider(
+    state: Arc<ServeState>,
+    id: ProviderId,
+    ctx: FetchContext,
+    account: Option<String>,
+    fetch_status: bool,
+) {
+    let (fetch, cost_today_usd) = tokio::join!(fetch_usage(id, ctx, fetch_status), today_cost_usd(id));
+    let now = Utc::now();
+
+    if let Ok(mut scheduler) = state.scheduler.lock() {
+        match &fetch.result {
+            Ok(result) => scheduler.record_success(id, &result.usage, now),
+            Err(_) => scheduler.record_failure(id, fetch.transient, now),
+        }
+    }
+    if let Some(store) = state.history.as_ref().and_then(|h| h.lock().ok()) {
+        if let Ok(result) = &fetch.result {
+            if let Err(e) = store.record(id, result) {
+                tracing::warn!("Failed to record usage history for {}: {}", id.cli_name(), e);
+            }
+        }
+        if let Err(e) = store.record_fetch_outcome(id, fetch.result.is_ok(), now) {
+            tracing::warn!("Failed to record fetch outcome for {}: {}", id.cli_name(), e);
+        }
+    }
+    if let Err(e) = &fetch.result {
+        tracing::warn!("Failed to fetch {}: {}", id.cli_name(), e);
+    }
+
+    if let Ok(mut entries) = state.entries.lock() {
+        let entry = entries.entry(id).or_default();
+        entry.fetches += 1;
+        if fetch.result.is_err() {
+            entry.errors += 1;
+        }
+        if let Ok(result) = &fetch.result {
+            entry.email = result.usage.account_email.clone().or(entry.email.take());
+        }
+        // Keep the last known status and cost when a lookup fails
+        let previous_status = entry.fetch.as_mut().and_then(|previous| previous.status.take());
+        entry.fetch = Some(UsageFetch {
+            status: fetch.status.or(previous_status),
+            ..fetch
+        });
+        entry.cost_today_usd = cost_today_usd.or(entry.cost_today_usd);
+        entry.account = account;
+        entry.updated_at = Some(now);
+    }
+}
+
+/// `GET /v1/usage`: the latest fetch of every provider, as `usage --json`
+async fn get_usage(State(state): State<Arc<ServeState>>) -> Json<Vec<serde_json::Value>> {
+    Json(state.snapshot(|id, entry| {
+        let fetch = entry.fetch.as_ref()?;
+        Some(match &fetch.result {
+            Ok(result) => usage_json(id, result, fetch.status.as_ref(), &state.converter),
+            Err(e) => usage_error_json(id, e, &fetch.attempts),
+        })
+    }))
+}
+
+/// Query of `GET /v1/cost`
+#[derive(Debug, Deserialize)]
+struct CostQuery {
+    provider: Option<String>,
+    days: Option<u32>,
+    /// Comma-separated dimensions, as `cost --group-by`
+    group_by: Option<String>,
+}
+
+/// `GET /v1/cost?provider=&days=&group_by=`: local log costs, as `cost --json`
+async fn get_cost(State(state): State<Arc<ServeState>>, Query(query): Query<CostQuery>) -> Response {
+    let providers = match query.provider.as_deref() {
+        Some(arg) => match ProviderSelection::from_arg(Some(arg)) {
+            Ok(selection) => selection.as_list(),
+            Err(e) => return bad_request(e.to_string()),
+        },
+        None => state
+            .providers
+            .iter()
+            .copied()
+            .filter(|&id| local_log_scanner(id).is_some())
+            .collect(),
+    };
+    let group_by: Vec<CostGroupBy> = match query
+        .group_by
+        .as_deref()
+        .unwrap_or("")
+        .split(',')
+        .filter(|part| !part.trim().is_empty())
+        .map(str::parse)
+        .collect()
+    {
+        Ok(group_by) => group_by,
+        Err(e) => return bad_request(e),
+    };
+    let days = query.days.unwrap_or(DEFAULT_COST_DAYS).max(1);
+
+    let converter = state.converter.clone();
+    let payload = tokio::task::spawn_blocking(move || {
+        let range = CostUsageDayRange::last_days(days);
+        cost_json(&cost_results(&providers, &range, &group_by, false), &group_by, &converter, days)
+    })
+    .await;
+    match payload {
+        Ok(payload) => Json(payload).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({ "error": e.to_string() }))).into_response(),
+    }
+}
+
+fn bad_request(error: String) -> Response {
+    (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "error": error }))).into_response()
+}
+
+/// `GET /v1/status`: the last status page result of every provider
+async fn get_status(State(state): State<Arc<ServeState>>) -> Json<Vec<serde_json::Value>> {
+    Json(state.snapshot(|id, entry| {
+        let status = entry.fetch.as_ref().and_then(|fetch| fetch.status.as_ref());
+        Some(serde_json::json!({
+            "provider": id.cli_name(),
+            "status": status.map(status_json),
+            "updated_at": entry.updated_at,
+        }))
+    }))
+}
+
+/// `GET /metrics`: Prometheus text exposition
+async fn get_metrics(State(state): State<Arc<ServeState>>) -> impl IntoResponse {
+    let Ok(entries) = state.entries.lock() else {
+        return (StatusCode::INTERNAL_SERVER_ERROR, "state unavailable").into_response();
+    };
+    let providers: Vec<(ProviderId, &ProviderState)> = state
+        .providers
+        .iter()
+        .filter_map(|id| entries.get(id).map(|entry| (*id, entry)))
+        .collect();
+    let body = render_metrics(&providers, Utc::now());
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")], body).into_response()
+}
+
+/// One metric family: its help, type and samples
+struct Family {
+    name: &'static str,
+    help: &'static str,
+    kind: &'static str,
+    samples: Vec<(Vec<(&'static str, String)>, f64)>,
+}
+
+impl Family {
+    fn new(name: &'static str, kind: &'static str, help: &'static str) -> Self {
+        Self {
+            name,
+            help,
+            kind,
+            samples: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, labels: Vec<(&'static str, String)>, value: f64) {
+        self.samples.push((labels, value));
+    }
+
+    fn render(&self, out: &mut String) {
+        let _ = writeln!(out, "# HELP {} {}", self.name, self.help);
+        let _ = writeln!(out, "# TYPE {} {}", self.name, self.kind);
+        for (labels, value) in &self.samples {
+            let labels: Vec<String> = labels
+                .iter()
+                .map(|(key, value)| format!("{}=\"{}\"", key, escape_label(value)))
+                .collect();
+            let _ = writeln!(out, "{}{{{}}} {}", self.name, labels.join(","), format_sample(*value));
+        }
+    }
+}
+
+/// Format a sample value, spelling non-finite values the way the text format does
+fn format_sample(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Escape a label value for the text format
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Severity of a status page level; unknown levels have none
+fn status_severity(level: StatusLevel) -> Option<f64> {
+    match level {
+        StatusLevel::Operational => Some(0.0),
+        StatusLevel::Degraded => Some(1.0),
+        StatusLevel::Partial => Some(2.0),
+        StatusLevel::Major => Some(3.0),
+        StatusLevel::Unknown => None,
+    }
+}
+
+/// Render the metrics of every provider
+fn render_metrics(providers: &[(ProviderId, &ProviderState)], now: DateTime<Utc>) -> String {
+    let mut used = Family::new(
+        "codexbar_usage_used_percent",
+        "gauge",
+        "Percent of the rate window used.",
+    );
+    let mut reset = Family::new(
+        "codexbar_usage_reset_seconds",
+        "gauge",
+        "Seconds until the rate window resets.",
+    );
+    let mut cost_used = Family::new(
+        "codexbar_cost_used",
+        "gauge",
+        "Spend reported by the provider in its currency.",
+    );
+    let mut cost_limit = Family::new(
+        "codexbar_cost_limit",
+        "gauge",
+        "Spend limit reported by the provider in its currency.",
+    );
+    let mut cost_today = Family::new(
+        "codexbar_local_cost_today_usd",
+        "gauge",
+        "Today's cost from the local agent logs in USD.",
+    );
+    let mut up = Family::new("codexbar_up", "gauge", "Whether the last usage fetch succeeded.");
+    let mut fetches = Family::new("codexbar_fetches_total", "counter", "Usage fetches since start.");
+    let mut errors = Family::new(
+        "codexbar_fetch_errors_total",
+        "counter",
+        "Failed usage fetches since start.",
+    );
+    let mut latency = Family::new(
+        "codexbar_fetch_duration_seconds",
+        "gauge",
+        "Duration of the last usage fetch.",
+    );
+    let mut status = Family::new(
+        "codexbar_status_level",
+        "gauge",
+        "Status page level: 0 operational, 1 degraded, 2 partial outage, 3 major outage.",
+    );
+
+    for (id, entry) in providers {
+        let provider = id.cli_name().to_string();
+        let account = entry.account_label();
+        let labels = || vec![("provider", provider.clone()), ("account", account.clone())];
+
+        if let Some(usd) = entry.cost_today_usd {
+            cost_today.add(vec![("provider", provider.clone())], usd);
+        }
+        let Some(fetch) = &entry.fetch else {
+            continue;
+        };
+        // Counters keep one series per provider whatever the account
+        fetches.add(vec![("provider", provider.clone())], entry.fetches as f64);
+        errors.add(vec![("provider", provider.clone())], entry.errors as f64);
+        latency.add(labels(), fetch.duration.as_secs_f64());
+        up.add(labels(), if fetch.result.is_ok() { 1.0 } else { 0.0 });
+        if let Some(severity) = fetch.status.as_ref().and_then(|s| status_severity(s.level)) {
+            status.add(vec![("provider", provider.clone())], severity);
+        }
+
+        let Ok(result) = &fetch.result else {
+            continue;
+        };
+        for named in &result.usage.windows {
+            let mut window_labels = labels();
+            window_labels.push(("window", named.id.clone()));
+            used.add(window_labels.clone(), named.window.used_percent);
+            if let Some(resets_at) = named.window.resets_at {
+                reset.add(window_labels, (resets_at - now).num_seconds().max(0) as f64);
+            }
+        }
+        if let Some(cost) = &result.cost {
+            let mut cost_labels = labels();
+            cost_labels.push(("period", cost.period.clone()));
+            cost_labels.push(("currency", cost.currency_code.clone()));
+            cost_used.add(cost_labels.clone(), cost.used);
+            if let Some(limit) = cost.limit {
+                cost_limit.add(cost_labels, limit);
+            }
+        }
+    }
+
+    let mut out = String::new();
+    for family in [
+        &used, &reset, &cost_used, &cost_limit, &cost_today, &up, &fetches, &errors, &latency, &status,
+    ] {
+        if !family.samples.is_empty() {
+            family.render(&mut out);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{NamedRateWindow, ProviderFetchResult, RateWindow, UsageSnapshot};
+
+    fn fetch(result: Result<ProviderFetchResult, String>) -> UsageFetch {
+        UsageFetch {
+            provider: ProviderId::Claude,
+            result,
+            attempts: Vec::new(),
+            status: None,
+            duration: Duration::from_millis(1500),
+            transient: false,
+        }
+    }
+
+    #[test]
+    fn test_render_metrics() {
+        let now = Utc::now();
+        let session = RateWindow::with_details(31.5, Some(300), Some(now + chrono::Duration::seconds(600)), None);
+        let usage = UsageSnapshot::from_window(NamedRateWindow::primary(session)).with_secondary(RateWindow::new(80.0));
+        let claude = ProviderState {
+            fetch: Some(fetch(Ok(ProviderFetchResult::new(usage, "oauth")))),
+            account: Some("Work \"main\"".to_string()),
+            email: None,
+            cost_today_usd: Some(2.25),
+            fetches: 3,
+            errors: 1,
+            updated_at: Some(now),
+        };
+        let codex = ProviderState {
+            fetch: Some(fetch(Err("Not logged in".to_string()))),
+            fetches: 1,
+            errors: 1,
+            ..ProviderState::default()
+        };
+
+        let text = render_metrics(&[(ProviderId::Claude, &claude), (ProviderId::Codex, &codex)], now);
+        let labels = r#"provider="claude",account="Work \"main\"""#;
+        assert!(text.contains("# TYPE codexbar_usage_used_percent gauge\n"));
+        assert!(text.contains(&format!("codexbar_usage_used_percent{{{},window=\"primary\"}} 31.5\n", labels)));
+        assert!(text.contains(&format!("codexbar_usage_used_percent{{{},window=\"secondary\"}} 80\n", labels)));
+        assert!(text.contains(&format!("codexbar_usage_reset_seconds{{{},window=\"primary\"}} 600\n", labels)));
+        assert!(!text.contains("window=\"secondary\"} 0"));
+        assert!(text.contains("codexbar_local_cost_today_usd{provider=\"claude\"} 2.25\n"));
+        assert!(text.contains(&format!("codexbar_fetch_duration_seconds{{{}}} 1.5\n", labels)));
+        assert!(text.contains("# TYPE codexbar_fetch_errors_total counter\n"));
+        assert!(text.contains("codexbar_fetches_total{provider=\"claude\"} 3\n"));
+        assert!(text.contains("codexbar_fetch_errors_total{provider=\"codex\"} 1\n"));
+        assert!(text.contains("codexbar_up{provider=\"codex\",account=\"\"} 0\n"));
+        // Families without samples are left out
+        assert!(!text.contains("codexbar_cost_used"));
+    }
+
+    #[test]
+    fn test_render_non_finite_samples() {
+        let mut family = Family::new("codexbar_test", "gauge", "Test");
+        family.add(vec![("window", "a".to_string())], f64::INFINITY);
+        family.add(vec![("window", "b".to_string())], f64::NEG_INFINITY);
+        family.add(vec![("window", "c".to_string())], f64::NAN);
+        family.add(vec![("window", "d".to_string())], 2.5);
+        let mut text = String::new();
+        family.render(&mut text);
+        assert!(text.contains("codexbar_test{window=\"a\"} +Inf\n"));
+        assert!(text.contains("codexbar_test{window=\"b\"} -Inf\n"));
+        assert!(text.contains("codexbar_test{window=\"c\"} NaN\n"));
+        assert!(text.contains("codexbar_test{window=\"d\"} 2.5\n"));
+    }
+}
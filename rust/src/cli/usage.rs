@@ -1,18 +1,21 @@
 //! Usage command implementation
 
+use std::time::{Duration, Instant};
+
 use clap::Args;
 use serde::Serialize;
 
-use crate::core::{FetchContext, ProviderId, Provider, ProviderFetchResult, SourceMode};
-use crate::providers::{
-    AmpProvider, AntigravityProvider, AugmentProvider, ClaudeProvider, CodexProvider,
-    CopilotProvider, CursorProvider, FactoryProvider, GeminiProvider, JetBrainsProvider,
-    KimiProvider, KimiK2Provider, KiroProvider, MiniMaxProvider, OllamaProvider,
-    OpenCodeProvider, OpenRouterProvider, SyntheticProvider, VertexAIProvider, WarpProvider,
-    ZaiProvider,
+use crate::core::{
+    CostSnapshot, CurrencyConverter, FetchContext, ProviderFetchAttempt, ProviderFetchResult, ProviderId, SourceMode,
+    TokenAccount, TokenAccountSupport,
 };
+use crate::providers::REGISTRY;
+use crate::settings::Settings;
 use crate::status::{fetch_provider_status, ProviderStatus as StatusInfo, StatusLevel};
 
+/// Longest wait for a provider's status page in the long-running commands
+const STATUS_TIMEOUT: Duration = Duration::from_secs(10);
+
 /// Arguments for the usage command
 #[derive(Args, Debug, Default)]
 pub struct UsageArgs {
@@ -109,7 +112,7 @@ impl ProviderSelection {
         match self {
             ProviderSelection::Single(id) => vec![*id],
             ProviderSelection::Both => vec![ProviderId::Codex, ProviderId::Claude],
-            ProviderSelection::All => ProviderId::all().to_vec(),
+            ProviderSelection::All => REGISTRY.all_ids(),
         }
     }
 }
@@ -132,33 +135,6 @@ struct ErrorPayload {
     error: String,
 }
 
-/// Create a provider instance by ID
-fn create_provider(id: ProviderId) -> Box<dyn Provider> {
-    match id {
-        ProviderId::Claude => Box::new(ClaudeProvider::new()),
-        ProviderId::Codex => Box::new(CodexProvider::new()),
-        ProviderId::Cursor => Box::new(CursorProvider::new()),
-        ProviderId::Gemini => Box::new(GeminiProvider::new()),
-        ProviderId::Copilot => Box::new(CopilotProvider::new()),
-        ProviderId::Antigravity => Box::new(AntigravityProvider::new()),
-        ProviderId::Factory => Box::new(FactoryProvider::new()),
-        ProviderId::Zai => Box::new(ZaiProvider::new()),
-        ProviderId::Kiro => Box::new(KiroProvider::new()),
-        ProviderId::VertexAI => Box::new(VertexAIProvider::new()),
-        ProviderId::Augment => Box::new(AugmentProvider::new()),
-        ProviderId::MiniMax => Box::new(MiniMaxProvider::new()),
-        ProviderId::OpenCode => Box::new(OpenCodeProvider::new()),
-        ProviderId::Kimi => Box::new(KimiProvider::new()),
-        ProviderId::KimiK2 => Box::new(KimiK2Provider::new()),
-        ProviderId::Amp => Box::new(AmpProvider::new()),
-        ProviderId::Warp => Box::new(WarpProvider::new()),
-        ProviderId::Ollama => Box::new(OllamaProvider::new()),
-        ProviderId::OpenRouter => Box::new(OpenRouterProvider::new()),
-        ProviderId::Synthetic => Box::new(SyntheticProvider::new()),
-        ProviderId::JetBrains => Box::new(JetBrainsProvider::new()),
-    }
-}
-
 /// Run the usage command
 pub async fn run(args: UsageArgs) -> anyhow::Result<()> {
     let format = if args.json {
@@ -187,13 +163,20 @@ pub async fn run(args: UsageArgs) -> anyhow::Result<()> {
         verbose: false,
         manual_cookie_header: None,
         api_key: None,
+        base_url: None,
     };
+    let settings = Settings::load();
+    let converter = settings.currency_converter();
 
     let mut results: Vec<serde_json::Value> = Vec::new();
     let mut text_sections: Vec<String> = Vec::new();
+    let mut spend: Vec<CostSnapshot> = Vec::new();
 
     for provider_id in providers.as_list() {
-        let provider = create_provider(provider_id);
+        let Some(provider) = REGISTRY.get(provider_id) else {
+            tracing::warn!("Provider {} is not registered", provider_id);
+            continue;
+        };
 
         // Optionally fetch status in parallel with usage
         let status_future = if fetch_status {
@@ -202,7 +185,11 @@ pub async fn run(args: UsageArgs) -> anyhow::Result<()> {
             None
         };
 
-        match provider.fetch_usage(&ctx).await {
+        let ctx = provider_fetch_context(provider_id, &settings, None, &ctx);
+        let outcome = provider.fetch_outcome(&ctx).await;
+        let attempts = outcome.attempts.clone();
+
+        match outcome.into_result() {
             Ok(result) => {
                 let status = if let Some(fut) = status_future {
                     fut.await
@@ -210,24 +197,22 @@ pub async fn run(args: UsageArgs) -> anyhow::Result<()> {
                     None
                 };
 
-                if format == OutputFormat::Text {
-                    text_sections.push(render_text_with_status(provider_id, &result, status.as_ref(), use_color));
-                } else {
-                    let mut json_result = serde_json::json!({
-                        "provider": provider_id.cli_name(),
-                        "source": result.source_label,
-                        "usage": result.usage,
-                        "cost": result.cost,
-                    });
-
-                    if let Some(ref s) = status {
-                        json_result["status"] = serde_json::json!({
-                            "level": format!("{:?}", s.level).to_lowercase(),
-                            "description": s.description,
-                        });
+                if let Some(ref cost) = result.cost {
+                    if cost.period != "Credits" {
+                        spend.push(cost.clone());
                     }
+                }
 
-                    results.push(json_result);
+                if format == OutputFormat::Text {
+                    text_sections.push(render_text_with_status(
+                        provider_id,
+                        &result,
+                        status.as_ref(),
+                        Some(&converter),
+                        use_color,
+                    ));
+                } else {
+                    results.push(usage_json(provider_id, &result, status.as_ref(), &converter));
                 }
             }
             Err(e) => {
@@ -238,17 +223,23 @@ pub async fn run(args: UsageArgs) -> anyhow::Result<()> {
                     } else {
                         provider_id.display_name().to_string()
                     };
-                    text_sections.push(format!("{}  Error: {}", header, error_msg));
+                    let mut section = format!("{}  Error: {}", header, error_msg);
+                    for attempt in &attempts {
+                        section.push_str(&format!("\n  Tried:   {}", attempt.summary()));
+                    }
+                    text_sections.push(section);
                 } else {
-                    results.push(serde_json::json!({
-                        "provider": provider_id.cli_name(),
-                        "error": error_msg,
-                    }));
+                    results.push(usage_error_json(provider_id, &error_msg, &attempts));
                 }
             }
         }
     }
 
+    // Total spend across providers, converted to the reporting currency
+    if spend.len() > 1 && format == OutputFormat::Text {
+        text_sections.push(render_spend_total(&spend, &converter, use_color));
+    }
+
     match format {
         OutputFormat::Text => {
             println!("{}", text_sections.join("\n\n"));
@@ -266,6 +257,138 @@ pub async fn run(args: UsageArgs) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// One provider's usage as in `usage --json`
+pub fn usage_json(
+    provider: ProviderId,
+    result: &ProviderFetchResult,
+    status: Option<&StatusInfo>,
+    converter: &CurrencyConverter,
+) -> serde_json::Value {
+    let mut json_result = serde_json::json!({
+        "provider": provider.cli_name(),
+        "source": result.source_label,
+        "usage": result.usage,
+        "cost": result.cost,
+        "attempts": result.attempts,
+    });
+
+    if let Some(converted) = result
+        .cost
+        .as_ref()
+        .filter(|c| c.needs_conversion(converter))
+        .and_then(|c| c.converted(converter))
+    {
+        json_result["reporting_cost"] = serde_json::json!(converted);
+    }
+
+    if let Some(s) = status {
+        json_result["status"] = status_json(s);
+    }
+
+    json_result
+}
+
+/// A failed fetch as in `usage --json`
+pub fn usage_error_json(provider: ProviderId, error: &str, attempts: &[ProviderFetchAttempt]) -> serde_json::Value {
+    serde_json::json!({
+        "provider": provider.cli_name(),
+        "error": error,
+        "attempts": attempts,
+    })
+}
+
+/// Status page level and description as JSON
+pub fn status_json(status: &StatusInfo) -> serde_json::Value {
+    serde_json::json!({
+        "level": format!("{:?}", status.level).to_lowercase(),
+        "description": status.description,
+    })
+}
+
+/// One provider's usage fetch (and status page lookup) for the long-running
+/// commands
+pub struct UsageFetch {
+    pub provider: ProviderId,
+    pub result: Result<ProviderFetchResult, String>,
+    pub attempts: Vec<ProviderFetchAttempt>,
+    pub status: Option<StatusInfo>,
+    /// How long the usage fetch took
+    pub duration: Duration,
+    /// Whether a failure is worth retrying soon (network, timeout, rate limit)
+    pub transient: bool,
+}
+
+/// Fetch a provider's usage and, with `fetch_status`, its status page, both
+/// bounded by timeouts so one stuck provider cannot stall a refresh loop
+pub async fn fetch_usage(provider: ProviderId, ctx: FetchContext, fetch_status: bool) -> UsageFetch {
+    let usage = async {
+        let started = Instant::now();
+        let Some(fetcher) = REGISTRY.get(provider) else {
+            return (Err("Provider not registered".to_string()), Vec::new(), false, started.elapsed());
+        };
+        let timeout = Duration::from_secs(ctx.web_timeout.max(1) + 5);
+        match tokio::time::timeout(timeout, fetcher.fetch_outcome(&ctx)).await {
+            Ok(outcome) => {
+                let attempts = outcome.attempts.clone();
+                match outcome.into_result() {
+                    Ok(result) => (Ok(result), attempts, false, started.elapsed()),
+                    Err(e) => (Err(e.to_string()), attempts, e.is_transient(), started.elapsed()),
+                }
+            }
+            Err(_) => (Err("Timed out".to_string()), Vec::new(), true, started.elapsed()),
+        }
+    };
+    let status = async {
+        if !fetch_status {
+            return None;
+        }
+        tokio::time::timeout(STATUS_TIMEOUT, fetch_provider_status(provider.cli_name()))
+            .await
+            .ok()
+            .flatten()
+    };
+
+    let ((result, attempts, transient, duration), status) = tokio::join!(usage, status);
+    UsageFetch {
+        provider,
+        result,
+        attempts,
+        status,
+        duration,
+        transient,
+    }
+}
+
+/// Fetch context for one provider: `base` with the provider's base URL
+/// override, signed in as `account` when given. Account tokens go where the
+/// provider reads them: an environment override (also passed as the API
+/// key) or a cookie header.
+pub fn provider_fetch_context(
+    provider: ProviderId,
+    settings: &Settings,
+    account: Option<&TokenAccount>,
+    base: &FetchContext,
+) -> FetchContext {
+    let mut ctx = FetchContext {
+        base_url: settings.base_url_override(provider),
+        ..base.clone()
+    };
+    if let Some(account) = account {
+        match TokenAccountSupport::env_override(provider, &account.token) {
+            Some(env_vars) => {
+                for (key, value) in &env_vars {
+                    std::env::set_var(key, value);
+                }
+                ctx.api_key = env_vars.values().next().cloned();
+            }
+            None => {
+                ctx.manual_cookie_header = Some(TokenAccountSupport::normalized_cookie_header(provider, &account.token));
+            }
+        }
+    }
+    ctx
+}
+
 /// Check if stdout is a terminal
 fn is_terminal() -> bool {
     use std::io::IsTerminal;
@@ -277,6 +400,7 @@ pub fn render_text_with_status(
     provider: ProviderId,
     result: &ProviderFetchResult,
     status: Option<&StatusInfo>,
+    converter: Option<&CurrencyConverter>,
     use_color: bool,
 ) -> String {
     let mut lines = Vec::new();
@@ -320,48 +444,50 @@ pub fn render_text_with_status(
     if let Some(ref method) = result.usage.login_method {
         lines.push(format!("  Plan:    {}", method));
     }
+    if result.usage.stale {
+        let as_of = result.usage.updated_at.with_timezone(&chrono::Local);
+        lines.push(format!("  Stale:   data from {}", as_of.format("%Y-%m-%d %H:%M")));
+    }
 
-    // Primary window
-    let primary = &result.usage.primary;
-    let session_bar = render_progress_bar(primary.used_percent, 20, use_color);
-    let session_reset = primary
-        .format_countdown()
-        .map(|c| format!(" (resets in {})", c))
-        .unwrap_or_default();
-    lines.push(format!(
-        "  Session: {} {:.0}% used{}",
-        session_bar, primary.used_percent, session_reset
-    ));
-
-    // Secondary window
-    if let Some(ref secondary) = result.usage.secondary {
-        let weekly_bar = render_progress_bar(secondary.used_percent, 20, use_color);
-        let weekly_reset = secondary
+    // Rate windows, primary first
+    let label_width = result
+        .usage
+        .windows
+        .iter()
+        .map(|w| w.label.chars().count() + 1)
+        .max()
+        .unwrap_or(0)
+        .max(8);
+    for named in &result.usage.windows {
+        let window = &named.window;
+        let bar = render_progress_bar(window.used_percent, 20, use_color);
+        let reset = window
             .format_countdown()
             .map(|c| format!(" (resets in {})", c))
             .unwrap_or_default();
         lines.push(format!(
-            "  Weekly:  {} {:.0}% used{}",
-            weekly_bar, secondary.used_percent, weekly_reset
+            "  {:<width$} {} {:.0}% used{}",
+            format!("{}:", named.label),
+            bar,
+            window.used_percent,
+            reset,
+            width = label_width
         ));
     }
 
-    // Model-specific window
-    if let Some(ref opus) = result.usage.model_specific {
-        let opus_bar = render_progress_bar(opus.used_percent, 20, use_color);
-        lines.push(format!(
-            "  Opus:    {} {:.0}% used",
-            opus_bar, opus.used_percent
-        ));
-    }
-
-    // Cost info
+    // Cost info, with the amount in the reporting currency when it differs
     if let Some(ref cost) = result.cost {
-        let cost_line = if let Some(limit) = cost.format_limit() {
+        let mut cost_line = if let Some(limit) = cost.format_limit() {
             format!("  Cost:    {} / {} ({})", cost.format_used(), limit, cost.period)
         } else {
             format!("  Cost:    {} ({})", cost.format_used(), cost.period)
         };
+        if let Some(converted) = converter
+            .filter(|c| cost.needs_conversion(c))
+            .and_then(|c| cost.converted(c))
+        {
+            cost_line.push_str(&format!(" ≈ {}", converted.format_used()));
+        }
         lines.push(cost_line);
     }
 
@@ -374,11 +500,25 @@ pub fn render_text(
     result: &ProviderFetchResult,
     use_color: bool,
 ) -> String {
-    render_text_with_status(provider, result, None, use_color)
+    render_text_with_status(provider, result, None, None, use_color)
+}
+
+/// Render the combined spend of several providers in the reporting currency
+fn render_spend_total(costs: &[CostSnapshot], converter: &CurrencyConverter, use_color: bool) -> String {
+    let (total, missing) = converter.total(costs.iter().map(|c| (c.used, c.currency_code.as_str())));
+    let mut line = if use_color {
+        format!("\x1b[1mTotal spend:\x1b[0m {}", converter.format(total))
+    } else {
+        format!("Total spend: {}", converter.format(total))
+    };
+    if !missing.is_empty() {
+        line.push_str(&format!(" (excludes {}: no exchange rate)", missing.join(", ")));
+    }
+    line
 }
 
 /// Render a text-based progress bar
-fn render_progress_bar(percent: f64, width: usize, use_color: bool) -> String {
+pub fn render_progress_bar(percent: f64, width: usize, use_color: bool) -> String {
     let filled = ((percent / 100.0) * width as f64).round() as usize;
     let empty = width.saturating_sub(filled);
 
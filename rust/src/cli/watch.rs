@@ -0,0 +1,587 @@
+//! Watch command implementation
+//!
+//! A full-screen terminal dashboard for SSH sessions and systems without the
+//! tray: every provider's rate windows with reset countdowns and pace, the
+//! status page level and today's cost from the local logs, refreshed on an
+//! interval. Keys refresh, switch token accounts and filter the providers.
+
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local, Utc};
+use clap::Args;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+
+use super::cost::today_cost_usd;
+use super::usage::{fetch_usage, provider_fetch_context, ProviderSelection};
+use crate::core::{
+    CurrencyConverter, FetchContext, NamedRateWindow, ProviderAccountData, ProviderFetchResult, ProviderId,
+    TokenAccountStore, UsagePace,
+};
+use crate::settings::Settings;
+use crate::status::{ProviderStatus as StatusInfo, StatusLevel};
+
+/// How often the screen is redrawn (countdowns tick while idle)
+const FRAME_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Width of the usage bars
+const BAR_WIDTH: usize = 20;
+
+/// Arguments for the watch command
+#[derive(Args, Debug)]
+pub struct WatchArgs {
+    /// Provider to show (codex, claude, ..., all, both; default: the enabled providers)
+    #[arg(short, long)]
+    pub provider: Option<String>,
+
+    /// Seconds between refreshes
+    #[arg(short, long, default_value = "60", value_parser = clap::value_parser!(u64).range(5..))]
+    pub interval: u64,
+
+    /// Skip the provider status pages
+    #[arg(long = "no-status")]
+    pub no_status: bool,
+
+    /// Web fetch timeout in seconds
+    #[arg(long = "web-timeout", default_value = "60")]
+    pub web_timeout: u64,
+}
+
+/// Run the watch command
+pub async fn run(args: WatchArgs) -> anyhow::Result<()> {
+    if !is_terminal() {
+        anyhow::bail!("codexbar watch needs an interactive terminal; use `codexbar usage` in scripts");
+    }
+
+    let settings = Settings::load();
+    let providers = match args.provider.as_deref() {
+        Some(arg) => ProviderSelection::from_arg(Some(arg))?.as_list(),
+        None => settings.get_enabled_provider_ids(),
+    };
+    if providers.is_empty() {
+        anyhow::bail!("No providers enabled. Enable some in settings or pass --provider.");
+    }
+
+    let handle = tokio::runtime::Handle::current();
+    tokio::task::spawn_blocking(move || {
+        let mut dashboard = Dashboard::new(providers, settings, &args, handle);
+        let mut terminal = ratatui::init();
+        crate::logging::set_stderr_muted(true);
+        let result = dashboard.run(&mut terminal);
+        crate::logging::set_stderr_muted(false);
+        ratatui::restore();
+        result
+    })
+    .await?
+}
+
+/// Latest data of one provider
+struct ProviderPanel {
+    id: ProviderId,
+    result: Option<Result<ProviderFetchResult, String>>,
+    status: Option<StatusInfo>,
+    /// Today's cost from the local logs in USD
+    cost_today_usd: Option<f64>,
+    loading: bool,
+}
+
+/// A finished fetch
+struct PanelUpdate {
+    id: ProviderId,
+    result: Result<ProviderFetchResult, String>,
+    status: Option<StatusInfo>,
+    cost_today_usd: Option<f64>,
+}
+
+/// What a key press asks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeyAction {
+    None,
+    Quit,
+    Refresh,
+    SwitchAccount,
+}
+
+/// Dashboard state and the fetches feeding it
+struct Dashboard {
+    panels: Vec<ProviderPanel>,
+    accounts: HashMap<ProviderId, ProviderAccountData>,
+    settings: Settings,
+    converter: CurrencyConverter,
+    base_ctx: FetchContext,
+    fetch_status: bool,
+    interval: Duration,
+    next_refresh: Instant,
+    last_refresh: Option<DateTime<Local>>,
+    list: ListState,
+    filter: String,
+    editing_filter: bool,
+    message: Option<String>,
+    handle: tokio::runtime::Handle,
+    updates_tx: Sender<PanelUpdate>,
+    updates: Receiver<PanelUpdate>,
+}
+
+impl Dashboard {
+    fn new(providers: Vec<ProviderId>, settings: Settings, args: &WatchArgs, handle: tokio::runtime::Handle) -> Self {
+        let (updates_tx, updates) = mpsc::channel();
+        Self {
+            panels: providers
+                .into_iter()
+                .map(|id| ProviderPanel {
+                    id,
+                    result: None,
+                    status: None,
+                    cost_today_usd: None,
+                    loading: false,
+                })
+                .collect(),
+            accounts: TokenAccountStore::new().load().unwrap_or_default(),
+            converter: settings.currency_converter(),
+            settings,
+            base_ctx: FetchContext {
+                web_timeout: args.web_timeout,
+                ..FetchContext::default()
+            },
+            fetch_status: !args.no_status,
+            interval: Duration::from_secs(args.interval),
+            next_refresh: Instant::now(),
+            last_refresh: None,
+            list: ListState::default().with_selected(Some(0)),
+            filter: String::new(),
+            editing_filter: false,
+            message: None,
+            handle,
+            updates_tx,
+            updates,
+        }
+    }
+
+    /// Draw, handle keys and apply fetch results until the user quits
+    fn run(&mut self, terminal: &mut ratatui::DefaultTerminal) -> anyhow::Result<()> {
+        loop {
+            if Instant::now() >= self.next_refresh {
+                self.refresh_all();
+            }
+            while let Ok(update) = self.updates.try_recv() {
+                self.apply(update);
+            }
+
+            terminal.draw(|frame| self.draw(frame))?;
+
+            if event::poll(FRAME_INTERVAL)? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind != KeyEventKind::Press {
+                        continue;
+                    }
+                    match self.handle_key(key) {
+                        KeyAction::Quit => return Ok(()),
+                        KeyAction::Refresh => self.refresh_all(),
+                        KeyAction::SwitchAccount => self.switch_account(),
+                        KeyAction::None => {}
+                    }
+                }
+            }
+        }
+    }
+
+    fn refresh_all(&mut self) {
+        let ids: Vec<ProviderId> = self.panels.iter().map(|panel| panel.id).collect();
+        for id in ids {
+            self.refresh(id);
+        }
+        self.last_refresh = Some(Local::now());
+        self.next_refresh = Instant::now() + self.interval;
+    }
+
+    /// Start fetching one provider in the background
+    fn refresh(&mut self, id: ProviderId) {
+        let Some(panel) = self.panels.iter_mut().find(|panel| panel.id == id) else {
+            return;
+        };
+        if panel.loading {
+            return;
+        }
+        panel.loading = true;
+
+        let account = self.accounts.get(&id).and_then(|data| data.active_account());
+        let ctx = provider_fetch_context(id, &self.settings, account, &self.base_ctx);
+        let fetch_status = self.fetch_status;
+        let tx = self.updates_tx.clone();
+        self.handle.spawn(async move {
+            let _ = tx.send(fetch_panel(id, ctx, fetch_status).await);
+        });
+    }
+
+    fn apply(&mut self, update: PanelUpdate) {
+        if let Some(panel) = self.panels.iter_mut().find(|panel| panel.id == update.id) {
+            panel.loading = false;
+            panel.result = Some(update.result);
+            // Keep the last known status and cost when a lookup fails
+            panel.status = update.status.or(panel.status.take());
+            panel.cost_today_usd = update.cost_today_usd.or(panel.cost_today_usd);
+        }
+    }
+
+    /// Make the selected provider's next token account active and refetch
+    fn switch_account(&mut self) {
+        let Some(id) = self.selected() else {
+            return;
+        };
+        let Some(data) = self.accounts.get_mut(&id).filter(|data| data.has_multiple()) else {
+            self.message = Some(format!(
+                "{} has no other accounts (add them with `codexbar account add {}`)",
+                id.display_name(),
+                id.cli_name()
+            ));
+            return;
+        };
+        data.set_active(next_account_index(data));
+        let label = data.active_account().map(|account| account.label.clone()).unwrap_or_default();
+        self.message = match TokenAccountStore::new().save_provider(id, data) {
+            Ok(()) => Some(format!("{}: switched to {}", id.display_name(), label)),
+            Err(e) => Some(format!("{}: switched to {} (not saved: {})", id.display_name(), label, e)),
+        };
+        self.refresh(id);
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> KeyAction {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return KeyAction::Quit;
+        }
+
+        if self.editing_filter {
+            match key.code {
+                KeyCode::Enter => self.editing_filter = false,
+                KeyCode::Esc => {
+                    self.editing_filter = false;
+                    self.filter.clear();
+                }
+                KeyCode::Backspace => {
+                    self.filter.pop();
+                }
+                KeyCode::Char(c) => self.filter.push(c),
+                _ => {}
+            }
+            self.list.select(Some(0));
+            return KeyAction::None;
+        }
+
+        self.message = None;
+        match key.code {
+            KeyCode::Char('q') => KeyAction::Quit,
+            KeyCode::Esc if self.filter.is_empty() => KeyAction::Quit,
+            KeyCode::Esc => {
+                self.filter.clear();
+                self.list.select(Some(0));
+                KeyAction::None
+            }
+            KeyCode::Char('r') => KeyAction::Refresh,
+            KeyCode::Char('a') => KeyAction::SwitchAccount,
+            KeyCode::Char('/') => {
+                self.editing_filter = true;
+                KeyAction::None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.move_selection(1);
+                KeyAction::None
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.move_selection(-1);
+                KeyAction::None
+            }
+            _ => KeyAction::None,
+        }
+    }
+
+    /// Panels matching the filter
+    fn visible(&self) -> Vec<&ProviderPanel> {
+        self.panels
+            .iter()
+            .filter(|panel| matches_filter(panel.id, &self.filter))
+            .collect()
+    }
+
+    fn selected(&self) -> Option<ProviderId> {
+        let visible = self.visible();
+        visible.get(self.list.selected().unwrap_or(0)).map(|panel| panel.id)
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let count = self.visible().len();
+        if count == 0 {
+            return;
+        }
+        let current = self.list.selected().unwrap_or(0) as isize;
+        self.list.select(Some((current + delta).rem_euclid(count as isize) as usize));
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [header, body, footer] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+
+        let now = Utc::now();
+        let items: Vec<ListItem> = self
+            .visible()
+            .into_iter()
+            .map(|panel| {
+                let account = self
+                    .accounts
+                    .get(&panel.id)
+                    .filter(|data| !data.accounts.is_empty())
+                    .map(|data| (data.active_account().map(|a| a.label.as_str()).unwrap_or(""), data.count()));
+                ListItem::new(panel_lines(panel, account, &self.converter, now))
+            })
+            .collect();
+        let empty = items.is_empty();
+
+        frame.render_widget(Paragraph::new(self.header_line()), header);
+        if empty {
+            frame.render_widget(Paragraph::new(format!("  No provider matches '{}'", self.filter)), body);
+        } else {
+            let list = List::new(items).highlight_symbol("▸ ").highlight_style(Style::default().bg(Color::DarkGray));
+            frame.render_stateful_widget(list, body, &mut self.list);
+        }
+        frame.render_widget(Paragraph::new(self.footer_line()), footer);
+    }
+
+    fn header_line(&self) -> Line<'static> {
+        let count = self.panels.len();
+        let mut text = format!("CodexBar · {} provider{}", count, if count == 1 { "" } else { "s" });
+        if let Some(last) = self.last_refresh {
+            text.push_str(&format!(" · updated {}", last.format("%H:%M:%S")));
+        }
+        let loading = self.panels.iter().filter(|panel| panel.loading).count();
+        if loading > 0 {
+            text.push_str(&format!(" · refreshing {}", loading));
+        } else {
+            let next = self.next_refresh.saturating_duration_since(Instant::now()).as_secs();
+            text.push_str(&format!(" · next in {}s", next));
+        }
+        if !self.filter.is_empty() && !self.editing_filter {
+            text.push_str(&format!(" · filter: {}", self.filter));
+        }
+        Line::from(Span::styled(text, Style::default().add_modifier(Modifier::BOLD)))
+    }
+
+    fn footer_line(&self) -> Line<'static> {
+        if self.editing_filter {
+            return Line::from(format!("Filter: {}▏ (Enter to keep, Esc to clear)", self.filter));
+        }
+        if let Some(message) = &self.message {
+            return Line::from(message.clone());
+        }
+        Line::from(Span::styled(
+            "q quit · r refresh · ↑/↓ select · a next account · / filter",
+            Style::default().fg(Color::DarkGray),
+        ))
+    }
+}
+
+/// Fetch usage, status and today's local cost of one provider
+async fn fetch_panel(id: ProviderId, ctx: FetchContext, fetch_status: bool) -> PanelUpdate {
+    let (fetch, cost_today_usd) = tokio::join!(fetch_usage(id, ctx, fetch_status), today_cost_usd(id));
+    PanelUpdate {
+        id,
+        result: fetch.result,
+        status: fetch.status,
+        cost_today_usd,
+    }
+}
+
+/// Whether a provider matches the filter text (by name, case-insensitive)
+fn matches_filter(id: ProviderId, filter: &str) -> bool {
+    let filter = filter.trim().to_lowercase();
+    filter.is_empty()
+        || id.cli_name().contains(&filter)
+        || id.display_name().to_lowercase().contains(&filter)
+}
+
+/// Index of the account after the active one, wrapping around
+fn next_account_index(data: &ProviderAccountData) -> usize {
+    if data.accounts.is_empty() {
+        0
+    } else {
+        (data.clamped_active_index() + 1) % data.accounts.len()
+    }
+}
+
+fn usage_color(percent: f64) -> Color {
+    if percent >= 90.0 {
+        Color::Red
+    } else if percent >= 70.0 {
+        Color::Yellow
+    } else {
+        Color::Green
+    }
+}
+
+fn status_span(status: &StatusInfo) -> Span<'static> {
+    let (symbol, color) = match status.level {
+        StatusLevel::Operational => ("●", Color::Green),
+        StatusLevel::Degraded => ("◐", Color::Yellow),
+        StatusLevel::Partial => ("◑", Color::Yellow),
+        StatusLevel::Major => ("○", Color::Red),
+        StatusLevel::Unknown => ("?", Color::DarkGray),
+    };
+    Span::styled(format!(" {} {}", symbol, status.description), Style::default().fg(color))
+}
+
+/// Lines of one provider: a heading, then its windows, cost and errors.
+/// `account` is the active token account label and the number of accounts.
+fn panel_lines(
+    panel: &ProviderPanel,
+    account: Option<(&str, usize)>,
+    converter: &CurrencyConverter,
+    now: DateTime<Utc>,
+) -> Vec<Line<'static>> {
+    let mut heading = vec![Span::styled(
+        panel.id.display_name().to_string(),
+        Style::default().add_modifier(Modifier::BOLD),
+    )];
+    if let Some(Ok(result)) = &panel.result {
+        heading.push(Span::raw(format!(" ({})", result.source_label)));
+    }
+    if let Some(status) = &panel.status {
+        heading.push(status_span(status));
+    }
+    if let Some((label, count)) = account {
+        heading.push(Span::raw(format!(" · account {} of {}", label, count)));
+    }
+    if let Some(usd) = panel.cost_today_usd {
+        let today = converter.convert(usd, "USD").map(|amount| converter.format(amount));
+        heading.push(Span::raw(format!(" · today {}", today.unwrap_or_else(|| format!("${:.2}", usd)))));
+    }
+    if panel.loading {
+        heading.push(Span::styled(" ⟳", Style::default().fg(Color::DarkGray)));
+    }
+
+    let mut lines = vec![Line::from(heading)];
+    match &panel.result {
+        None => lines.push(Line::from(Span::styled("  Loading…", Style::default().fg(Color::DarkGray)))),
+        Some(Err(error)) => lines.push(Line::from(Span::styled(
+            format!("  Error: {}", error),
+            Style::default().fg(Color::Red),
+        ))),
+        Some(Ok(result)) => {
+            let label_width = result
+                .usage
+                .windows
+                .iter()
+                .map(|w| w.label.chars().count())
+                .max()
+                .unwrap_or(0)
+                .max(8);
+            for named in &result.usage.windows {
+                lines.push(window_line(named, label_width, now));
+            }
+            if let Some(cost) = &result.cost {
+                let text = match cost.format_limit() {
+                    Some(limit) => format!("  {:<width$}  {} / {} ({})", "Cost", cost.format_used(), limit, cost.period, width = label_width),
+                    None => format!("  {:<width$}  {} ({})", "Cost", cost.format_used(), cost.period, width = label_width),
+                };
+                lines.push(Line::from(text));
+            }
+            if result.usage.stale {
+                let as_of = result.usage.updated_at.with_timezone(&Local);
+                lines.push(Line::from(Span::styled(
+                    format!("  Stale: data from {}", as_of.format("%Y-%m-%d %H:%M")),
+                    Style::default().fg(Color::Yellow),
+                )));
+            }
+        }
+    }
+    lines.push(Line::default());
+    lines
+}
+
+/// One window: bar, percent used, reset countdown and pace
+fn window_line(named: &NamedRateWindow, label_width: usize, now: DateTime<Utc>) -> Line<'static> {
+    let window = &named.window;
+    let percent = window.used_percent.clamp(0.0, 100.0);
+    let filled = ((percent / 100.0) * BAR_WIDTH as f64).round() as usize;
+    let mut spans = vec![
+        Span::raw(format!("  {:<width$}  ", named.label, width = label_width)),
+        Span::styled("█".repeat(filled), Style::default().fg(usage_color(percent))),
+        Span::styled("░".repeat(BAR_WIDTH - filled), Style::default().fg(Color::DarkGray)),
+        Span::raw(format!(" {:>3.0}% used", window.used_percent)),
+    ];
+    if let Some(countdown) = window.format_countdown() {
+        spans.push(Span::raw(format!(" · resets in {}", countdown)));
+    }
+    if let Some(pace) = UsagePace::for_window(named, Some(now)) {
+        let color = if pace.stage.is_ahead() { Color::Yellow } else { Color::DarkGray };
+        spans.push(Span::styled(format!(" · {}", pace.format_status()), Style::default().fg(color)));
+    }
+    Line::from(spans)
+}
+
+fn is_terminal() -> bool {
+    use std::io::IsTerminal;
+    std::io::stdout().is_terminal()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{RateWindow, TokenAccount, UsageSnapshot};
+
+    fn line_text(line: &Line) -> String {
+        line.spans.iter().map(|span| span.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn test_panel_lines() {
+        let now = Utc::now();
+        let session = RateWindow::with_details(31.0, Some(300), Some(now + chrono::Duration::minutes(134)), None);
+        let weekly = RateWindow::with_details(90.0, Some(10080), Some(now + chrono::Duration::days(3)), None);
+        let usage = UsageSnapshot::from_window(NamedRateWindow::primary(session)).with_secondary(weekly);
+        let panel = ProviderPanel {
+            id: ProviderId::Claude,
+            result: Some(Ok(ProviderFetchResult::new(usage, "oauth"))),
+            status: None,
+            cost_today_usd: Some(1.5),
+            loading: false,
+        };
+        let converter = CurrencyConverter::new("USD", &HashMap::new());
+
+        let lines: Vec<String> = panel_lines(&panel, Some(("Work", 2)), &converter, now)
+            .iter()
+            .map(line_text)
+            .collect();
+        assert_eq!(lines[0], "Claude (oauth) · account Work of 2 · today $1.50");
+        assert!(lines[1].starts_with("  Session   ██████░░░░░░░░░░░░░░  31% used · resets in 2h"));
+        // 90% used with 4 of 7 days elapsed is far ahead of pace
+        assert!(lines[2].contains(" 90% used · resets in 2d 23h · Far Ahead - exhausted in"), "{}", lines[2]);
+
+        let failed = ProviderPanel {
+            result: Some(Err("Not logged in".to_string())),
+            cost_today_usd: None,
+            ..panel
+        };
+        let lines = panel_lines(&failed, None, &converter, now);
+        assert_eq!(line_text(&lines[0]), "Claude");
+        assert_eq!(line_text(&lines[1]), "  Error: Not logged in");
+    }
+
+    #[test]
+    fn test_filter_and_accounts() {
+        assert!(matches_filter(ProviderId::Claude, ""));
+        assert!(matches_filter(ProviderId::Claude, "CLA"));
+        assert!(!matches_filter(ProviderId::Codex, "cla"));
+
+        let mut data = ProviderAccountData::new();
+        assert_eq!(next_account_index(&data), 0);
+        data.add_account(TokenAccount::new("Personal", "a"));
+        data.add_account(TokenAccount::new("Work", "b"));
+        assert_eq!(next_account_index(&data), 1);
+        data.set_active(1);
+        assert_eq!(next_account_index(&data), 0);
+    }
+}
@@ -0,0 +1,564 @@
+//! Spend budgets
+//!
+//! Monthly and weekly USD budgets, per provider or for all providers together.
+//! Spend comes from two sources: the token cost in the local Codex/Claude logs
+//! and the billed amounts providers report as `CostSnapshot`s, which the usage
+//! history records on every refresh. When a provider has both, the larger
+//! amount per day counts, since both measure the same usage.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    local_log_scanner, CostSnapshot, CostUsageDayRange, CurrencyConverter, JsonlScanner, ProviderId, UsageHistoryEntry,
+    UsageHistoryStore,
+};
+
+/// Days of spend averaged for the moving-average forecast
+pub const BUDGET_MOVING_AVERAGE_DAYS: i64 = 7;
+
+/// Budget percentages that trigger a notification unless configured otherwise
+pub const DEFAULT_BUDGET_ALERT_THRESHOLDS: [f64; 3] = [50.0, 80.0, 100.0];
+
+/// Length of a budget period
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetPeriod {
+    Monthly,
+    Weekly,
+}
+
+impl BudgetPeriod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BudgetPeriod::Monthly => "monthly",
+            BudgetPeriod::Weekly => "weekly",
+        }
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            BudgetPeriod::Monthly => "Monthly",
+            BudgetPeriod::Weekly => "Weekly",
+        }
+    }
+
+    /// First and last day of the period containing `day` (weeks start on Monday)
+    pub fn bounds(&self, day: NaiveDate) -> (NaiveDate, NaiveDate) {
+        match self {
+            BudgetPeriod::Monthly => {
+                let start = day.with_day(1).unwrap_or(day);
+                let next = if start.month() == 12 {
+                    NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)
+                } else {
+                    NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)
+                };
+                (start, next.map_or(day, |next| next - Duration::days(1)))
+            }
+            BudgetPeriod::Weekly => {
+                let start = day - Duration::days(day.weekday().num_days_from_monday() as i64);
+                (start, start + Duration::days(6))
+            }
+        }
+    }
+}
+
+/// Monthly and weekly limits in USD
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct BudgetLimits {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monthly: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weekly: Option<f64>,
+}
+
+impl BudgetLimits {
+    /// Limit for a period, if one is set
+    pub fn get(&self, period: BudgetPeriod) -> Option<f64> {
+        match period {
+            BudgetPeriod::Monthly => self.monthly,
+            BudgetPeriod::Weekly => self.weekly,
+        }
+        .filter(|limit| limit.is_finite() && *limit > 0.0)
+    }
+}
+
+/// What a budget covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BudgetScope {
+    /// All providers together
+    Total,
+    Provider(ProviderId),
+}
+
+impl BudgetScope {
+    /// "total" or the provider's CLI name
+    pub fn key(&self) -> &'static str {
+        match self {
+            BudgetScope::Total => "total",
+            BudgetScope::Provider(id) => id.cli_name(),
+        }
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            BudgetScope::Total => "All providers",
+            BudgetScope::Provider(id) => id.display_name(),
+        }
+    }
+}
+
+/// Budget configuration (the `budgets` section of the settings)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BudgetSettings {
+    /// Limits on the combined spend of all providers
+    pub total: BudgetLimits,
+    /// Limits per provider, keyed by CLI name
+    pub providers: BTreeMap<String, BudgetLimits>,
+    /// Budget percentages that trigger a notification
+    pub alert_thresholds: Vec<f64>,
+}
+
+impl Default for BudgetSettings {
+    fn default() -> Self {
+        Self {
+            total: BudgetLimits::default(),
+            providers: BTreeMap::new(),
+            alert_thresholds: DEFAULT_BUDGET_ALERT_THRESHOLDS.to_vec(),
+        }
+    }
+}
+
+impl BudgetSettings {
+    /// Configured budgets as (scope, period, limit): the total first, then
+    /// providers by name. Unknown provider names are skipped.
+    pub fn budgets(&self) -> Vec<(BudgetScope, BudgetPeriod, f64)> {
+        let scopes = std::iter::once((BudgetScope::Total, &self.total)).chain(
+            self.providers
+                .iter()
+                .filter_map(|(name, limits)| ProviderId::from_cli_name(name).map(|id| (BudgetScope::Provider(id), limits))),
+        );
+        scopes
+            .flat_map(|(scope, limits)| {
+                [BudgetPeriod::Monthly, BudgetPeriod::Weekly]
+                    .into_iter()
+                    .filter_map(move |period| limits.get(period).map(|limit| (scope, period, limit)))
+            })
+            .collect()
+    }
+
+    /// Provider names in the settings that match no provider
+    pub fn unknown_providers(&self) -> Vec<&str> {
+        self.providers
+            .keys()
+            .filter(|name| ProviderId::from_cli_name(name).is_none())
+            .map(String::as_str)
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.budgets().is_empty()
+    }
+
+    /// Providers with a budget of their own
+    pub fn providers(&self) -> Vec<ProviderId> {
+        let mut providers: Vec<ProviderId> = self
+            .budgets()
+            .into_iter()
+            .filter_map(|(scope, _, _)| match scope {
+                BudgetScope::Provider(id) => Some(id),
+                BudgetScope::Total => None,
+            })
+            .collect();
+        providers.dedup();
+        providers
+    }
+
+    /// Providers whose spend counts: the enabled ones plus any with a budget
+    pub fn spend_providers(&self, enabled: &[ProviderId]) -> Vec<ProviderId> {
+        let mut providers = enabled.to_vec();
+        for id in self.providers() {
+            if !providers.contains(&id) {
+                providers.push(id);
+            }
+        }
+        providers
+    }
+}
+
+/// Daily spend in USD per provider
+#[derive(Debug, Clone, Default)]
+pub struct SpendHistory {
+    days: HashMap<ProviderId, BTreeMap<NaiveDate, f64>>,
+}
+
+impl SpendHistory {
+    /// Add spend to a provider's day
+    pub fn add(&mut self, provider: ProviderId, day: NaiveDate, usd: f64) {
+        *self.days.entry(provider).or_default().entry(day).or_insert(0.0) += usd;
+    }
+
+    /// Combine with another source, keeping the larger amount per provider and day
+    pub fn merge_max(&mut self, other: SpendHistory) {
+        for (provider, days) in other.days {
+            let mine = self.days.entry(provider).or_default();
+            for (day, usd) in days {
+                let slot = mine.entry(day).or_insert(0.0);
+                *slot = slot.max(usd);
+            }
+        }
+    }
+
+    /// Providers with any recorded spend
+    pub fn providers(&self) -> Vec<ProviderId> {
+        let mut providers: Vec<ProviderId> = self.days.keys().copied().collect();
+        providers.sort_by_key(|id| id.cli_name());
+        providers
+    }
+
+    /// Daily spend of a scope from `since` to `until` (inclusive)
+    pub fn daily(&self, scope: BudgetScope, since: NaiveDate, until: NaiveDate) -> BTreeMap<NaiveDate, f64> {
+        let mut daily = BTreeMap::new();
+        let providers = self
+            .days
+            .iter()
+            .filter(|(id, _)| scope == BudgetScope::Total || scope == BudgetScope::Provider(**id));
+        for (_, days) in providers {
+            for (day, usd) in days.range(since..=until) {
+                *daily.entry(*day).or_insert(0.0) += usd;
+            }
+        }
+        daily
+    }
+
+    /// Spend of a scope from `since` to `until` (inclusive)
+    pub fn total(&self, scope: BudgetScope, since: NaiveDate, until: NaiveDate) -> f64 {
+        self.daily(scope, since, until).values().sum()
+    }
+
+    /// Token cost from the agents' local logs (other providers are skipped)
+    pub fn from_local_logs(providers: &[ProviderId], since: NaiveDate, until: NaiveDate) -> Self {
+        let range = CostUsageDayRange::new(since, until);
+        let mut spend = Self::default();
+        for &provider in providers {
+            if local_log_scanner(provider).is_none() {
+                continue;
+            }
+            let cache = JsonlScanner::load_and_refresh(provider, &range, None);
+            for (day_key, usd) in cache.daily_costs(&range) {
+                if let Some(day) = CostUsageDayRange::parse_day_key(&day_key).filter(|_| usd > 0.0) {
+                    spend.add(provider, day, usd);
+                }
+            }
+        }
+        spend
+    }
+
+    /// Spend since `since` derived from the cost snapshots in the usage
+    /// history (oldest first). A credit balance counts its decreases; a
+    /// period-to-date amount counts its increases, and starts over when the
+    /// billing period resets. Snapshots from before `since` only serve as the
+    /// baseline for the first one inside the window.
+    pub fn from_history(entries: &[UsageHistoryEntry], converter: &CurrencyConverter, since: NaiveDate) -> Self {
+        let mut spend = Self::default();
+        let mut previous: HashMap<ProviderId, (String, f64)> = HashMap::new();
+        for entry in entries {
+            let Some(cost) = &entry.cost else {
+                continue;
+            };
+            let Some(used) = converter.convert_between(cost.used, &cost.currency_code, "USD") else {
+                continue;
+            };
+            let day = entry.captured_at.date_naive();
+            let is_balance = cost.period.eq_ignore_ascii_case("credits");
+            let spent = match previous.get(&entry.provider).filter(|(period, _)| *period == cost.period) {
+                Some((_, before)) if is_balance => (before - used).max(0.0),
+                Some((_, before)) if used >= *before => used - before,
+                // A new billing period began since the last snapshot
+                Some(_) => used,
+                // Without a baseline, what was already spent only counts
+                // when the billing period started inside the window
+                None if !is_balance && period_start(cost).is_some_and(|start| start >= since) => used,
+                None => 0.0,
+            };
+            previous.insert(entry.provider, (cost.period.clone(), used));
+            if spent > 0.0 && day >= since {
+                spend.add(entry.provider, day, spent);
+            }
+        }
+        spend
+    }
+
+    /// Spend of `providers` from both sources
+    pub fn collect(
+        providers: &[ProviderId],
+        history: Option<&UsageHistoryStore>,
+        converter: &CurrencyConverter,
+        since: NaiveDate,
+        until: NaiveDate,
+    ) -> Self {
+        let mut spend = Self::from_local_logs(providers, since, until);
+        if let Some(store) = history {
+            let since_time = DateTime::<Utc>::from_naive_utc_and_offset(since.and_hms_opt(0, 0, 0).unwrap_or_default(), Utc);
+            let mut entries = Vec::new();
+            for &provider in providers {
+                match store.latest_cost_before(provider, since_time) {
+                    Ok(baseline) => entries.extend(baseline),
+                    Err(e) => tracing::warn!("Failed to read usage history for budgets: {}", e),
+                }
+            }
+            match store.query(None, since_time) {
+                Ok(recent) => {
+                    entries.extend(recent.into_iter().filter(|e| providers.contains(&e.provider)));
+                    spend.merge_max(Self::from_history(&entries, converter, since));
+                }
+                Err(e) => tracing::warn!("Failed to read usage history for budgets: {}", e),
+            }
+        }
+        spend
+    }
+}
+
+/// First day of the billing period a cost snapshot belongs to, when it
+/// reports when the period resets
+fn period_start(cost: &CostSnapshot) -> Option<NaiveDate> {
+    let resets_on = cost.resets_at?.date_naive();
+    match cost.period.to_ascii_lowercase().as_str() {
+        "monthly" => resets_on.checked_sub_months(Months::new(1)),
+        "weekly" => Some(resets_on - Duration::days(7)),
+        "daily" => Some(resets_on - Duration::days(1)),
+        _ => None,
+    }
+}
+
+/// Expected spend at the end of a budget period
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct BudgetForecast {
+    /// Spend so far extrapolated over the whole period
+    pub linear_usd: f64,
+    /// Spend so far plus the recent daily average for each remaining day
+    pub moving_average_usd: f64,
+}
+
+impl BudgetForecast {
+    /// The higher of the two forecasts
+    pub fn high_usd(&self) -> f64 {
+        self.linear_usd.max(self.moving_average_usd)
+    }
+}
+
+/// A budget checked against spend
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetStatus {
+    pub scope: BudgetScope,
+    pub period: BudgetPeriod,
+    pub limit_usd: f64,
+    pub spent_usd: f64,
+    /// First and last day of the current period
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub forecast: BudgetForecast,
+}
+
+impl BudgetStatus {
+    /// Share of the budget spent
+    pub fn percent(&self) -> f64 {
+        self.spent_usd / self.limit_usd * 100.0
+    }
+
+    /// Share of the budget the higher forecast reaches
+    pub fn forecast_percent(&self) -> f64 {
+        self.forecast.high_usd() / self.limit_usd * 100.0
+    }
+
+    /// The highest of `thresholds` (percentages) the spend has reached
+    pub fn reached_threshold(&self, thresholds: &[f64]) -> Option<f64> {
+        let percent = self.percent();
+        thresholds
+            .iter()
+            .copied()
+            .filter(|threshold| percent >= *threshold)
+            .max_by(|a, b| a.total_cmp(b))
+    }
+}
+
+/// Check every configured budget against `spend` on `today`
+pub fn evaluate_budgets(settings: &BudgetSettings, spend: &SpendHistory, today: NaiveDate) -> Vec<BudgetStatus> {
+    settings
+        .budgets()
+        .into_iter()
+        .map(|(scope, period, limit_usd)| {
+            let (start, end) = period.bounds(today);
+            let spent_usd = spend.total(scope, start, today);
+            let elapsed_days = (today - start).num_days() + 1;
+            let remaining_days = (end - today).num_days();
+            let recent = spend.total(scope, today - Duration::days(BUDGET_MOVING_AVERAGE_DAYS - 1), today);
+            BudgetStatus {
+                scope,
+                period,
+                limit_usd,
+                spent_usd,
+                start,
+                end,
+                forecast: BudgetForecast {
+                    linear_usd: spent_usd / elapsed_days as f64 * (elapsed_days + remaining_days) as f64,
+                    moving_average_usd: spent_usd
+                        + recent / BUDGET_MOVING_AVERAGE_DAYS as f64 * remaining_days as f64,
+                },
+            }
+        })
+        .collect()
+}
+
+/// First day of spend needed to evaluate budgets on `today` (the earliest
+/// period start, or the start of the moving-average window)
+pub fn budget_spend_since(today: NaiveDate) -> NaiveDate {
+    let month_start = BudgetPeriod::Monthly.bounds(today).0;
+    let week_start = BudgetPeriod::Weekly.bounds(today).0;
+    month_start
+        .min(week_start)
+        .min(today - Duration::days(BUDGET_MOVING_AVERAGE_DAYS - 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn day(month: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, month, d).unwrap()
+    }
+
+    fn entry(provider: ProviderId, d: u32, hour: u32, used: f64, period: &str) -> UsageHistoryEntry {
+        UsageHistoryEntry {
+            provider,
+            captured_at: Utc.with_ymd_and_hms(2026, 1, d, hour, 0, 0).unwrap(),
+            source_label: "api".to_string(),
+            account_email: None,
+            account_organization: None,
+            login_method: None,
+            windows: Vec::new(),
+            cost: Some(CostSnapshot::new(used, "USD", period)),
+        }
+    }
+
+    #[test]
+    fn test_budget_period_bounds() {
+        assert_eq!(BudgetPeriod::Monthly.bounds(day(2, 14)), (day(2, 1), day(2, 28)));
+        assert_eq!(
+            BudgetPeriod::Monthly.bounds(day(12, 31)),
+            (day(12, 1), day(12, 31))
+        );
+        // 2026-01-15 is a Thursday
+        assert_eq!(BudgetPeriod::Weekly.bounds(day(1, 15)), (day(1, 12), day(1, 18)));
+        assert_eq!(BudgetPeriod::Weekly.bounds(day(1, 12)), (day(1, 12), day(1, 18)));
+    }
+
+    #[test]
+    fn test_spend_from_history() {
+        let entries = vec![
+            // Credits: only decreases count; a top-up is not spend
+            entry(ProviderId::Codex, 10, 9, 50.0, "Credits"),
+            entry(ProviderId::Codex, 10, 12, 45.0, "Credits"),
+            entry(ProviderId::Codex, 11, 9, 60.0, "Credits"),
+            entry(ProviderId::Codex, 11, 12, 58.5, "Credits"),
+            // Month-to-date: the first snapshot is only a baseline, a drop
+            // means a new billing period
+            entry(ProviderId::Cursor, 10, 9, 20.0, "Monthly"),
+            entry(ProviderId::Cursor, 10, 12, 26.0, "Monthly"),
+            entry(ProviderId::Cursor, 11, 9, 4.0, "Monthly"),
+        ];
+        let spend = SpendHistory::from_history(&entries, &CurrencyConverter::default(), day(1, 1));
+        let codex = spend.daily(BudgetScope::Provider(ProviderId::Codex), day(1, 1), day(1, 31));
+        assert_eq!(codex.into_iter().collect::<Vec<_>>(), vec![(day(1, 10), 5.0), (day(1, 11), 1.5)]);
+        assert_eq!(spend.total(BudgetScope::Provider(ProviderId::Cursor), day(1, 10), day(1, 10)), 6.0);
+        assert_eq!(spend.total(BudgetScope::Provider(ProviderId::Cursor), day(1, 11), day(1, 11)), 4.0);
+        assert_eq!(spend.total(BudgetScope::Total, day(1, 1), day(1, 31)), 16.5);
+
+        // Local logs and billed amounts measure the same usage
+        let mut local = SpendHistory::default();
+        local.add(ProviderId::Codex, day(1, 10), 3.0);
+        local.add(ProviderId::Codex, day(1, 11), 2.0);
+        local.merge_max(spend);
+        assert_eq!(local.total(BudgetScope::Provider(ProviderId::Codex), day(1, 1), day(1, 31)), 7.0);
+    }
+
+    #[test]
+    fn test_spend_from_history_across_month_boundary() {
+        let monthly = |month: u32, d: u32, used: f64, resets: (u32, u32)| UsageHistoryEntry {
+            captured_at: Utc.with_ymd_and_hms(2026, month, d, 9, 0, 0).unwrap(),
+            cost: Some(
+                CostSnapshot::new(used, "USD", "Monthly")
+                    .with_resets_at(Utc.with_ymd_and_hms(2026, resets.0, resets.1, 0, 0, 0).unwrap()),
+            ),
+            ..entry(ProviderId::Cursor, 1, 0, 0.0, "Monthly")
+        };
+        let converter = CurrencyConverter::default();
+        // The window reaches back into January for the moving average
+        let since = day(1, 27);
+
+        // With a baseline from before the window, only the increase counts
+        let entries = vec![
+            monthly(1, 20, 30.0, (2, 1)),
+            monthly(1, 28, 34.0, (2, 1)),
+            monthly(2, 1, 2.0, (3, 1)),
+        ];
+        let spend = SpendHistory::from_history(&entries, &converter, since);
+        assert_eq!(spend.total(BudgetScope::Total, day(1, 1), day(1, 27)), 0.0);
+        assert_eq!(spend.total(BudgetScope::Total, day(1, 28), day(1, 28)), 4.0);
+        assert_eq!(spend.total(BudgetScope::Total, day(2, 1), day(2, 1)), 2.0);
+
+        // Without one, January's month-to-date amount is not booked on the
+        // first day seen, but February's started inside the window
+        let spend = SpendHistory::from_history(&entries[1..], &converter, since);
+        assert_eq!(spend.total(BudgetScope::Total, day(1, 1), day(1, 31)), 0.0);
+        assert_eq!(spend.total(BudgetScope::Total, day(2, 1), day(2, 1)), 2.0);
+        let spend = SpendHistory::from_history(&entries[2..], &converter, since);
+        assert_eq!(spend.total(BudgetScope::Total, day(2, 1), day(2, 1)), 2.0);
+    }
+
+    #[test]
+    fn test_evaluate_budgets() {
+        let settings: BudgetSettings = serde_json::from_str(
+            r#"{"total": {"monthly": 100, "weekly": 0}, "providers": {"claude": {"weekly": 20}, "nope": {"monthly": 5}}}"#,
+        )
+        .unwrap();
+        assert_eq!(settings.alert_thresholds, DEFAULT_BUDGET_ALERT_THRESHOLDS.to_vec());
+        assert_eq!(settings.providers(), vec![ProviderId::Claude]);
+        assert_eq!(settings.unknown_providers(), vec!["nope"]);
+
+        let mut spend = SpendHistory::default();
+        // Before January: outside the monthly budget, inside the 7-day average
+        spend.add(ProviderId::Codex, NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(), 7.0);
+        for d in 1..=10 {
+            spend.add(ProviderId::Claude, day(1, d), 2.0);
+        }
+
+        // Saturday 2026-01-10
+        let statuses = evaluate_budgets(&settings, &spend, day(1, 10));
+        assert_eq!(statuses.len(), 2);
+
+        let monthly = &statuses[0];
+        assert_eq!((monthly.scope, monthly.period), (BudgetScope::Total, BudgetPeriod::Monthly));
+        assert_eq!((monthly.start, monthly.end), (day(1, 1), day(1, 31)));
+        assert_eq!(monthly.spent_usd, 20.0);
+        assert!((monthly.forecast.linear_usd - 62.0).abs() < 1e-9);
+        // $14 over the last 7 days -> $2/day for 21 more days
+        assert!((monthly.forecast.moving_average_usd - 62.0).abs() < 1e-9);
+        assert_eq!(monthly.reached_threshold(&settings.alert_thresholds), None);
+
+        let weekly = &statuses[1];
+        assert_eq!(weekly.scope, BudgetScope::Provider(ProviderId::Claude));
+        assert_eq!(weekly.spent_usd, 12.0);
+        assert_eq!(weekly.percent(), 60.0);
+        assert_eq!(weekly.reached_threshold(&settings.alert_thresholds), Some(50.0));
+        assert!((weekly.forecast_percent() - 70.0).abs() < 1e-9);
+
+        assert_eq!(budget_spend_since(day(1, 10)), day(1, 1));
+        // Early in the month the moving average reaches back further
+        assert_eq!(budget_spend_since(day(2, 2)), day(1, 27));
+    }
+}
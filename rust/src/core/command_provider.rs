@@ -0,0 +1,298 @@
+//! External-command providers
+//!
+//! A command provider runs an executable and reads a versioned JSON document
+//! from its stdout. Command providers are declared next to the HTTP ones in
+//! the custom providers file:
+//!
+//! ```toml
+//! [[command]]
+//! id = "gateway"
+//! name = "Internal Gateway"
+//! command = "C:\\tools\\gateway-usage.exe"
+//! args = ["--json"]
+//! timeout_secs = 20
+//! ```
+//!
+//! On success the document mirrors `ProviderFetchResult`:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "usage": { "windows": [{ "id": "primary", "label": "Daily", "kind": "session", "used_percent": 42.0 }] },
+//!   "cost": { "used": 12.5, "currency_code": "USD", "period": "Monthly" },
+//!   "source_label": "gateway"
+//! }
+//! ```
+//!
+//! On failure it carries an error instead:
+//!
+//! ```json
+//! { "version": 1, "error": { "kind": "auth_required", "message": "token expired" } }
+//! ```
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use super::{CostSnapshot, ProviderError, ProviderFetchResult, ProviderId, UsageSnapshot};
+
+/// Protocol version understood by this build
+pub const COMMAND_PROVIDER_PROTOCOL_VERSION: u32 = 1;
+
+/// Environment variables set for the command
+pub const COMMAND_PROVIDER_ENV_ID: &str = "CODEXBAR_PROVIDER_ID";
+pub const COMMAND_PROVIDER_ENV_VERSION: &str = "CODEXBAR_PROTOCOL_VERSION";
+pub const COMMAND_PROVIDER_ENV_API_KEY: &str = "CODEXBAR_API_KEY";
+
+/// One external-command provider definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandProviderConfig {
+    /// CLI name (lowercase letters, digits, `-` and `_`)
+    pub id: String,
+    /// Display name
+    pub name: String,
+    /// Executable name (looked up in PATH) or path
+    pub command: String,
+    /// Arguments passed to the executable
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Extra environment variables
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Working directory
+    #[serde(default)]
+    pub working_directory: Option<PathBuf>,
+    /// Time allowed for the command to finish, in seconds
+    #[serde(default = "default_command_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Link shown on the detail card
+    #[serde(default)]
+    pub dashboard_url: Option<String>,
+}
+
+fn default_command_timeout_secs() -> u64 {
+    30
+}
+
+impl CommandProviderConfig {
+    /// Provider ID for this definition
+    pub fn provider_id(&'static self) -> ProviderId {
+        ProviderId::Custom(self.id.as_str())
+    }
+
+    /// Check the definition, returning a description of the first problem
+    pub fn check(&self) -> Result<(), String> {
+        if self.command.trim().is_empty() {
+            return Err("command is empty".to_string());
+        }
+        if self.timeout_secs == 0 {
+            return Err("timeout_secs must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Error kinds a command may report in its output document
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandProviderErrorKind {
+    AuthRequired,
+    RateLimited,
+    NotInstalled,
+    Timeout,
+    Network,
+    Parse,
+    Other,
+}
+
+/// Error reported by a command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandProviderErrorDocument {
+    pub kind: CommandProviderErrorKind,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+impl CommandProviderErrorDocument {
+    /// Map the reported error onto a `ProviderError`
+    pub fn into_provider_error(self, name: &str) -> ProviderError {
+        let message = self
+            .message
+            .filter(|m| !m.trim().is_empty())
+            .unwrap_or_else(|| format!("{:?}", self.kind));
+        match self.kind {
+            CommandProviderErrorKind::AuthRequired => ProviderError::AuthRequired,
+            CommandProviderErrorKind::RateLimited => ProviderError::RateLimited,
+            CommandProviderErrorKind::Timeout => ProviderError::Timeout,
+            CommandProviderErrorKind::NotInstalled => ProviderError::NotInstalled(format!("{}: {}", name, message)),
+            CommandProviderErrorKind::Parse => ProviderError::Parse(format!("{}: {}", name, message)),
+            CommandProviderErrorKind::Network => ProviderError::Network(format!("{}: {}", name, message)),
+            CommandProviderErrorKind::Other => ProviderError::Other(format!("{}: {}", name, message)),
+        }
+    }
+}
+
+/// Success document body
+#[derive(Debug, Deserialize)]
+struct CommandProviderDocument {
+    usage: UsageSnapshot,
+    #[serde(default)]
+    cost: Option<CostSnapshot>,
+    #[serde(default)]
+    source_label: Option<String>,
+}
+
+/// Parse and validate a command's stdout
+///
+/// `name` labels error messages. Anything that does not match the protocol
+/// is reported as `ProviderError::Parse`.
+pub fn parse_command_output(name: &str, stdout: &str) -> Result<ProviderFetchResult, ProviderError> {
+    let parse_error = |message: String| ProviderError::Parse(format!("{}: {}", name, message));
+
+    let trimmed = stdout.trim();
+    if trimmed.is_empty() {
+        return Err(parse_error("command printed nothing on stdout".to_string()));
+    }
+    let mut value: serde_json::Value =
+        serde_json::from_str(trimmed).map_err(|e| parse_error(format!("stdout is not JSON: {}", e)))?;
+
+    let Some(object) = value.as_object_mut() else {
+        return Err(parse_error("output must be a JSON object".to_string()));
+    };
+    match object.get("version").and_then(|v| v.as_u64()) {
+        Some(v) if v == COMMAND_PROVIDER_PROTOCOL_VERSION as u64 => {}
+        Some(v) => {
+            return Err(parse_error(format!(
+                "unsupported protocol version {} (expected {})",
+                v, COMMAND_PROVIDER_PROTOCOL_VERSION
+            )))
+        }
+        None => return Err(parse_error("missing integer \"version\" field".to_string())),
+    }
+
+    if let Some(error) = object.get("error") {
+        let error: CommandProviderErrorDocument = serde_json::from_value(error.clone())
+            .map_err(|e| parse_error(format!("invalid \"error\": {}", e)))?;
+        return Err(error.into_provider_error(name));
+    }
+
+    // Commands may leave capture times out; stamp them with the fetch time
+    let now = serde_json::Value::String(Utc::now().to_rfc3339());
+    for key in ["usage", "cost"] {
+        if let Some(section) = object.get_mut(key).and_then(|v| v.as_object_mut()) {
+            section.entry("updated_at").or_insert_with(|| now.clone());
+        }
+    }
+
+    let document: CommandProviderDocument =
+        serde_json::from_value(value).map_err(|e| parse_error(format!("invalid document: {}", e)))?;
+
+    for window in &document.usage.windows {
+        let percent = window.window.used_percent;
+        if !percent.is_finite() || !(0.0..=100.0).contains(&percent) {
+            return Err(parse_error(format!(
+                "window '{}' used_percent {} is outside 0-100",
+                window.id, percent
+            )));
+        }
+    }
+    if let Some(ref cost) = document.cost {
+        if !cost.used.is_finite() || cost.limit.is_some_and(|l| !l.is_finite()) {
+            return Err(parse_error("cost amounts must be finite numbers".to_string()));
+        }
+    }
+
+    let mut result = ProviderFetchResult::new(
+        document.usage,
+        document.source_label.unwrap_or_else(|| "command".to_string()),
+    );
+    if let Some(cost) = document.cost {
+        result = result.with_cost(cost);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_success_document() {
+        let stdout = r#"
+            {
+              "version": 1,
+              "usage": {
+                "windows": [
+                  { "id": "primary", "label": "Daily", "kind": "session", "used_percent": 42.0 },
+                  { "id": "secondary", "label": "Monthly", "kind": "monthly", "used_percent": 10.0 }
+                ],
+                "account_email": "ops@example.com"
+              },
+              "cost": { "used": 12.5, "currency_code": "CNY", "period": "Monthly" },
+              "source_label": "gateway"
+            }
+        "#;
+        let result = parse_command_output("Gateway", stdout).unwrap();
+        assert_eq!(result.source_label, "gateway");
+        assert_eq!(result.usage.primary().used_percent, 42.0);
+        assert_eq!(result.usage.secondary().unwrap().used_percent, 10.0);
+        assert_eq!(result.usage.windows[0].label, "Daily");
+        assert_eq!(result.usage.account_email.as_deref(), Some("ops@example.com"));
+        assert_eq!(result.cost.unwrap().currency_code, "CNY");
+    }
+
+    #[test]
+    fn test_parse_legacy_usage_shape() {
+        let stdout = r#"{ "version": 1, "usage": { "primary": { "used_percent": 5.0 } } }"#;
+        let result = parse_command_output("Gateway", stdout).unwrap();
+        assert_eq!(result.source_label, "command");
+        assert_eq!(result.usage.primary().used_percent, 5.0);
+    }
+
+    #[test]
+    fn test_parse_error_document() {
+        let auth = r#"{ "version": 1, "error": { "kind": "auth_required", "message": "token expired" } }"#;
+        assert!(matches!(parse_command_output("Gateway", auth), Err(ProviderError::AuthRequired)));
+
+        let limited = r#"{ "version": 1, "error": { "kind": "rate_limited" } }"#;
+        assert!(matches!(parse_command_output("Gateway", limited), Err(ProviderError::RateLimited)));
+
+        let other = r#"{ "version": 1, "error": { "kind": "other", "message": "gateway down" } }"#;
+        match parse_command_output("Gateway", other) {
+            Err(ProviderError::Other(message)) => assert_eq!(message, "Gateway: gateway down"),
+            other => panic!("unexpected {:?}", other.map(|r| r.source_label)),
+        }
+    
+        let network = r#"{ "version": 1, "error": { "kind": "network", "message": "connection refused" } }"#;
+        match parse_command_output("Gateway", network) {
+            Err(error @ ProviderError::Network(_)) => {
+                assert!(error.is_transient());
+                assert_eq!(error.to_string(), "Network error: Gateway: connection refused");
+            }
+            other => panic!("unexpected {:?}", other.map(|r| r.source_label)),
+        }
+    }
+
+    #[test]
+    fn test_parse_rejects_schema_violations() {
+        let cases = [
+            "",
+            "not json",
+            "[1, 2]",
+            r#"{ "usage": { "primary": { "used_percent": 5.0 } } }"#,
+            r#"{ "version": 2, "usage": { "primary": { "used_percent": 5.0 } } }"#,
+            r#"{ "version": 1 }"#,
+            r#"{ "version": 1, "usage": { "windows": [] } }"#,
+            r#"{ "version": 1, "usage": { "primary": { "used_percent": 150.0 } } }"#,
+            r#"{ "version": 1, "error": { "kind": "exploded" } }"#,
+        ];
+        for stdout in cases {
+            assert!(
+                matches!(parse_command_output("Gateway", stdout), Err(ProviderError::Parse(_))),
+                "expected Parse error for {:?}",
+                stdout
+            );
+        }
+    }
+}
@@ -0,0 +1,196 @@
+//! Cache efficiency analytics
+//!
+//! Breaks the token usage from the agents' local logs down by model and day into how
+//! well the prompt cache is used: the share of input served from the cache,
+//! what cache reads and writes cost, the output/input ratio, and how much the
+//! same tokens would have cost without caching. Costs use each model's base
+//! rates, since pricing tiers apply per request and the daily totals cannot
+//! reproduce them.
+
+use std::collections::BTreeMap;
+
+use super::{CostGroupBy, CostUsageCache, CostUsageDayRange, CostUsagePricing, CostUsageTokens, ProviderId};
+
+/// Cache use and its cost for a set of tokens
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CacheEfficiency {
+    pub tokens: CostUsageTokens,
+    /// Cost of the cache reads
+    pub cache_read_usd: f64,
+    /// Cost of the cache writes
+    pub cache_write_usd: f64,
+    /// Cost of all tokens at base rates
+    pub cost_usd: f64,
+    /// Cost at base rates with cache reads and writes priced as plain input
+    pub uncached_cost_usd: f64,
+}
+
+impl CacheEfficiency {
+    /// Price `tokens` of one model (costs stay 0 for models without a price)
+    pub fn from_tokens(provider: ProviderId, model: &str, tokens: &CostUsageTokens) -> Self {
+        let mut efficiency = Self {
+            tokens: *tokens,
+            ..Self::default()
+        };
+        if let Some(pricing) = CostUsagePricing::model_pricing(provider, model) {
+            let per_token = |rate: f64| rate / 1_000_000.0;
+            let input = per_token(pricing.input);
+            let output_usd = tokens.output as f64 * per_token(pricing.output);
+            efficiency.cache_read_usd = tokens.cache_read as f64 * per_token(pricing.cache_read.unwrap_or(pricing.input));
+            efficiency.cache_write_usd =
+                tokens.cache_creation as f64 * per_token(pricing.cache_write.unwrap_or(pricing.input));
+            efficiency.cost_usd =
+                tokens.input as f64 * input + efficiency.cache_read_usd + efficiency.cache_write_usd + output_usd;
+            efficiency.uncached_cost_usd = efficiency.input_tokens() as f64 * input + output_usd;
+        }
+        efficiency
+    }
+
+    pub fn add(&mut self, other: &CacheEfficiency) {
+        self.tokens.add(&other.tokens);
+        self.cache_read_usd += other.cache_read_usd;
+        self.cache_write_usd += other.cache_write_usd;
+        self.cost_usd += other.cost_usd;
+        self.uncached_cost_usd += other.uncached_cost_usd;
+    }
+
+    /// All input tokens: uncached, cache reads and cache writes
+    pub fn input_tokens(&self) -> i64 {
+        self.tokens.input + self.tokens.cache_read + self.tokens.cache_creation
+    }
+
+    /// Share of input tokens served from the cache (0-1)
+    pub fn cache_hit_ratio(&self) -> Option<f64> {
+        let input = self.input_tokens();
+        (input > 0).then(|| self.tokens.cache_read as f64 / input as f64)
+    }
+
+    /// Output tokens per input token
+    pub fn output_input_ratio(&self) -> Option<f64> {
+        let input = self.input_tokens();
+        (input > 0).then(|| self.tokens.output as f64 / input as f64)
+    }
+
+    /// What caching saved compared with uncached pricing (negative when cache
+    /// writes cost more than the reads saved)
+    pub fn savings_usd(&self) -> f64 {
+        self.uncached_cost_usd - self.cost_usd
+    }
+}
+
+/// Cache efficiency of a provider's usage over a day range
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CostAnalytics {
+    /// Per model, most expensive first
+    pub by_model: Vec<(String, CacheEfficiency)>,
+    /// Per day with usage, oldest first
+    pub by_day: Vec<(String, CacheEfficiency)>,
+    pub total: CacheEfficiency,
+}
+
+impl CostAnalytics {
+    /// Analyze the cached usage inside `range`
+    pub fn from_cache(cache: &CostUsageCache, provider: ProviderId, range: &CostUsageDayRange) -> Self {
+        let mut by_model: BTreeMap<String, CacheEfficiency> = BTreeMap::new();
+        let mut by_day: BTreeMap<String, CacheEfficiency> = BTreeMap::new();
+        let mut total = CacheEfficiency::default();
+
+        for group in cache.grouped(range, &[CostGroupBy::Day, CostGroupBy::Model]) {
+            let [day, model] = group.keys.as_slice() else {
+                continue;
+            };
+            let efficiency = CacheEfficiency::from_tokens(provider, model, &group.tokens);
+            by_model.entry(model.clone()).or_default().add(&efficiency);
+            by_day.entry(day.clone()).or_default().add(&efficiency);
+            total.add(&efficiency);
+        }
+
+        let mut by_model: Vec<(String, CacheEfficiency)> = by_model.into_iter().collect();
+        by_model.sort_by(|a, b| b.1.cost_usd.total_cmp(&a.1.cost_usd));
+        Self {
+            by_model,
+            by_day: by_day.into_iter().collect(),
+            total,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::CostUsageFileUsage;
+    use std::collections::HashMap;
+
+    fn tokens(input: i64, cache_read: i64, cache_creation: i64, output: i64) -> CostUsageTokens {
+        CostUsageTokens {
+            input,
+            cache_read,
+            cache_creation,
+            output,
+            cost_usd: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_cache_efficiency() {
+        // claude-sonnet-4-5: $3 input, $15 output, $0.30 cache read, $3.75 cache write
+        let efficiency =
+            CacheEfficiency::from_tokens(ProviderId::Claude, "claude-sonnet-4-5", &tokens(100_000, 800_000, 100_000, 50_000));
+        assert_eq!(efficiency.cache_hit_ratio(), Some(0.8));
+        assert_eq!(efficiency.output_input_ratio(), Some(0.05));
+        assert!((efficiency.cache_read_usd - 0.24).abs() < 1e-9);
+        assert!((efficiency.cache_write_usd - 0.375).abs() < 1e-9);
+        // Uncached: 1M input at $3 + 50K output at $15
+        assert!((efficiency.uncached_cost_usd - 3.75).abs() < 1e-9);
+        assert!((efficiency.cost_usd - (0.3 + 0.24 + 0.375 + 0.75)).abs() < 1e-9);
+        assert!((efficiency.savings_usd() - 2.085).abs() < 1e-9);
+
+        // Writes that are never read cost more than no cache at all
+        let cold = CacheEfficiency::from_tokens(ProviderId::Claude, "claude-sonnet-4-5", &tokens(0, 0, 1_000_000, 0));
+        assert_eq!(cold.cache_hit_ratio(), Some(0.0));
+        assert!(cold.savings_usd() < 0.0);
+
+        let unpriced = CacheEfficiency::from_tokens(ProviderId::Claude, "mystery-model", &tokens(10, 10, 0, 10));
+        assert_eq!(unpriced.cost_usd, 0.0);
+        assert_eq!(unpriced.cache_hit_ratio(), Some(0.5));
+        assert_eq!(CacheEfficiency::default().cache_hit_ratio(), None);
+    }
+
+    #[test]
+    fn test_analytics_from_cache() {
+        let mut days: HashMap<String, HashMap<String, CostUsageTokens>> = HashMap::new();
+        days.entry("2026-01-10".into())
+            .or_default()
+            .insert("claude-sonnet-4-5".into(), tokens(100, 900, 0, 10));
+        days.entry("2026-01-10".into())
+            .or_default()
+            .insert("claude-haiku-4-5".into(), tokens(500, 500, 0, 10));
+        days.entry("2026-01-11".into())
+            .or_default()
+            .insert("claude-sonnet-4-5".into(), tokens(1000, 0, 0, 10));
+        days.entry("2026-02-01".into())
+            .or_default()
+            .insert("claude-sonnet-4-5".into(), tokens(1, 0, 0, 0));
+        let mut cache = CostUsageCache::default();
+        cache.files.insert(
+            "session.jsonl".into(),
+            CostUsageFileUsage {
+                days,
+                ..CostUsageFileUsage::default()
+            },
+        );
+
+        let range = CostUsageDayRange::new(
+            chrono::NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(),
+            chrono::NaiveDate::from_ymd_opt(2026, 1, 31).unwrap(),
+        );
+        let analytics = CostAnalytics::from_cache(&cache, ProviderId::Claude, &range);
+        let days: Vec<&str> = analytics.by_day.iter().map(|(day, _)| day.as_str()).collect();
+        assert_eq!(days, vec!["2026-01-10", "2026-01-11"]);
+        assert_eq!(analytics.by_day[0].1.cache_hit_ratio(), Some(0.7));
+        assert_eq!(analytics.by_model[0].0, "claude-sonnet-4-5");
+        assert_eq!(analytics.by_model[0].1.tokens.input, 1100);
+        assert_eq!(analytics.total.tokens.cache_read, 1400);
+        assert_eq!(analytics.total.cache_hit_ratio(), Some(1400.0 / 3000.0));
+    }
+}
@@ -1,225 +1,289 @@
 //! Cost Usage Pricing
 //!
-//! Model-specific token pricing for Codex (OpenAI) and Claude (Anthropic) models.
-//! Supports tiered pricing for models with token thresholds.
+//! Model-specific token pricing for the agents with local logs: Codex (OpenAI),
+//! Claude (Anthropic), Gemini CLI (Google) and OpenCode, read from a versioned
+//! JSON pricing file. The built-in table ships as
+//! `assets/pricing.json`; `pricing.json` in the CodexBar config directory (or the
+//! file named by `CODEXBAR_PRICING`) adds or replaces models on top of it:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "claude": {
+//!     "claude-sonnet-4-5": {
+//!       "input": 3.0, "output": 15.0, "cache_read": 0.3, "cache_write": 3.75,
+//!       "tiers": [{ "above_tokens": 200000, "input": 6.0, "output": 22.5 }]
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! Prices are USD per million tokens. Once a request's prompt (input plus cache
+//! reads and writes) is larger than a tier's `above_tokens`, the whole request,
+//! output included, is priced at that tier's rates.
 
 #![allow(dead_code)]
 
-use std::collections::HashMap;
-use std::sync::LazyLock;
-
-/// Codex (OpenAI) model pricing
-#[derive(Debug, Clone, Copy)]
-pub struct CodexPricing {
-    /// Cost per input token in USD
-    pub input_cost_per_token: f64,
-    /// Cost per output token in USD
-    pub output_cost_per_token: f64,
-    /// Cost per cached input token in USD
-    pub cache_read_input_cost_per_token: f64,
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, RwLock};
+use thiserror::Error;
+
+use super::ProviderId;
+
+/// Environment variable that points at an alternative pricing file
+pub const PRICING_ENV: &str = "CODEXBAR_PRICING";
+
+/// Current pricing file format version
+pub const PRICING_VERSION: u32 = 1;
+
+/// Pricing table compiled into the binary
+const BUILTIN_PRICING: &str = include_str!("../../assets/pricing.json");
+
+/// Pricing used for cost calculations: the built-in table merged with the
+/// user's pricing file, unless replaced via `CostUsagePricing::use_pricing_file`
+static ACTIVE_PRICING: LazyLock<RwLock<Arc<PricingFile>>> = LazyLock::new(|| {
+    let mut pricing = PricingFile::builtin();
+    if let Some(path) = PricingFile::default_path().filter(|path| path.exists()) {
+        match PricingFile::load_from(&path) {
+            Ok(user) => pricing.merge(user),
+            Err(e) => tracing::warn!("Ignoring pricing file {}: {}", path.display(), e),
+        }
+    }
+    RwLock::new(Arc::new(pricing))
+});
+
+/// Errors that can occur when loading a pricing file
+#[derive(Debug, Error)]
+pub enum PricingError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Unsupported pricing file version {0}")]
+    UnsupportedVersion(u32),
+    #[error("Invalid pricing for '{model}': {message}")]
+    Invalid { model: String, message: String },
 }
 
-/// Claude (Anthropic) model pricing with optional tiered pricing
-#[derive(Debug, Clone, Copy)]
-pub struct ClaudePricing {
-    /// Cost per input token in USD
-    pub input_cost_per_token: f64,
-    /// Cost per output token in USD
-    pub output_cost_per_token: f64,
-    /// Cost per cache creation input token in USD
-    pub cache_creation_input_cost_per_token: f64,
-    /// Cost per cache read input token in USD
-    pub cache_read_input_cost_per_token: f64,
-    /// Token threshold for tiered pricing (None = no tiering)
-    pub threshold_tokens: Option<i32>,
-    /// Cost per input token above threshold
-    pub input_cost_per_token_above_threshold: Option<f64>,
-    /// Cost per output token above threshold
-    pub output_cost_per_token_above_threshold: Option<f64>,
-    /// Cost per cache creation input token above threshold
-    pub cache_creation_input_cost_per_token_above_threshold: Option<f64>,
-    /// Cost per cache read input token above threshold
-    pub cache_read_input_cost_per_token_above_threshold: Option<f64>,
+/// Rates that replace the base rates for requests with a prompt beyond a
+/// token threshold. Unset rates keep the rate of the tier below.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PricingTier {
+    /// Prompt tokens per request after which this tier applies
+    pub above_tokens: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_write: Option<f64>,
 }
 
-/// Codex model pricing table
-static CODEX_PRICING: LazyLock<HashMap<&'static str, CodexPricing>> = LazyLock::new(|| {
-    let mut m = HashMap::new();
-
-    // GPT-5 pricing
-    m.insert("gpt-5", CodexPricing {
-        input_cost_per_token: 1.25e-6,
-        output_cost_per_token: 1e-5,
-        cache_read_input_cost_per_token: 1.25e-7,
-    });
-    m.insert("gpt-5-codex", CodexPricing {
-        input_cost_per_token: 1.25e-6,
-        output_cost_per_token: 1e-5,
-        cache_read_input_cost_per_token: 1.25e-7,
-    });
-    m.insert("gpt-5.1", CodexPricing {
-        input_cost_per_token: 1.25e-6,
-        output_cost_per_token: 1e-5,
-        cache_read_input_cost_per_token: 1.25e-7,
-    });
-    m.insert("gpt-5.2", CodexPricing {
-        input_cost_per_token: 1.75e-6,
-        output_cost_per_token: 1.4e-5,
-        cache_read_input_cost_per_token: 1.75e-7,
-    });
-    m.insert("gpt-5.2-codex", CodexPricing {
-        input_cost_per_token: 1.75e-6,
-        output_cost_per_token: 1.4e-5,
-        cache_read_input_cost_per_token: 1.75e-7,
-    });
-
-    m
-});
+/// Prices for one model in USD per million tokens
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelPricing {
+    /// Uncached input tokens
+    pub input: f64,
+    /// Output tokens
+    pub output: f64,
+    /// Cached input reads (default: the input rate)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read: Option<f64>,
+    /// Cache writes / cache creation input (default: the input rate)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_write: Option<f64>,
+    /// Higher rates for large requests, ordered by threshold
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tiers: Vec<PricingTier>,
+}
 
-/// Claude model pricing table
-static CLAUDE_PRICING: LazyLock<HashMap<&'static str, ClaudePricing>> = LazyLock::new(|| {
-    let mut m = HashMap::new();
-
-    // Haiku 4.5
-    m.insert("claude-haiku-4-5", ClaudePricing {
-        input_cost_per_token: 1e-6,
-        output_cost_per_token: 5e-6,
-        cache_creation_input_cost_per_token: 1.25e-6,
-        cache_read_input_cost_per_token: 1e-7,
-        threshold_tokens: None,
-        input_cost_per_token_above_threshold: None,
-        output_cost_per_token_above_threshold: None,
-        cache_creation_input_cost_per_token_above_threshold: None,
-        cache_read_input_cost_per_token_above_threshold: None,
-    });
-    m.insert("claude-haiku-4-5-20251001", ClaudePricing {
-        input_cost_per_token: 1e-6,
-        output_cost_per_token: 5e-6,
-        cache_creation_input_cost_per_token: 1.25e-6,
-        cache_read_input_cost_per_token: 1e-7,
-        threshold_tokens: None,
-        input_cost_per_token_above_threshold: None,
-        output_cost_per_token_above_threshold: None,
-        cache_creation_input_cost_per_token_above_threshold: None,
-        cache_read_input_cost_per_token_above_threshold: None,
-    });
-
-    // Opus 4.6
-    m.insert("claude-opus-4-6", ClaudePricing {
-        input_cost_per_token: 5e-6,
-        output_cost_per_token: 2.5e-5,
-        cache_creation_input_cost_per_token: 6.25e-6,
-        cache_read_input_cost_per_token: 5e-7,
-        threshold_tokens: None,
-        input_cost_per_token_above_threshold: None,
-        output_cost_per_token_above_threshold: None,
-        cache_creation_input_cost_per_token_above_threshold: None,
-        cache_read_input_cost_per_token_above_threshold: None,
-    });
-    m.insert("claude-opus-4-6-20260205", ClaudePricing {
-        input_cost_per_token: 5e-6,
-        output_cost_per_token: 2.5e-5,
-        cache_creation_input_cost_per_token: 6.25e-6,
-        cache_read_input_cost_per_token: 5e-7,
-        threshold_tokens: None,
-        input_cost_per_token_above_threshold: None,
-        output_cost_per_token_above_threshold: None,
-        cache_creation_input_cost_per_token_above_threshold: None,
-        cache_read_input_cost_per_token_above_threshold: None,
-    });
-
-    // Opus 4.5
-    m.insert("claude-opus-4-5", ClaudePricing {
-        input_cost_per_token: 5e-6,
-        output_cost_per_token: 2.5e-5,
-        cache_creation_input_cost_per_token: 6.25e-6,
-        cache_read_input_cost_per_token: 5e-7,
-        threshold_tokens: None,
-        input_cost_per_token_above_threshold: None,
-        output_cost_per_token_above_threshold: None,
-        cache_creation_input_cost_per_token_above_threshold: None,
-        cache_read_input_cost_per_token_above_threshold: None,
-    });
-    m.insert("claude-opus-4-5-20251101", ClaudePricing {
-        input_cost_per_token: 5e-6,
-        output_cost_per_token: 2.5e-5,
-        cache_creation_input_cost_per_token: 6.25e-6,
-        cache_read_input_cost_per_token: 5e-7,
-        threshold_tokens: None,
-        input_cost_per_token_above_threshold: None,
-        output_cost_per_token_above_threshold: None,
-        cache_creation_input_cost_per_token_above_threshold: None,
-        cache_read_input_cost_per_token_above_threshold: None,
-    });
-
-    // Sonnet 4.5 (with tiered pricing at 200k tokens)
-    m.insert("claude-sonnet-4-5", ClaudePricing {
-        input_cost_per_token: 3e-6,
-        output_cost_per_token: 1.5e-5,
-        cache_creation_input_cost_per_token: 3.75e-6,
-        cache_read_input_cost_per_token: 3e-7,
-        threshold_tokens: Some(200_000),
-        input_cost_per_token_above_threshold: Some(6e-6),
-        output_cost_per_token_above_threshold: Some(2.25e-5),
-        cache_creation_input_cost_per_token_above_threshold: Some(7.5e-6),
-        cache_read_input_cost_per_token_above_threshold: Some(6e-7),
-    });
-    m.insert("claude-sonnet-4-5-20250929", ClaudePricing {
-        input_cost_per_token: 3e-6,
-        output_cost_per_token: 1.5e-5,
-        cache_creation_input_cost_per_token: 3.75e-6,
-        cache_read_input_cost_per_token: 3e-7,
-        threshold_tokens: Some(200_000),
-        input_cost_per_token_above_threshold: Some(6e-6),
-        output_cost_per_token_above_threshold: Some(2.25e-5),
-        cache_creation_input_cost_per_token_above_threshold: Some(7.5e-6),
-        cache_read_input_cost_per_token_above_threshold: Some(6e-7),
-    });
-
-    // Opus 4
-    m.insert("claude-opus-4-20250514", ClaudePricing {
-        input_cost_per_token: 1.5e-5,
-        output_cost_per_token: 7.5e-5,
-        cache_creation_input_cost_per_token: 1.875e-5,
-        cache_read_input_cost_per_token: 1.5e-6,
-        threshold_tokens: None,
-        input_cost_per_token_above_threshold: None,
-        output_cost_per_token_above_threshold: None,
-        cache_creation_input_cost_per_token_above_threshold: None,
-        cache_read_input_cost_per_token_above_threshold: None,
-    });
-    m.insert("claude-opus-4-1", ClaudePricing {
-        input_cost_per_token: 1.5e-5,
-        output_cost_per_token: 7.5e-5,
-        cache_creation_input_cost_per_token: 1.875e-5,
-        cache_read_input_cost_per_token: 1.5e-6,
-        threshold_tokens: None,
-        input_cost_per_token_above_threshold: None,
-        output_cost_per_token_above_threshold: None,
-        cache_creation_input_cost_per_token_above_threshold: None,
-        cache_read_input_cost_per_token_above_threshold: None,
-    });
-
-    // Sonnet 4
-    m.insert("claude-sonnet-4-20250514", ClaudePricing {
-        input_cost_per_token: 3e-6,
-        output_cost_per_token: 1.5e-5,
-        cache_creation_input_cost_per_token: 3.75e-6,
-        cache_read_input_cost_per_token: 3e-7,
-        threshold_tokens: Some(200_000),
-        input_cost_per_token_above_threshold: Some(6e-6),
-        output_cost_per_token_above_threshold: Some(2.25e-5),
-        cache_creation_input_cost_per_token_above_threshold: Some(7.5e-6),
-        cache_read_input_cost_per_token_above_threshold: Some(6e-7),
-    });
-
-    m
-});
+impl ModelPricing {
+    /// Cost of one request in USD. Once the prompt (input plus cache reads
+    /// and writes) passes a tier's threshold, every token of the request is
+    /// priced at that tier's rates.
+    pub fn cost_usd(&self, input_tokens: i64, cache_read_tokens: i64, cache_write_tokens: i64, output_tokens: i64) -> f64 {
+        let [input_tokens, cache_read_tokens, cache_write_tokens, output_tokens] =
+            [input_tokens, cache_read_tokens, cache_write_tokens, output_tokens].map(|tokens| tokens.max(0));
+        let prompt_tokens = input_tokens + cache_read_tokens + cache_write_tokens;
+
+        let mut input = self.input;
+        let mut cache_read = self.cache_read.unwrap_or(self.input);
+        let mut cache_write = self.cache_write.unwrap_or(self.input);
+        let mut output = self.output;
+        for tier in self.tiers.iter().take_while(|tier| prompt_tokens > tier.above_tokens) {
+            input = tier.input.unwrap_or(input);
+            cache_read = tier.cache_read.unwrap_or(cache_read);
+            cache_write = tier.cache_write.unwrap_or(cache_write);
+            output = tier.output.unwrap_or(output);
+        }
+
+        (input_tokens as f64 * input
+            + cache_read_tokens as f64 * cache_read
+            + cache_write_tokens as f64 * cache_write
+            + output_tokens as f64 * output)
+            / 1_000_000.0
+    }
+
+    fn validate(&self, model: &str) -> Result<(), PricingError> {
+        let invalid = |message: String| PricingError::Invalid {
+            model: model.to_string(),
+            message,
+        };
+        let check = |name: &str, rate: Option<f64>| match rate {
+            Some(rate) if !rate.is_finite() || rate < 0.0 => {
+                Err(invalid(format!("{} must be a non-negative number", name)))
+            }
+            _ => Ok(()),
+        };
+
+        check("input", Some(self.input))?;
+        check("output", Some(self.output))?;
+        check("cache_read", self.cache_read)?;
+        check("cache_write", self.cache_write)?;
+
+        let mut previous = 0;
+        for tier in &self.tiers {
+            if tier.above_tokens <= previous {
+                return Err(invalid("tier thresholds must be positive and increasing".to_string()));
+            }
+            previous = tier.above_tokens;
+            check("tier input", tier.input)?;
+            check("tier output", tier.output)?;
+            check("tier cache_read", tier.cache_read)?;
+            check("tier cache_write", tier.cache_write)?;
+        }
+        Ok(())
+    }
+}
+
+/// Contents of a pricing file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PricingFile {
+    #[serde(default = "default_version")]
+    pub version: u32,
+    /// When the prices were last checked (informational)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated: Option<String>,
+    /// Codex (OpenAI) models by name
+    #[serde(default)]
+    pub codex: BTreeMap<String, ModelPricing>,
+    /// Claude (Anthropic) models by name
+    #[serde(default)]
+    pub claude: BTreeMap<String, ModelPricing>,
+    /// Gemini (Google) models by name
+    #[serde(default)]
+    pub gemini: BTreeMap<String, ModelPricing>,
+    /// OpenCode models not covered by the other sections (OpenCode falls
+    /// back to the Codex, Claude and Gemini prices)
+    #[serde(default)]
+    pub opencode: BTreeMap<String, ModelPricing>,
+}
+
+impl Default for PricingFile {
+    fn default() -> Self {
+        Self {
+            version: PRICING_VERSION,
+            updated: None,
+            codex: BTreeMap::new(),
+            claude: BTreeMap::new(),
+            gemini: BTreeMap::new(),
+            opencode: BTreeMap::new(),
+        }
+    }
+}
+
+fn default_version() -> u32 {
+    PRICING_VERSION
+}
+
+impl PricingFile {
+    /// The pricing table shipped with this release
+    pub fn builtin() -> Self {
+        Self::from_json(BUILTIN_PRICING).expect("built-in pricing.json is valid")
+    }
+
+    /// Path of the user pricing file: `CODEXBAR_PRICING`, else `pricing.json`
+    /// in the config directory
+    pub fn default_path() -> Option<PathBuf> {
+        if let Ok(path) = std::env::var(PRICING_ENV) {
+            if !path.trim().is_empty() {
+                return Some(PathBuf::from(path));
+            }
+        }
+        Some(dirs::config_dir()?.join("CodexBar").join("pricing.json"))
+    }
+
+    /// Load and validate a pricing file
+    pub fn load_from(path: &Path) -> Result<Self, PricingError> {
+        let content = std::fs::read_to_string(path)?;
+        Self::from_json(&content)
+    }
+
+    /// Parse and validate pricing JSON
+    pub fn from_json(content: &str) -> Result<Self, PricingError> {
+        let file: Self = serde_json::from_str(content)?;
+        if file.version > PRICING_VERSION {
+            return Err(PricingError::UnsupportedVersion(file.version));
+        }
+        for (model, pricing) in file.codex.iter().chain(&file.claude).chain(&file.gemini).chain(&file.opencode) {
+            pricing.validate(model)?;
+        }
+        Ok(file)
+    }
+
+    /// Add or replace models from `other`
+    pub fn merge(&mut self, other: PricingFile) {
+        if other.updated.is_some() {
+            self.updated = other.updated;
+        }
+        self.codex.extend(other.codex);
+        self.claude.extend(other.claude);
+        self.gemini.extend(other.gemini);
+        self.opencode.extend(other.opencode);
+    }
+
+    /// Models priced for a provider
+    pub fn models(&self, provider: ProviderId) -> Option<&BTreeMap<String, ModelPricing>> {
+        match provider {
+            ProviderId::Codex => Some(&self.codex),
+            ProviderId::Claude => Some(&self.claude),
+            ProviderId::Gemini => Some(&self.gemini),
+            ProviderId::OpenCode => Some(&self.opencode),
+            _ => None,
+        }
+    }
+
+    /// Short hash of the table, used to invalidate costs cached under other prices
+    pub fn fingerprint(&self) -> String {
+        let json = serde_json::to_string(self).unwrap_or_default();
+        let digest = format!("{:x}", Sha256::digest(json.as_bytes()));
+        digest[..16].to_string()
+    }
+}
 
 /// Cost usage pricing utilities
 pub struct CostUsagePricing;
 
 impl CostUsagePricing {
+    /// The pricing table currently in use
+    pub fn table() -> Arc<PricingFile> {
+        Arc::clone(&ACTIVE_PRICING.read().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// Price with `path` merged over the built-in table instead of the user's
+    /// pricing file, for the rest of this process
+    pub fn use_pricing_file(path: &Path) -> Result<(), PricingError> {
+        let mut pricing = PricingFile::builtin();
+        pricing.merge(PricingFile::load_from(path)?);
+        *ACTIVE_PRICING.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(pricing);
+        Ok(())
+    }
+
     /// Normalize a Codex model name for pricing lookup
     pub fn normalize_codex_model(raw: &str) -> String {
         let mut trimmed = raw.trim().to_string();
@@ -232,7 +296,7 @@ impl CostUsagePricing {
         // Check if base model (without -codex suffix) exists in pricing
         if let Some(idx) = trimmed.find("-codex") {
             let base = &trimmed[..idx];
-            if CODEX_PRICING.contains_key(base) {
+            if Self::table().codex.contains_key(base) {
                 return base.to_string();
             }
         }
@@ -267,7 +331,7 @@ impl CostUsagePricing {
         let date_pattern = regex_lite::Regex::new(r"-\d{8}$").unwrap();
         if let Some(mat) = date_pattern.find(&trimmed) {
             let base = &trimmed[..mat.start()];
-            if CLAUDE_PRICING.contains_key(base) {
+            if Self::table().claude.contains_key(base) {
                 return base.to_string();
             }
         }
@@ -275,73 +339,131 @@ impl CostUsagePricing {
         trimmed
     }
 
+    /// Normalize a Gemini model name for pricing lookup
+    pub fn normalize_gemini_model(raw: &str) -> String {
+        let mut trimmed = raw.trim().to_string();
+
+        // Remove "models/" and "google/" prefixes
+        for prefix in ["models/", "google/"] {
+            if let Some(rest) = trimmed.strip_prefix(prefix) {
+                trimmed = rest.to_string();
+            }
+        }
+
+        // Dated previews like "gemini-2.5-flash-preview-05-20" use the base price
+        if let Some(idx) = trimmed.find("-preview-") {
+            let base = &trimmed[..idx];
+            if Self::table().gemini.contains_key(base) {
+                return base.to_string();
+            }
+        }
+
+        trimmed
+    }
+
+    /// Normalize an OpenCode model id for pricing lookup. Ids may carry the
+    /// upstream provider ("anthropic/claude-sonnet-4-5"), which is dropped,
+    /// and are then normalized like the agent that serves the model.
+    pub fn normalize_opencode_model(raw: &str) -> String {
+        let trimmed = raw.trim();
+        let model = trimmed.rsplit('/').next().unwrap_or(trimmed);
+        if model.starts_with("claude-") {
+            Self::normalize_claude_model(model)
+        } else if model.starts_with("gemini-") {
+            Self::normalize_gemini_model(model)
+        } else {
+            Self::normalize_codex_model(model)
+        }
+    }
+
     /// Calculate cost for Codex usage in USD
     pub fn codex_cost_usd(
         model: &str,
-        input_tokens: i32,
-        cached_input_tokens: i32,
-        output_tokens: i32,
+        input_tokens: i64,
+        cached_input_tokens: i64,
+        output_tokens: i64,
     ) -> Option<f64> {
         let key = Self::normalize_codex_model(model);
-        let pricing = CODEX_PRICING.get(key.as_str())?;
+        let table = Self::table();
+        let pricing = table.codex.get(key.as_str())?;
 
         let cached = cached_input_tokens.max(0).min(input_tokens.max(0));
         let non_cached = (input_tokens.max(0) - cached).max(0);
 
-        let cost = (non_cached as f64) * pricing.input_cost_per_token
-            + (cached as f64) * pricing.cache_read_input_cost_per_token
-            + (output_tokens.max(0) as f64) * pricing.output_cost_per_token;
-
-        Some(cost)
+        Some(pricing.cost_usd(non_cached, cached, 0, output_tokens))
     }
 
     /// Calculate cost for Claude usage in USD
     pub fn claude_cost_usd(
         model: &str,
-        input_tokens: i32,
-        cache_read_input_tokens: i32,
-        cache_creation_input_tokens: i32,
-        output_tokens: i32,
+        input_tokens: i64,
+        cache_read_input_tokens: i64,
+        cache_creation_input_tokens: i64,
+        output_tokens: i64,
     ) -> Option<f64> {
         let key = Self::normalize_claude_model(model);
-        let pricing = CLAUDE_PRICING.get(key.as_str())?;
-
-        /// Calculate tiered cost
-        fn tiered(tokens: i32, base: f64, above: Option<f64>, threshold: Option<i32>) -> f64 {
-            let tokens = tokens.max(0);
-            match (threshold, above) {
-                (Some(thresh), Some(above_rate)) => {
-                    let below = tokens.min(thresh);
-                    let over = (tokens - thresh).max(0);
-                    (below as f64) * base + (over as f64) * above_rate
-                }
-                _ => (tokens as f64) * base,
-            }
-        }
+        let table = Self::table();
+        let pricing = table.claude.get(key.as_str())?;
 
-        let cost = tiered(
+        Some(pricing.cost_usd(
             input_tokens,
-            pricing.input_cost_per_token,
-            pricing.input_cost_per_token_above_threshold,
-            pricing.threshold_tokens,
-        ) + tiered(
             cache_read_input_tokens,
-            pricing.cache_read_input_cost_per_token,
-            pricing.cache_read_input_cost_per_token_above_threshold,
-            pricing.threshold_tokens,
-        ) + tiered(
             cache_creation_input_tokens,
-            pricing.cache_creation_input_cost_per_token,
-            pricing.cache_creation_input_cost_per_token_above_threshold,
-            pricing.threshold_tokens,
-        ) + tiered(
             output_tokens,
-            pricing.output_cost_per_token,
-            pricing.output_cost_per_token_above_threshold,
-            pricing.threshold_tokens,
-        );
+        ))
+    }
+
+    /// Calculate cost for Gemini usage in USD (`input_tokens` includes the
+    /// cached tokens, as in Gemini's `promptTokenCount`)
+    pub fn gemini_cost_usd(
+        model: &str,
+        input_tokens: i64,
+        cached_input_tokens: i64,
+        output_tokens: i64,
+    ) -> Option<f64> {
+        let pricing = Self::model_pricing(ProviderId::Gemini, model)?;
+
+        let cached = cached_input_tokens.max(0).min(input_tokens.max(0));
+        let non_cached = (input_tokens.max(0) - cached).max(0);
+
+        Some(pricing.cost_usd(non_cached, cached, 0, output_tokens))
+    }
+
+    /// Calculate cost for OpenCode usage in USD
+    pub fn opencode_cost_usd(
+        model: &str,
+        input_tokens: i64,
+        cache_read_tokens: i64,
+        cache_write_tokens: i64,
+        output_tokens: i64,
+    ) -> Option<f64> {
+        let pricing = Self::model_pricing(ProviderId::OpenCode, model)?;
+        Some(pricing.cost_usd(input_tokens, cache_read_tokens, cache_write_tokens, output_tokens))
+    }
 
-        Some(cost)
+    /// Whether a (normalized) model has a price for this provider
+    pub fn is_priced(provider: ProviderId, model: &str) -> bool {
+        Self::model_pricing(provider, model).is_some()
+    }
+
+    /// Prices of a (normalized) model for this provider
+    pub fn model_pricing(provider: ProviderId, model: &str) -> Option<ModelPricing> {
+        let key = match provider {
+            ProviderId::Codex => Self::normalize_codex_model(model),
+            ProviderId::Claude => Self::normalize_claude_model(model),
+            ProviderId::Gemini => Self::normalize_gemini_model(model),
+            ProviderId::OpenCode => Self::normalize_opencode_model(model),
+            _ => return None,
+        };
+        let table = Self::table();
+        let fallbacks: &[&BTreeMap<String, ModelPricing>] = match provider {
+            ProviderId::OpenCode => &[&table.claude, &table.codex, &table.gemini],
+            _ => &[],
+        };
+        std::iter::once(table.models(provider)?)
+            .chain(fallbacks.iter().copied())
+            .find_map(|models| models.get(key.as_str()))
+            .cloned()
     }
 
     /// Format model name for display (e.g., "claude-3.5-sonnet" → "Sonnet 3.5")
@@ -399,6 +521,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_normalize_gemini_and_opencode_models() {
+        assert_eq!(CostUsagePricing::normalize_gemini_model("models/gemini-2.5-pro"), "gemini-2.5-pro");
+        assert_eq!(
+            CostUsagePricing::normalize_gemini_model("gemini-2.5-flash-preview-05-20"),
+            "gemini-2.5-flash"
+        );
+        assert_eq!(
+            CostUsagePricing::normalize_opencode_model("anthropic/claude-sonnet-4-5-20250929"),
+            "claude-sonnet-4-5"
+        );
+        assert_eq!(CostUsagePricing::normalize_opencode_model("gpt-5-codex"), "gpt-5");
+    }
+
+    #[test]
+    fn test_gemini_and_opencode_cost() {
+        // 1000 prompt tokens of which 400 cached: 600 * 1.25e-6 + 400 * 0.125e-6 + 100 * 1e-5
+        let cost = CostUsagePricing::gemini_cost_usd("gemini-2.5-pro", 1000, 400, 100).unwrap();
+        assert!((cost - 0.0018).abs() < 1e-10);
+
+        // OpenCode prices its own models and falls back to the other sections
+        assert!(CostUsagePricing::is_priced(ProviderId::OpenCode, "grok-code"));
+        let cost = CostUsagePricing::opencode_cost_usd("claude-haiku-4-5", 1000, 0, 0, 0).unwrap();
+        assert!((cost - 0.001).abs() < 1e-10);
+        assert!(!CostUsagePricing::is_priced(ProviderId::Gemini, "claude-haiku-4-5"));
+    }
+
     #[test]
     fn test_codex_cost() {
         let cost = CostUsagePricing::codex_cost_usd("gpt-5", 1000, 0, 500);
@@ -417,6 +566,72 @@ mod tests {
         assert!(cost.is_some());
     }
 
+    #[test]
+    fn test_tiered_pricing() {
+        // Sonnet 4.5 switches the whole request to long-context rates once
+        // the prompt passes 200k tokens
+        let cost = CostUsagePricing::claude_cost_usd("claude-sonnet-4-5", 250_000, 0, 0, 0).unwrap();
+        assert!((cost - 250_000.0 * 6e-6).abs() < 1e-9);
+        let cost = CostUsagePricing::claude_cost_usd("claude-sonnet-4-5", 200_000, 0, 0, 1_000).unwrap();
+        assert!((cost - (200_000.0 * 3e-6 + 1_000.0 * 15e-6)).abs() < 1e-9);
+
+        // Cache reads count towards the prompt size, and output is tiered too
+        let cost = CostUsagePricing::claude_cost_usd("claude-sonnet-4-5", 10, 250_000, 0, 1_000).unwrap();
+        assert!((cost - (10.0 * 6e-6 + 250_000.0 * 0.6e-6 + 1_000.0 * 22.5e-6)).abs() < 1e-9);
+
+        let pricing = ModelPricing {
+            input: 1.0,
+            output: 2.0,
+            cache_read: None,
+            cache_write: Some(4.0),
+            tiers: vec![
+                PricingTier { above_tokens: 10, input: Some(3.0), ..PricingTier::default() },
+                PricingTier { above_tokens: 20, output: Some(5.0), ..PricingTier::default() },
+            ],
+        };
+        // Below every threshold; cache reads default to the input rate
+        assert!((pricing.cost_usd(5, 5, 0, 30) - (5.0 + 5.0 + 60.0) * 1e-6).abs() < 1e-12);
+        // First tier: input 3.0, output still 2.0
+        assert!((pricing.cost_usd(15, 0, 0, 30) - (45.0 + 60.0) * 1e-6).abs() < 1e-12);
+        // Second tier keeps the first tier's input rate and raises output
+        assert!((pricing.cost_usd(20, 0, 1, 30) - (60.0 + 4.0 + 150.0) * 1e-6).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_pricing_file() {
+        let builtin = PricingFile::builtin();
+        assert!(builtin.codex.contains_key("gpt-5"));
+        assert!(builtin.claude.contains_key("claude-opus-4-6"));
+
+        let user = PricingFile::from_json(
+            r#"{"version": 1, "codex": {"gpt-6": {"input": 2.0, "output": 16.0}}, "claude": {"claude-opus-4-6": {"input": 4.0, "output": 20.0}}}"#,
+        )
+        .unwrap();
+        let mut merged = builtin.clone();
+        merged.merge(user);
+        assert!(merged.codex.contains_key("gpt-6"));
+        assert!(merged.codex.contains_key("gpt-5"));
+        assert_eq!(merged.claude["claude-opus-4-6"].input, 4.0);
+        assert_ne!(merged.fingerprint(), builtin.fingerprint());
+        assert_eq!(builtin.fingerprint(), PricingFile::builtin().fingerprint());
+
+        assert!(matches!(
+            PricingFile::from_json(r#"{"version": 99}"#),
+            Err(PricingError::UnsupportedVersion(99))
+        ));
+        assert!(matches!(
+            PricingFile::from_json(r#"{"codex": {"x": {"input": -1.0, "output": 1.0}}}"#),
+            Err(PricingError::Invalid { .. })
+        ));
+        assert!(PricingFile::from_json(
+            r#"{"claude": {"x": {"input": 1.0, "output": 1.0, "tiers": [{"above_tokens": 5}, {"above_tokens": 5}]}}}"#
+        )
+        .is_err());
+
+        assert!(CostUsagePricing::is_priced(ProviderId::Claude, "claude-haiku-4-5-20251001"));
+        assert!(!CostUsagePricing::is_priced(ProviderId::Codex, "gpt-unreleased"));
+    }
+
     #[test]
     fn test_format_model_name() {
         assert_eq!(CostUsagePricing::format_model_name("claude-3.5-sonnet"), "Sonnet 3.5");
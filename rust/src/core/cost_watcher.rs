@@ -0,0 +1,254 @@
+//! Live cost from the agent log directories
+//!
+//! The agents update their session logs after every turn. Instead of
+//! rescanning the logs on each refresh, `CostWatcher` watches the log
+//! directories and re-reads only the files that changed, so today's cost is
+//! current within seconds of a turn finishing. Changes are debounced: a busy
+//! session writing many lines triggers one rescan, not one per line.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use super::{local_log_scanner, CostUsageCache, CostUsageDayRange, JsonlScanner, ProviderId};
+
+/// Quiet period after the last change before the logs are re-read
+pub const COST_WATCH_DEBOUNCE: Duration = Duration::from_millis(750);
+
+/// Longest a rescan waits while changes keep arriving
+pub const COST_WATCH_MAX_DELAY: Duration = Duration::from_secs(5);
+
+/// How often the in-memory caches are written back to disk
+const COST_WATCH_SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Watches the agents' session logs and keeps their cost caches current
+pub struct CostWatcher {
+    // Dropping the watcher stops the events
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<Event>>,
+    roots: Vec<(ProviderId, PathBuf)>,
+    caches: HashMap<ProviderId, CostUsageCache>,
+    cache_root: Option<PathBuf>,
+    last_saved: Instant,
+}
+
+impl CostWatcher {
+    /// Watch the default log directories of `providers` (those without local
+    /// logs are ignored), scanning `range` up front
+    pub fn new(providers: &[ProviderId], range: &CostUsageDayRange) -> notify::Result<Self> {
+        let roots = providers
+            .iter()
+            .flat_map(|&provider| {
+                JsonlScanner::default_log_roots(provider)
+                    .into_iter()
+                    .map(move |root| (provider, root))
+            })
+            .collect();
+        Self::with_roots(roots, range, None)
+    }
+
+    /// Watch explicit log directories, with caches under `cache_root`
+    pub fn with_roots(
+        roots: Vec<(ProviderId, PathBuf)>,
+        range: &CostUsageDayRange,
+        cache_root: Option<PathBuf>,
+    ) -> notify::Result<Self> {
+        let (tx, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            let _ = tx.send(event);
+        })?;
+
+        let roots: Vec<(ProviderId, PathBuf)> = roots.into_iter().filter(|(_, root)| root.is_dir()).collect();
+        for (_, root) in &roots {
+            watcher.watch(root, RecursiveMode::Recursive)?;
+        }
+
+        let mut caches = HashMap::new();
+        let providers: HashSet<ProviderId> = roots.iter().map(|(provider, _)| *provider).collect();
+        for provider in providers {
+            let provider_roots: Vec<PathBuf> = roots
+                .iter()
+                .filter(|(p, _)| *p == provider)
+                .map(|(_, root)| root.clone())
+                .collect();
+            let mut cache = JsonlScanner::load_cache(provider, cache_root.as_deref());
+            let files = JsonlScanner::session_files(provider, &provider_roots, range);
+            JsonlScanner::refresh_cache(provider, &mut cache, &files);
+            JsonlScanner::save_cache(provider, &cache, cache_root.as_deref());
+            caches.insert(provider, cache);
+        }
+
+        Ok(Self {
+            _watcher: watcher,
+            events,
+            roots,
+            caches,
+            cache_root,
+            last_saved: Instant::now(),
+        })
+    }
+
+    /// Providers being watched
+    pub fn providers(&self) -> Vec<ProviderId> {
+        let mut providers: Vec<ProviderId> = self.caches.keys().copied().collect();
+        providers.sort_by_key(|provider| provider.cli_name());
+        providers
+    }
+
+    /// Current cache of a watched provider
+    pub fn cache(&self, provider: ProviderId) -> Option<&CostUsageCache> {
+        self.caches.get(&provider)
+    }
+
+    /// Daily cost for the last `days` days as (day_key, cost_usd), oldest first
+    pub fn daily_costs(&self, provider: ProviderId, days: u32) -> Vec<(String, f64)> {
+        self.cache(provider)
+            .map(|cache| cache.daily_costs(&CostUsageDayRange::last_days(days)))
+            .unwrap_or_default()
+    }
+
+    /// Block until session logs change or `timeout` passes. Once a change
+    /// arrives, waits for the debounce period, re-reads the changed files and
+    /// returns the providers that were updated (empty on timeout).
+    pub fn wait(&mut self, timeout: Duration) -> Vec<ProviderId> {
+        let deadline = Instant::now() + timeout;
+        let mut changed: HashMap<ProviderId, HashSet<PathBuf>> = HashMap::new();
+        while changed.is_empty() {
+            match self.events.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(event) => self.collect(event, &mut changed),
+                Err(_) => return Vec::new(),
+            }
+        }
+
+        let flush_by = Instant::now() + COST_WATCH_MAX_DELAY;
+        while Instant::now() < flush_by {
+            let quiet = COST_WATCH_DEBOUNCE.min(flush_by.saturating_duration_since(Instant::now()));
+            match self.events.recv_timeout(quiet) {
+                Ok(event) => self.collect(event, &mut changed),
+                Err(_) => break,
+            }
+        }
+
+        let mut updated = Vec::new();
+        for (provider, files) in changed {
+            let files: Vec<PathBuf> = files.into_iter().collect();
+            if let Some(cache) = self.caches.get_mut(&provider) {
+                JsonlScanner::refresh_cache(provider, cache, &files);
+                updated.push(provider);
+            }
+        }
+        updated.sort_by_key(|provider| provider.cli_name());
+
+        if self.last_saved.elapsed() >= COST_WATCH_SAVE_INTERVAL {
+            self.save();
+        }
+        updated
+    }
+
+    /// Write the caches to disk
+    pub fn save(&mut self) {
+        for (provider, cache) in &self.caches {
+            JsonlScanner::save_cache(*provider, cache, self.cache_root.as_deref());
+        }
+        self.last_saved = Instant::now();
+    }
+
+    /// Add the session logs touched by `event` to `changed`
+    fn collect(&self, event: notify::Result<Event>, changed: &mut HashMap<ProviderId, HashSet<PathBuf>>) {
+        let event = match event {
+            Ok(event) => event,
+            Err(e) => {
+                tracing::debug!("Cost watcher error: {}", e);
+                return;
+            }
+        };
+        if matches!(event.kind, EventKind::Access(_)) {
+            return;
+        }
+        for path in event.paths {
+            let Some(provider) = self.provider_for(&path) else {
+                continue;
+            };
+            if local_log_scanner(provider).is_some_and(|scanner| scanner.is_session_file(&path)) {
+                changed.entry(provider).or_default().insert(path);
+            }
+        }
+    }
+
+    fn provider_for(&self, path: &Path) -> Option<ProviderId> {
+        self.roots
+            .iter()
+            .find(|(_, root)| path.starts_with(root))
+            .map(|(provider, _)| *provider)
+    }
+}
+
+impl Drop for CostWatcher {
+    fn drop(&mut self) {
+        self.save();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use std::fs;
+    use std::io::Write;
+
+    fn claude_entry(id: &str, input: i64) -> String {
+        format!(
+            r#"{{"timestamp":"{}","type":"assistant","requestId":"req-{}","message":{{"id":"msg-{}","model":"claude-haiku-4-5","usage":{{"input_tokens":{},"output_tokens":10}}}}}}"#,
+            Utc::now().to_rfc3339(),
+            id,
+            id,
+            input
+        ) + "\n"
+    }
+
+    fn append(path: &Path, text: &str) {
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(path).unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn test_watch_appended_lines() {
+        let logs = tempfile::tempdir().unwrap();
+        let cache_dir = tempfile::tempdir().unwrap();
+        let project = logs.path().join("-home-dev-app");
+        fs::create_dir_all(&project).unwrap();
+        let session = project.join("session.jsonl");
+        append(&session, &claude_entry("1", 1000));
+
+        let mut watcher = CostWatcher::with_roots(
+            vec![(ProviderId::Claude, logs.path().to_path_buf())],
+            &CostUsageDayRange::last_days(1),
+            Some(cache_dir.path().to_path_buf()),
+        )
+        .unwrap();
+        let today = CostUsageDayRange::last_days(1);
+        let input = |watcher: &CostWatcher| watcher.cache(ProviderId::Claude).unwrap().summary(&today).input_tokens;
+        assert_eq!(watcher.providers(), vec![ProviderId::Claude]);
+        assert_eq!(input(&watcher), 1000);
+
+        // Lines written in a burst are picked up by one rescan
+        append(&session, &claude_entry("2", 500));
+        append(&session, &claude_entry("3", 250));
+        let mut updated = Vec::new();
+        for _ in 0..10 {
+            updated = watcher.wait(Duration::from_secs(1));
+            if input(&watcher) == 1750 {
+                break;
+            }
+        }
+        assert_eq!(updated, vec![ProviderId::Claude]);
+        assert_eq!(input(&watcher), 1750);
+
+        // Other files under the root are ignored
+        fs::write(project.join("notes.txt"), "hello").unwrap();
+        assert!(watcher.wait(Duration::from_millis(300)).is_empty());
+    }
+}
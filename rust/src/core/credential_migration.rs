@@ -6,6 +6,7 @@
 #![allow(dead_code)]
 
 use crate::core::ProviderId;
+use std::borrow::Cow;
 use std::sync::atomic::{AtomicBool, Ordering};
 
 /// Migration version tracking key
@@ -286,9 +287,10 @@ pub fn service_name_for_provider(_provider: ProviderId) -> &'static str {
     "CodexBar"
 }
 
-/// Get the account name for a provider's credentials
-pub fn account_name_for_provider(provider: ProviderId) -> &'static str {
-    match provider {
+/// Get the account name for a provider's credentials; each custom provider
+/// gets its own so their tokens do not overwrite each other
+pub fn account_name_for_provider(provider: ProviderId) -> Cow<'static, str> {
+    Cow::Borrowed(match provider {
         ProviderId::Codex => "codex-cookie",
         ProviderId::Claude => "claude-cookie",
         ProviderId::Cursor => "cursor-cookie",
@@ -310,7 +312,8 @@ pub fn account_name_for_provider(provider: ProviderId) -> &'static str {
         ProviderId::Warp => "warp-token",
         ProviderId::Ollama => "ollama-cookie",
         ProviderId::OpenRouter => "openrouter-token",
-    }
+        ProviderId::Custom(id) => return Cow::Owned(format!("custom-{}-api-token", id)),
+    })
 }
 
 /// Migrate credentials for a specific provider from one format to another
@@ -362,6 +365,11 @@ mod tests {
     fn test_account_name_for_provider() {
         assert_eq!(account_name_for_provider(ProviderId::Claude), "claude-cookie");
         assert_eq!(account_name_for_provider(ProviderId::Copilot), "copilot-api-token");
+        assert_eq!(account_name_for_provider(ProviderId::Custom("my-gw")), "custom-my-gw-api-token");
+        assert_ne!(
+            account_name_for_provider(ProviderId::Custom("a")),
+            account_name_for_provider(ProviderId::Custom("b"))
+        );
     }
 
     #[test]
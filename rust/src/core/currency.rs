@@ -0,0 +1,209 @@
+//! Currency formatting and conversion
+//!
+//! Formats amounts for common ISO 4217 codes and converts spend between
+//! currencies using an exchange-rate table expressed as units per 1 USD.
+
+use std::collections::HashMap;
+
+/// Display details for a currency
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CurrencyInfo {
+    /// ISO 4217 code (e.g., "USD")
+    pub code: &'static str,
+    /// Symbol shown before the amount (e.g., "$", "CN¥")
+    pub symbol: &'static str,
+    /// Number of minor-unit digits to display
+    pub decimals: usize,
+}
+
+/// Currencies with a known symbol, in the order shown in pickers
+pub const CURRENCIES: &[CurrencyInfo] = &[
+    CurrencyInfo { code: "USD", symbol: "$", decimals: 2 },
+    CurrencyInfo { code: "EUR", symbol: "€", decimals: 2 },
+    CurrencyInfo { code: "GBP", symbol: "£", decimals: 2 },
+    CurrencyInfo { code: "CNY", symbol: "CN¥", decimals: 2 },
+    CurrencyInfo { code: "JPY", symbol: "¥", decimals: 0 },
+    CurrencyInfo { code: "KRW", symbol: "₩", decimals: 0 },
+    CurrencyInfo { code: "INR", symbol: "₹", decimals: 2 },
+    CurrencyInfo { code: "CAD", symbol: "CA$", decimals: 2 },
+    CurrencyInfo { code: "AUD", symbol: "A$", decimals: 2 },
+    CurrencyInfo { code: "NZD", symbol: "NZ$", decimals: 2 },
+    CurrencyInfo { code: "HKD", symbol: "HK$", decimals: 2 },
+    CurrencyInfo { code: "TWD", symbol: "NT$", decimals: 2 },
+    CurrencyInfo { code: "SGD", symbol: "S$", decimals: 2 },
+    CurrencyInfo { code: "CHF", symbol: "CHF ", decimals: 2 },
+    CurrencyInfo { code: "SEK", symbol: "SEK ", decimals: 2 },
+    CurrencyInfo { code: "NOK", symbol: "NOK ", decimals: 2 },
+    CurrencyInfo { code: "DKK", symbol: "DKK ", decimals: 2 },
+    CurrencyInfo { code: "PLN", symbol: "zł ", decimals: 2 },
+    CurrencyInfo { code: "BRL", symbol: "R$", decimals: 2 },
+    CurrencyInfo { code: "MXN", symbol: "MX$", decimals: 2 },
+];
+
+/// Built-in exchange rates (units per 1 USD). Approximate; users can
+/// override any entry in settings.
+pub const DEFAULT_EXCHANGE_RATES: &[(&str, f64)] = &[
+    ("EUR", 0.92),
+    ("GBP", 0.79),
+    ("CNY", 7.20),
+    ("JPY", 150.0),
+    ("KRW", 1350.0),
+    ("INR", 83.0),
+    ("CAD", 1.36),
+    ("AUD", 1.52),
+    ("NZD", 1.65),
+    ("HKD", 7.80),
+    ("TWD", 32.0),
+    ("SGD", 1.34),
+    ("CHF", 0.88),
+    ("SEK", 10.5),
+    ("NOK", 10.7),
+    ("DKK", 6.9),
+    ("PLN", 4.0),
+    ("BRL", 5.0),
+    ("MXN", 17.0),
+];
+
+/// Look up display details for a currency code (case-insensitive)
+pub fn currency_info(code: &str) -> Option<&'static CurrencyInfo> {
+    let code = code.trim();
+    CURRENCIES.iter().find(|c| c.code.eq_ignore_ascii_case(code))
+}
+
+/// Format a value as currency
+pub fn format_currency(value: f64, currency_code: &str) -> String {
+    match currency_info(currency_code) {
+        Some(info) => {
+            let sign = if value < 0.0 { "-" } else { "" };
+            format!("{}{}{:.*}", sign, info.symbol, info.decimals, value.abs())
+        }
+        None => format!("{:.2} {}", value, currency_code.trim().to_uppercase()),
+    }
+}
+
+/// Converts amounts into a reporting currency
+#[derive(Debug, Clone)]
+pub struct CurrencyConverter {
+    reporting_currency: String,
+    /// Units per 1 USD, keyed by upper-case ISO code
+    rates: HashMap<String, f64>,
+}
+
+impl CurrencyConverter {
+    /// Create a converter using the built-in rates, with `overrides` (units
+    /// per 1 USD) taking precedence
+    pub fn new(reporting_currency: &str, overrides: &HashMap<String, f64>) -> Self {
+        let mut rates: HashMap<String, f64> = DEFAULT_EXCHANGE_RATES
+            .iter()
+            .map(|(code, rate)| (code.to_string(), *rate))
+            .collect();
+        for (code, rate) in overrides {
+            if rate.is_finite() && *rate > 0.0 {
+                rates.insert(code.trim().to_uppercase(), *rate);
+            }
+        }
+        rates.insert("USD".to_string(), 1.0);
+
+        Self {
+            reporting_currency: reporting_currency.trim().to_uppercase(),
+            rates,
+        }
+    }
+
+    /// Reporting currency code
+    pub fn reporting_currency(&self) -> &str {
+        &self.reporting_currency
+    }
+
+    /// Units of `code` per 1 USD, if known
+    pub fn rate(&self, code: &str) -> Option<f64> {
+        self.rates.get(&code.trim().to_uppercase()).copied()
+    }
+
+    /// Convert an amount between two currencies
+    pub fn convert_between(&self, amount: f64, from: &str, to: &str) -> Option<f64> {
+        if from.trim().eq_ignore_ascii_case(to.trim()) {
+            return Some(amount);
+        }
+        Some(amount / self.rate(from)? * self.rate(to)?)
+    }
+
+    /// Convert an amount into the reporting currency
+    pub fn convert(&self, amount: f64, from: &str) -> Option<f64> {
+        self.convert_between(amount, from, &self.reporting_currency)
+    }
+
+    /// Format an amount that is already in the reporting currency
+    pub fn format(&self, amount: f64) -> String {
+        format_currency(amount, &self.reporting_currency)
+    }
+
+    /// Add up amounts in mixed currencies. Returns the total in the
+    /// reporting currency plus the codes that had no exchange rate.
+    pub fn total<'a>(&self, amounts: impl IntoIterator<Item = (f64, &'a str)>) -> (f64, Vec<String>) {
+        let mut total = 0.0;
+        let mut missing: Vec<String> = Vec::new();
+        for (amount, code) in amounts {
+            match self.convert(amount, code) {
+                Some(converted) => total += converted,
+                None => {
+                    let code = code.trim().to_uppercase();
+                    if !missing.contains(&code) {
+                        missing.push(code);
+                    }
+                }
+            }
+        }
+        (total, missing)
+    }
+}
+
+impl Default for CurrencyConverter {
+    fn default() -> Self {
+        Self::new("USD", &HashMap::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_currency_symbols() {
+        assert_eq!(format_currency(12.5, "USD"), "$12.50");
+        assert_eq!(format_currency(12.5, "eur"), "€12.50");
+        assert_eq!(format_currency(88.0, "CNY"), "CN¥88.00");
+        assert_eq!(format_currency(1234.4, "JPY"), "¥1234");
+        assert_eq!(format_currency(5.0, "CHF"), "CHF 5.00");
+        assert_eq!(format_currency(-3.0, "GBP"), "-£3.00");
+        assert_eq!(format_currency(7.0, "xyz"), "7.00 XYZ");
+    }
+
+    #[test]
+    fn test_convert_with_defaults_and_overrides() {
+        let converter = CurrencyConverter::default();
+        let usd = converter.convert(72.0, "CNY").unwrap();
+        assert!((usd - 10.0).abs() < 1e-9);
+
+        let mut overrides = HashMap::new();
+        overrides.insert("cny".to_string(), 8.0);
+        overrides.insert("EUR".to_string(), 0.5);
+        overrides.insert("BAD".to_string(), -1.0);
+        let converter = CurrencyConverter::new("eur", &overrides);
+        assert_eq!(converter.reporting_currency(), "EUR");
+        assert!((converter.convert(80.0, "CNY").unwrap() - 5.0).abs() < 1e-9);
+        assert!((converter.convert(10.0, "USD").unwrap() - 5.0).abs() < 1e-9);
+        assert_eq!(converter.convert(1.0, "EUR"), Some(1.0));
+        assert_eq!(converter.rate("BAD"), None);
+        assert_eq!(converter.convert(1.0, "XYZ"), None);
+    }
+
+    #[test]
+    fn test_total_mixed_currencies() {
+        let converter = CurrencyConverter::default();
+        let (total, missing) = converter.total([(10.0, "USD"), (72.0, "CNY"), (3.0, "XYZ")]);
+        assert!((total - 20.0).abs() < 1e-9);
+        assert_eq!(missing, vec!["XYZ".to_string()]);
+        assert_eq!(converter.format(total), "$20.00");
+    }
+}
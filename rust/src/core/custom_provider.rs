@@ -0,0 +1,683 @@
+//! Declarative custom HTTP providers
+//!
+//! Custom providers are read from `custom_providers.toml` (or
+//! `custom_providers.json`) in the CodexBar config directory. Each entry names
+//! a JSON usage endpoint, how to authenticate against it, and JSON paths that
+//! map the response onto a usage snapshot:
+//!
+//! ```toml
+//! [[provider]]
+//! id = "acme"
+//! name = "Acme AI"
+//! url = "https://usage.acme.example/v1/usage"
+//!
+//! [provider.auth]
+//! api_key_env = "ACME_API_KEY"
+//!
+//! [provider.mapping]
+//! used_percent = "$.quota.used_percent"
+//! resets_at = "$.quota.resets_at"
+//! cost_used = "$.billing.spent"
+//! account_email = "$.user.email"
+//! ```
+//!
+//! The same file also declares external-command providers under `[[command]]`;
+//! see `command_provider`.
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use thiserror::Error;
+
+use super::{
+    normalize_base_url_override, CommandProviderConfig, CostSnapshot, ProviderError, ProviderId,
+    RateWindow, UsageSnapshot,
+};
+
+/// Environment variable that points at an alternative custom providers file
+pub const CUSTOM_PROVIDERS_ENV: &str = "CODEXBAR_CUSTOM_PROVIDERS";
+
+/// Current custom providers file format version
+pub const CUSTOM_PROVIDERS_VERSION: u32 = 1;
+
+/// Custom providers loaded from the default file on first use
+pub static CUSTOM_PROVIDERS: LazyLock<CustomProvidersFile> = LazyLock::new(|| {
+    let Some(path) = CustomProvidersFile::default_path() else {
+        return CustomProvidersFile::default();
+    };
+    if !path.exists() {
+        return CustomProvidersFile::default();
+    }
+    match CustomProvidersFile::load_from(&path) {
+        Ok(file) => file,
+        Err(e) => {
+            tracing::warn!("Ignoring custom providers in {}: {}", path.display(), e);
+            CustomProvidersFile::default()
+        }
+    }
+});
+
+/// Look up a loaded custom HTTP provider by ID (case-insensitive)
+pub fn custom_provider(id: &str) -> Option<&'static CustomProviderConfig> {
+    CUSTOM_PROVIDERS.providers.iter().find(|c| c.id.eq_ignore_ascii_case(id))
+}
+
+/// Look up a loaded external-command provider by ID (case-insensitive)
+pub fn command_provider(id: &str) -> Option<&'static CommandProviderConfig> {
+    CUSTOM_PROVIDERS.commands.iter().find(|c| c.id.eq_ignore_ascii_case(id))
+}
+
+/// Errors that can occur when loading custom providers
+#[derive(Debug, Error)]
+pub enum CustomProviderError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("TOML error: {0}")]
+    Toml(#[from] toml::de::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Unsupported custom providers version {0}")]
+    UnsupportedVersion(u32),
+    #[error("Invalid custom provider '{id}': {message}")]
+    Invalid { id: String, message: String },
+}
+
+/// Contents of the custom providers file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomProvidersFile {
+    #[serde(default = "default_version")]
+    pub version: u32,
+    #[serde(default, rename = "provider", alias = "providers")]
+    pub providers: Vec<CustomProviderConfig>,
+    #[serde(default, rename = "command", alias = "commands")]
+    pub commands: Vec<CommandProviderConfig>,
+}
+
+impl Default for CustomProvidersFile {
+    fn default() -> Self {
+        Self {
+            version: CUSTOM_PROVIDERS_VERSION,
+            providers: Vec::new(),
+            commands: Vec::new(),
+        }
+    }
+}
+
+fn default_version() -> u32 {
+    CUSTOM_PROVIDERS_VERSION
+}
+
+impl CustomProvidersFile {
+    /// Path of the custom providers file: `CODEXBAR_CUSTOM_PROVIDERS`, else
+    /// `custom_providers.toml` (or an existing `.json`) in the config directory
+    pub fn default_path() -> Option<PathBuf> {
+        if let Ok(path) = std::env::var(CUSTOM_PROVIDERS_ENV) {
+            if !path.trim().is_empty() {
+                return Some(PathBuf::from(path));
+            }
+        }
+
+        let dir = dirs::config_dir()?.join("CodexBar");
+        let json = dir.join("custom_providers.json");
+        if json.exists() {
+            return Some(json);
+        }
+        Some(dir.join("custom_providers.toml"))
+    }
+
+    /// Load and validate a custom providers file (JSON by extension, TOML otherwise)
+    pub fn load_from(path: &Path) -> Result<Self, CustomProviderError> {
+        let content = std::fs::read_to_string(path)?;
+        let is_json = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+        let file = if is_json {
+            Self::from_json(&content)?
+        } else {
+            Self::from_toml(&content)?
+        };
+        file.validate()?;
+        Ok(file)
+    }
+
+    /// Parse TOML contents without validating
+    pub fn from_toml(content: &str) -> Result<Self, CustomProviderError> {
+        Ok(toml::from_str(content)?)
+    }
+
+    /// Parse JSON contents without validating
+    pub fn from_json(content: &str) -> Result<Self, CustomProviderError> {
+        Ok(serde_json::from_str(content)?)
+    }
+
+    /// Check the version, ID uniqueness and every provider definition
+    pub fn validate(&self) -> Result<(), CustomProviderError> {
+        if self.version != CUSTOM_PROVIDERS_VERSION {
+            return Err(CustomProviderError::UnsupportedVersion(self.version));
+        }
+
+        let mut seen = HashSet::new();
+        for provider in &self.providers {
+            provider.validate()?;
+            if !seen.insert(provider.id.to_lowercase()) {
+                return Err(invalid(&provider.id, "duplicate id"));
+            }
+        }
+        for command in &self.commands {
+            validate_id(&command.id, &command.name)?;
+            command.check().map_err(|message| invalid(&command.id, message))?;
+            if !seen.insert(command.id.to_lowercase()) {
+                return Err(invalid(&command.id, "duplicate id"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Provider IDs of every definition, HTTP providers first
+    pub fn provider_ids(&'static self) -> Vec<ProviderId> {
+        let http = self.providers.iter().map(|c| c.provider_id());
+        let commands = self.commands.iter().map(|c| c.provider_id());
+        http.chain(commands).collect()
+    }
+
+    /// Display name of a definition (case-insensitive ID)
+    pub fn display_name(&self, id: &str) -> Option<&str> {
+        let http = self.providers.iter().map(|c| (&c.id, &c.name));
+        let commands = self.commands.iter().map(|c| (&c.id, &c.name));
+        http.chain(commands)
+            .find(|(candidate, _)| candidate.eq_ignore_ascii_case(id))
+            .map(|(_, name)| name.as_str())
+    }
+}
+
+fn invalid(id: &str, message: impl Into<String>) -> CustomProviderError {
+    CustomProviderError::Invalid {
+        id: id.to_string(),
+        message: message.into(),
+    }
+}
+
+/// Check the ID and name shared by HTTP and command definitions
+fn validate_id(id: &str, name: &str) -> Result<(), CustomProviderError> {
+    let id_ok = !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if !id_ok {
+        return Err(invalid(id, "id must use lowercase letters, digits, '-' or '_'"));
+    }
+    if ProviderId::from_builtin_cli_name(id).is_some() || matches!(id, "all" | "both") {
+        return Err(invalid(id, "id clashes with a built-in provider"));
+    }
+    if name.trim().is_empty() {
+        return Err(invalid(id, "name is empty"));
+    }
+    Ok(())
+}
+
+/// One custom provider definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomProviderConfig {
+    /// CLI name (lowercase letters, digits, `-` and `_`)
+    pub id: String,
+    /// Display name
+    pub name: String,
+    /// Usage endpoint (HTTPS, or HTTP on loopback)
+    pub url: String,
+    /// Extra request headers
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// How to send the API key, if the endpoint needs one
+    #[serde(default)]
+    pub auth: Option<CustomProviderAuth>,
+    /// JSON paths into the response
+    pub mapping: CustomProviderMapping,
+    /// Link shown on the detail card
+    #[serde(default)]
+    pub dashboard_url: Option<String>,
+    /// Request timeout in seconds
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_timeout_secs() -> u64 {
+    15
+}
+
+/// API key placement for a custom provider
+///
+/// The key comes from the API Keys tab (stored under the provider ID) or,
+/// failing that, from `api_key_env`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomProviderAuth {
+    /// Header that carries the key
+    #[serde(default = "default_auth_header")]
+    pub header: String,
+    /// Text placed before the key in the header value
+    #[serde(default = "default_auth_prefix")]
+    pub prefix: String,
+    /// Environment variable holding the key
+    #[serde(default)]
+    pub api_key_env: Option<String>,
+}
+
+fn default_auth_header() -> String {
+    "Authorization".to_string()
+}
+
+fn default_auth_prefix() -> String {
+    "Bearer ".to_string()
+}
+
+/// JSON path mappings from the response onto a usage snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomProviderMapping {
+    /// Percent of the quota used (0-100)
+    pub used_percent: String,
+    /// Reset time: RFC 3339 string or Unix timestamp (seconds or milliseconds)
+    #[serde(default)]
+    pub resets_at: Option<String>,
+    /// Window length in minutes
+    #[serde(default)]
+    pub window_minutes: Option<String>,
+    /// Label for the usage window
+    #[serde(default = "default_window_label")]
+    pub window_label: String,
+    /// Amount spent
+    #[serde(default)]
+    pub cost_used: Option<String>,
+    /// Spending limit
+    #[serde(default)]
+    pub cost_limit: Option<String>,
+    /// ISO currency code of the cost fields
+    #[serde(default = "default_currency")]
+    pub currency: String,
+    /// Billing period label for the cost fields
+    #[serde(default = "default_cost_period")]
+    pub cost_period: String,
+    /// Account email
+    #[serde(default)]
+    pub account_email: Option<String>,
+    /// Plan name
+    #[serde(default)]
+    pub plan: Option<String>,
+}
+
+fn default_window_label() -> String {
+    "Usage".to_string()
+}
+
+fn default_currency() -> String {
+    "USD".to_string()
+}
+
+fn default_cost_period() -> String {
+    "Monthly".to_string()
+}
+
+impl CustomProviderConfig {
+    /// Provider ID for this definition
+    pub fn provider_id(&'static self) -> ProviderId {
+        ProviderId::Custom(self.id.as_str())
+    }
+
+    fn invalid(&self, message: impl Into<String>) -> CustomProviderError {
+        invalid(&self.id, message)
+    }
+
+    /// Check the ID, endpoint and JSON paths
+    pub fn validate(&self) -> Result<(), CustomProviderError> {
+        validate_id(&self.id, &self.name)?;
+        if normalize_base_url_override(&self.url).is_none() || reqwest::Url::parse(&self.url).is_err() {
+            return Err(self.invalid("url must be an HTTPS URL (or HTTP on localhost)"));
+        }
+
+        let mapping = &self.mapping;
+        let paths = [
+            Some(&mapping.used_percent),
+            mapping.resets_at.as_ref(),
+            mapping.window_minutes.as_ref(),
+            mapping.cost_used.as_ref(),
+            mapping.cost_limit.as_ref(),
+            mapping.account_email.as_ref(),
+            mapping.plan.as_ref(),
+        ];
+        for path in paths.into_iter().flatten() {
+            parse_json_path(path).map_err(|e| self.invalid(format!("bad JSON path '{}': {}", path, e)))?;
+        }
+        Ok(())
+    }
+
+    /// Resolve the request URL, swapping in the base URL override's origin
+    pub fn request_url(&self, base_url: Option<&str>) -> String {
+        let Some(base) = base_url else {
+            return self.url.clone();
+        };
+        match reqwest::Url::parse(&self.url) {
+            Ok(url) => {
+                let mut path = url.path().to_string();
+                if let Some(query) = url.query() {
+                    path.push('?');
+                    path.push_str(query);
+                }
+                format!("{}{}", base.trim_end_matches('/'), path)
+            }
+            Err(_) => self.url.clone(),
+        }
+    }
+
+    /// Resolve the API key: the stored key first, then the configured env var
+    pub fn api_key(&self, stored: Option<&str>) -> Option<String> {
+        if let Some(key) = stored.filter(|k| !k.is_empty()) {
+            return Some(key.to_string());
+        }
+        let var = self.auth.as_ref()?.api_key_env.as_ref()?;
+        std::env::var(var).ok().filter(|k| !k.is_empty())
+    }
+
+    /// Map a JSON response onto a usage snapshot and optional cost
+    pub fn parse_response(
+        &self,
+        json: &serde_json::Value,
+    ) -> Result<(UsageSnapshot, Option<CostSnapshot>), ProviderError> {
+        let mapping = &self.mapping;
+        let lookup = |path: &Option<String>| path.as_deref().and_then(|p| json_path(json, p));
+
+        let used_percent = json_path(json, &mapping.used_percent)
+            .and_then(value_as_f64)
+            .ok_or_else(|| {
+                ProviderError::Parse(format!("{}: no number at '{}'", self.name, mapping.used_percent))
+            })?;
+        let resets_at = lookup(&mapping.resets_at).and_then(value_as_datetime);
+        let window_minutes = lookup(&mapping.window_minutes)
+            .and_then(value_as_f64)
+            .map(|m| m.round() as u32);
+
+        let window = RateWindow::with_details(used_percent.clamp(0.0, 100.0), window_minutes, resets_at, None);
+        let mut usage = UsageSnapshot::new(window);
+        if let Some(primary) = usage.windows.first_mut() {
+            primary.label = mapping.window_label.clone();
+        }
+        if let Some(email) = lookup(&mapping.account_email).and_then(|v| v.as_str()) {
+            usage = usage.with_email(email);
+        }
+        if let Some(plan) = lookup(&mapping.plan).and_then(|v| v.as_str()) {
+            usage = usage.with_login_method(plan);
+        }
+
+        let cost = lookup(&mapping.cost_used).and_then(value_as_f64).map(|used| {
+            let mut cost = CostSnapshot::new(used, mapping.currency.to_uppercase(), mapping.cost_period.clone());
+            if let Some(limit) = lookup(&mapping.cost_limit).and_then(value_as_f64) {
+                cost = cost.with_limit(limit);
+            }
+            if let Some(reset) = resets_at {
+                cost = cost.with_resets_at(reset);
+            }
+            cost
+        });
+
+        Ok((usage, cost))
+    }
+}
+
+/// One step of a JSON path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JsonPathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// Parse a JSON path such as `$.data.limits[0].used` or `data["plan name"]`
+pub fn parse_json_path(path: &str) -> Result<Vec<JsonPathSegment>, String> {
+    let path = path.trim();
+    let rest = path.strip_prefix('$').unwrap_or(path);
+    let chars: Vec<char> = rest.chars().collect();
+    let mut segments = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '.' => {
+                let start = i + 1;
+                let mut end = start;
+                while end < chars.len() && chars[end] != '.' && chars[end] != '[' {
+                    end += 1;
+                }
+                if end == start {
+                    return Err("empty key".to_string());
+                }
+                segments.push(JsonPathSegment::Key(chars[start..end].iter().collect()));
+                i = end;
+            }
+            '[' => {
+                let close = chars[i..]
+                    .iter()
+                    .position(|&c| c == ']')
+                    .map(|p| p + i)
+                    .ok_or_else(|| "unclosed '['".to_string())?;
+                let inner: String = chars[i + 1..close].iter().collect();
+                let inner = inner.trim();
+                let quoted = inner.len() >= 2
+                    && ((inner.starts_with('"') && inner.ends_with('"'))
+                        || (inner.starts_with('\'') && inner.ends_with('\'')));
+                if quoted {
+                    segments.push(JsonPathSegment::Key(inner[1..inner.len() - 1].to_string()));
+                } else {
+                    let index = inner.parse().map_err(|_| format!("bad index '{}'", inner))?;
+                    segments.push(JsonPathSegment::Index(index));
+                }
+                i = close + 1;
+            }
+            _ if i == 0 && !path.starts_with('$') => {
+                // Bare leading key, e.g. `data.used`
+                let mut end = 0;
+                while end < chars.len() && chars[end] != '.' && chars[end] != '[' {
+                    end += 1;
+                }
+                segments.push(JsonPathSegment::Key(chars[..end].iter().collect()));
+                i = end;
+            }
+            c => return Err(format!("unexpected '{}'", c)),
+        }
+    }
+
+    if segments.is_empty() {
+        return Err("path selects nothing".to_string());
+    }
+    Ok(segments)
+}
+
+/// Evaluate a JSON path, returning `None` when it is invalid or missing
+pub fn json_path<'a>(value: &'a serde_json::Value, path: &str) -> Option<&'a serde_json::Value> {
+    let segments = parse_json_path(path).ok()?;
+    segments.iter().try_fold(value, |current, segment| match segment {
+        JsonPathSegment::Key(key) => current.get(key),
+        JsonPathSegment::Index(index) => current.get(index),
+    })
+}
+
+/// Read a number from a JSON number or numeric string
+fn value_as_f64(value: &serde_json::Value) -> Option<f64> {
+    match value {
+        serde_json::Value::Number(n) => n.as_f64(),
+        serde_json::Value::String(s) => s.trim().trim_end_matches('%').trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Read a timestamp from an RFC 3339 string or a Unix timestamp
+fn value_as_datetime(value: &serde_json::Value) -> Option<DateTime<Utc>> {
+    if let Some(s) = value.as_str() {
+        if let Ok(dt) = DateTime::parse_from_rfc3339(s.trim()) {
+            return Some(dt.with_timezone(&Utc));
+        }
+    }
+    let ts = value_as_f64(value)?;
+    // Treat large values as milliseconds
+    let millis = if ts.abs() >= 1e12 { ts } else { ts * 1000.0 };
+    Utc.timestamp_millis_opt(millis as i64).single()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = r#"
+[[provider]]
+id = "acme"
+name = "Acme AI"
+url = "https://usage.acme.example/v1/usage?team=core"
+headers = { "X-Team" = "core" }
+dashboard_url = "https://acme.example/billing"
+
+[provider.auth]
+api_key_env = "ACME_API_KEY"
+
+[provider.mapping]
+used_percent = "$.quota.used_percent"
+resets_at = "$.quota.resets_at"
+window_minutes = "$.quota.window_minutes"
+window_label = "Daily"
+cost_used = "$.billing.spent"
+cost_limit = "$.billing['budget']"
+currency = "eur"
+account_email = "$.user.email"
+plan = "$.plans[0].name"
+"#;
+
+    #[test]
+    fn test_parse_toml_file() {
+        let file = CustomProvidersFile::from_toml(EXAMPLE).unwrap();
+        file.validate().unwrap();
+        assert_eq!(file.version, 1);
+        assert_eq!(file.providers.len(), 1);
+
+        let acme = &file.providers[0];
+        assert_eq!(acme.id, "acme");
+        assert_eq!(acme.headers.get("X-Team").map(String::as_str), Some("core"));
+        let auth = acme.auth.as_ref().unwrap();
+        assert_eq!(auth.header, "Authorization");
+        assert_eq!(auth.prefix, "Bearer ");
+        assert_eq!(acme.timeout_secs, 15);
+    }
+
+    #[test]
+    fn test_parse_json_file() {
+        let json = r#"{
+            "version": 1,
+            "providers": [{
+                "id": "local-llm",
+                "name": "Local LLM",
+                "url": "http://127.0.0.1:8080/usage",
+                "mapping": { "used_percent": "used" }
+            }]
+        }"#;
+        let file = CustomProvidersFile::from_json(json).unwrap();
+        file.validate().unwrap();
+        assert!(file.providers[0].auth.is_none());
+        assert_eq!(file.providers[0].mapping.window_label, "Usage");
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_definitions() {
+        let base = CustomProvidersFile::from_toml(EXAMPLE).unwrap();
+
+        let mut clash = base.clone();
+        clash.providers[0].id = "claude".to_string();
+        assert!(clash.validate().is_err());
+
+        let mut insecure = base.clone();
+        insecure.providers[0].url = "http://usage.acme.example/v1".to_string();
+        assert!(insecure.validate().is_err());
+
+        let mut bad_path = base.clone();
+        bad_path.providers[0].mapping.used_percent = "$.quota[".to_string();
+        assert!(bad_path.validate().is_err());
+
+        let mut duplicate = base.clone();
+        duplicate.providers.push(base.providers[0].clone());
+        assert!(duplicate.validate().is_err());
+
+        let mut future = base;
+        future.version = 2;
+        assert!(matches!(future.validate(), Err(CustomProviderError::UnsupportedVersion(2))));
+    }
+
+    #[test]
+    fn test_command_providers() {
+        let content = format!(
+            "{}\n[[command]]\nid = \"gateway\"\nname = \"Gateway\"\ncommand = \"gateway-usage\"\nargs = [\"--json\"]\n",
+            EXAMPLE
+        );
+        let file = CustomProvidersFile::from_toml(&content).unwrap();
+        file.validate().unwrap();
+        assert_eq!(file.commands.len(), 1);
+        assert_eq!(file.commands[0].args, vec!["--json".to_string()]);
+        assert_eq!(file.commands[0].timeout_secs, 30);
+        assert_eq!(file.display_name("GATEWAY"), Some("Gateway"));
+
+        let mut duplicate = file.clone();
+        duplicate.commands[0].id = "acme".to_string();
+        assert!(duplicate.validate().is_err());
+
+        let mut empty = file;
+        empty.commands[0].command = " ".to_string();
+        assert!(empty.validate().is_err());
+    }
+
+    #[test]
+    fn test_json_path() {
+        let value = serde_json::json!({
+            "data": { "limits": [{ "used": 12 }, { "used": "40.5" }], "plan name": "Pro" }
+        });
+        assert_eq!(json_path(&value, "$.data.limits[0].used"), Some(&serde_json::json!(12)));
+        assert_eq!(json_path(&value, "data.limits[1].used"), Some(&serde_json::json!("40.5")));
+        assert_eq!(json_path(&value, "$.data[\"plan name\"]"), Some(&serde_json::json!("Pro")));
+        assert_eq!(json_path(&value, "$.data.missing"), None);
+        assert_eq!(json_path(&value, "$.data.limits[9]"), None);
+        assert!(parse_json_path("$").is_err());
+        assert!(parse_json_path("$..a").is_err());
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let file = CustomProvidersFile::from_toml(EXAMPLE).unwrap();
+        let acme = &file.providers[0];
+        let response = serde_json::json!({
+            "quota": { "used_percent": "37.5", "resets_at": 1767225600, "window_minutes": 1440 },
+            "billing": { "spent": 12.25, "budget": 50 },
+            "user": { "email": "dev@acme.example" },
+            "plans": [{ "name": "Team" }]
+        });
+
+        let (usage, cost) = acme.parse_response(&response).unwrap();
+        let primary = usage.primary();
+        assert_eq!(primary.used_percent, 37.5);
+        assert_eq!(primary.window_minutes, Some(1440));
+        assert_eq!(primary.resets_at, Utc.timestamp_opt(1767225600, 0).single());
+        assert_eq!(usage.windows[0].label, "Daily");
+        assert_eq!(usage.account_email.as_deref(), Some("dev@acme.example"));
+        assert_eq!(usage.login_method.as_deref(), Some("Team"));
+
+        let cost = cost.unwrap();
+        assert_eq!(cost.used, 12.25);
+        assert_eq!(cost.limit, Some(50.0));
+        assert_eq!(cost.currency_code, "EUR");
+
+        let missing = acme.parse_response(&serde_json::json!({ "quota": {} }));
+        assert!(matches!(missing, Err(ProviderError::Parse(_))));
+    }
+
+    #[test]
+    fn test_request_url_with_override() {
+        let file = CustomProvidersFile::from_toml(EXAMPLE).unwrap();
+        let acme = &file.providers[0];
+        assert_eq!(acme.request_url(None), "https://usage.acme.example/v1/usage?team=core");
+        assert_eq!(
+            acme.request_url(Some("http://127.0.0.1:9000/")),
+            "http://127.0.0.1:9000/v1/usage?team=core"
+        );
+    }
+}
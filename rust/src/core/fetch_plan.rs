@@ -5,12 +5,11 @@
 
 #![allow(dead_code)]
 
-use crate::core::{CreditsSnapshot, OpenAIDashboardSnapshot, ProviderId, UsageSnapshot};
+use crate::core::{FetchContext, ProviderError, ProviderFetchResult, ProviderId, SourceMode};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
-use std::future::Future;
-use std::pin::Pin;
 use std::sync::Arc;
-use thiserror::Error;
 
 /// Provider runtime context
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -52,22 +51,60 @@ impl ProviderSourceMode {
 }
 
 /// Kind of fetch strategy
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ProviderFetchKind {
     /// Command-line interface
+    #[serde(rename = "cli")]
     Cli,
     /// Web scraping
+    #[serde(rename = "web")]
     Web,
     /// OAuth token flow
+    #[serde(rename = "oauth")]
     OAuth,
     /// API token authentication
+    #[serde(rename = "api_token")]
     ApiToken,
     /// Local probe (e.g., SQLite, file system)
+    #[serde(rename = "local_probe")]
     LocalProbe,
     /// Web dashboard parsing
+    #[serde(rename = "web_dashboard")]
     WebDashboard,
 }
 
+impl ProviderFetchKind {
+    /// Short identifier used in JSON output and logs
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Cli => "cli",
+            Self::Web => "web",
+            Self::OAuth => "oauth",
+            Self::ApiToken => "api_token",
+            Self::LocalProbe => "local_probe",
+            Self::WebDashboard => "web_dashboard",
+        }
+    }
+
+    /// The user-facing source mode that selects this kind of strategy
+    ///
+    /// API tokens are grouped with OAuth and local probes with CLI, matching
+    /// the `--source` values the CLI has always accepted.
+    pub fn source_mode(&self) -> SourceMode {
+        match self {
+            Self::OAuth | Self::ApiToken => SourceMode::OAuth,
+            Self::Web | Self::WebDashboard => SourceMode::Web,
+            Self::Cli | Self::LocalProbe => SourceMode::Cli,
+        }
+    }
+}
+
+impl std::fmt::Display for ProviderFetchKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Context provided to fetch strategies
 #[derive(Debug, Clone)]
 pub struct ProviderFetchContext {
@@ -130,53 +167,8 @@ impl ProviderFetchContext {
     }
 }
 
-/// Result of a successful pipeline fetch
-#[derive(Debug, Clone)]
-pub struct PipelineFetchResult {
-    /// Usage data
-    pub usage: UsageSnapshot,
-    /// Credits data (if available)
-    pub credits: Option<CreditsSnapshot>,
-    /// Dashboard data (for OpenAI/Codex)
-    pub dashboard: Option<OpenAIDashboardSnapshot>,
-    /// Human-readable source label (e.g., "Chrome", "CLI")
-    pub source_label: String,
-    /// Strategy identifier
-    pub strategy_id: String,
-    /// Kind of strategy used
-    pub strategy_kind: ProviderFetchKind,
-}
-
-impl PipelineFetchResult {
-    pub fn new(
-        usage: UsageSnapshot,
-        source_label: impl Into<String>,
-        strategy_id: impl Into<String>,
-        strategy_kind: ProviderFetchKind,
-    ) -> Self {
-        Self {
-            usage,
-            credits: None,
-            dashboard: None,
-            source_label: source_label.into(),
-            strategy_id: strategy_id.into(),
-            strategy_kind,
-        }
-    }
-
-    pub fn with_credits(mut self, credits: CreditsSnapshot) -> Self {
-        self.credits = Some(credits);
-        self
-    }
-
-    pub fn with_dashboard(mut self, dashboard: OpenAIDashboardSnapshot) -> Self {
-        self.dashboard = Some(dashboard);
-        self
-    }
-}
-
 /// Record of a single fetch attempt
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ProviderFetchAttempt {
     /// Strategy identifier
     pub strategy_id: String,
@@ -185,6 +177,7 @@ pub struct ProviderFetchAttempt {
     /// Whether the strategy was available
     pub was_available: bool,
     /// Error description if the attempt failed
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub error_description: Option<String>,
 }
 
@@ -219,26 +212,43 @@ impl ProviderFetchAttempt {
             error_description: Some(error.into()),
         }
     }
+
+    /// Whether this attempt produced the returned result
+    pub fn succeeded(&self) -> bool {
+        self.was_available && self.error_description.is_none()
+    }
+
+    /// One-line summary, e.g. `web (web): No cookies available for web API`
+    pub fn summary(&self) -> String {
+        let outcome = if !self.was_available {
+            "unavailable".to_string()
+        } else if let Some(ref error) = self.error_description {
+            error.clone()
+        } else {
+            "ok".to_string()
+        };
+        format!("{} ({}): {}", self.strategy_id, self.kind, outcome)
+    }
 }
 
 /// Outcome of a fetch operation
 #[derive(Debug)]
 pub struct ProviderFetchOutcome {
     /// The result (success or error)
-    pub result: Result<PipelineFetchResult, ProviderFetchError>,
+    pub result: Result<ProviderFetchResult, ProviderError>,
     /// All attempts made during the fetch
     pub attempts: Vec<ProviderFetchAttempt>,
 }
 
 impl ProviderFetchOutcome {
-    pub fn success(result: PipelineFetchResult, attempts: Vec<ProviderFetchAttempt>) -> Self {
+    pub fn success(result: ProviderFetchResult, attempts: Vec<ProviderFetchAttempt>) -> Self {
         Self {
             result: Ok(result),
             attempts,
         }
     }
 
-    pub fn failure(error: ProviderFetchError, attempts: Vec<ProviderFetchAttempt>) -> Self {
+    pub fn failure(error: ProviderError, attempts: Vec<ProviderFetchAttempt>) -> Self {
         Self {
             result: Err(error),
             attempts,
@@ -248,37 +258,16 @@ impl ProviderFetchOutcome {
     pub fn is_success(&self) -> bool {
         self.result.is_ok()
     }
-}
-
-/// Errors that can occur during provider fetch
-#[derive(Debug, Error)]
-pub enum ProviderFetchError {
-    #[error("No available fetch strategy for {0}")]
-    NoAvailableStrategy(ProviderId),
 
-    #[error("All strategies failed for {0}")]
-    AllStrategiesFailed(ProviderId),
-
-    #[error("Strategy error: {0}")]
-    StrategyError(String),
-
-    #[error("Network error: {0}")]
-    NetworkError(String),
-
-    #[error("Authentication required")]
-    AuthenticationRequired,
-
-    #[error("Rate limited")]
-    RateLimited,
-
-    #[error("Parse error: {0}")]
-    ParseError(String),
-
-    #[error("Timeout")]
-    Timeout,
+    /// Convert into a plain result, attaching the attempts to a successful fetch
+    pub fn into_result(self) -> Result<ProviderFetchResult, ProviderError> {
+        let attempts = self.attempts;
+        self.result.map(|result| result.with_attempts(attempts))
+    }
 }
 
 /// Trait for fetch strategies
+#[async_trait]
 pub trait ProviderFetchStrategy: Send + Sync {
     /// Unique identifier for this strategy
     fn id(&self) -> &str;
@@ -287,31 +276,30 @@ pub trait ProviderFetchStrategy: Send + Sync {
     fn kind(&self) -> ProviderFetchKind;
 
     /// Check if this strategy is available in the given context
-    fn is_available(&self, context: &ProviderFetchContext) -> bool;
+    fn is_available(&self, _context: &FetchContext) -> bool {
+        true
+    }
 
     /// Execute the fetch
-    fn fetch(
-        &self,
-        context: &ProviderFetchContext,
-    ) -> Pin<Box<dyn Future<Output = Result<PipelineFetchResult, ProviderFetchError>> + Send + '_>>;
+    async fn fetch(&self, context: &FetchContext) -> Result<ProviderFetchResult, ProviderError>;
 
     /// Whether to fallback to the next strategy on this error
-    fn should_fallback(&self, error: &ProviderFetchError, _context: &ProviderFetchContext) -> bool {
-        // Default: fallback on network errors and timeouts
-        matches!(
-            error,
-            ProviderFetchError::NetworkError(_) | ProviderFetchError::Timeout
-        )
+    fn should_fallback(&self, _error: &ProviderError, context: &FetchContext) -> bool {
+        // Default: keep trying in auto mode, stop when a source was forced
+        context.source_mode == SourceMode::Auto
     }
 }
 
 /// Pipeline that executes strategies in order with fallback
-pub struct ProviderFetchPipeline {
-    strategies: Vec<Arc<dyn ProviderFetchStrategy>>,
+///
+/// Strategies usually borrow the provider that declares them, so a pipeline
+/// is built per fetch via `Provider::fetch_pipeline`.
+pub struct ProviderFetchPipeline<'a> {
+    strategies: Vec<Arc<dyn ProviderFetchStrategy + 'a>>,
 }
 
-impl ProviderFetchPipeline {
-    pub fn new(strategies: Vec<Arc<dyn ProviderFetchStrategy>>) -> Self {
+impl<'a> ProviderFetchPipeline<'a> {
+    pub fn new(strategies: Vec<Arc<dyn ProviderFetchStrategy + 'a>>) -> Self {
         Self { strategies }
     }
 
@@ -321,22 +309,48 @@ impl ProviderFetchPipeline {
         }
     }
 
-    pub fn with_strategy(mut self, strategy: Arc<dyn ProviderFetchStrategy>) -> Self {
-        self.strategies.push(strategy);
+    pub fn with_strategy(mut self, strategy: impl ProviderFetchStrategy + 'a) -> Self {
+        self.strategies.push(Arc::new(strategy));
         self
     }
 
+    /// Kinds of all declared strategies, in order
+    pub fn kinds(&self) -> Vec<ProviderFetchKind> {
+        self.strategies.iter().map(|s| s.kind()).collect()
+    }
+
+    /// Source modes this pipeline can serve (always includes `Auto`)
+    pub fn source_modes(&self) -> Vec<SourceMode> {
+        let mut modes = vec![SourceMode::Auto];
+        for kind in self.kinds() {
+            let mode = kind.source_mode();
+            if !modes.contains(&mode) {
+                modes.push(mode);
+            }
+        }
+        modes
+    }
+
     /// Execute the pipeline
-    pub async fn fetch(
-        &self,
-        context: &ProviderFetchContext,
-        provider: ProviderId,
-    ) -> ProviderFetchOutcome {
+    ///
+    /// In `Auto` mode every strategy is tried in order; otherwise only the
+    /// strategies whose kind maps to the requested source mode run.
+    pub async fn fetch(&self, context: &FetchContext, provider: ProviderId) -> ProviderFetchOutcome {
         let mut attempts = Vec::with_capacity(self.strategies.len());
+        let mut last_error = None;
+        let mut matched_mode = false;
 
         for strategy in &self.strategies {
+            if context.source_mode != SourceMode::Auto
+                && strategy.kind().source_mode() != context.source_mode
+            {
+                continue;
+            }
+            matched_mode = true;
+
             // Check availability
             if !strategy.is_available(context) {
+                tracing::debug!("{}: strategy '{}' unavailable", provider, strategy.id());
                 attempts.push(ProviderFetchAttempt::unavailable(
                     strategy.id(),
                     strategy.kind(),
@@ -351,6 +365,7 @@ impl ProviderFetchPipeline {
                     return ProviderFetchOutcome::success(result, attempts);
                 }
                 Err(error) => {
+                    tracing::debug!("{}: strategy '{}' failed: {}", provider, strategy.id(), error);
                     let should_fallback = strategy.should_fallback(&error, context);
                     attempts.push(ProviderFetchAttempt::failed(
                         strategy.id(),
@@ -361,15 +376,16 @@ impl ProviderFetchPipeline {
                     if !should_fallback {
                         return ProviderFetchOutcome::failure(error, attempts);
                     }
+                    last_error = Some(error);
                 }
             }
         }
 
-        // No strategies succeeded
-        let error = if attempts.is_empty() {
-            ProviderFetchError::NoAvailableStrategy(provider)
-        } else {
-            ProviderFetchError::AllStrategiesFailed(provider)
+        // No strategies succeeded: surface the last real error when there is one
+        let error = match last_error {
+            Some(error) => error,
+            None if !matched_mode => ProviderError::UnsupportedSource(context.source_mode),
+            None => ProviderError::NoAvailableStrategy(provider),
         };
 
         ProviderFetchOutcome::failure(error, attempts)
@@ -377,15 +393,15 @@ impl ProviderFetchPipeline {
 }
 
 /// Top-level fetch plan for a provider
-pub struct ProviderFetchPlan {
+pub struct ProviderFetchPlan<'a> {
     /// Supported source modes
     pub source_modes: HashSet<ProviderSourceMode>,
     /// Fetch pipeline
-    pub pipeline: ProviderFetchPipeline,
+    pub pipeline: ProviderFetchPipeline<'a>,
 }
 
-impl ProviderFetchPlan {
-    pub fn new(source_modes: HashSet<ProviderSourceMode>, pipeline: ProviderFetchPipeline) -> Self {
+impl<'a> ProviderFetchPlan<'a> {
+    pub fn new(source_modes: HashSet<ProviderSourceMode>, pipeline: ProviderFetchPipeline<'a>) -> Self {
         Self {
             source_modes,
             pipeline,
@@ -393,7 +409,7 @@ impl ProviderFetchPlan {
     }
 
     /// Create a plan with a single source mode
-    pub fn single_mode(mode: ProviderSourceMode, pipeline: ProviderFetchPipeline) -> Self {
+    pub fn single_mode(mode: ProviderSourceMode, pipeline: ProviderFetchPipeline<'a>) -> Self {
         let mut modes = HashSet::new();
         modes.insert(mode);
         Self::new(modes, pipeline)
@@ -407,7 +423,7 @@ impl ProviderFetchPlan {
     /// Execute the fetch plan
     pub async fn fetch_outcome(
         &self,
-        context: &ProviderFetchContext,
+        context: &FetchContext,
         provider: ProviderId,
     ) -> ProviderFetchOutcome {
         self.pipeline.fetch(context, provider).await
@@ -455,4 +471,141 @@ mod tests {
         assert!(failed.was_available);
         assert_eq!(failed.error_description, Some("auth failed".to_string()));
     }
+
+    struct TestStrategy {
+        id: &'static str,
+        kind: ProviderFetchKind,
+        available: bool,
+        error: Option<&'static str>,
+    }
+
+    impl TestStrategy {
+        fn ok(id: &'static str, kind: ProviderFetchKind) -> Self {
+            Self { id, kind, available: true, error: None }
+        }
+
+        fn failing(id: &'static str, kind: ProviderFetchKind, error: &'static str) -> Self {
+            Self { id, kind, available: true, error: Some(error) }
+        }
+
+        fn unavailable(id: &'static str, kind: ProviderFetchKind) -> Self {
+            Self { id, kind, available: false, error: None }
+        }
+    }
+
+    #[async_trait]
+    impl ProviderFetchStrategy for TestStrategy {
+        fn id(&self) -> &str {
+            self.id
+        }
+
+        fn kind(&self) -> ProviderFetchKind {
+            self.kind
+        }
+
+        fn is_available(&self, _context: &FetchContext) -> bool {
+            self.available
+        }
+
+        async fn fetch(&self, _context: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+            match self.error {
+                Some(error) => Err(ProviderError::Other(error.to_string())),
+                None => Ok(ProviderFetchResult::new(
+                    crate::core::UsageSnapshot::new(crate::core::RateWindow::new(10.0)),
+                    self.id,
+                )),
+            }
+        }
+    }
+
+    fn auto_context() -> FetchContext {
+        FetchContext::default()
+    }
+
+    #[test]
+    fn test_fetch_kind_source_mode() {
+        assert_eq!(ProviderFetchKind::ApiToken.source_mode(), SourceMode::OAuth);
+        assert_eq!(ProviderFetchKind::WebDashboard.source_mode(), SourceMode::Web);
+        assert_eq!(ProviderFetchKind::LocalProbe.source_mode(), SourceMode::Cli);
+        assert_eq!(ProviderFetchKind::ApiToken.as_str(), "api_token");
+    }
+
+    #[test]
+    fn test_pipeline_records_fallback_attempts() {
+        let pipeline = ProviderFetchPipeline::empty()
+            .with_strategy(TestStrategy::unavailable("oauth", ProviderFetchKind::OAuth))
+            .with_strategy(TestStrategy::failing("web", ProviderFetchKind::Web, "no cookies"))
+            .with_strategy(TestStrategy::ok("cli", ProviderFetchKind::Cli));
+
+        let outcome = tokio_test::block_on(pipeline.fetch(&auto_context(), ProviderId::Claude));
+        assert!(outcome.is_success());
+        assert_eq!(outcome.attempts.len(), 3);
+        assert!(!outcome.attempts[0].was_available);
+        assert_eq!(outcome.attempts[1].error_description.as_deref(), Some("no cookies"));
+        assert!(outcome.attempts[2].succeeded());
+
+        let result = outcome.into_result().unwrap();
+        assert_eq!(result.source_label, "cli");
+        assert_eq!(result.attempts.len(), 3);
+    }
+
+    #[test]
+    fn test_pipeline_returns_last_error_when_all_fail() {
+        let pipeline = ProviderFetchPipeline::empty()
+            .with_strategy(TestStrategy::failing("web", ProviderFetchKind::Web, "first"))
+            .with_strategy(TestStrategy::failing("cli", ProviderFetchKind::Cli, "second"));
+
+        let outcome = tokio_test::block_on(pipeline.fetch(&auto_context(), ProviderId::Amp));
+        assert_eq!(outcome.attempts.len(), 2);
+        assert_eq!(outcome.result.unwrap_err().to_string(), "second");
+    }
+
+    #[test]
+    fn test_pipeline_respects_forced_source_mode() {
+        let pipeline = ProviderFetchPipeline::empty()
+            .with_strategy(TestStrategy::ok("web", ProviderFetchKind::Web))
+            .with_strategy(TestStrategy::failing("cli", ProviderFetchKind::Cli, "cli failed"));
+
+        let ctx = FetchContext {
+            source_mode: SourceMode::Cli,
+            ..FetchContext::default()
+        };
+        let outcome = tokio_test::block_on(pipeline.fetch(&ctx, ProviderId::Amp));
+        assert_eq!(outcome.attempts.len(), 1);
+        assert_eq!(outcome.attempts[0].strategy_id, "cli");
+        assert!(!outcome.is_success());
+
+        let ctx = FetchContext {
+            source_mode: SourceMode::OAuth,
+            ..FetchContext::default()
+        };
+        let outcome = tokio_test::block_on(pipeline.fetch(&ctx, ProviderId::Amp));
+        assert!(outcome.attempts.is_empty());
+        assert!(matches!(
+            outcome.result,
+            Err(ProviderError::UnsupportedSource(SourceMode::OAuth))
+        ));
+    }
+
+    #[test]
+    fn test_pipeline_no_available_strategy() {
+        let pipeline = ProviderFetchPipeline::empty()
+            .with_strategy(TestStrategy::unavailable("cli", ProviderFetchKind::Cli));
+
+        let outcome = tokio_test::block_on(pipeline.fetch(&auto_context(), ProviderId::Kiro));
+        assert!(matches!(
+            outcome.result,
+            Err(ProviderError::NoAvailableStrategy(ProviderId::Kiro))
+        ));
+        assert_eq!(pipeline.source_modes(), vec![SourceMode::Auto, SourceMode::Cli]);
+    }
+
+    #[test]
+    fn test_fetch_attempt_serializes_kind() {
+        let attempt = ProviderFetchAttempt::failed("zai-api", ProviderFetchKind::ApiToken, "401");
+        let json = serde_json::to_value(&attempt).unwrap();
+        assert_eq!(json["kind"], "api_token");
+        assert_eq!(json["error_description"], "401");
+        assert_eq!(attempt.summary(), "zai-api (api_token): 401");
+    }
 }
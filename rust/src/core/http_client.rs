@@ -0,0 +1,242 @@
+//! Shared HTTP client factory
+//!
+//! Every outgoing request (providers, status pages, the updater and OAuth
+//! token refreshers) builds its `reqwest::Client` here so the proxy, extra
+//! root certificates, timeout and user-agent from settings apply everywhere.
+//! Callers pass their default timeout here rather than per request. The only
+//! requests that set their own user-agent are the ones whose upstream needs
+//! a specific one: Copilot and Warp expect their own clients, and the
+//! scrapers of cookie-authenticated web pages (Kimi, Ollama, OpenCode) send
+//! a browser user-agent.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{LazyLock, Mutex, PoisonError, RwLock};
+use std::time::Duration;
+use thiserror::Error;
+
+/// User-agent sent when settings do not override it
+pub const DEFAULT_USER_AGENT: &str = concat!("CodexBar/", env!("CARGO_PKG_VERSION"));
+
+/// Active configuration, installed from settings at startup and on change
+static HTTP_CLIENT_CONFIG: RwLock<Option<HttpClientConfig>> = RwLock::new(None);
+
+/// Clients built by `http_client`, reused so connections are pooled and the
+/// CA bundle is read once; cleared when the configuration changes
+static HTTP_CLIENTS: LazyLock<Mutex<HashMap<(HttpClientConfig, Duration), reqwest::Client>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Errors in the HTTP client configuration
+#[derive(Debug, Error)]
+pub enum HttpClientError {
+    #[error("Invalid proxy URL '{url}': {message}")]
+    InvalidProxy { url: String, message: String },
+    #[error("Could not read CA bundle {path}: {message}")]
+    CaBundle { path: PathBuf, message: String },
+    #[error("Could not build HTTP client: {0}")]
+    Build(#[from] reqwest::Error),
+}
+
+/// Network settings shared by every HTTP client
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct HttpClientConfig {
+    /// Proxy for all requests (`http://`, `https://`, `socks5://` or `socks5h://`).
+    /// When unset, the `HTTP_PROXY`/`HTTPS_PROXY` environment variables apply.
+    pub proxy: Option<String>,
+    /// Comma-separated hosts, domains or CIDR ranges that bypass the proxy
+    pub no_proxy: Option<String>,
+    /// PEM file with extra root certificates (e.g. a corporate CA)
+    pub ca_bundle: Option<PathBuf>,
+    /// Request timeout in seconds, replacing each caller's default
+    pub timeout_secs: Option<u64>,
+    /// User-agent, replacing `DEFAULT_USER_AGENT`
+    pub user_agent: Option<String>,
+}
+
+impl HttpClientConfig {
+    /// Proxy URL, if one is configured
+    pub fn proxy_url(&self) -> Option<&str> {
+        self.proxy.as_deref().map(str::trim).filter(|p| !p.is_empty())
+    }
+
+    /// Timeout to use when the caller's default is `default_timeout`
+    pub fn timeout(&self, default_timeout: Duration) -> Duration {
+        self.timeout_secs
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs)
+            .unwrap_or(default_timeout)
+    }
+
+    /// Client builder with this configuration applied
+    pub fn try_builder(&self, default_timeout: Duration) -> Result<reqwest::ClientBuilder, HttpClientError> {
+        let user_agent = self
+            .user_agent
+            .as_deref()
+            .map(str::trim)
+            .filter(|ua| !ua.is_empty())
+            .unwrap_or(DEFAULT_USER_AGENT);
+
+        let mut builder = reqwest::Client::builder()
+            .use_rustls_tls()
+            .user_agent(user_agent)
+            .timeout(self.timeout(default_timeout));
+
+        if let Some(url) = self.proxy_url() {
+            let proxy = reqwest::Proxy::all(url).map_err(|e| HttpClientError::InvalidProxy {
+                url: url.to_string(),
+                message: e.to_string(),
+            })?;
+            let no_proxy = self
+                .no_proxy
+                .as_deref()
+                .and_then(reqwest::NoProxy::from_string);
+            builder = builder.proxy(proxy.no_proxy(no_proxy));
+        }
+
+        if let Some(ref path) = self.ca_bundle {
+            let ca_error = |message: String| HttpClientError::CaBundle {
+                path: path.clone(),
+                message,
+            };
+            let pem = std::fs::read(path).map_err(|e| ca_error(e.to_string()))?;
+            let certificates =
+                reqwest::Certificate::from_pem_bundle(&pem).map_err(|e| ca_error(e.to_string()))?;
+            if certificates.is_empty() {
+                return Err(ca_error("no certificates found".to_string()));
+            }
+            for certificate in certificates {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+
+        Ok(builder)
+    }
+
+    /// Build a client with this configuration applied
+    pub fn try_build(&self, default_timeout: Duration) -> Result<reqwest::Client, HttpClientError> {
+        Ok(self.try_builder(default_timeout)?.build()?)
+    }
+}
+
+/// Install the configuration used by `http_client` and `http_client_builder`
+pub fn set_http_client_config(config: HttpClientConfig) {
+    if let Ok(mut current) = HTTP_CLIENT_CONFIG.write() {
+        *current = Some(config);
+    }
+    HTTP_CLIENTS.lock().unwrap_or_else(PoisonError::into_inner).clear();
+}
+
+/// Currently installed configuration
+pub fn http_client_config() -> HttpClientConfig {
+    HTTP_CLIENT_CONFIG
+        .read()
+        .ok()
+        .and_then(|config| config.clone())
+        .unwrap_or_default()
+}
+
+/// Client builder with the shared network settings applied
+///
+/// For callers that chain further options (redirect policy, TLS); the others
+/// use the cached `http_client`. An unusable proxy or CA bundle is an error
+/// rather than a silent direct connection.
+pub fn http_client_builder(default_timeout: Duration) -> Result<reqwest::ClientBuilder, HttpClientError> {
+    http_client_config().try_builder(default_timeout)
+}
+
+/// Shared client with the network settings applied, built once per
+/// configuration and timeout
+pub fn http_client(default_timeout: Duration) -> Result<reqwest::Client, HttpClientError> {
+    let config = http_client_config();
+    let mut clients = HTTP_CLIENTS.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(client) = clients.get(&(config.clone(), default_timeout)) {
+        return Ok(client.clone());
+    }
+    let client = config.try_build(default_timeout)?;
+    clients.insert((config, default_timeout), client.clone());
+    Ok(client)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timeout_override() {
+        let default = Duration::from_secs(30);
+        assert_eq!(HttpClientConfig::default().timeout(default), default);
+
+        let config = HttpClientConfig {
+            timeout_secs: Some(5),
+            ..Default::default()
+        };
+        assert_eq!(config.timeout(default), Duration::from_secs(5));
+
+        let zero = HttpClientConfig {
+            timeout_secs: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(zero.timeout(default), default);
+    }
+
+    #[test]
+    fn test_proxy_configuration() {
+        for url in ["http://proxy.corp:8080", "socks5h://127.0.0.1:1080"] {
+            let config = HttpClientConfig {
+                proxy: Some(url.to_string()),
+                no_proxy: Some("localhost,.corp.example,10.0.0.0/8".to_string()),
+                ..Default::default()
+            };
+            assert!(config.try_build(Duration::from_secs(10)).is_ok(), "{}", url);
+        }
+
+        let blank = HttpClientConfig {
+            proxy: Some("  ".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(blank.proxy_url(), None);
+
+        let bad = HttpClientConfig {
+            proxy: Some("not a url".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            bad.try_builder(Duration::from_secs(10)),
+            Err(HttpClientError::InvalidProxy { .. })
+        ));
+    }
+
+    #[test]
+    fn test_shared_client_is_cached() {
+        let timeout = Duration::from_secs(17);
+        assert!(http_client(timeout).is_ok());
+        assert!(http_client(timeout).is_ok());
+        let clients = HTTP_CLIENTS.lock().unwrap();
+        assert!(clients.contains_key(&(http_client_config(), timeout)));
+    }
+
+    #[test]
+    fn test_ca_bundle_errors() {
+        let missing = HttpClientConfig {
+            ca_bundle: Some(PathBuf::from("/nonexistent/codexbar-ca.pem")),
+            ..Default::default()
+        };
+        assert!(matches!(
+            missing.try_builder(Duration::from_secs(10)),
+            Err(HttpClientError::CaBundle { .. })
+        ));
+
+        let dir = tempfile::tempdir().unwrap();
+        let empty = dir.path().join("empty.pem");
+        std::fs::write(&empty, "not a certificate\n").unwrap();
+        let config = HttpClientConfig {
+            ca_bundle: Some(empty),
+            ..Default::default()
+        };
+        assert!(matches!(
+            config.try_builder(Duration::from_secs(10)),
+            Err(HttpClientError::CaBundle { .. })
+        ));
+    }
+}
@@ -1,65 +1,229 @@
 //! JSONL Scanner with Caching
 //!
-//! Incremental log file parsing for Codex and Claude session logs.
-//! Supports file-level caching to avoid re-parsing unchanged files.
+//! Incremental parsing of the agents' local session logs (see
+//! `LocalLogScanner` for the agents and their log formats).
+//! Each file's per-day, per-model token usage is cached together with its
+//! mtime, size and the byte offset parsed so far, so unchanged files are
+//! skipped and appended files are only read from where the last scan stopped.
+//!
+//! Claude Code copies earlier messages into resumed and forked sessions, and
+//! logs one line per content block of a response. Claude entries are
+//! therefore counted once per message id + request id across all files; the
+//! cache records which file counted each entry.
 
 #![allow(dead_code)]
 
-use crate::core::{CostUsagePricing, ProviderId};
-use chrono::{NaiveDate, Utc};
+use crate::core::{local_log_scanner, CostUsagePricing, CurrencyConverter, ProviderId, RateWindow, UsageEntry};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::{self, File};
 use std::io::{BufRead, BufReader, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
 
+/// Cache layout version, also bumped when costs are computed differently;
+/// caches written with another version are discarded
+pub const COST_USAGE_CACHE_VERSION: u32 = 4;
+
+/// Days of Codex sessions searched for the latest rate-limit snapshot
+pub const CODEX_RATE_LIMITS_LOOKBACK_DAYS: i64 = 7;
+
+/// Rate limits reported by the latest Codex `token_count` event
+#[derive(Debug, Clone)]
+pub struct CodexRateLimits {
+    /// When the event was logged
+    pub timestamp: DateTime<Utc>,
+    pub primary: Option<RateWindow>,
+    pub secondary: Option<RateWindow>,
+    pub plan_type: Option<String>,
+}
+
+/// Token counts and cost for one model on one day
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CostUsageTokens {
+    /// Input tokens, excluding cache reads and writes
+    pub input: i64,
+    /// Input tokens served from the prompt cache
+    pub cache_read: i64,
+    /// Input tokens written to the prompt cache (Claude, OpenCode)
+    pub cache_creation: i64,
+    /// Output tokens
+    pub output: i64,
+    /// Cost in USD (0 for models without pricing)
+    pub cost_usd: f64,
+}
+
+impl CostUsageTokens {
+    pub fn add(&mut self, other: &CostUsageTokens) {
+        self.input += other.input;
+        self.cache_read += other.cache_read;
+        self.cache_creation += other.cache_creation;
+        self.output += other.output;
+        self.cost_usd += other.cost_usd;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.input == 0 && self.cache_read == 0 && self.cache_creation == 0 && self.output == 0
+    }
+}
+
+/// Usage by day and model: day_key -> model -> tokens
+pub type CostUsageDays = HashMap<String, HashMap<String, CostUsageTokens>>;
+
 /// Cache for scanned file data
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct CostUsageCache {
+    /// Layout version (see `COST_USAGE_CACHE_VERSION`)
+    #[serde(default)]
+    pub version: u32,
+    /// Fingerprint of the pricing table the cached costs were computed with
+    #[serde(default)]
+    pub pricing_fingerprint: String,
     /// Last scan timestamp in milliseconds
     pub last_scan_unix_ms: i64,
-    /// Per-file usage data
+    /// Per-file usage data, keyed by path
     pub files: HashMap<String, CostUsageFileUsage>,
-    /// Aggregated daily data: day_key -> model -> [input, cached, output]
-    pub days: HashMap<String, HashMap<String, Vec<i32>>>,
 }
 
 /// Per-file usage tracking
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CostUsageFileUsage {
     /// File modification time in milliseconds
     pub mtime_unix_ms: i64,
     /// File size in bytes
     pub size: i64,
     /// Daily usage data extracted from this file
-    pub days: HashMap<String, HashMap<String, Vec<i32>>>,
+    pub days: CostUsageDays,
     /// Bytes parsed so far (for incremental parsing)
-    pub parsed_bytes: Option<i64>,
+    pub parsed_bytes: i64,
     /// Last model seen (for delta calculations)
     pub last_model: Option<String>,
     /// Last token totals (for delta calculations)
     pub last_totals: Option<CodexTotals>,
+    /// Claude entries (message id + request id) counted in this file
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub entry_keys: Vec<String>,
+    /// Claude entries skipped because another file counted them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub duplicate_keys: Vec<String>,
+    /// Project the session belongs to (Claude: encoded project directory,
+    /// Codex: working directory)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    /// Session id (defaults to the file name)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
 }
 
 /// Running totals for Codex token counting
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CodexTotals {
-    pub input: i32,
-    pub cached: i32,
-    pub output: i32,
+    pub input: i64,
+    pub cached: i64,
+    pub output: i64,
 }
 
-/// Result of parsing a Codex file
-#[derive(Debug)]
-pub struct CodexParseResult {
-    /// Daily usage: day_key -> model -> [input, cached, output]
-    pub days: HashMap<String, HashMap<String, Vec<i32>>>,
-    /// Bytes parsed
+/// Result of parsing (part of) a session log
+#[derive(Debug, Default)]
+pub struct CostUsageParseResult {
+    /// Daily usage by model
+    pub days: CostUsageDays,
+    /// Offset just past the last complete line
     pub parsed_bytes: i64,
     /// Last model seen
     pub last_model: Option<String>,
     /// Last totals seen
     pub last_totals: Option<CodexTotals>,
+    /// Claude entries counted by this parse
+    pub entry_keys: Vec<String>,
+    /// Claude entries skipped because another file counted them
+    pub duplicate_keys: Vec<String>,
+    /// Project the session belongs to
+    pub project: Option<String>,
+    /// Session id
+    pub session_id: Option<String>,
+}
+
+impl CostUsageParseResult {
+    pub fn add(&mut self, day_key: &str, model: String, tokens: CostUsageTokens) {
+        self.days
+            .entry(day_key.to_string())
+            .or_default()
+            .entry(model)
+            .or_default()
+            .add(&tokens);
+    }
+}
+
+/// Cost summary for a provider over a day range
+#[derive(Debug, Clone, Default)]
+pub struct CostSummary {
+    /// Total cost in USD for the period
+    pub total_cost_usd: f64,
+    /// Total input tokens (excluding cached)
+    pub input_tokens: u64,
+    /// Total output tokens
+    pub output_tokens: u64,
+    /// Total cached input tokens (cache reads and writes)
+    pub cached_tokens: u64,
+    /// Number of sessions (log files) with usage in the period
+    pub sessions_count: u32,
+    /// Cost breakdown by model
+    pub by_model: HashMap<String, f64>,
+    /// Period start date
+    pub period_start: Option<NaiveDate>,
+    /// Period end date
+    pub period_end: Option<NaiveDate>,
+}
+
+impl CostSummary {
+    /// Format the total in the converter's reporting currency
+    pub fn format_total(&self, converter: &CurrencyConverter) -> String {
+        let total = converter.convert(self.total_cost_usd, "USD").unwrap_or(self.total_cost_usd);
+        converter.format(total)
+    }
+}
+
+/// Dimension a cost report can be grouped by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CostGroupBy {
+    Project,
+    Model,
+    Day,
+    Session,
+}
+
+impl CostGroupBy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CostGroupBy::Project => "project",
+            CostGroupBy::Model => "model",
+            CostGroupBy::Day => "day",
+            CostGroupBy::Session => "session",
+        }
+    }
+}
+
+impl std::str::FromStr for CostGroupBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "project" => Ok(CostGroupBy::Project),
+            "model" => Ok(CostGroupBy::Model),
+            "day" => Ok(CostGroupBy::Day),
+            "session" => Ok(CostGroupBy::Session),
+            _ => Err(format!("Invalid group: {}. Use 'project', 'model', 'day' or 'session'", s)),
+        }
+    }
+}
+
+/// Usage for one combination of group keys
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostUsageGroup {
+    /// One key per requested `CostGroupBy`, in the same order
+    pub keys: Vec<String>,
+    pub tokens: CostUsageTokens,
 }
 
 /// Day range for scanning
@@ -83,6 +247,13 @@ impl CostUsageDayRange {
         }
     }
 
+    /// The last `days` days (UTC), including today
+    pub fn last_days(days: u32) -> Self {
+        let today = Utc::now().date_naive();
+        let since = today - chrono::Duration::days(days.max(1) as i64 - 1);
+        Self::new(since, today)
+    }
+
     pub fn day_key(date: NaiveDate) -> String {
         date.format("%Y-%m-%d").to_string()
     }
@@ -94,6 +265,128 @@ impl CostUsageDayRange {
     pub fn parse_day_key(key: &str) -> Option<NaiveDate> {
         NaiveDate::parse_from_str(key, "%Y-%m-%d").ok()
     }
+
+    /// Whether a day falls inside the reported range
+    pub fn contains(&self, day_key: &str) -> bool {
+        Self::is_in_range(day_key, &self.since_key, &self.until_key)
+    }
+}
+
+impl CostUsageCache {
+    /// Summarize cached usage inside `range`
+    pub fn summary(&self, range: &CostUsageDayRange) -> CostSummary {
+        let mut summary = CostSummary {
+            period_start: CostUsageDayRange::parse_day_key(&range.since_key),
+            period_end: CostUsageDayRange::parse_day_key(&range.until_key),
+            ..CostSummary::default()
+        };
+
+        // Agents that store one file per message share a session id across files
+        let mut sessions: HashSet<&str> = HashSet::new();
+        for (path, file) in &self.files {
+            let mut has_tokens = false;
+            for (day_key, models) in &file.days {
+                if !range.contains(day_key) {
+                    continue;
+                }
+                for (model, tokens) in models {
+                    summary.input_tokens += tokens.input.max(0) as u64;
+                    summary.output_tokens += tokens.output.max(0) as u64;
+                    summary.cached_tokens += (tokens.cache_read + tokens.cache_creation).max(0) as u64;
+                    summary.total_cost_usd += tokens.cost_usd;
+                    *summary.by_model.entry(model.clone()).or_insert(0.0) += tokens.cost_usd;
+                    has_tokens |= !tokens.is_empty();
+                }
+            }
+            if has_tokens {
+                sessions.insert(file.session_id.as_deref().unwrap_or(path));
+            }
+        }
+        summary.sessions_count = sessions.len() as u32;
+
+        summary
+    }
+
+    /// Usage inside `range` grouped by `group_by`, ordered by key
+    pub fn grouped(&self, range: &CostUsageDayRange, group_by: &[CostGroupBy]) -> Vec<CostUsageGroup> {
+        let mut groups: HashMap<Vec<String>, CostUsageTokens> = HashMap::new();
+
+        for (path, file) in &self.files {
+            for (day_key, models) in &file.days {
+                if !range.contains(day_key) {
+                    continue;
+                }
+                for (model, tokens) in models {
+                    let keys = group_by
+                        .iter()
+                        .map(|group| match group {
+                            CostGroupBy::Project => file.project.clone().unwrap_or_else(|| "(unknown)".to_string()),
+                            CostGroupBy::Model => model.clone(),
+                            CostGroupBy::Day => day_key.clone(),
+                            CostGroupBy::Session => file.session_id.clone().unwrap_or_else(|| {
+                                Path::new(path)
+                                    .file_stem()
+                                    .map(|stem| stem.to_string_lossy().to_string())
+                                    .unwrap_or_else(|| path.clone())
+                            }),
+                        })
+                        .collect();
+                    groups.entry(keys).or_default().add(tokens);
+                }
+            }
+        }
+
+        let mut result: Vec<CostUsageGroup> = groups
+            .into_iter()
+            .filter(|(_, tokens)| !tokens.is_empty())
+            .map(|(keys, tokens)| CostUsageGroup { keys, tokens })
+            .collect();
+        result.sort_by(|a, b| a.keys.cmp(&b.keys));
+        result
+    }
+
+    /// Models with usage inside `range` that have no price (counted as $0), sorted
+    pub fn unpriced_models(&self, provider: ProviderId, range: &CostUsageDayRange) -> Vec<String> {
+        let mut models: Vec<String> = self
+            .files
+            .values()
+            .flat_map(|file| &file.days)
+            .filter(|(day_key, _)| range.contains(day_key))
+            .flat_map(|(_, models)| models)
+            .filter(|(model, tokens)| !tokens.is_empty() && !CostUsagePricing::is_priced(provider, model))
+            .map(|(model, _)| model.clone())
+            .collect();
+        models.sort();
+        models.dedup();
+        models
+    }
+
+    /// Cost per day inside `range` as (day_key, cost_usd), oldest first,
+    /// with zero for days without usage
+    pub fn daily_costs(&self, range: &CostUsageDayRange) -> Vec<(String, f64)> {
+        let mut daily: HashMap<String, f64> = HashMap::new();
+        if let (Some(mut date), Some(until)) = (
+            CostUsageDayRange::parse_day_key(&range.since_key),
+            CostUsageDayRange::parse_day_key(&range.until_key),
+        ) {
+            while date <= until {
+                daily.insert(CostUsageDayRange::day_key(date), 0.0);
+                date += chrono::Duration::days(1);
+            }
+        }
+
+        for file in self.files.values() {
+            for (day_key, models) in &file.days {
+                if let Some(cost) = daily.get_mut(day_key) {
+                    *cost += models.values().map(|t| t.cost_usd).sum::<f64>();
+                }
+            }
+        }
+
+        let mut result: Vec<(String, f64)> = daily.into_iter().collect();
+        result.sort_by(|a, b| a.0.cmp(&b.0));
+        result
+    }
 }
 
 /// JSONL Scanner for cost/usage logs
@@ -120,17 +413,24 @@ impl JsonlScanner {
 
         // Check CLAUDE_CONFIG_DIR
         if let Ok(config_dir) = std::env::var("CLAUDE_CONFIG_DIR") {
-            let path = PathBuf::from(config_dir.trim()).join("projects");
-            if path.exists() {
-                roots.push(path);
+            let config_dir = config_dir.trim();
+            if !config_dir.is_empty() {
+                let path = PathBuf::from(config_dir).join("projects");
+                if path.exists() {
+                    roots.push(path);
+                }
             }
         }
 
         // Default locations
         if let Some(home) = dirs::home_dir() {
-            let default_path = home.join(".claude").join("projects");
-            if default_path.exists() && !roots.contains(&default_path) {
-                roots.push(default_path);
+            for default_path in [
+                home.join(".claude").join("projects"),
+                home.join(".config").join("claude").join("projects"),
+            ] {
+                if default_path.exists() && !roots.contains(&default_path) {
+                    roots.push(default_path);
+                }
             }
         }
 
@@ -162,7 +462,7 @@ impl JsonlScanner {
             if let Ok(entries) = fs::read_dir(&day_dir) {
                 for entry in entries.flatten() {
                     let path = entry.path();
-                    if path.extension().map_or(false, |e| e.eq_ignore_ascii_case("jsonl")) {
+                    if path.extension().is_some_and(|e| e.eq_ignore_ascii_case("jsonl")) {
                         files.push(path);
                     }
                 }
@@ -174,170 +474,512 @@ impl JsonlScanner {
         files
     }
 
-    /// Parse a Codex JSONL file
+    /// List Claude session files under the project roots that were modified
+    /// on or after `since` (older files cannot contain entries in range)
+    pub fn list_claude_session_files(roots: &[PathBuf], since: NaiveDate) -> Vec<PathBuf> {
+        Self::list_modified_files(roots, since, |path| {
+            path.extension().is_some_and(|e| e.eq_ignore_ascii_case("jsonl"))
+        })
+    }
+
+    /// Files under `roots` accepted by `filter` that were modified on or
+    /// after `since`
+    pub fn list_modified_files(roots: &[PathBuf], since: NaiveDate, filter: impl Fn(&Path) -> bool) -> Vec<PathBuf> {
+        fn walk(
+            dir: &Path,
+            cutoff: Option<DateTime<Utc>>,
+            filter: &dyn Fn(&Path) -> bool,
+            files: &mut Vec<PathBuf>,
+        ) {
+            let Ok(entries) = fs::read_dir(dir) else {
+                return;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let Ok(metadata) = entry.metadata() else {
+                    continue;
+                };
+                if metadata.is_dir() {
+                    walk(&path, cutoff, filter, files);
+                } else if filter(&path) {
+                    let modified = metadata.modified().ok().map(DateTime::<Utc>::from);
+                    if cutoff.is_none_or(|cutoff| modified.is_none_or(|m| m >= cutoff)) {
+                        files.push(path);
+                    }
+                }
+            }
+        }
+
+        let cutoff = since.and_hms_opt(0, 0, 0).map(|dt| dt.and_utc());
+        let mut files = Vec::new();
+        for root in roots {
+            walk(root, cutoff, &filter, &mut files);
+        }
+        files
+    }
+
+    /// Parse a Codex JSONL file from `start_offset`
+    ///
+    /// `token_count` events carry running totals, so `initial_totals` (from
+    /// the previous scan of the same file) is needed to compute deltas.
     pub fn parse_codex_file(
         file_path: &Path,
-        range: &CostUsageDayRange,
         start_offset: i64,
         initial_model: Option<String>,
         initial_totals: Option<CodexTotals>,
-    ) -> std::io::Result<CodexParseResult> {
-        let file = File::open(file_path)?;
-        let file_size = file.metadata()?.len() as i64;
-
-        let mut reader = BufReader::new(file);
-        if start_offset > 0 {
-            reader.seek(SeekFrom::Start(start_offset as u64))?;
-        }
-
-        let mut current_model = initial_model;
-        let mut previous_totals = initial_totals;
-        let mut days: HashMap<String, HashMap<String, Vec<i32>>> = HashMap::new();
-        let mut parsed_bytes = start_offset;
-
-        let mut line = String::new();
-        while reader.read_line(&mut line)? > 0 {
-            parsed_bytes += line.len() as i64;
+    ) -> std::io::Result<CostUsageParseResult> {
+        let mut result = CostUsageParseResult {
+            last_model: initial_model,
+            last_totals: initial_totals,
+            ..CostUsageParseResult::default()
+        };
 
+        result.parsed_bytes = for_each_line(file_path, start_offset, |line| {
             // Quick check for relevant lines
-            if !line.contains("\"type\":\"event_msg\"") && !line.contains("\"type\":\"turn_context\"") {
-                line.clear();
-                continue;
+            if !line.contains("\"type\":\"event_msg\"")
+                && !line.contains("\"type\":\"turn_context\"")
+                && !line.contains("\"type\":\"session_meta\"")
+            {
+                return;
             }
 
             // Skip event_msg without token_count
             if line.contains("\"type\":\"event_msg\"") && !line.contains("\"token_count\"") {
-                line.clear();
-                continue;
+                return;
             }
 
-            // Parse JSON
-            if let Ok(obj) = serde_json::from_str::<serde_json::Value>(&line) {
-                let msg_type = obj.get("type").and_then(|v| v.as_str());
-                let timestamp = obj.get("timestamp").and_then(|v| v.as_str());
-
-                if let (Some(msg_type), Some(ts)) = (msg_type, timestamp) {
-                    // Extract day key from timestamp
-                    let day_key = if ts.len() >= 10 {
-                        &ts[..10]
-                    } else {
-                        line.clear();
-                        continue;
-                    };
-
-                    if !CostUsageDayRange::is_in_range(day_key, &range.scan_since_key, &range.scan_until_key) {
-                        line.clear();
-                        continue;
+            let Ok(obj) = serde_json::from_str::<serde_json::Value>(line) else {
+                return;
+            };
+            let msg_type = obj.get("type").and_then(|v| v.as_str());
+            if msg_type == Some("session_meta") {
+                if let Some(payload) = obj.get("payload") {
+                    let text = |key: &str| payload.get(key).and_then(|v| v.as_str()).map(str::to_string);
+                    result.project = text("cwd").or(result.project.take());
+                    result.session_id = text("id").or(result.session_id.take());
+                }
+                return;
+            }
+            let Some(day_key) = entry_day_key(&obj) else {
+                return;
+            };
+
+            if msg_type == Some("turn_context") {
+                // Extract model from turn_context
+                if let Some(payload) = obj.get("payload") {
+                    if let Some(model) = payload.get("model").and_then(|v| v.as_str()) {
+                        result.last_model = Some(model.to_string());
+                    } else if let Some(model) = payload
+                        .get("info")
+                        .and_then(|info| info.get("model"))
+                        .and_then(|v| v.as_str())
+                    {
+                        result.last_model = Some(model.to_string());
                     }
+                }
+                return;
+            }
+
+            let Some(payload) = obj.get("payload") else {
+                return;
+            };
+            if payload.get("type").and_then(|v| v.as_str()) != Some("token_count") {
+                return;
+            }
+
+            let info = payload.get("info");
+
+            // Get model
+            let model = info
+                .and_then(|i| i.get("model").or(i.get("model_name")))
+                .or(payload.get("model"))
+                .or(obj.get("model"))
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+                .or(result.last_model.clone())
+                .unwrap_or_else(|| "gpt-5".to_string());
+
+            let read_usage = |usage: &serde_json::Value| {
+                let get = |key: &str| usage.get(key).and_then(|v| v.as_i64()).unwrap_or(0);
+                let cached = usage
+                    .get("cached_input_tokens")
+                    .or(usage.get("cache_read_input_tokens"))
+                    .and_then(|v| v.as_i64())
+                    .unwrap_or(0);
+                (get("input_tokens"), cached, get("output_tokens"))
+            };
 
-                    if msg_type == "turn_context" {
-                        // Extract model from turn_context
-                        if let Some(payload) = obj.get("payload") {
-                            if let Some(model) = payload.get("model").and_then(|v| v.as_str()) {
-                                current_model = Some(model.to_string());
-                            } else if let Some(info) = payload.get("info") {
-                                if let Some(model) = info.get("model").and_then(|v| v.as_str()) {
-                                    current_model = Some(model.to_string());
-                                }
-                            }
-                        }
-                    } else if msg_type == "event_msg" {
-                        if let Some(payload) = obj.get("payload") {
-                            if payload.get("type").and_then(|v| v.as_str()) != Some("token_count") {
-                                line.clear();
-                                continue;
-                            }
-
-                            let info = payload.get("info");
-
-                            // Get model
-                            let model = info
-                                .and_then(|i| i.get("model").or(i.get("model_name")))
-                                .or(payload.get("model"))
-                                .or(obj.get("model"))
-                                .and_then(|v| v.as_str())
-                                .map(|s| s.to_string())
-                                .or(current_model.clone())
-                                .unwrap_or_else(|| "gpt-5".to_string());
-
-                            // Calculate deltas
-                            let (delta_input, delta_cached, delta_output) = if let Some(total) =
-                                info.and_then(|i| i.get("total_token_usage"))
-                            {
-                                let input = total.get("input_tokens").and_then(|v| v.as_i64()).unwrap_or(0) as i32;
-                                let cached = total
-                                    .get("cached_input_tokens")
-                                    .or(total.get("cache_read_input_tokens"))
-                                    .and_then(|v| v.as_i64())
-                                    .unwrap_or(0) as i32;
-                                let output = total.get("output_tokens").and_then(|v| v.as_i64()).unwrap_or(0) as i32;
-
-                                let delta_input = (input - previous_totals.as_ref().map_or(0, |t| t.input)).max(0);
-                                let delta_cached = (cached - previous_totals.as_ref().map_or(0, |t| t.cached)).max(0);
-                                let delta_output = (output - previous_totals.as_ref().map_or(0, |t| t.output)).max(0);
-
-                                previous_totals = Some(CodexTotals { input, cached, output });
-
-                                (delta_input, delta_cached, delta_output)
-                            } else if let Some(last) = info.and_then(|i| i.get("last_token_usage")) {
-                                let input = last.get("input_tokens").and_then(|v| v.as_i64()).unwrap_or(0) as i32;
-                                let cached = last
-                                    .get("cached_input_tokens")
-                                    .or(last.get("cache_read_input_tokens"))
-                                    .and_then(|v| v.as_i64())
-                                    .unwrap_or(0) as i32;
-                                let output = last.get("output_tokens").and_then(|v| v.as_i64()).unwrap_or(0) as i32;
-
-                                (input.max(0), cached.max(0), output.max(0))
-                            } else {
-                                line.clear();
-                                continue;
-                            };
-
-                            if delta_input == 0 && delta_cached == 0 && delta_output == 0 {
-                                line.clear();
-                                continue;
-                            }
-
-                            // Normalize model name and add to days
-                            let norm_model = CostUsagePricing::normalize_codex_model(&model);
-                            let cached_clamp = delta_cached.min(delta_input);
-
-                            let day_models = days.entry(day_key.to_string()).or_default();
-                            let packed = day_models.entry(norm_model).or_insert_with(|| vec![0, 0, 0]);
-                            packed[0] += delta_input;
-                            packed[1] += cached_clamp;
-                            packed[2] += delta_output;
-                        }
+            // Calculate deltas
+            let (delta_input, delta_cached, delta_output) =
+                if let Some(total) = info.and_then(|i| i.get("total_token_usage")) {
+                    let (input, cached, output) = read_usage(total);
+                    let previous = result.last_totals.as_ref();
+                    let deltas = (
+                        (input - previous.map_or(0, |t| t.input)).max(0),
+                        (cached - previous.map_or(0, |t| t.cached)).max(0),
+                        (output - previous.map_or(0, |t| t.output)).max(0),
+                    );
+                    result.last_totals = Some(CodexTotals { input, cached, output });
+                    deltas
+                } else if let Some(last) = info.and_then(|i| i.get("last_token_usage")) {
+                    let (input, cached, output) = read_usage(last);
+                    (input.max(0), cached.max(0), output.max(0))
+                } else {
+                    return;
+                };
+
+            if delta_input == 0 && delta_cached == 0 && delta_output == 0 {
+                return;
+            }
+
+            // Codex input includes cached tokens
+            let cached = delta_cached.min(delta_input);
+            let tokens = CostUsageTokens {
+                input: delta_input - cached,
+                cache_read: cached,
+                cache_creation: 0,
+                output: delta_output,
+                cost_usd: CostUsagePricing::codex_cost_usd(&model, delta_input, cached, delta_output)
+                    .unwrap_or(0.0),
+            };
+            result.add(&day_key, CostUsagePricing::normalize_codex_model(&model), tokens);
+        })?;
+
+        Ok(result)
+    }
+
+    /// Parse a Claude JSONL file from `start_offset`
+    ///
+    /// Every assistant message carries its own usage, so entries are priced
+    /// individually (tiered rates apply per request). `owners` maps entry keys
+    /// (message id + request id) to the file that counted them; entries owned
+    /// by any file, including this one, are skipped. Entries without ids are
+    /// always counted.
+    pub fn parse_claude_file(
+        file_path: &Path,
+        start_offset: i64,
+        owners: &mut HashMap<String, String>,
+    ) -> std::io::Result<CostUsageParseResult> {
+        let file_key = file_path.to_string_lossy().to_string();
+        let mut result = CostUsageParseResult {
+            project: claude_project_name(file_path),
+            session_id: file_path.file_stem().map(|stem| stem.to_string_lossy().to_string()),
+            ..CostUsageParseResult::default()
+        };
+
+        result.parsed_bytes = for_each_line(file_path, start_offset, |line| {
+            let Some(entry) = ClaudeLineUsage::parse(line) else {
+                return;
+            };
+
+            if let Some(entry_key) = entry.key {
+                match owners.get(&entry_key) {
+                    Some(owner) if *owner == file_key => return,
+                    Some(_) => {
+                        result.duplicate_keys.push(entry_key);
+                        return;
+                    }
+                    None => {
+                        owners.insert(entry_key.clone(), file_key.clone());
+                        result.entry_keys.push(entry_key);
                     }
                 }
             }
 
-            line.clear();
+            if entry.tokens.is_empty() {
+                return;
+            }
+            result.last_model = Some(entry.model.clone());
+            result.add(&entry.day_key, CostUsagePricing::normalize_claude_model(&entry.model), entry.tokens);
+        })?;
+
+        Ok(result)
+    }
+
+    /// Individual Claude usage entries logged since `since`, oldest first.
+    /// Entries copied into resumed or forked sessions are counted once.
+    pub fn claude_usage_entries(roots: &[PathBuf], since: DateTime<Utc>) -> Vec<UsageEntry> {
+        let mut files: Vec<(i64, PathBuf)> = Self::list_claude_session_files(roots, since.date_naive())
+            .into_iter()
+            .map(|path| (modified_unix_ms(&path).unwrap_or(0), path))
+            .collect();
+        files.sort();
+
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut entries = Vec::new();
+        for (_, path) in files {
+            let parsed = for_each_line(&path, 0, |line| {
+                let Some(entry) = ClaudeLineUsage::parse(line) else {
+                    return;
+                };
+                if entry.key.is_some_and(|key| !seen.insert(key)) || entry.tokens.is_empty() {
+                    return;
+                }
+                let Some(timestamp) = entry.timestamp.filter(|ts| *ts >= since) else {
+                    return;
+                };
+                entries.push(UsageEntry {
+                    timestamp,
+                    model: CostUsagePricing::normalize_claude_model(&entry.model),
+                    tokens: entry.tokens,
+                });
+            });
+            if let Err(e) = parsed {
+                tracing::debug!("Skipping {}: {}", path.display(), e);
+            }
         }
 
-        Ok(CodexParseResult {
-            days,
-            parsed_bytes: file_size.max(parsed_bytes),
-            last_model: current_model,
-            last_totals: previous_totals,
-        })
+        entries.sort_by_key(|entry| entry.timestamp);
+        entries
+    }
+
+    /// Latest rate-limit snapshot in the Codex sessions of the last week.
+    /// Files are read newest first until no older file can hold a later event.
+    pub fn latest_codex_rate_limits(root: &Path, now: DateTime<Utc>) -> Option<CodexRateLimits> {
+        let since = now - chrono::Duration::days(CODEX_RATE_LIMITS_LOOKBACK_DAYS);
+        let mut files: Vec<(i64, PathBuf)> = Self::list_codex_session_files(
+            root,
+            &CostUsageDayRange::day_key(since.date_naive()),
+            &CostUsageDayRange::day_key(now.date_naive()),
+        )
+        .into_iter()
+        .map(|path| (modified_unix_ms(&path).unwrap_or(0), path))
+        .collect();
+        files.sort_by(|a, b| b.cmp(a));
+
+        let mut latest: Option<CodexRateLimits> = None;
+        for (mtime, path) in files {
+            if latest.as_ref().is_some_and(|l| l.timestamp.timestamp_millis() >= mtime) {
+                break;
+            }
+            match Self::parse_codex_rate_limits(&path) {
+                Ok(Some(found)) if latest.as_ref().is_none_or(|l| found.timestamp > l.timestamp) => {
+                    latest = Some(found);
+                }
+                Ok(_) => {}
+                Err(e) => tracing::debug!("Skipping {}: {}", path.display(), e),
+            }
+        }
+        latest
+    }
+
+    /// Last rate-limit snapshot in one Codex session file
+    pub fn parse_codex_rate_limits(file_path: &Path) -> std::io::Result<Option<CodexRateLimits>> {
+        let mut latest: Option<CodexRateLimits> = None;
+        for_each_line(file_path, 0, |line| {
+            if !line.contains("\"token_count\"") || !line.contains("\"rate_limits\"") {
+                return;
+            }
+            let Ok(obj) = serde_json::from_str::<serde_json::Value>(line) else {
+                return;
+            };
+            let Some(payload) = obj.get("payload").filter(|p| p.get("type").and_then(|v| v.as_str()) == Some("token_count")) else {
+                return;
+            };
+            let Some(limits) = payload.get("rate_limits").filter(|v| v.is_object()) else {
+                return;
+            };
+            let Some(timestamp) = obj
+                .get("timestamp")
+                .and_then(|v| v.as_str())
+                .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+                .map(|ts| ts.with_timezone(&Utc))
+            else {
+                return;
+            };
+
+            let primary = limits.get("primary").and_then(|w| codex_rate_window(w, timestamp));
+            let secondary = limits.get("secondary").and_then(|w| codex_rate_window(w, timestamp));
+            if primary.is_none() && secondary.is_none() {
+                return;
+            }
+            if latest.as_ref().is_none_or(|l| timestamp >= l.timestamp) {
+                latest = Some(CodexRateLimits {
+                    timestamp,
+                    primary,
+                    secondary,
+                    plan_type: limits.get("plan_type").and_then(|v| v.as_str()).map(str::to_string),
+                });
+            }
+        })?;
+        Ok(latest)
+    }
+
+    /// Daily cost for the last `days` days as (day_key, cost_usd), oldest first
+    pub fn daily_cost_history(provider: ProviderId, days: u32) -> Vec<(String, f64)> {
+        let range = CostUsageDayRange::last_days(days);
+        Self::load_and_refresh(provider, &range, None).daily_costs(&range)
+    }
+
+    /// Load the cache, bring it up to date for `range` and save it
+    pub fn load_and_refresh(
+        provider: ProviderId,
+        range: &CostUsageDayRange,
+        cache_root: Option<&Path>,
+    ) -> CostUsageCache {
+        let mut cache = Self::load_cache(provider, cache_root);
+        if local_log_scanner(provider).is_none() {
+            return cache;
+        }
+        let files = Self::session_files(provider, &Self::default_log_roots(provider), range);
+        Self::refresh_cache(provider, &mut cache, &files);
+        Self::save_cache(provider, &cache, cache_root);
+        cache
+    }
+
+    /// Log directories scanned for a provider (empty for providers without local logs)
+    pub fn default_log_roots(provider: ProviderId) -> Vec<PathBuf> {
+        local_log_scanner(provider)
+            .map(|scanner| scanner.default_roots())
+            .unwrap_or_default()
+    }
+
+    /// Session files under `roots` that may hold usage for `range`
+    pub fn session_files(provider: ProviderId, roots: &[PathBuf], range: &CostUsageDayRange) -> Vec<PathBuf> {
+        local_log_scanner(provider)
+            .map(|scanner| scanner.session_files(roots, range))
+            .unwrap_or_default()
+    }
+
+    /// Re-parse what changed in `files` since the last scan and drop cache
+    /// entries for files that no longer exist
+    ///
+    /// Files are read oldest first, so an entry copied into a resumed session
+    /// stays attributed to the session it came from. When a file disappears,
+    /// files that skipped its entries as duplicates are re-read to count them.
+    pub fn refresh_cache(provider: ProviderId, cache: &mut CostUsageCache, files: &[PathBuf]) {
+        let mut owners: HashMap<String, String> = HashMap::new();
+        for (path, usage) in &cache.files {
+            for key in &usage.entry_keys {
+                owners.insert(key.clone(), path.clone());
+            }
+        }
+
+        let removed: Vec<String> = cache
+            .files
+            .keys()
+            .filter(|path| !Path::new(path.as_str()).exists())
+            .cloned()
+            .collect();
+        let mut released: HashSet<String> = HashSet::new();
+        for path in removed {
+            if let Some(usage) = cache.files.remove(&path) {
+                released.extend(usage.entry_keys);
+            }
+        }
+        owners.retain(|key, _| !released.contains(key));
+
+        let mut reread: HashSet<PathBuf> = cache
+            .files
+            .iter()
+            .filter(|(_, usage)| usage.duplicate_keys.iter().any(|key| released.contains(key)))
+            .map(|(path, _)| PathBuf::from(path))
+            .collect();
+
+        let mut work: Vec<(i64, PathBuf)> = files
+            .iter()
+            .chain(reread.iter().filter(|path| !files.contains(path)))
+            .map(|path| (modified_unix_ms(path).unwrap_or(i64::MAX), path.clone()))
+            .collect();
+        work.sort();
+
+        for (_, path) in work {
+            let force = reread.remove(&path);
+            Self::refresh_file(provider, cache, &path, &mut owners, force);
+        }
+        cache.last_scan_unix_ms = Utc::now().timestamp_millis();
+    }
+
+    fn refresh_file(
+        provider: ProviderId,
+        cache: &mut CostUsageCache,
+        path: &Path,
+        owners: &mut HashMap<String, String>,
+        force: bool,
+    ) {
+        let Some(scanner) = local_log_scanner(provider) else {
+            return;
+        };
+        let Ok(metadata) = fs::metadata(path) else {
+            return;
+        };
+        let size = metadata.len() as i64;
+        let mtime_unix_ms = metadata
+            .modified()
+            .map(|t| DateTime::<Utc>::from(t).timestamp_millis())
+            .unwrap_or(0);
+        let key = path.to_string_lossy().to_string();
+
+        let cached = cache.files.get(&key);
+        if !force && cached.is_some_and(|c| c.size == size && c.mtime_unix_ms == mtime_unix_ms) {
+            return;
+        }
+
+        // Append-only logs resume a file that grew and re-read one that shrank;
+        // files rewritten in place are always re-read
+        let resume = cached.filter(|c| !force && scanner.append_only() && size >= c.size && c.parsed_bytes <= size);
+        if resume.is_none() {
+            // Entries counted by the previous read are up for grabs again
+            if let Some(previous) = cached {
+                for entry_key in &previous.entry_keys {
+                    if owners.get(entry_key) == Some(&key) {
+                        owners.remove(entry_key);
+                    }
+                }
+            }
+        }
+        let offset = resume.map_or(0, |c| c.parsed_bytes);
+        let parsed = match scanner.parse_file(path, offset, resume, owners) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                tracing::debug!("Failed to scan {}: {}", path.display(), e);
+                return;
+            }
+        };
+
+        let mut usage = resume.cloned().unwrap_or_default();
+        for (day_key, models) in parsed.days {
+            let day = usage.days.entry(day_key).or_default();
+            for (model, tokens) in models {
+                day.entry(model).or_default().add(&tokens);
+            }
+        }
+        usage.mtime_unix_ms = mtime_unix_ms;
+        usage.size = size;
+        usage.parsed_bytes = parsed.parsed_bytes;
+        usage.last_model = parsed.last_model.or(usage.last_model);
+        usage.last_totals = parsed.last_totals.or(usage.last_totals);
+        usage.entry_keys.extend(parsed.entry_keys);
+        usage.duplicate_keys.extend(parsed.duplicate_keys);
+        usage.project = parsed.project.or(usage.project);
+        usage.session_id = parsed.session_id.or(usage.session_id);
+        cache.files.insert(key, usage);
     }
 
     /// Load cache from disk
     pub fn load_cache(provider: ProviderId, cache_root: Option<&Path>) -> CostUsageCache {
         let cache_path = Self::cache_path(provider, cache_root);
 
+        // Costs are stored per entry, so a pricing change means a full rescan
+        let pricing_fingerprint = CostUsagePricing::table().fingerprint();
+
         if let Ok(contents) = fs::read_to_string(&cache_path) {
-            if let Ok(cache) = serde_json::from_str(&contents) {
-                return cache;
+            if let Ok(cache) = serde_json::from_str::<CostUsageCache>(&contents) {
+                if cache.version == COST_USAGE_CACHE_VERSION && cache.pricing_fingerprint == pricing_fingerprint {
+                    return cache;
+                }
             }
         }
 
-        CostUsageCache::default()
+        CostUsageCache {
+            version: COST_USAGE_CACHE_VERSION,
+            pricing_fingerprint,
+            ..CostUsageCache::default()
+        }
     }
 
-    /// Save cache to disk
+    /// Save cache to disk (via a temporary file, so concurrent readers never
+    /// see a partial write)
     pub fn save_cache(provider: ProviderId, cache: &CostUsageCache, cache_root: Option<&Path>) {
         let cache_path = Self::cache_path(provider, cache_root);
 
@@ -345,8 +987,11 @@ impl JsonlScanner {
             let _ = fs::create_dir_all(parent);
         }
 
-        if let Ok(json) = serde_json::to_string_pretty(cache) {
-            let _ = fs::write(&cache_path, json);
+        if let Ok(json) = serde_json::to_string(cache) {
+            let tmp_path = cache_path.with_extension(format!("json.{}.tmp", std::process::id()));
+            if fs::write(&tmp_path, json).is_ok() && fs::rename(&tmp_path, &cache_path).is_err() {
+                let _ = fs::remove_file(&tmp_path);
+            }
         }
     }
 
@@ -360,11 +1005,172 @@ impl JsonlScanner {
     }
 }
 
-use chrono::Datelike;
+/// File modification time in milliseconds
+fn modified_unix_ms(path: &Path) -> Option<i64> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    Some(DateTime::<Utc>::from(modified).timestamp_millis())
+}
+
+/// Claude project of a session file: the encoded project directory right
+/// below a `projects` root (e.g. `-home-dev-app`), else the parent directory
+fn claude_project_name(path: &Path) -> Option<String> {
+    let mut dir = path.parent()?;
+    while let Some(parent) = dir.parent() {
+        if parent.file_name().is_some_and(|name| name == "projects") {
+            break;
+        }
+        if parent.parent().is_none() {
+            dir = path.parent()?;
+            break;
+        }
+        dir = parent;
+    }
+    dir.file_name().map(|name| name.to_string_lossy().to_string())
+}
+
+/// Usage logged on one Claude assistant line
+struct ClaudeLineUsage {
+    key: Option<String>,
+    day_key: String,
+    timestamp: Option<DateTime<Utc>>,
+    model: String,
+    tokens: CostUsageTokens,
+}
+
+impl ClaudeLineUsage {
+    fn parse(line: &str) -> Option<Self> {
+        if !line.contains("\"type\":\"assistant\"") || !line.contains("\"usage\"") {
+            return None;
+        }
+        let obj = serde_json::from_str::<serde_json::Value>(line).ok()?;
+        if obj.get("type").and_then(|v| v.as_str()) != Some("assistant") {
+            return None;
+        }
+        let day_key = entry_day_key(&obj)?;
+        let message = obj.get("message")?;
+        let usage = message.get("usage")?;
+        let model = message
+            .get("model")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown");
+
+        let get = |key: &str| usage.get(key).and_then(|v| v.as_i64()).unwrap_or(0).max(0);
+        let input = get("input_tokens");
+        let cache_read = get("cache_read_input_tokens");
+        let cache_creation = get("cache_creation_input_tokens");
+        let output = get("output_tokens");
+
+        Some(Self {
+            key: claude_entry_key(&obj),
+            day_key,
+            timestamp: obj
+                .get("timestamp")
+                .and_then(|v| v.as_str())
+                .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+                .map(|ts| ts.with_timezone(&Utc)),
+            model: model.to_string(),
+            tokens: CostUsageTokens {
+                input,
+                cache_read,
+                cache_creation,
+                output,
+                cost_usd: CostUsagePricing::claude_cost_usd(model, input, cache_read, cache_creation, output)
+                    .unwrap_or(0.0),
+            },
+        })
+    }
+}
+
+/// One Codex rate-limit window; resets are given either as a unix time or
+/// as seconds after the event
+fn codex_rate_window(value: &serde_json::Value, event_time: DateTime<Utc>) -> Option<RateWindow> {
+    let used_percent = value.get("used_percent")?.as_f64()?;
+    let window_minutes = value
+        .get("window_minutes")
+        .and_then(|v| v.as_u64())
+        .map(|minutes| minutes as u32);
+    let resets_at = value
+        .get("resets_at")
+        .and_then(|v| v.as_i64())
+        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+        .or_else(|| {
+            value
+                .get("resets_in_seconds")
+                .and_then(|v| v.as_i64())
+                .map(|secs| event_time + chrono::Duration::seconds(secs))
+        });
+    Some(RateWindow::with_details(used_percent, window_minutes, resets_at, None))
+}
+
+/// Dedup key of a Claude entry: message id + request id
+fn claude_entry_key(obj: &serde_json::Value) -> Option<String> {
+    let message_id = obj.get("message")?.get("id")?.as_str()?;
+    let request_id = obj.get("requestId").or(obj.get("request_id"))?.as_str()?;
+    Some(format!("{}:{}", message_id, request_id))
+}
+
+/// UTC day key of an entry's `timestamp`
+fn entry_day_key(obj: &serde_json::Value) -> Option<String> {
+    let ts = obj.get("timestamp").and_then(|v| v.as_str())?;
+    let day_key = ts.get(..10)?;
+    CostUsageDayRange::parse_day_key(day_key).map(|_| day_key.to_string())
+}
+
+/// Call `handle` for every complete line from `start_offset` and return the
+/// offset just past the last one. A final line without a newline may still
+/// be being written, so it is only consumed if it is already valid JSON.
+fn for_each_line(file_path: &Path, start_offset: i64, mut handle: impl FnMut(&str)) -> std::io::Result<i64> {
+    let file = File::open(file_path)?;
+    let mut reader = BufReader::new(file);
+    if start_offset > 0 {
+        reader.seek(SeekFrom::Start(start_offset as u64))?;
+    }
+
+    let mut parsed_bytes = start_offset;
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        let read = reader.read_until(b'\n', &mut buf)?;
+        if read == 0 {
+            break;
+        }
+        let line = String::from_utf8_lossy(&buf);
+        if !line.ends_with('\n') && serde_json::from_str::<serde_json::Value>(&line).is_err() {
+            break;
+        }
+        parsed_bytes += read as i64;
+        handle(line.trim_end());
+    }
+
+    Ok(parsed_bytes)
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::TimeZone;
+    use std::io::Write;
+
+    const CODEX_TURN: &str = r#"{"timestamp":"2026-01-15T10:00:00Z","type":"turn_context","payload":{"model":"gpt-5"}}"#;
+
+    fn codex_tokens(ts: &str, input: i64, cached: i64, output: i64) -> String {
+        format!(
+            r#"{{"timestamp":"{}","type":"event_msg","payload":{{"type":"token_count","info":{{"total_token_usage":{{"input_tokens":{},"cached_input_tokens":{},"output_tokens":{}}}}}}}}}"#,
+            ts, input, cached, output
+        )
+    }
+
+    fn claude_entry(ts: &str, model: &str, input: i64, output: i64) -> String {
+        format!(
+            r#"{{"timestamp":"{}","type":"assistant","message":{{"model":"{}","usage":{{"input_tokens":{},"cache_read_input_tokens":100,"output_tokens":{}}}}}}}"#,
+            ts, model, input, output
+        )
+    }
+
+    fn append(path: &Path, text: &str) {
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(path).unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
 
     #[test]
     fn test_day_range() {
@@ -394,4 +1200,282 @@ mod tests {
         assert_eq!(date.month(), 1);
         assert_eq!(date.day(), 15);
     }
+
+    #[test]
+    fn test_codex_incremental_scan() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rollout.jsonl");
+        append(&path, &format!("{}\n{}\n", CODEX_TURN, codex_tokens("2026-01-15T10:01:00Z", 1000, 200, 100)));
+
+        let mut cache = CostUsageCache::default();
+        JsonlScanner::refresh_cache(ProviderId::Codex, &mut cache, std::slice::from_ref(&path));
+        let first = cache.files.values().next().unwrap().clone();
+        let tokens = first.days["2026-01-15"]["gpt-5"];
+        assert_eq!((tokens.input, tokens.cache_read, tokens.output), (800, 200, 100));
+
+        // Running totals continue from the cached state; a half-written
+        // line is left for the next scan
+        append(
+            &path,
+            &format!("{}\n{{\"timestamp\":\"2026-01-16", codex_tokens("2026-01-16T09:00:00Z", 1500, 200, 300)),
+        );
+        JsonlScanner::refresh_cache(ProviderId::Codex, &mut cache, std::slice::from_ref(&path));
+        let second = cache.files.values().next().unwrap();
+        assert!(second.parsed_bytes > first.parsed_bytes);
+        assert!(second.parsed_bytes < second.size);
+        let day2 = second.days["2026-01-16"]["gpt-5"];
+        assert_eq!((day2.input, day2.cache_read, day2.output), (500, 0, 200));
+
+        let range = CostUsageDayRange::new(
+            NaiveDate::from_ymd_opt(2026, 1, 15).unwrap(),
+            NaiveDate::from_ymd_opt(2026, 1, 16).unwrap(),
+        );
+        let summary = cache.summary(&range);
+        assert_eq!(summary.input_tokens, 1300);
+        assert_eq!(summary.output_tokens, 300);
+        assert_eq!(summary.sessions_count, 1);
+        // (1300 * 1.25e-6) + (200 * 1.25e-7) + (300 * 1e-5)
+        assert!((summary.total_cost_usd - 0.004650).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_claude_scan_and_summary() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("-home-me-app");
+        fs::create_dir_all(&project).unwrap();
+        let path = project.join("session.jsonl");
+        append(
+            &path,
+            &[
+                r#"{"timestamp":"2026-01-14T23:59:00Z","type":"user","message":{"content":"hi"}}"#.to_string(),
+                claude_entry("2026-01-14T23:59:30Z", "claude-sonnet-4-5-20250929", 1000, 500),
+                claude_entry("2026-01-15T00:00:10Z", "claude-haiku-4-5", 2000, 100),
+            ]
+            .join("\n"),
+        );
+
+        let files = JsonlScanner::list_claude_session_files(
+            &[dir.path().to_path_buf()],
+            NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(),
+        );
+        assert_eq!(files, vec![path.clone()]);
+
+        let mut cache = CostUsageCache::default();
+        JsonlScanner::refresh_cache(ProviderId::Claude, &mut cache, &files);
+        let file = &cache.files[&path.to_string_lossy().to_string()];
+        // The final line has no newline but is complete JSON
+        assert_eq!(file.parsed_bytes, file.size);
+
+        let day = |d: u32| NaiveDate::from_ymd_opt(2026, 1, d).unwrap();
+        let jan15 = cache.summary(&CostUsageDayRange::new(day(15), day(15)));
+        assert_eq!(jan15.input_tokens, 2000);
+        assert_eq!(jan15.cached_tokens, 100);
+        assert_eq!(jan15.by_model.keys().collect::<Vec<_>>(), vec!["claude-haiku-4-5"]);
+
+        let both = cache.summary(&CostUsageDayRange::new(day(14), day(15)));
+        assert_eq!(both.input_tokens, 3000);
+        assert_eq!(both.sessions_count, 1);
+
+        let daily = cache.daily_costs(&CostUsageDayRange::new(day(13), day(15)));
+        assert_eq!(daily.len(), 3);
+        assert_eq!(daily[0], ("2026-01-13".to_string(), 0.0));
+        assert!(daily[1].1 > 0.0 && daily[2].1 > 0.0);
+
+        // Deleted files drop out of the cache
+        fs::remove_file(&path).unwrap();
+        JsonlScanner::refresh_cache(ProviderId::Claude, &mut cache, &[]);
+        assert!(cache.files.is_empty());
+    }
+
+    fn resumed_fixture_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/cost/claude/resumed")
+    }
+
+    fn fixture_range() -> CostUsageDayRange {
+        CostUsageDayRange::new(
+            NaiveDate::from_ymd_opt(2026, 1, 14).unwrap(),
+            NaiveDate::from_ymd_opt(2026, 1, 16).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_claude_dedupe_resumed_sessions() {
+        let files = JsonlScanner::list_claude_session_files(
+            &[resumed_fixture_dir()],
+            NaiveDate::from_ymd_opt(2000, 1, 1).unwrap(),
+        );
+        assert_eq!(files.len(), 3);
+
+        let mut cache = CostUsageCache::default();
+        JsonlScanner::refresh_cache(ProviderId::Claude, &mut cache, &files);
+
+        // Each message/request pair counts once, however many files and
+        // content-block lines repeat it; the entry without ids still counts
+        let summary = cache.summary(&fixture_range());
+        assert_eq!(summary.input_tokens, 1085);
+        assert_eq!(summary.output_tokens, 1305);
+        assert_eq!(summary.cached_tokens, 12000);
+
+        // Entries are filtered by their own timestamp, not the file's
+        let jan14 = NaiveDate::from_ymd_opt(2026, 1, 14).unwrap();
+        let day = cache.summary(&CostUsageDayRange::new(jan14, jan14));
+        assert_eq!((day.input_tokens, day.output_tokens), (1050, 600));
+
+        // A rescan of unchanged files keeps the same totals
+        JsonlScanner::refresh_cache(ProviderId::Claude, &mut cache, &files);
+        assert_eq!(cache.summary(&fixture_range()).input_tokens, 1085);
+    }
+
+    #[test]
+    fn test_latest_codex_rate_limits() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/cost/codex/rate_limits");
+        let now = Utc.with_ymd_and_hms(2026, 1, 15, 12, 0, 0).unwrap();
+
+        // The last event with rate limits wins; a later event without them is ignored
+        let latest = JsonlScanner::latest_codex_rate_limits(&root, now).unwrap();
+        assert_eq!(latest.timestamp, Utc.with_ymd_and_hms(2026, 1, 15, 8, 30, 0).unwrap());
+        let primary = latest.primary.unwrap();
+        assert_eq!(primary.used_percent, 25.5);
+        assert_eq!(primary.window_minutes, Some(300));
+        assert_eq!(primary.resets_at, Some(Utc.with_ymd_and_hms(2026, 1, 15, 11, 0, 0).unwrap()));
+        assert_eq!(latest.secondary.unwrap().used_percent, 42.0);
+        assert_eq!(latest.plan_type.as_deref(), Some("plus"));
+
+        // Older logs give resets relative to the event time
+        let older = root.join("2026/01/14/rollout-2026-01-14T09-00-00-a.jsonl");
+        let limits = JsonlScanner::parse_codex_rate_limits(&older).unwrap().unwrap();
+        assert_eq!(
+            limits.primary.unwrap().resets_at,
+            Some(Utc.with_ymd_and_hms(2026, 1, 14, 10, 10, 0).unwrap())
+        );
+
+        // Nothing within the lookback window
+        let much_later = now + chrono::Duration::days(30);
+        assert!(JsonlScanner::latest_codex_rate_limits(&root, much_later).is_none());
+    }
+
+    #[test]
+    fn test_claude_usage_entries() {
+        let since = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        let entries = JsonlScanner::claude_usage_entries(&[resumed_fixture_dir()], since);
+
+        // Same dedupe as the cache, one entry per message/request pair
+        assert_eq!(entries.len(), 5);
+        assert!(entries.windows(2).all(|pair| pair[0].timestamp <= pair[1].timestamp));
+        assert_eq!(entries.iter().map(|e| e.tokens.input).sum::<i64>(), 1085);
+
+        let later = Utc.with_ymd_and_hms(2026, 1, 16, 0, 0, 0).unwrap();
+        let recent = JsonlScanner::claude_usage_entries(&[resumed_fixture_dir()], later);
+        assert!(recent.iter().all(|e| e.timestamp >= later));
+        assert!(recent.len() < entries.len());
+    }
+
+    #[test]
+    fn test_claude_dedupe_follows_file_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("-home-dev-app");
+        fs::create_dir_all(&project).unwrap();
+        let base = std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+        for (i, name) in ["original.jsonl", "resumed.jsonl", "fork.jsonl"].iter().enumerate() {
+            let path = project.join(name);
+            fs::copy(resumed_fixture_dir().join("-home-dev-app").join(name), &path).unwrap();
+            let modified = base + std::time::Duration::from_secs(60 * i as u64);
+            File::options().write(true).open(&path).unwrap().set_modified(modified).unwrap();
+        }
+        let list = || {
+            JsonlScanner::list_claude_session_files(
+                &[dir.path().to_path_buf()],
+                NaiveDate::from_ymd_opt(2000, 1, 1).unwrap(),
+            )
+        };
+
+        let mut cache = CostUsageCache::default();
+        JsonlScanner::refresh_cache(ProviderId::Claude, &mut cache, &list());
+        let original = &cache.files[&project.join("original.jsonl").to_string_lossy().to_string()];
+        // The oldest file keeps the entries copied into the resumed session
+        assert_eq!(original.entry_keys, vec!["msg_01A1:req_01A1", "msg_01A2:req_01A2"]);
+        assert_eq!(cache.summary(&fixture_range()).sessions_count, 3);
+
+        // Appending an already-counted entry to a growing file changes nothing
+        let copied = fs::read_to_string(project.join("resumed.jsonl")).unwrap();
+        let last_line = copied.lines().last().unwrap();
+        append(&project.join("fork.jsonl"), &format!("\n{}\n", last_line));
+        JsonlScanner::refresh_cache(ProviderId::Claude, &mut cache, &list());
+        assert_eq!(cache.summary(&fixture_range()).input_tokens, 1085);
+
+        // Removing the original hands its entries to the resumed session
+        fs::remove_file(project.join("original.jsonl")).unwrap();
+        JsonlScanner::refresh_cache(ProviderId::Claude, &mut cache, &list());
+        let summary = cache.summary(&fixture_range());
+        assert_eq!(summary.input_tokens, 1085);
+        assert_eq!(summary.sessions_count, 2);
+    }
+
+    #[test]
+    fn test_grouped_usage() {
+        let files = JsonlScanner::list_claude_session_files(
+            &[resumed_fixture_dir()],
+            NaiveDate::from_ymd_opt(2000, 1, 1).unwrap(),
+        );
+        let mut cache = CostUsageCache::default();
+        JsonlScanner::refresh_cache(ProviderId::Claude, &mut cache, &files);
+
+        let by_project = cache.grouped(&fixture_range(), &[CostGroupBy::Project]);
+        assert_eq!(by_project.len(), 1);
+        assert_eq!(by_project[0].keys, vec!["-home-dev-app"]);
+        assert_eq!(by_project[0].tokens.input, 1085);
+
+        let by_day_session = cache.grouped(&fixture_range(), &[CostGroupBy::Day, CostGroupBy::Session]);
+        let days: Vec<&str> = by_day_session.iter().map(|g| g.keys[0].as_str()).collect();
+        assert_eq!(days.first(), Some(&"2026-01-14"));
+        assert_eq!(days.last(), Some(&"2026-01-16"));
+        let total: i64 = by_day_session.iter().map(|g| g.tokens.input).sum();
+        assert_eq!(total, 1085);
+
+        // Codex projects come from the session metadata
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rollout-2026-01-15T10-00-00-abc.jsonl");
+        append(
+            &path,
+            &format!(
+                "{}\n{}\n{}\n",
+                r#"{"timestamp":"2026-01-15T10:00:00Z","type":"session_meta","payload":{"id":"abc","cwd":"/home/dev/client-a"}}"#,
+                CODEX_TURN,
+                codex_tokens("2026-01-15T10:01:00Z", 1000, 0, 100)
+            ),
+        );
+        let mut cache = CostUsageCache::default();
+        JsonlScanner::refresh_cache(ProviderId::Codex, &mut cache, &[path]);
+        let groups = cache.grouped(&fixture_range(), &[CostGroupBy::Project, CostGroupBy::Session, CostGroupBy::Model]);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].keys, vec!["/home/dev/client-a", "abc", "gpt-5"]);
+        assert!(groups[0].tokens.cost_usd > 0.0);
+
+        assert_eq!("Model".parse::<CostGroupBy>(), Ok(CostGroupBy::Model));
+        assert!("team".parse::<CostGroupBy>().is_err());
+    }
+
+    #[test]
+    fn test_cache_roundtrip_and_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut cache = JsonlScanner::load_cache(ProviderId::Codex, Some(dir.path()));
+        assert_eq!(cache.version, COST_USAGE_CACHE_VERSION);
+        cache.files.insert("a.jsonl".to_string(), CostUsageFileUsage::default());
+        JsonlScanner::save_cache(ProviderId::Codex, &cache, Some(dir.path()));
+        assert_eq!(JsonlScanner::load_cache(ProviderId::Codex, Some(dir.path())).files.len(), 1);
+
+        // Caches from another layout version are discarded
+        let path = dir.path().join("codex_cost_cache.json");
+        fs::write(&path, r#"{"last_scan_unix_ms":0,"files":{},"days":{}}"#).unwrap();
+        let stale = JsonlScanner::load_cache(ProviderId::Codex, Some(dir.path()));
+        assert_eq!(stale.version, COST_USAGE_CACHE_VERSION);
+        assert!(stale.files.is_empty());
+
+        // So are costs computed with other prices
+        cache.pricing_fingerprint = "0000000000000000".to_string();
+        JsonlScanner::save_cache(ProviderId::Codex, &cache, Some(dir.path()));
+        let repriced = JsonlScanner::load_cache(ProviderId::Codex, Some(dir.path()));
+        assert!(repriced.files.is_empty());
+        assert_eq!(repriced.pricing_fingerprint, CostUsagePricing::table().fingerprint());
+    }
 }
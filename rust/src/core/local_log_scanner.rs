@@ -0,0 +1,460 @@
+//! Local log scanners
+//!
+//! Coding agents that keep token counts on disk each get a `LocalLogScanner`
+//! that knows where the agent writes its session logs, which files may hold
+//! usage for a day range and how to read them. `JsonlScanner` drives the
+//! scanners: it caches each file's usage and only re-reads files that changed.
+//!
+//! - Codex: `~/.codex/sessions/YYYY/MM/DD/*.jsonl`, appended per turn
+//! - Claude: `~/.claude/projects/<project>/*.jsonl`, appended per message
+//! - Gemini CLI: `~/.gemini/tmp/<project hash>/chats/session-*.json`, one
+//!   JSON document per session that is rewritten after every turn
+//! - OpenCode: `~/.local/share/opencode/storage/message/<session>/*.json`,
+//!   one JSON document per message
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+
+use super::{
+    CostUsageDayRange, CostUsageFileUsage, CostUsageParseResult, CostUsagePricing, CostUsageTokens, JsonlScanner,
+    ProviderId,
+};
+
+/// Reads one agent's local session logs
+pub trait LocalLogScanner: Send + Sync {
+    /// Provider whose usage the logs record
+    fn provider(&self) -> ProviderId;
+
+    /// Log directories scanned by default
+    fn default_roots(&self) -> Vec<PathBuf>;
+
+    /// Session files under `roots` that may hold usage for `range`
+    fn session_files(&self, roots: &[PathBuf], range: &CostUsageDayRange) -> Vec<PathBuf>;
+
+    /// Whether a file under the roots is a session log
+    fn is_session_file(&self, path: &Path) -> bool;
+
+    /// Whether the logs only ever grow, so a grown file can be read from
+    /// where the last scan stopped. Files rewritten in place are re-read whole.
+    fn append_only(&self) -> bool {
+        true
+    }
+
+    /// Parse a session file from `offset`. `previous` is the cached usage
+    /// of the file when resuming; `owners` maps entry keys to the file that
+    /// counted them, for agents that copy entries between sessions.
+    fn parse_file(
+        &self,
+        path: &Path,
+        offset: i64,
+        previous: Option<&CostUsageFileUsage>,
+        owners: &mut HashMap<String, String>,
+    ) -> std::io::Result<CostUsageParseResult>;
+}
+
+/// Scanners for all agents with local logs
+static LOCAL_LOG_SCANNERS: [&dyn LocalLogScanner; 4] =
+    [&CodexLogScanner, &ClaudeLogScanner, &GeminiLogScanner, &OpenCodeLogScanner];
+
+/// The scanner for a provider's local logs, if it has any
+pub fn local_log_scanner(provider: ProviderId) -> Option<&'static dyn LocalLogScanner> {
+    LOCAL_LOG_SCANNERS
+        .iter()
+        .copied()
+        .find(|scanner| scanner.provider() == provider)
+}
+
+/// Providers whose cost can be read from local logs
+pub fn local_log_providers() -> Vec<ProviderId> {
+    LOCAL_LOG_SCANNERS.iter().map(|scanner| scanner.provider()).collect()
+}
+
+fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension().is_some_and(|e| e.eq_ignore_ascii_case(extension))
+}
+
+/// Files under `roots` matching `scanner` that were modified on or after the
+/// start of `range` (older files cannot contain entries in range)
+fn modified_session_files(
+    scanner: &dyn LocalLogScanner,
+    roots: &[PathBuf],
+    range: &CostUsageDayRange,
+) -> Vec<PathBuf> {
+    CostUsageDayRange::parse_day_key(&range.scan_since_key)
+        .map(|since| JsonlScanner::list_modified_files(roots, since, |path| scanner.is_session_file(path)))
+        .unwrap_or_default()
+}
+
+/// UTC day key of an RFC 3339 timestamp
+fn rfc3339_day_key(value: Option<&serde_json::Value>) -> Option<String> {
+    let ts = DateTime::parse_from_rfc3339(value?.as_str()?).ok()?;
+    Some(CostUsageDayRange::day_key(ts.with_timezone(&Utc).date_naive()))
+}
+
+/// Codex CLI session logs
+pub struct CodexLogScanner;
+
+impl LocalLogScanner for CodexLogScanner {
+    fn provider(&self) -> ProviderId {
+        ProviderId::Codex
+    }
+
+    fn default_roots(&self) -> Vec<PathBuf> {
+        JsonlScanner::default_codex_sessions_root().into_iter().collect()
+    }
+
+    fn session_files(&self, roots: &[PathBuf], range: &CostUsageDayRange) -> Vec<PathBuf> {
+        roots
+            .iter()
+            .flat_map(|root| JsonlScanner::list_codex_session_files(root, &range.scan_since_key, &range.scan_until_key))
+            .collect()
+    }
+
+    fn is_session_file(&self, path: &Path) -> bool {
+        has_extension(path, "jsonl")
+    }
+
+    fn parse_file(
+        &self,
+        path: &Path,
+        offset: i64,
+        previous: Option<&CostUsageFileUsage>,
+        _owners: &mut HashMap<String, String>,
+    ) -> std::io::Result<CostUsageParseResult> {
+        JsonlScanner::parse_codex_file(
+            path,
+            offset,
+            previous.and_then(|usage| usage.last_model.clone()),
+            previous.and_then(|usage| usage.last_totals.clone()),
+        )
+    }
+}
+
+/// Claude Code project logs
+pub struct ClaudeLogScanner;
+
+impl LocalLogScanner for ClaudeLogScanner {
+    fn provider(&self) -> ProviderId {
+        ProviderId::Claude
+    }
+
+    fn default_roots(&self) -> Vec<PathBuf> {
+        JsonlScanner::default_claude_projects_roots()
+    }
+
+    fn session_files(&self, roots: &[PathBuf], range: &CostUsageDayRange) -> Vec<PathBuf> {
+        modified_session_files(self, roots, range)
+    }
+
+    fn is_session_file(&self, path: &Path) -> bool {
+        has_extension(path, "jsonl")
+    }
+
+    fn parse_file(
+        &self,
+        path: &Path,
+        offset: i64,
+        _previous: Option<&CostUsageFileUsage>,
+        owners: &mut HashMap<String, String>,
+    ) -> std::io::Result<CostUsageParseResult> {
+        JsonlScanner::parse_claude_file(path, offset, owners)
+    }
+}
+
+/// Gemini CLI chat recordings
+///
+/// Each `gemini` message carries the token counts of its response: `input`
+/// is the whole prompt including `cached` tokens, `thoughts` are billed as
+/// output and `tool` counts the tool-use prompt.
+pub struct GeminiLogScanner;
+
+impl LocalLogScanner for GeminiLogScanner {
+    fn provider(&self) -> ProviderId {
+        ProviderId::Gemini
+    }
+
+    fn default_roots(&self) -> Vec<PathBuf> {
+        dirs::home_dir()
+            .map(|home| home.join(".gemini").join("tmp"))
+            .filter(|root| root.is_dir())
+            .into_iter()
+            .collect()
+    }
+
+    fn session_files(&self, roots: &[PathBuf], range: &CostUsageDayRange) -> Vec<PathBuf> {
+        modified_session_files(self, roots, range)
+    }
+
+    fn is_session_file(&self, path: &Path) -> bool {
+        has_extension(path, "json")
+            && path
+                .parent()
+                .and_then(|dir| dir.file_name())
+                .is_some_and(|name| name == "chats")
+    }
+
+    fn append_only(&self) -> bool {
+        false
+    }
+
+    fn parse_file(
+        &self,
+        path: &Path,
+        _offset: i64,
+        _previous: Option<&CostUsageFileUsage>,
+        _owners: &mut HashMap<String, String>,
+    ) -> std::io::Result<CostUsageParseResult> {
+        let content = fs::read(path)?;
+        let record: serde_json::Value = serde_json::from_slice(&content).map_err(std::io::Error::other)?;
+        let mut result = CostUsageParseResult {
+            parsed_bytes: content.len() as i64,
+            // Chats live in <project hash>/chats/
+            project: path
+                .parent()
+                .and_then(Path::parent)
+                .and_then(Path::file_name)
+                .map(|name| name.to_string_lossy().to_string()),
+            session_id: record.get("sessionId").and_then(|v| v.as_str()).map(str::to_string),
+            ..CostUsageParseResult::default()
+        };
+
+        let messages = record.get("messages").and_then(|v| v.as_array());
+        for message in messages.into_iter().flatten() {
+            if message.get("type").and_then(|v| v.as_str()) != Some("gemini") {
+                continue;
+            }
+            let (Some(tokens), Some(day_key)) = (
+                message.get("tokens").filter(|v| v.is_object()),
+                rfc3339_day_key(message.get("timestamp")),
+            ) else {
+                continue;
+            };
+            let model = message.get("model").and_then(|v| v.as_str()).unwrap_or("unknown");
+
+            let get = |key: &str| tokens.get(key).and_then(|v| v.as_i64()).unwrap_or(0).max(0);
+            let prompt = get("input") + get("tool");
+            let cached = get("cached").min(prompt);
+            let output = get("output") + get("thoughts");
+            let usage = CostUsageTokens {
+                input: prompt - cached,
+                cache_read: cached,
+                cache_creation: 0,
+                output,
+                cost_usd: CostUsagePricing::gemini_cost_usd(model, prompt, cached, output).unwrap_or(0.0),
+            };
+            if usage.is_empty() {
+                continue;
+            }
+            result.last_model = Some(model.to_string());
+            result.add(&day_key, CostUsagePricing::normalize_gemini_model(model), usage);
+        }
+
+        Ok(result)
+    }
+}
+
+/// OpenCode message storage
+///
+/// Assistant messages record the model id, the uncached input, output,
+/// reasoning (billed as output) and cache read/write tokens.
+pub struct OpenCodeLogScanner;
+
+impl LocalLogScanner for OpenCodeLogScanner {
+    fn provider(&self) -> ProviderId {
+        ProviderId::OpenCode
+    }
+
+    fn default_roots(&self) -> Vec<PathBuf> {
+        // OpenCode follows the XDG layout on every platform
+        let data_dir = std::env::var("XDG_DATA_HOME")
+            .ok()
+            .map(|dir| dir.trim().to_string())
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| dirs::home_dir().map(|home| home.join(".local").join("share")));
+        let Some(storage) = data_dir.map(|dir| dir.join("opencode").join("storage")) else {
+            return Vec::new();
+        };
+        // Older releases kept messages under storage/session/message
+        [storage.join("message"), storage.join("session").join("message")]
+            .into_iter()
+            .filter(|root| root.is_dir())
+            .collect()
+    }
+
+    fn session_files(&self, roots: &[PathBuf], range: &CostUsageDayRange) -> Vec<PathBuf> {
+        modified_session_files(self, roots, range)
+    }
+
+    fn is_session_file(&self, path: &Path) -> bool {
+        has_extension(path, "json")
+    }
+
+    fn append_only(&self) -> bool {
+        false
+    }
+
+    fn parse_file(
+        &self,
+        path: &Path,
+        _offset: i64,
+        _previous: Option<&CostUsageFileUsage>,
+        _owners: &mut HashMap<String, String>,
+    ) -> std::io::Result<CostUsageParseResult> {
+        let content = fs::read(path)?;
+        let message: serde_json::Value = serde_json::from_slice(&content).map_err(std::io::Error::other)?;
+        let text = |value: Option<&serde_json::Value>| value.and_then(|v| v.as_str()).map(str::to_string);
+        let mut result = CostUsageParseResult {
+            parsed_bytes: content.len() as i64,
+            project: text(message.pointer("/path/root")).or_else(|| text(message.pointer("/path/cwd"))),
+            session_id: text(message.get("sessionID")),
+            ..CostUsageParseResult::default()
+        };
+
+        if message.get("role").and_then(|v| v.as_str()) != Some("assistant") {
+            return Ok(result);
+        }
+        let Some(day_key) = message
+            .pointer("/time/created")
+            .and_then(|v| v.as_i64())
+            .and_then(DateTime::from_timestamp_millis)
+            .map(|created| CostUsageDayRange::day_key(created.date_naive()))
+        else {
+            return Ok(result);
+        };
+        let model = message.get("modelID").and_then(|v| v.as_str()).unwrap_or("unknown");
+
+        let get = |pointer: &str| message.pointer(pointer).and_then(|v| v.as_i64()).unwrap_or(0).max(0);
+        let input = get("/tokens/input");
+        let cache_read = get("/tokens/cache/read");
+        let cache_write = get("/tokens/cache/write");
+        let output = get("/tokens/output") + get("/tokens/reasoning");
+        let usage = CostUsageTokens {
+            input,
+            cache_read,
+            cache_creation: cache_write,
+            output,
+            cost_usd: CostUsagePricing::opencode_cost_usd(model, input, cache_read, cache_write, output)
+                .unwrap_or(0.0),
+        };
+        if !usage.is_empty() {
+            result.last_model = Some(model.to_string());
+            result.add(&day_key, CostUsagePricing::normalize_opencode_model(model), usage);
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::CostUsageCache;
+    use chrono::NaiveDate;
+
+    fn range() -> CostUsageDayRange {
+        CostUsageDayRange::new(
+            NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2026, 1, 31).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_registry() {
+        assert_eq!(
+            local_log_providers(),
+            vec![ProviderId::Codex, ProviderId::Claude, ProviderId::Gemini, ProviderId::OpenCode]
+        );
+        assert!(local_log_scanner(ProviderId::Gemini).is_some());
+        assert!(local_log_scanner(ProviderId::Cursor).is_none());
+    }
+
+    #[test]
+    fn test_gemini_chat_scan() {
+        let dir = tempfile::tempdir().unwrap();
+        let chats = dir.path().join("3f9a0c").join("chats");
+        fs::create_dir_all(&chats).unwrap();
+        let session = chats.join("session-2026-01-15T10-00-a1b2c3d4.json");
+        fs::write(
+            &session,
+            r#"{"sessionId":"a1b2c3d4","projectHash":"3f9a0c","messages":[
+                {"id":"1","timestamp":"2026-01-15T10:00:00.000Z","type":"user","content":"hi"},
+                {"id":"2","timestamp":"2026-01-15T10:00:05.000Z","type":"gemini","content":"hello","model":"gemini-2.5-pro",
+                 "tokens":{"input":1000,"output":80,"cached":400,"thoughts":20,"tool":0,"total":1100}},
+                {"id":"3","timestamp":"2026-01-16T09:00:00.000Z","type":"gemini","content":"again","model":"gemini-2.5-flash",
+                 "tokens":{"input":200,"output":10,"cached":0,"thoughts":0,"tool":0,"total":210}},
+                {"id":"4","timestamp":"2026-01-16T09:00:01.000Z","type":"info","content":"note"}
+            ]}"#,
+        )
+        .unwrap();
+        fs::write(dir.path().join("3f9a0c").join("logs.json"), "[]").unwrap();
+
+        let scanner = local_log_scanner(ProviderId::Gemini).unwrap();
+        let roots = vec![dir.path().to_path_buf()];
+        let files = scanner.session_files(&roots, &range());
+        assert_eq!(files, vec![session.clone()]);
+
+        let parsed = scanner.parse_file(&session, 0, None, &mut HashMap::new()).unwrap();
+        assert_eq!(parsed.session_id.as_deref(), Some("a1b2c3d4"));
+        assert_eq!(parsed.project.as_deref(), Some("3f9a0c"));
+        let pro = parsed.days["2026-01-15"]["gemini-2.5-pro"];
+        assert_eq!((pro.input, pro.cache_read, pro.output), (600, 400, 100));
+        // 600 * $1.25 + 400 * $0.125 + 100 * $10 per million
+        assert!((pro.cost_usd - 0.0018).abs() < 1e-10);
+        assert_eq!(parsed.days["2026-01-16"]["gemini-2.5-flash"].input, 200);
+
+        // The whole document is rewritten after each turn, so a grown file is
+        // read again from the start instead of counting the first turn twice
+        let mut cache = CostUsageCache::default();
+        JsonlScanner::refresh_cache(ProviderId::Gemini, &mut cache, &files);
+        let content = fs::read_to_string(&session).unwrap().replace(
+            r#"{"id":"4""#,
+            r#"{"id":"5","timestamp":"2026-01-16T09:05:00.000Z","type":"gemini","model":"gemini-2.5-flash","tokens":{"input":300,"output":0,"cached":0}},{"id":"4""#,
+        );
+        fs::write(&session, content).unwrap();
+        JsonlScanner::refresh_cache(ProviderId::Gemini, &mut cache, &files);
+        let summary = cache.summary(&range());
+        assert_eq!(summary.input_tokens, 600 + 200 + 300);
+        assert_eq!(summary.sessions_count, 1);
+    }
+
+    #[test]
+    fn test_opencode_message_scan() {
+        let dir = tempfile::tempdir().unwrap();
+        let messages = dir.path().join("ses_abc");
+        fs::create_dir_all(&messages).unwrap();
+        let assistant = messages.join("msg_2.json");
+        // 2026-01-15T10:00:00Z
+        fs::write(
+            &assistant,
+            r#"{"id":"msg_2","sessionID":"ses_abc","role":"assistant","time":{"created":1768471200000},
+                "modelID":"claude-sonnet-4-5-20250929","providerID":"anthropic","path":{"cwd":"/home/dev/app/src","root":"/home/dev/app"},
+                "cost":0,"tokens":{"input":100,"output":50,"reasoning":10,"cache":{"read":1000,"write":200}}}"#,
+        )
+        .unwrap();
+        let user = messages.join("msg_1.json");
+        fs::write(&user, r#"{"id":"msg_1","sessionID":"ses_abc","role":"user","time":{"created":1768471190000}}"#).unwrap();
+
+        let scanner = local_log_scanner(ProviderId::OpenCode).unwrap();
+        let mut files = scanner.session_files(&[dir.path().to_path_buf()], &range());
+        files.sort();
+        assert_eq!(files, vec![user.clone(), assistant.clone()]);
+        assert!(scanner.parse_file(&user, 0, None, &mut HashMap::new()).unwrap().days.is_empty());
+
+        let parsed = scanner.parse_file(&assistant, 0, None, &mut HashMap::new()).unwrap();
+        assert_eq!(parsed.session_id.as_deref(), Some("ses_abc"));
+        assert_eq!(parsed.project.as_deref(), Some("/home/dev/app"));
+        let tokens = parsed.days["2026-01-15"]["claude-sonnet-4-5"];
+        assert_eq!((tokens.input, tokens.cache_read, tokens.cache_creation, tokens.output), (100, 1000, 200, 60));
+        // Priced from the Claude section: 100 * $3 + 1000 * $0.30 + 200 * $3.75 + 60 * $15 per million
+        assert!((tokens.cost_usd - 0.00225).abs() < 1e-10);
+
+        // Sessions are counted by id, not by message file
+        let mut cache = CostUsageCache::default();
+        JsonlScanner::refresh_cache(ProviderId::OpenCode, &mut cache, &files);
+        assert_eq!(cache.summary(&range()).sessions_count, 1);
+    }
+}
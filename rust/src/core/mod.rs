@@ -3,32 +3,54 @@
 #![allow(dead_code)]
 #![allow(unused_imports)]
 
+mod budget;
+mod command_provider;
+mod cost_analytics;
 mod cost_pricing;
+mod cost_watcher;
 mod credential_migration;
 mod credentials;
+mod currency;
+mod custom_provider;
 mod fetch_plan;
+mod http_client;
 mod jsonl_scanner;
+mod local_log_scanner;
 mod openai_dashboard;
 mod provider;
 mod rate_window;
 mod redactor;
+mod refresh_scheduler;
+mod session_blocks;
 mod session_quota;
 mod token_accounts;
+mod usage_history;
 mod usage_pace;
 mod usage_snapshot;
 mod widget_snapshot;
 
+pub use budget::*;
+pub use command_provider::*;
+pub use cost_analytics::*;
 pub use cost_pricing::*;
+pub use cost_watcher::*;
 pub use credential_migration::*;
 pub use credentials::*;
+pub use currency::*;
+pub use custom_provider::*;
 pub use fetch_plan::*;
+pub use http_client::*;
 pub use jsonl_scanner::*;
+pub use local_log_scanner::*;
 pub use openai_dashboard::*;
 pub use provider::*;
 pub use rate_window::*;
 pub use redactor::*;
+pub use refresh_scheduler::*;
+pub use session_blocks::*;
 pub use session_quota::*;
 pub use token_accounts::*;
+pub use usage_history::*;
 pub use usage_pace::*;
 pub use usage_snapshot::*;
 pub use widget_snapshot::*;
@@ -1,16 +1,18 @@
 //! Provider trait and registry - defines the interface all providers must implement
 
 use async_trait::async_trait;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::HashMap;
-use std::sync::{LazyLock, RwLock};
+use std::sync::{Arc, RwLock};
 use thiserror::Error;
 
-use super::ProviderFetchResult;
+use super::{ProviderFetchOutcome, ProviderFetchPipeline, ProviderFetchResult, CUSTOM_PROVIDERS};
 
 /// Unique identifier for a provider
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
+///
+/// Serialized as the CLI name. `Custom` carries the ID of a provider loaded
+/// from the custom providers file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ProviderId {
     Codex,
     Claude,
@@ -33,10 +35,11 @@ pub enum ProviderId {
     OpenRouter,
     Synthetic,
     JetBrains,
+    Custom(&'static str),
 }
 
 impl ProviderId {
-    /// Get all provider IDs
+    /// Get all built-in provider IDs
     pub fn all() -> &'static [ProviderId] {
         &[
             ProviderId::Codex,
@@ -63,6 +66,18 @@ impl ProviderId {
         ]
     }
 
+    /// Get all built-in provider IDs followed by the loaded custom providers
+    pub fn all_with_custom() -> Vec<ProviderId> {
+        let mut ids = Self::all().to_vec();
+        ids.extend(CUSTOM_PROVIDERS.provider_ids());
+        ids
+    }
+
+    /// Whether this provider comes from the custom providers file
+    pub fn is_custom(&self) -> bool {
+        matches!(self, ProviderId::Custom(_))
+    }
+
     /// Get the CLI name for this provider
     pub fn cli_name(&self) -> &'static str {
         match self {
@@ -87,6 +102,7 @@ impl ProviderId {
             ProviderId::OpenRouter => "openrouter",
             ProviderId::Synthetic => "synthetic",
             ProviderId::JetBrains => "jetbrains",
+            ProviderId::Custom(id) => id,
         }
     }
 
@@ -114,6 +130,7 @@ impl ProviderId {
             ProviderId::OpenRouter => "OpenRouter",
             ProviderId::Synthetic => "Synthetic",
             ProviderId::JetBrains => "JetBrains AI",
+            ProviderId::Custom(id) => CUSTOM_PROVIDERS.display_name(id).unwrap_or(id),
         }
     }
 
@@ -144,11 +161,23 @@ impl ProviderId {
             ProviderId::Synthetic => None,
             ProviderId::Warp => None,
             ProviderId::OpenRouter => None,
+            ProviderId::Custom(_) => None,
         }
     }
 
-    /// Parse from CLI name string
+    /// Parse from CLI name string, including custom provider IDs
     pub fn from_cli_name(name: &str) -> Option<Self> {
+        Self::from_builtin_cli_name(name)
+            .or_else(|| {
+                CUSTOM_PROVIDERS
+                    .provider_ids()
+                    .into_iter()
+                    .find(|id| id.cli_name().eq_ignore_ascii_case(name))
+            })
+    }
+
+    /// Parse a built-in provider from its CLI name or alias
+    pub fn from_builtin_cli_name(name: &str) -> Option<Self> {
         match name.to_lowercase().as_str() {
             "codex" | "openai" => Some(ProviderId::Codex),
             "claude" | "anthropic" => Some(ProviderId::Claude),
@@ -174,6 +203,60 @@ impl ProviderId {
             _ => None,
         }
     }
+
+    /// Environment variable that overrides this provider's API base URL
+    /// (e.g. `CODEXBAR_OPENROUTER_BASE_URL`). Characters a shell cannot
+    /// export, such as the '-' custom IDs may use, become '_'.
+    pub fn base_url_env_var(&self) -> String {
+        let name: String = self
+            .cli_name()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+            .collect();
+        format!("CODEXBAR_{}_BASE_URL", name)
+    }
+}
+
+/// Normalize a user-supplied base URL override.
+///
+/// Trailing slashes are removed. Only HTTPS URLs, or plain HTTP on the
+/// loopback interface (for local mock servers), are accepted so that
+/// credentials are never sent in the clear.
+pub fn normalize_base_url_override(raw: &str) -> Option<String> {
+    let trimmed = raw.trim().trim_end_matches('/');
+    if trimmed.is_empty() {
+        return None;
+    }
+
+    let lower = trimmed.to_lowercase();
+    let is_loopback = ["http://127.0.0.1", "http://localhost", "http://[::1]"]
+        .iter()
+        .any(|prefix| {
+            lower.strip_prefix(prefix).is_some_and(|rest| {
+                rest.is_empty() || rest.starts_with(':') || rest.starts_with('/')
+            })
+        });
+
+    if lower.starts_with("https://") || is_loopback {
+        Some(trimmed.to_string())
+    } else {
+        tracing::warn!("Ignoring insecure base URL override (must be HTTPS): {}", trimmed);
+        None
+    }
+}
+
+impl Serialize for ProviderId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.cli_name())
+    }
+}
+
+impl<'de> Deserialize<'de> for ProviderId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        ProviderId::from_cli_name(&name)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown provider '{}'", name)))
+    }
 }
 
 impl std::fmt::Display for ProviderId {
@@ -240,21 +323,54 @@ pub enum ProviderError {
     Parse(String),
 
     #[error("Network error: {0}")]
-    Network(#[from] reqwest::Error),
+    Network(String),
 
     #[error("Timeout")]
     Timeout,
 
+    #[error("Rate limited")]
+    RateLimited,
+
     #[error("Source mode '{0:?}' not supported for this provider")]
     UnsupportedSource(SourceMode),
 
     #[error("No cookies available for web API")]
     NoCookies,
 
+    #[error("No available fetch strategy for {0}")]
+    NoAvailableStrategy(ProviderId),
+
     #[error("{0}")]
     Other(String),
 }
 
+impl From<reqwest::Error> for ProviderError {
+    fn from(e: reqwest::Error) -> Self {
+        ProviderError::Network(e.to_string())
+    }
+}
+
+impl ProviderError {
+    /// Map an unsuccessful HTTP status to an error; `label` names the API in
+    /// the generic message
+    pub fn from_http_status(status: reqwest::StatusCode, label: &str) -> Self {
+        match status {
+            reqwest::StatusCode::UNAUTHORIZED => ProviderError::AuthRequired,
+            reqwest::StatusCode::TOO_MANY_REQUESTS => ProviderError::RateLimited,
+            _ => ProviderError::Other(format!("{} returned status {}", label, status)),
+        }
+    }
+
+    /// Whether retrying later is likely to help (network errors, timeouts
+    /// and rate limiting)
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            ProviderError::Network(_) | ProviderError::Timeout | ProviderError::RateLimited
+        )
+    }
+}
+
 /// Context passed to provider fetch operations
 #[derive(Debug, Clone)]
 pub struct FetchContext {
@@ -275,6 +391,18 @@ pub struct FetchContext {
 
     /// API key for providers that require authentication
     pub api_key: Option<String>,
+
+    /// Base URL override for the provider's API (settings or env var)
+    pub base_url: Option<String>,
+}
+
+impl FetchContext {
+    /// Build an endpoint URL from the base URL override, or `default_base`
+    /// when no override is set
+    pub fn endpoint(&self, default_base: &str, path: &str) -> String {
+        let base = self.base_url.as_deref().unwrap_or(default_base);
+        format!("{}{}", base.trim_end_matches('/'), path)
+    }
 }
 
 impl Default for FetchContext {
@@ -286,6 +414,7 @@ impl Default for FetchContext {
             verbose: false,
             manual_cookie_header: None,
             api_key: None,
+            base_url: None,
         }
     }
 }
@@ -299,27 +428,37 @@ pub trait Provider: Send + Sync {
     /// Get provider metadata
     fn metadata(&self) -> &ProviderMetadata;
 
+    /// Declare the fetch strategies for this provider, in `Auto` fallback order
+    fn fetch_pipeline(&self) -> ProviderFetchPipeline<'_>;
+
+    /// Fetch usage data, recording every strategy that was tried
+    async fn fetch_outcome(&self, ctx: &FetchContext) -> ProviderFetchOutcome {
+        self.fetch_pipeline().fetch(ctx, self.id()).await
+    }
+
     /// Fetch usage data from this provider
-    async fn fetch_usage(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError>;
+    async fn fetch_usage(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        self.fetch_outcome(ctx).await.into_result()
+    }
 
     /// Get the available source modes for this provider
     fn available_sources(&self) -> Vec<SourceMode> {
-        vec![SourceMode::Auto]
+        self.fetch_pipeline().source_modes()
     }
 
     /// Check if OAuth is supported
     fn supports_oauth(&self) -> bool {
-        false
+        self.available_sources().contains(&SourceMode::OAuth)
     }
 
     /// Check if web API (cookies) is supported
     fn supports_web(&self) -> bool {
-        false
+        self.available_sources().contains(&SourceMode::Web)
     }
 
     /// Check if CLI probe is supported
     fn supports_cli(&self) -> bool {
-        false
+        self.available_sources().contains(&SourceMode::Cli)
     }
 
     /// Detect the version of the CLI tool (if applicable)
@@ -329,37 +468,64 @@ pub trait Provider: Send + Sync {
 }
 
 /// Registry of all available providers
+///
+/// Providers are stored as `Arc<dyn Provider>` so a single instance can be
+/// shared between the CLI, the tray app and background refresh tasks.
+/// Registration order is preserved for iteration.
 pub struct ProviderRegistry {
-    providers: RwLock<HashMap<ProviderId, Box<dyn Provider>>>,
+    providers: RwLock<Vec<Arc<dyn Provider>>>,
 }
 
 impl ProviderRegistry {
     /// Create a new empty registry
     pub fn new() -> Self {
         Self {
-            providers: RwLock::new(HashMap::new()),
+            providers: RwLock::new(Vec::new()),
         }
     }
 
-    /// Register a provider
-    pub fn register(&self, provider: Box<dyn Provider>) {
+    /// Register a provider, replacing any provider already registered with the same ID
+    pub fn register(&self, provider: Arc<dyn Provider>) {
         let id = provider.id();
         let mut providers = self.providers.write().unwrap();
-        providers.insert(id, provider);
+        if let Some(existing) = providers.iter_mut().find(|p| p.id() == id) {
+            *existing = provider;
+        } else {
+            providers.push(provider);
+        }
     }
 
     /// Get a provider by ID
-    pub fn get(&self, id: ProviderId) -> Option<Box<dyn Provider>> {
+    pub fn get(&self, id: ProviderId) -> Option<Arc<dyn Provider>> {
         let providers = self.providers.read().unwrap();
-        // We need to clone here, but since Provider is not Clone, we'll return None for now
-        // In practice, we'll use Arc<dyn Provider> instead
-        providers.get(&id).map(|_| todo!("Use Arc<dyn Provider> instead"))
+        providers.iter().find(|p| p.id() == id).cloned()
     }
 
-    /// Get all registered provider IDs
+    /// Iterate over a snapshot of all registered providers in registration order
+    pub fn iter(&self) -> impl Iterator<Item = Arc<dyn Provider>> {
+        let providers = self.providers.read().unwrap();
+        providers.clone().into_iter()
+    }
+
+    /// Get all registered provider IDs in registration order
     pub fn all_ids(&self) -> Vec<ProviderId> {
         let providers = self.providers.read().unwrap();
-        providers.keys().copied().collect()
+        providers.iter().map(|p| p.id()).collect()
+    }
+
+    /// Check if a provider is registered
+    pub fn contains(&self, id: ProviderId) -> bool {
+        self.get(id).is_some()
+    }
+
+    /// Number of registered providers
+    pub fn len(&self) -> usize {
+        self.providers.read().unwrap().len()
+    }
+
+    /// Check if the registry is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
     }
 }
 
@@ -369,9 +535,6 @@ impl Default for ProviderRegistry {
     }
 }
 
-/// Global provider registry
-pub static REGISTRY: LazyLock<ProviderRegistry> = LazyLock::new(ProviderRegistry::new);
-
 /// Get the CLI name map for argument parsing
 pub fn cli_name_map() -> HashMap<&'static str, ProviderId> {
     let mut map = HashMap::new();
@@ -464,6 +627,57 @@ mod tests {
         assert!(!ctx.verbose);
         assert!(ctx.manual_cookie_header.is_none());
         assert!(ctx.api_key.is_none());
+        assert!(ctx.base_url.is_none());
+    }
+
+    #[test]
+    fn test_fetch_context_endpoint() {
+        let ctx = FetchContext::default();
+        assert_eq!(ctx.endpoint("https://api.example.com", "/v1/usage"), "https://api.example.com/v1/usage");
+
+        let ctx = FetchContext {
+            base_url: Some("http://127.0.0.1:9000/".to_string()),
+            ..FetchContext::default()
+        };
+        assert_eq!(ctx.endpoint("https://api.example.com", "/v1/usage"), "http://127.0.0.1:9000/v1/usage");
+    }
+
+    #[test]
+    fn test_normalize_base_url_override() {
+        assert_eq!(
+            normalize_base_url_override(" https://proxy.corp.example/openrouter/ "),
+            Some("https://proxy.corp.example/openrouter".to_string())
+        );
+        assert_eq!(
+            normalize_base_url_override("http://127.0.0.1:8080"),
+            Some("http://127.0.0.1:8080".to_string())
+        );
+        assert_eq!(normalize_base_url_override("http://localhost"), Some("http://localhost".to_string()));
+        assert_eq!(normalize_base_url_override("http://localhost.evil.com"), None);
+        assert_eq!(normalize_base_url_override("http://proxy.corp.example"), None);
+        assert_eq!(normalize_base_url_override("   "), None);
+    }
+
+    #[test]
+    fn test_provider_error_from_http_status() {
+        assert!(matches!(
+            ProviderError::from_http_status(reqwest::StatusCode::UNAUTHORIZED, "API"),
+            ProviderError::AuthRequired
+        ));
+        assert!(matches!(
+            ProviderError::from_http_status(reqwest::StatusCode::TOO_MANY_REQUESTS, "API"),
+            ProviderError::RateLimited
+        ));
+        let err = ProviderError::from_http_status(reqwest::StatusCode::BAD_GATEWAY, "Warp API");
+        assert_eq!(err.to_string(), "Warp API returned status 502 Bad Gateway");
+    }
+
+    #[test]
+    fn test_provider_id_base_url_env_var() {
+        assert_eq!(ProviderId::OpenRouter.base_url_env_var(), "CODEXBAR_OPENROUTER_BASE_URL");
+        assert_eq!(ProviderId::KimiK2.base_url_env_var(), "CODEXBAR_KIMIK2_BASE_URL");
+        assert_eq!(ProviderId::Custom("my-gw").base_url_env_var(), "CODEXBAR_MY_GW_BASE_URL");
+        assert_eq!(ProviderId::Custom("corp_gw2").base_url_env_var(), "CODEXBAR_CORP_GW2_BASE_URL");
     }
 
     #[test]
@@ -475,6 +689,103 @@ mod tests {
         assert_eq!(map.get("openai"), Some(&ProviderId::Codex));
     }
 
+    struct StubProvider {
+        metadata: ProviderMetadata,
+        label: &'static str,
+    }
+
+    impl StubProvider {
+        fn new(id: ProviderId, label: &'static str) -> Self {
+            Self {
+                metadata: ProviderMetadata {
+                    id,
+                    display_name: id.display_name(),
+                    session_label: "Session",
+                    weekly_label: "Weekly",
+                    supports_opus: false,
+                    supports_credits: false,
+                    default_enabled: false,
+                    is_primary: false,
+                    dashboard_url: None,
+                    status_page_url: None,
+                },
+                label,
+            }
+        }
+    }
+
+    struct StubStrategy<'a>(&'a StubProvider);
+
+    #[async_trait]
+    impl crate::core::ProviderFetchStrategy for StubStrategy<'_> {
+        fn id(&self) -> &str {
+            "stub"
+        }
+
+        fn kind(&self) -> crate::core::ProviderFetchKind {
+            crate::core::ProviderFetchKind::LocalProbe
+        }
+
+        async fn fetch(&self, _ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+            Err(ProviderError::Other(self.0.label.to_string()))
+        }
+    }
+
+    #[async_trait]
+    impl Provider for StubProvider {
+        fn id(&self) -> ProviderId {
+            self.metadata.id
+        }
+
+        fn metadata(&self) -> &ProviderMetadata {
+            &self.metadata
+        }
+
+        fn fetch_pipeline(&self) -> ProviderFetchPipeline<'_> {
+            ProviderFetchPipeline::empty().with_strategy(StubStrategy(self))
+        }
+    }
+
+    #[test]
+    fn test_registry_register_and_get() {
+        let registry = ProviderRegistry::new();
+        assert!(registry.is_empty());
+        assert!(registry.get(ProviderId::Claude).is_none());
+
+        registry.register(Arc::new(StubProvider::new(ProviderId::Claude, "claude")));
+        registry.register(Arc::new(StubProvider::new(ProviderId::Codex, "codex")));
+
+        assert_eq!(registry.len(), 2);
+        assert!(registry.contains(ProviderId::Codex));
+        assert_eq!(registry.get(ProviderId::Claude).unwrap().id(), ProviderId::Claude);
+        assert!(registry.get(ProviderId::Cursor).is_none());
+    }
+
+    #[test]
+    fn test_registry_preserves_order_and_replaces() {
+        let registry = ProviderRegistry::new();
+        registry.register(Arc::new(StubProvider::new(ProviderId::Codex, "first")));
+        registry.register(Arc::new(StubProvider::new(ProviderId::Claude, "claude")));
+        registry.register(Arc::new(StubProvider::new(ProviderId::Codex, "second")));
+
+        assert_eq!(registry.all_ids(), vec![ProviderId::Codex, ProviderId::Claude]);
+        let ids: Vec<_> = registry.iter().map(|p| p.id()).collect();
+        assert_eq!(ids, vec![ProviderId::Codex, ProviderId::Claude]);
+
+        let codex = registry.get(ProviderId::Codex).unwrap();
+        let err = tokio_test::block_on(codex.fetch_usage(&FetchContext::default())).unwrap_err();
+        assert_eq!(err.to_string(), "second");
+    }
+
+    #[test]
+    fn test_provider_sources_derived_from_pipeline() {
+        let provider = StubProvider::new(ProviderId::JetBrains, "local");
+        assert_eq!(provider.available_sources(), vec![SourceMode::Auto, SourceMode::Cli]);
+        assert!(provider.supports_cli());
+        assert!(!provider.supports_web());
+        assert!(!provider.supports_oauth());
+    }
+
     #[test]
     fn test_provider_id_cookie_domain() {
         // Cookie-based providers
@@ -93,6 +93,109 @@ impl Default for RateWindow {
     }
 }
 
+/// What a rate window measures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RateWindowKind {
+    /// Short rolling window (e.g., 5-hour session)
+    Session,
+    /// Weekly limit
+    Weekly,
+    /// Monthly or billing-cycle limit
+    Monthly,
+    /// Limit scoped to a single model or model family
+    Model,
+    /// Prepaid credits or request buckets
+    Credits,
+}
+
+impl RateWindowKind {
+    /// Lowercase name, matching the serialized form
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RateWindowKind::Session => "session",
+            RateWindowKind::Weekly => "weekly",
+            RateWindowKind::Monthly => "monthly",
+            RateWindowKind::Model => "model",
+            RateWindowKind::Credits => "credits",
+        }
+    }
+
+    /// Parse a lowercase kind name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "session" => Some(RateWindowKind::Session),
+            "weekly" => Some(RateWindowKind::Weekly),
+            "monthly" => Some(RateWindowKind::Monthly),
+            "model" => Some(RateWindowKind::Model),
+            "credits" => Some(RateWindowKind::Credits),
+            _ => None,
+        }
+    }
+}
+
+/// A rate window with a stable id, display label and kind
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NamedRateWindow {
+    /// Stable identifier (e.g., "primary", "opus", "gemini-2.5-pro")
+    pub id: String,
+
+    /// Human-readable label (e.g., "Session", "Opus")
+    pub label: String,
+
+    /// What the window measures
+    pub kind: RateWindowKind,
+
+    /// Usage and reset details
+    #[serde(flatten)]
+    pub window: RateWindow,
+}
+
+impl NamedRateWindow {
+    /// Id of the window that maps to the legacy `primary` field
+    pub const PRIMARY_ID: &'static str = "primary";
+    /// Id of the window that maps to the legacy `secondary` field
+    pub const SECONDARY_ID: &'static str = "secondary";
+    /// Id of the window that maps to the legacy `model_specific` field
+    pub const MODEL_SPECIFIC_ID: &'static str = "model_specific";
+
+    /// Create a named rate window
+    pub fn new(
+        id: impl Into<String>,
+        label: impl Into<String>,
+        kind: RateWindowKind,
+        window: RateWindow,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            label: label.into(),
+            kind,
+            window,
+        }
+    }
+
+    /// Window mapped from the legacy `primary` field
+    pub fn primary(window: RateWindow) -> Self {
+        Self::new(Self::PRIMARY_ID, "Session", RateWindowKind::Session, window)
+    }
+
+    /// Window mapped from the legacy `secondary` field
+    pub fn secondary(window: RateWindow) -> Self {
+        Self::new(Self::SECONDARY_ID, "Weekly", RateWindowKind::Weekly, window)
+    }
+
+    /// Window mapped from the legacy `model_specific` field
+    pub fn model_specific(window: RateWindow) -> Self {
+        Self::new(Self::MODEL_SPECIFIC_ID, "Model", RateWindowKind::Model, window)
+    }
+
+    /// Builder pattern: set label
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = label.into();
+        self
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -117,4 +220,13 @@ mod tests {
         assert!(RateWindow::new(100.0).is_exhausted());
         assert!(!RateWindow::new(99.0).is_exhausted());
     }
+
+    #[test]
+    fn test_named_window_serializes_flat() {
+        let named = NamedRateWindow::new("opus", "Opus", RateWindowKind::Model, RateWindow::new(40.0));
+        let json = serde_json::to_value(&named).unwrap();
+        assert_eq!(json["id"], "opus");
+        assert_eq!(json["kind"], "model");
+        assert_eq!(json["used_percent"], 40.0);
+    }
 }
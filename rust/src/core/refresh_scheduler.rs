@@ -0,0 +1,327 @@
+//! Adaptive per-provider refresh scheduling
+//!
+//! Each provider gets its own refresh interval. After a successful fetch the
+//! next refresh comes sooner when a window is nearly exhausted or about to
+//! reset; after transient failures (network errors, timeouts, HTTP 429) it
+//! backs off exponentially. Paused providers are never scheduled.
+
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+
+use super::{ProviderId, UsageSnapshot};
+
+/// Shortest interval the adaptive rules will choose
+pub const MIN_ADAPTIVE_INTERVAL_SECS: i64 = 30;
+
+/// Longest delay after repeated transient failures
+pub const MAX_BACKOFF_SECS: i64 = 60 * 60;
+
+/// Delay after a window's reset before refreshing, so the provider has
+/// rolled the window over
+const RESET_GRACE_SECS: i64 = 30;
+
+/// Usage (percent) at which polling doubles in frequency
+const NEAR_LIMIT_PERCENT: f64 = 75.0;
+
+/// Usage (percent) at which polling quadruples in frequency
+const CRITICAL_PERCENT: f64 = 90.0;
+
+/// How one provider should be refreshed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RefreshPolicy {
+    /// Base interval in seconds (0 = manual refresh only)
+    pub interval_secs: u64,
+    /// Poll faster near exhaustion or a reset
+    pub adaptive: bool,
+    /// Skip this provider entirely
+    pub paused: bool,
+}
+
+impl RefreshPolicy {
+    /// Base interval, if automatic refresh is on
+    fn interval(&self) -> Option<Duration> {
+        (self.interval_secs > 0 && !self.paused).then(|| Duration::seconds(self.interval_secs as i64))
+    }
+}
+
+/// Scheduling state for one provider
+#[derive(Debug, Clone)]
+struct ScheduleEntry {
+    policy: RefreshPolicy,
+    /// When the provider is next due (`None` = as soon as possible)
+    next_due: Option<DateTime<Utc>>,
+    /// Consecutive transient failures
+    failures: u32,
+    in_flight: bool,
+}
+
+/// Decides which providers are due for a refresh
+#[derive(Debug, Clone, Default)]
+pub struct RefreshScheduler {
+    entries: HashMap<ProviderId, ScheduleEntry>,
+}
+
+impl RefreshScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the set of scheduled providers and their policies.
+    /// New providers are due immediately; providers missing from `policies`
+    /// are dropped. A shorter interval pulls an existing due time forward.
+    pub fn configure(&mut self, policies: impl IntoIterator<Item = (ProviderId, RefreshPolicy)>, now: DateTime<Utc>) {
+        let mut entries = HashMap::new();
+        for (id, policy) in policies {
+            let entry = match self.entries.remove(&id) {
+                Some(mut entry) => {
+                    if let (Some(due), Some(interval)) = (entry.next_due, policy.interval()) {
+                        if entry.failures == 0 && due > now + interval {
+                            entry.next_due = Some(now + interval);
+                        }
+                    }
+                    entry.policy = policy;
+                    entry
+                }
+                None => ScheduleEntry {
+                    policy,
+                    next_due: None,
+                    failures: 0,
+                    in_flight: false,
+                },
+            };
+            entries.insert(id, entry);
+        }
+        self.entries = entries;
+    }
+
+    /// Providers whose refresh is due, excluding paused, manual-only and
+    /// in-flight ones
+    pub fn due(&self, now: DateTime<Utc>) -> Vec<ProviderId> {
+        let mut due: Vec<ProviderId> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| {
+                !entry.in_flight
+                    && entry.policy.interval().is_some()
+                    && entry.next_due.is_none_or(|due| due <= now)
+            })
+            .map(|(id, _)| *id)
+            .collect();
+        due.sort_by_key(|id| id.cli_name());
+        due
+    }
+
+    /// Scheduled providers that are not paused (for a manual refresh)
+    pub fn active(&self) -> Vec<ProviderId> {
+        let mut active: Vec<ProviderId> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| !entry.policy.paused)
+            .map(|(id, _)| *id)
+            .collect();
+        active.sort_by_key(|id| id.cli_name());
+        active
+    }
+
+    /// Whether a provider is paused
+    pub fn is_paused(&self, id: ProviderId) -> bool {
+        self.entries.get(&id).is_some_and(|entry| entry.policy.paused)
+    }
+
+    /// When a provider is next due, if it is scheduled automatically
+    pub fn next_due(&self, id: ProviderId) -> Option<DateTime<Utc>> {
+        let entry = self.entries.get(&id)?;
+        entry.policy.interval()?;
+        entry.next_due
+    }
+
+    /// Consecutive transient failures for a provider
+    pub fn failures(&self, id: ProviderId) -> u32 {
+        self.entries.get(&id).map_or(0, |entry| entry.failures)
+    }
+
+    /// Mark providers as being fetched so they are not picked again
+    pub fn mark_started(&mut self, ids: &[ProviderId]) {
+        for id in ids {
+            if let Some(entry) = self.entries.get_mut(id) {
+                entry.in_flight = true;
+            }
+        }
+    }
+
+    /// Record a successful fetch and schedule the next one
+    pub fn record_success(&mut self, id: ProviderId, usage: &UsageSnapshot, now: DateTime<Utc>) {
+        let Some(entry) = self.entries.get_mut(&id) else {
+            return;
+        };
+        entry.in_flight = false;
+        entry.failures = 0;
+        entry.next_due = entry.policy.interval().map(|interval| {
+            let delay = if entry.policy.adaptive {
+                adaptive_interval(interval, usage, now)
+            } else {
+                interval
+            };
+            now + delay
+        });
+    }
+
+    /// Record a failed fetch. Transient errors back off exponentially;
+    /// anything else waits one regular interval.
+    pub fn record_failure(&mut self, id: ProviderId, transient: bool, now: DateTime<Utc>) {
+        let Some(entry) = self.entries.get_mut(&id) else {
+            return;
+        };
+        entry.in_flight = false;
+        if transient {
+            entry.failures = entry.failures.saturating_add(1);
+        } else {
+            entry.failures = 0;
+        }
+        entry.next_due = entry.policy.interval().map(|interval| {
+            let delay = if transient {
+                backoff_delay(interval, entry.failures)
+            } else {
+                interval
+            };
+            now + delay
+        });
+    }
+}
+
+/// Interval after a successful fetch: shorter when a window is nearly used
+/// up, and no later than just after the next window reset
+pub fn adaptive_interval(interval: Duration, usage: &UsageSnapshot, now: DateTime<Utc>) -> Duration {
+    let floor = interval.min(Duration::seconds(MIN_ADAPTIVE_INTERVAL_SECS));
+
+    let max_used = usage
+        .windows
+        .iter()
+        .map(|w| w.window.used_percent)
+        .fold(0.0_f64, f64::max);
+    let mut delay = if max_used >= CRITICAL_PERCENT {
+        interval / 4
+    } else if max_used >= NEAR_LIMIT_PERCENT {
+        interval / 2
+    } else {
+        interval
+    };
+
+    let next_reset = usage
+        .windows
+        .iter()
+        .filter_map(|w| w.window.resets_at)
+        .filter(|resets_at| *resets_at > now)
+        .min();
+    if let Some(resets_at) = next_reset {
+        delay = delay.min(resets_at - now + Duration::seconds(RESET_GRACE_SECS));
+    }
+
+    delay.max(floor)
+}
+
+/// Delay after `failures` consecutive transient failures
+pub fn backoff_delay(interval: Duration, failures: u32) -> Duration {
+    let factor = 1_i32 << failures.min(10);
+    let cap = Duration::seconds(MAX_BACKOFF_SECS).max(interval);
+    (interval * factor).min(cap)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{ProviderError, RateWindow};
+
+    fn policy(interval_secs: u64) -> RefreshPolicy {
+        RefreshPolicy {
+            interval_secs,
+            adaptive: true,
+            paused: false,
+        }
+    }
+
+    fn usage(used_percent: f64, resets_at: Option<DateTime<Utc>>) -> UsageSnapshot {
+        let mut window = RateWindow::new(used_percent);
+        window.resets_at = resets_at;
+        UsageSnapshot::new(window)
+    }
+
+    #[test]
+    fn test_adaptive_interval() {
+        let now = Utc::now();
+        let five_min = Duration::seconds(300);
+
+        assert_eq!(adaptive_interval(five_min, &usage(10.0, None), now), five_min);
+        assert_eq!(adaptive_interval(five_min, &usage(80.0, None), now), Duration::seconds(150));
+        assert_eq!(adaptive_interval(five_min, &usage(95.0, None), now), Duration::seconds(75));
+
+        // Refresh just after an imminent reset
+        let soon = now + Duration::seconds(60);
+        assert_eq!(adaptive_interval(five_min, &usage(10.0, Some(soon)), now), Duration::seconds(90));
+
+        // Never faster than the floor, unless the user asked for less
+        let now_reset = now + Duration::seconds(1);
+        assert_eq!(adaptive_interval(five_min, &usage(99.0, Some(now_reset)), now), Duration::seconds(31));
+        assert_eq!(adaptive_interval(Duration::seconds(60), &usage(99.0, None), now), Duration::seconds(30));
+        assert_eq!(adaptive_interval(Duration::seconds(10), &usage(99.0, None), now), Duration::seconds(10));
+    }
+
+    #[test]
+    fn test_backoff_delay() {
+        let minute = Duration::seconds(60);
+        assert_eq!(backoff_delay(minute, 1), Duration::seconds(120));
+        assert_eq!(backoff_delay(minute, 3), Duration::seconds(480));
+        assert_eq!(backoff_delay(minute, 20), Duration::seconds(MAX_BACKOFF_SECS));
+        assert_eq!(backoff_delay(Duration::seconds(7200), 2), Duration::seconds(7200));
+    }
+
+    #[test]
+    fn test_scheduler_due_and_pause() {
+        let now = Utc::now();
+        let mut scheduler = RefreshScheduler::new();
+        scheduler.configure(
+            [
+                (ProviderId::Claude, policy(300)),
+                (ProviderId::Codex, RefreshPolicy { paused: true, ..policy(300) }),
+                (ProviderId::Cursor, policy(0)),
+            ],
+            now,
+        );
+
+        assert_eq!(scheduler.due(now), vec![ProviderId::Claude]);
+        assert_eq!(scheduler.active(), vec![ProviderId::Claude, ProviderId::Cursor]);
+        assert!(scheduler.is_paused(ProviderId::Codex));
+
+        scheduler.mark_started(&[ProviderId::Claude]);
+        assert!(scheduler.due(now).is_empty());
+
+        scheduler.record_success(ProviderId::Claude, &usage(10.0, None), now);
+        assert!(scheduler.due(now + Duration::seconds(299)).is_empty());
+        assert_eq!(scheduler.due(now + Duration::seconds(300)), vec![ProviderId::Claude]);
+
+        // Shortening the interval pulls the due time forward
+        scheduler.configure([(ProviderId::Claude, policy(60))], now);
+        assert_eq!(scheduler.next_due(ProviderId::Claude), Some(now + Duration::seconds(60)));
+        assert_eq!(scheduler.next_due(ProviderId::Codex), None);
+    }
+
+    #[test]
+    fn test_scheduler_backoff() {
+        let now = Utc::now();
+        let mut scheduler = RefreshScheduler::new();
+        scheduler.configure([(ProviderId::Zai, policy(60))], now);
+
+        scheduler.record_failure(ProviderId::Zai, true, now);
+        scheduler.record_failure(ProviderId::Zai, true, now);
+        assert_eq!(scheduler.failures(ProviderId::Zai), 2);
+        assert_eq!(scheduler.next_due(ProviderId::Zai), Some(now + Duration::seconds(240)));
+
+        scheduler.record_failure(ProviderId::Zai, false, now);
+        assert_eq!(scheduler.failures(ProviderId::Zai), 0);
+        assert_eq!(scheduler.next_due(ProviderId::Zai), Some(now + Duration::seconds(60)));
+
+        assert!(ProviderError::RateLimited.is_transient());
+        assert!(ProviderError::Timeout.is_transient());
+        assert!(!ProviderError::AuthRequired.is_transient());
+    }
+}
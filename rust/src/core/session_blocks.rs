@@ -0,0 +1,253 @@
+//! Claude 5-hour session blocks
+//!
+//! Claude's session limit applies to 5-hour billing blocks: a block starts at
+//! the hour of the first message sent after the previous block ended and
+//! lasts five hours. Rebuilding the blocks from the local Claude logs shows
+//! how much of the current block is used between remote refreshes, and keeps
+//! working without a network connection.
+
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use serde::Serialize;
+
+use super::{CostUsageTokens, RateWindow};
+
+/// Length of a session block in hours
+pub const SESSION_BLOCK_HOURS: i64 = 5;
+
+/// Environment variable with the token limit of one session block
+pub const SESSION_TOKEN_LIMIT_ENV: &str = "CODEXBAR_CLAUDE_SESSION_TOKEN_LIMIT";
+
+/// Days of history rebuilt to estimate the session limit
+pub const SESSION_LIMIT_LOOKBACK_DAYS: i64 = 7;
+
+/// One usage entry from the local logs
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageEntry {
+    pub timestamp: DateTime<Utc>,
+    /// Normalized model name
+    pub model: String,
+    pub tokens: CostUsageTokens,
+}
+
+/// A reconstructed 5-hour session block
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SessionBlock {
+    /// Start of the block (the hour of its first entry)
+    pub start: DateTime<Utc>,
+    /// When the block ends (start + 5 hours)
+    pub end: DateTime<Utc>,
+    pub first_entry: DateTime<Utc>,
+    pub last_entry: DateTime<Utc>,
+    /// Number of usage entries in the block
+    pub entries: usize,
+    pub tokens: CostUsageTokens,
+    /// Models used, sorted
+    pub models: Vec<String>,
+    /// Whether the block is still running
+    pub is_active: bool,
+}
+
+/// Token and cost rate of a block
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct BurnRate {
+    pub tokens_per_minute: f64,
+    pub cost_per_hour_usd: f64,
+}
+
+/// Expected usage of the active block at its end, at the current burn rate
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct BlockProjection {
+    pub total_tokens: i64,
+    pub cost_usd: f64,
+    pub remaining_minutes: i64,
+}
+
+impl SessionBlock {
+    fn new(entry: &UsageEntry) -> Self {
+        let start = entry
+            .timestamp
+            .duration_trunc(Duration::hours(1))
+            .unwrap_or(entry.timestamp);
+        Self {
+            start,
+            end: start + Duration::hours(SESSION_BLOCK_HOURS),
+            first_entry: entry.timestamp,
+            last_entry: entry.timestamp,
+            entries: 0,
+            tokens: CostUsageTokens::default(),
+            models: Vec::new(),
+            is_active: false,
+        }
+    }
+
+    fn add(&mut self, entry: &UsageEntry) {
+        self.last_entry = entry.timestamp;
+        self.entries += 1;
+        self.tokens.add(&entry.tokens);
+        if let Err(pos) = self.models.binary_search(&entry.model) {
+            self.models.insert(pos, entry.model.clone());
+        }
+    }
+
+    /// Input, output and cache tokens together
+    pub fn total_tokens(&self) -> i64 {
+        self.tokens.input + self.tokens.output + self.tokens.cache_read + self.tokens.cache_creation
+    }
+
+    /// Usage rate between the first and last entry (needs at least a minute of activity)
+    pub fn burn_rate(&self) -> Option<BurnRate> {
+        let minutes = (self.last_entry - self.first_entry).num_seconds() as f64 / 60.0;
+        if minutes < 1.0 {
+            return None;
+        }
+        Some(BurnRate {
+            tokens_per_minute: self.total_tokens() as f64 / minutes,
+            cost_per_hour_usd: self.tokens.cost_usd / minutes * 60.0,
+        })
+    }
+
+    /// Projected totals at the end of an active block
+    pub fn projection(&self, now: DateTime<Utc>) -> Option<BlockProjection> {
+        if !self.is_active {
+            return None;
+        }
+        let rate = self.burn_rate()?;
+        let remaining_minutes = (self.end - now).num_minutes().max(0);
+        Some(BlockProjection {
+            total_tokens: self.total_tokens() + (rate.tokens_per_minute * remaining_minutes as f64) as i64,
+            cost_usd: self.tokens.cost_usd + rate.cost_per_hour_usd * remaining_minutes as f64 / 60.0,
+            remaining_minutes,
+        })
+    }
+}
+
+/// Group entries (sorted by timestamp) into session blocks, oldest first.
+///
+/// A new block starts when an entry falls after the current block's end or
+/// more than five hours after the previous entry.
+pub fn build_session_blocks(entries: &[UsageEntry], now: DateTime<Utc>) -> Vec<SessionBlock> {
+    let block_length = Duration::hours(SESSION_BLOCK_HOURS);
+    let mut blocks: Vec<SessionBlock> = Vec::new();
+
+    for entry in entries {
+        let starts_new = blocks
+            .last()
+            .is_none_or(|block| entry.timestamp >= block.end || entry.timestamp - block.last_entry > block_length);
+        if starts_new {
+            blocks.push(SessionBlock::new(entry));
+        }
+        if let Some(block) = blocks.last_mut() {
+            block.add(entry);
+        }
+    }
+
+    if let Some(block) = blocks.last_mut() {
+        block.is_active = now < block.end && now - block.last_entry < block_length;
+    }
+    blocks
+}
+
+/// Session block token limit from `CODEXBAR_CLAUDE_SESSION_TOKEN_LIMIT`
+pub fn session_token_limit_from_env() -> Option<i64> {
+    std::env::var(SESSION_TOKEN_LIMIT_ENV)
+        .ok()
+        .and_then(|value| value.trim().parse::<i64>().ok())
+        .filter(|limit| *limit > 0)
+}
+
+/// Largest token total of any finished block, a stand-in for the session
+/// limit when none is configured
+pub fn max_completed_block_tokens(blocks: &[SessionBlock]) -> Option<i64> {
+    blocks
+        .iter()
+        .filter(|block| !block.is_active)
+        .map(SessionBlock::total_tokens)
+        .filter(|tokens| *tokens > 0)
+        .max()
+}
+
+/// Session window estimated from the blocks: the active block's share of
+/// `limit_tokens`, capped at 100% and resetting when the block ends (0% when
+/// no block is active)
+pub fn estimate_session_window(blocks: &[SessionBlock], limit_tokens: i64) -> RateWindow {
+    match blocks.last().filter(|block| block.is_active) {
+        Some(block) => {
+            let used_percent = if limit_tokens > 0 {
+                (block.total_tokens() as f64 / limit_tokens as f64 * 100.0).clamp(0.0, 100.0)
+            } else {
+                0.0
+            };
+            RateWindow::with_details(
+                used_percent,
+                Some((SESSION_BLOCK_HOURS * 60) as u32),
+                Some(block.end),
+                None,
+            )
+        }
+        None => RateWindow::with_details(0.0, Some((SESSION_BLOCK_HOURS * 60) as u32), None, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn entry(hour: u32, minute: u32, tokens: i64) -> UsageEntry {
+        UsageEntry {
+            timestamp: Utc.with_ymd_and_hms(2026, 1, 14, hour, minute, 0).unwrap(),
+            model: "claude-sonnet-4-5".to_string(),
+            tokens: CostUsageTokens {
+                input: tokens,
+                output: tokens,
+                cost_usd: tokens as f64 * 1e-4,
+                ..CostUsageTokens::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_build_session_blocks() {
+        let entries = vec![
+            entry(9, 20, 100),
+            entry(10, 0, 100),
+            entry(13, 59, 100),
+            // Past 14:00, the end of the first block
+            entry(14, 5, 50),
+            // More than five hours after the previous entry
+            entry(19, 30, 10),
+            entry(20, 30, 20),
+        ];
+        let now = Utc.with_ymd_and_hms(2026, 1, 14, 21, 0, 0).unwrap();
+        let blocks = build_session_blocks(&entries, now);
+
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0].start, Utc.with_ymd_and_hms(2026, 1, 14, 9, 0, 0).unwrap());
+        assert_eq!(blocks[0].end, Utc.with_ymd_and_hms(2026, 1, 14, 14, 0, 0).unwrap());
+        assert_eq!(blocks[0].entries, 3);
+        assert_eq!(blocks[0].total_tokens(), 600);
+        assert_eq!(blocks[1].start, Utc.with_ymd_and_hms(2026, 1, 14, 14, 0, 0).unwrap());
+        assert_eq!(blocks[2].start, Utc.with_ymd_and_hms(2026, 1, 14, 19, 0, 0).unwrap());
+        assert!(!blocks[0].is_active && !blocks[1].is_active);
+        assert!(blocks[2].is_active);
+        assert_eq!(max_completed_block_tokens(&blocks), Some(600));
+
+        // 60 tokens over 60 minutes, with 180 minutes left until 00:00
+        let rate = blocks[2].burn_rate().unwrap();
+        assert!((rate.tokens_per_minute - 1.0).abs() < 1e-9);
+        let projection = blocks[2].projection(now).unwrap();
+        assert_eq!(projection.remaining_minutes, 180);
+        assert_eq!(projection.total_tokens, 240);
+
+        let window = estimate_session_window(&blocks, 600);
+        assert!((window.used_percent - 10.0).abs() < 1e-9);
+        assert_eq!(window.resets_at, Some(blocks[2].end));
+        // Past the largest earlier block the estimate stays at 100%
+        assert_eq!(estimate_session_window(&blocks, 40).used_percent, 100.0);
+
+        // Once the block has ended nothing is active
+        let later = build_session_blocks(&entries, now + Duration::hours(4));
+        assert!(later.iter().all(|block| !block.is_active));
+        assert_eq!(estimate_session_window(&later, 600).used_percent, 0.0);
+    }
+}
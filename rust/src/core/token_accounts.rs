@@ -115,7 +115,8 @@ impl TokenAccountSupport {
             | ProviderId::JetBrains
             | ProviderId::Warp
             | ProviderId::Ollama
-            | ProviderId::OpenRouter => None,
+            | ProviderId::OpenRouter
+            | ProviderId::Custom(_) => None,
         }
     }
 
@@ -0,0 +1,517 @@
+//! Usage History
+//!
+//! Persists every fetched usage snapshot in a local SQLite database so past
+//! burn rates can be reviewed later (`codexbar history`). Fetch successes and
+//! failures are kept alongside so recent error rates can inform `codexbar pick`.
+
+#![allow(dead_code)]
+
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::core::{CostSnapshot, ProviderFetchResult, ProviderId, RateWindowKind};
+
+/// Errors that can occur while reading or writing usage history
+#[derive(Debug, Error)]
+pub enum UsageHistoryError {
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("No data directory available")]
+    NoDataDir,
+}
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS snapshots (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    provider TEXT NOT NULL,
+    captured_at INTEGER NOT NULL,
+    source_label TEXT NOT NULL,
+    account_email TEXT,
+    account_organization TEXT,
+    login_method TEXT,
+    cost_used REAL,
+    cost_limit REAL,
+    cost_currency TEXT,
+    cost_period TEXT,
+    cost_resets_at INTEGER
+);
+CREATE INDEX IF NOT EXISTS idx_snapshots_provider_time ON snapshots (provider, captured_at);
+CREATE INDEX IF NOT EXISTS idx_snapshots_time ON snapshots (captured_at);
+CREATE TABLE IF NOT EXISTS snapshot_windows (
+    snapshot_id INTEGER NOT NULL REFERENCES snapshots(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    window_id TEXT NOT NULL,
+    label TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    used_percent REAL NOT NULL,
+    window_minutes INTEGER,
+    resets_at INTEGER,
+    reset_description TEXT,
+    PRIMARY KEY (snapshot_id, position)
+);
+CREATE TABLE IF NOT EXISTS fetch_outcomes (
+    provider TEXT NOT NULL,
+    attempted_at INTEGER NOT NULL,
+    success INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_fetch_outcomes_provider_time ON fetch_outcomes (provider, attempted_at);
+CREATE INDEX IF NOT EXISTS idx_fetch_outcomes_time ON fetch_outcomes (attempted_at);
+";
+
+/// A rate window as stored in history
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UsageHistoryWindow {
+    /// Stable window id from the snapshot (e.g., "primary", "opus")
+    pub id: String,
+    pub label: String,
+    pub kind: RateWindowKind,
+    pub used_percent: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window_minutes: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resets_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reset_description: Option<String>,
+}
+
+/// One recorded snapshot
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageHistoryEntry {
+    pub provider: ProviderId,
+    pub captured_at: DateTime<Utc>,
+    pub source_label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_organization: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub login_method: Option<String>,
+    pub windows: Vec<UsageHistoryWindow>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost: Option<CostSnapshot>,
+}
+
+impl UsageHistoryEntry {
+    /// Look up a window by id
+    pub fn window(&self, id: &str) -> Option<&UsageHistoryWindow> {
+        self.windows.iter().find(|w| w.id == id)
+    }
+}
+
+/// SQLite-backed usage history store
+pub struct UsageHistoryStore {
+    conn: Connection,
+}
+
+impl UsageHistoryStore {
+    /// Default database location
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_local_dir().map(|d| d.join("CodexBar").join("usage_history.sqlite"))
+    }
+
+    /// Open the store at the default location, creating it if needed
+    pub fn open_default() -> Result<Self, UsageHistoryError> {
+        let path = Self::default_path().ok_or(UsageHistoryError::NoDataDir)?;
+        Self::open(&path)
+    }
+
+    /// Open (or create) a store at the given path
+    pub fn open(path: &Path) -> Result<Self, UsageHistoryError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(path)?;
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// Record a fetch result, timestamped with the snapshot's `updated_at`.
+    /// Stale snapshots (old data served from a local fallback) are skipped.
+    pub fn record(&self, provider: ProviderId, result: &ProviderFetchResult) -> Result<(), UsageHistoryError> {
+        if result.usage.stale {
+            return Ok(());
+        }
+        self.record_at(provider, result, result.usage.updated_at)
+    }
+
+    /// Record a fetch result with an explicit capture time. Nothing is written
+    /// when the provider's newest snapshot has the same capture time and source,
+    /// as when the same logged event is read again.
+    pub fn record_at(
+        &self,
+        provider: ProviderId,
+        result: &ProviderFetchResult,
+        captured_at: DateTime<Utc>,
+    ) -> Result<(), UsageHistoryError> {
+        let usage = &result.usage;
+        let cost = result.cost.as_ref();
+
+        let newest: Option<(i64, String)> = self
+            .conn
+            .query_row(
+                "SELECT captured_at, source_label FROM snapshots WHERE provider = ?1
+                 ORDER BY captured_at DESC, id DESC LIMIT 1",
+                params![provider.cli_name()],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        if newest.is_some_and(|(at, source)| at == captured_at.timestamp_millis() && source == result.source_label) {
+            return Ok(());
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO snapshots (
+                provider, captured_at, source_label, account_email, account_organization,
+                login_method, cost_used, cost_limit, cost_currency, cost_period, cost_resets_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                provider.cli_name(),
+                captured_at.timestamp_millis(),
+                result.source_label,
+                usage.account_email,
+                usage.account_organization,
+                usage.login_method,
+                cost.map(|c| c.used),
+                cost.and_then(|c| c.limit),
+                cost.map(|c| c.currency_code.as_str()),
+                cost.map(|c| c.period.as_str()),
+                cost.and_then(|c| c.resets_at).map(|t| t.timestamp_millis()),
+            ],
+        )?;
+        let snapshot_id = tx.last_insert_rowid();
+
+        for (position, named) in usage.windows.iter().enumerate() {
+            let window = &named.window;
+            tx.execute(
+                "INSERT INTO snapshot_windows (
+                    snapshot_id, position, window_id, label, kind, used_percent, window_minutes,
+                    resets_at, reset_description
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    snapshot_id,
+                    position as i64,
+                    named.id,
+                    named.label,
+                    named.kind.as_str(),
+                    window.used_percent,
+                    window.window_minutes,
+                    window.resets_at.map(|t| t.timestamp_millis()),
+                    window.reset_description,
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Query snapshots captured at or after `since`, oldest first.
+    /// `provider: None` returns every provider.
+    pub fn query(
+        &self,
+        provider: Option<ProviderId>,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<UsageHistoryEntry>, UsageHistoryError> {
+        self.load(
+            "WHERE captured_at >= ?1 AND (?2 IS NULL OR provider = ?2) ORDER BY captured_at ASC, id ASC",
+            params![since.timestamp_millis(), provider.map(|p| p.cli_name())],
+        )
+    }
+
+    /// The newest snapshot of `provider` with a cost captured before `before`
+    pub fn latest_cost_before(
+        &self,
+        provider: ProviderId,
+        before: DateTime<Utc>,
+    ) -> Result<Option<UsageHistoryEntry>, UsageHistoryError> {
+        let entries = self.load(
+            "WHERE captured_at < ?1 AND provider = ?2 AND cost_used IS NOT NULL
+             ORDER BY captured_at DESC, id DESC LIMIT 1",
+            params![before.timestamp_millis(), provider.cli_name()],
+        )?;
+        Ok(entries.into_iter().next())
+    }
+
+    /// Snapshots matching `filter` (a WHERE / ORDER BY tail) with their windows
+    fn load(&self, filter: &str, params: impl rusqlite::Params) -> Result<Vec<UsageHistoryEntry>, UsageHistoryError> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id, provider, captured_at, source_label, account_email, account_organization,
+                    login_method, cost_used, cost_limit, cost_currency, cost_period, cost_resets_at
+             FROM snapshots {}",
+            filter
+        ))?;
+        let mut window_stmt = self.conn.prepare(
+            "SELECT window_id, label, kind, used_percent, window_minutes, resets_at, reset_description
+             FROM snapshot_windows WHERE snapshot_id = ?1 ORDER BY position ASC",
+        )?;
+
+        let rows = stmt.query_map(params, |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, Option<String>>(5)?,
+                row.get::<_, Option<String>>(6)?,
+                row.get::<_, Option<f64>>(7)?,
+                row.get::<_, Option<f64>>(8)?,
+                row.get::<_, Option<String>>(9)?,
+                row.get::<_, Option<String>>(10)?,
+                row.get::<_, Option<i64>>(11)?,
+            ))
+        })?;
+
+        let mut entries = Vec::new();
+        for row in rows {
+            let (
+                id,
+                provider_name,
+                captured_at,
+                source_label,
+                account_email,
+                account_organization,
+                login_method,
+                cost_used,
+                cost_limit,
+                cost_currency,
+                cost_period,
+                cost_resets_at,
+            ) = row?;
+
+            // Skip rows written for providers this build no longer knows
+            let Some(provider) = ProviderId::from_cli_name(&provider_name) else {
+                continue;
+            };
+            let captured_at = from_millis(captured_at);
+
+            let windows = window_stmt
+                .query_map(params![id], |row| {
+                    let kind: String = row.get(2)?;
+                    Ok(UsageHistoryWindow {
+                        id: row.get(0)?,
+                        label: row.get(1)?,
+                        kind: RateWindowKind::from_name(&kind).unwrap_or(RateWindowKind::Session),
+                        used_percent: row.get(3)?,
+                        window_minutes: row.get(4)?,
+                        resets_at: row.get::<_, Option<i64>>(5)?.map(from_millis),
+                        reset_description: row.get(6)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let cost = cost_used.map(|used| CostSnapshot {
+                used,
+                limit: cost_limit,
+                currency_code: cost_currency.unwrap_or_else(|| "USD".to_string()),
+                period: cost_period.unwrap_or_default(),
+                resets_at: cost_resets_at.map(from_millis),
+                updated_at: captured_at,
+            });
+
+            entries.push(UsageHistoryEntry {
+                provider,
+                captured_at,
+                source_label,
+                account_email,
+                account_organization,
+                login_method,
+                windows,
+                cost,
+            });
+        }
+
+        Ok(entries)
+    }
+
+    /// Record whether a fetch attempt for `provider` succeeded
+    pub fn record_fetch_outcome(
+        &self,
+        provider: ProviderId,
+        success: bool,
+        attempted_at: DateTime<Utc>,
+    ) -> Result<(), UsageHistoryError> {
+        self.conn.execute(
+            "INSERT INTO fetch_outcomes (provider, attempted_at, success) VALUES (?1, ?2, ?3)",
+            params![provider.cli_name(), attempted_at.timestamp_millis(), success],
+        )?;
+        Ok(())
+    }
+
+    /// Fraction of fetch attempts for `provider` at or after `since` that
+    /// failed, or `None` when there were no attempts
+    pub fn error_rate(&self, provider: ProviderId, since: DateTime<Utc>) -> Result<Option<f64>, UsageHistoryError> {
+        let (attempts, failures): (i64, i64) = self.conn.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(success = 0), 0)
+             FROM fetch_outcomes WHERE provider = ?1 AND attempted_at >= ?2",
+            params![provider.cli_name(), since.timestamp_millis()],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        Ok((attempts > 0).then(|| failures as f64 / attempts as f64))
+    }
+
+    /// Delete snapshots and fetch outcomes from before `cutoff`, returning how
+    /// many snapshots were removed
+    pub fn prune_before(&self, cutoff: DateTime<Utc>) -> Result<usize, UsageHistoryError> {
+        let removed = self.conn.execute(
+            "DELETE FROM snapshots WHERE captured_at < ?1",
+            params![cutoff.timestamp_millis()],
+        )?;
+        self.conn.execute(
+            "DELETE FROM fetch_outcomes WHERE attempted_at < ?1",
+            params![cutoff.timestamp_millis()],
+        )?;
+        Ok(removed)
+    }
+}
+
+fn from_millis(millis: i64) -> DateTime<Utc> {
+    Utc.timestamp_millis_opt(millis).single().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{NamedRateWindow, RateWindow, UsageSnapshot};
+    use chrono::Duration;
+
+    fn sample_result(primary: f64, secondary: f64) -> ProviderFetchResult {
+        let usage = UsageSnapshot::new(RateWindow::with_details(primary, Some(300), None, None))
+            .with_secondary(RateWindow::with_details(secondary, Some(10080), None, None))
+            .with_window(NamedRateWindow::new("opus", "Opus", RateWindowKind::Model, RateWindow::new(5.0)))
+            .with_email("dev@example.com")
+            .with_login_method("Claude Max");
+        ProviderFetchResult::new(usage, "oauth")
+            .with_cost(CostSnapshot::new(12.5, "USD", "Monthly").with_limit(100.0))
+    }
+
+    #[test]
+    fn test_record_and_query_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = UsageHistoryStore::open(&dir.path().join("history.sqlite")).unwrap();
+        let now = Utc::now();
+
+        store.record_at(ProviderId::Claude, &sample_result(10.0, 40.0), now - Duration::hours(2)).unwrap();
+        store.record_at(ProviderId::Claude, &sample_result(25.0, 55.0), now - Duration::hours(1)).unwrap();
+        store.record_at(ProviderId::Codex, &sample_result(5.0, 5.0), now).unwrap();
+
+        let claude = store.query(Some(ProviderId::Claude), now - Duration::days(1)).unwrap();
+        assert_eq!(claude.len(), 2);
+        assert!(claude[0].captured_at < claude[1].captured_at);
+
+        let latest = &claude[1];
+        assert_eq!(latest.source_label, "oauth");
+        assert_eq!(latest.account_email.as_deref(), Some("dev@example.com"));
+        assert_eq!(latest.login_method.as_deref(), Some("Claude Max"));
+        assert_eq!(latest.windows.len(), 3);
+        assert_eq!(latest.window("primary").unwrap().used_percent, 25.0);
+        assert_eq!(latest.window("secondary").unwrap().window_minutes, Some(10080));
+        let opus = latest.window("opus").unwrap();
+        assert_eq!(opus.label, "Opus");
+        assert_eq!(opus.kind, RateWindowKind::Model);
+
+        let cost = latest.cost.as_ref().unwrap();
+        assert_eq!(cost.used, 12.5);
+        assert_eq!(cost.limit, Some(100.0));
+        assert_eq!(cost.period, "Monthly");
+
+        let all = store.query(None, now - Duration::days(1)).unwrap();
+        assert_eq!(all.len(), 3);
+    }
+
+    #[test]
+    fn test_query_respects_since() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = UsageHistoryStore::open(&dir.path().join("history.sqlite")).unwrap();
+        let now = Utc::now();
+
+        store.record_at(ProviderId::Claude, &sample_result(10.0, 40.0), now - Duration::days(10)).unwrap();
+        store.record_at(ProviderId::Claude, &sample_result(20.0, 50.0), now - Duration::days(1)).unwrap();
+
+        let recent = store.query(Some(ProviderId::Claude), now - Duration::days(7)).unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].window("primary").unwrap().used_percent, 20.0);
+
+        let before = store.latest_cost_before(ProviderId::Claude, now - Duration::days(7)).unwrap().unwrap();
+        assert_eq!(before.window("primary").unwrap().used_percent, 10.0);
+        assert!(store.latest_cost_before(ProviderId::Claude, now - Duration::days(11)).unwrap().is_none());
+        assert!(store.latest_cost_before(ProviderId::Codex, now).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_record_skips_stale_and_repeated_snapshots() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = UsageHistoryStore::open(&dir.path().join("history.sqlite")).unwrap();
+        let logged_at = Utc::now() - Duration::hours(3);
+
+        let mut result = sample_result(10.0, 40.0);
+        result.usage = result.usage.with_updated_at(logged_at);
+        store.record(ProviderId::Codex, &result).unwrap();
+        // The same logged event read again, then after the window reset
+        store.record(ProviderId::Codex, &result).unwrap();
+        let mut stale = sample_result(0.0, 40.0);
+        stale.usage = stale.usage.with_updated_at(logged_at).with_stale(true);
+        store.record(ProviderId::Codex, &stale).unwrap();
+
+        let entries = store.query(Some(ProviderId::Codex), logged_at - Duration::hours(1)).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].window("primary").unwrap().used_percent, 10.0);
+
+        // A different source at the same time is a separate snapshot
+        let mut other = sample_result(12.0, 40.0);
+        other.source_label = "local".to_string();
+        other.usage = other.usage.with_updated_at(logged_at);
+        store.record(ProviderId::Codex, &other).unwrap();
+        assert_eq!(store.query(Some(ProviderId::Codex), logged_at - Duration::hours(1)).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_prune_before_removes_windows() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = UsageHistoryStore::open(&dir.path().join("history.sqlite")).unwrap();
+        let now = Utc::now();
+
+        store.record_at(ProviderId::Claude, &sample_result(10.0, 40.0), now - Duration::days(10)).unwrap();
+        store.record_at(ProviderId::Claude, &sample_result(20.0, 50.0), now).unwrap();
+
+        assert_eq!(store.prune_before(now - Duration::days(5)).unwrap(), 1);
+        let remaining: i64 = store
+            .conn
+            .query_row("SELECT COUNT(*) FROM snapshot_windows", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(remaining, 3);
+    }
+
+    #[test]
+    fn test_error_rate() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = UsageHistoryStore::open(&dir.path().join("history.sqlite")).unwrap();
+        let now = Utc::now();
+
+        assert_eq!(store.error_rate(ProviderId::Codex, now - Duration::days(1)).unwrap(), None);
+
+        store.record_fetch_outcome(ProviderId::Codex, false, now - Duration::days(3)).unwrap();
+        store.record_fetch_outcome(ProviderId::Codex, true, now - Duration::hours(3)).unwrap();
+        store.record_fetch_outcome(ProviderId::Codex, false, now - Duration::hours(2)).unwrap();
+        store.record_fetch_outcome(ProviderId::Codex, true, now - Duration::hours(1)).unwrap();
+        store.record_fetch_outcome(ProviderId::Codex, true, now).unwrap();
+        store.record_fetch_outcome(ProviderId::Claude, false, now).unwrap();
+
+        assert_eq!(store.error_rate(ProviderId::Codex, now - Duration::days(1)).unwrap(), Some(0.25));
+        assert_eq!(store.error_rate(ProviderId::Codex, now - Duration::days(7)).unwrap(), Some(0.4));
+        assert_eq!(store.error_rate(ProviderId::Claude, now - Duration::days(1)).unwrap(), Some(1.0));
+
+        store.prune_before(now - Duration::days(1)).unwrap();
+        assert_eq!(store.error_rate(ProviderId::Codex, now - Duration::days(7)).unwrap(), Some(0.25));
+    }
+}
@@ -5,7 +5,7 @@
 
 use chrono::{DateTime, Utc};
 
-use super::RateWindow;
+use super::{NamedRateWindow, RateWindow, RateWindowKind};
 
 /// Usage pace stage indicating consumption rate relative to time
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -193,6 +193,19 @@ impl UsagePace {
         }
     }
 
+    /// Calculate the pace of a named window, using the usual period length for
+    /// its kind when the provider didn't report one. Windows without a known
+    /// length (credits, model limits) have no pace.
+    pub fn for_window(named: &NamedRateWindow, now: Option<DateTime<Utc>>) -> Option<Self> {
+        let default_minutes = match named.kind {
+            RateWindowKind::Session => 5 * 60,
+            RateWindowKind::Weekly => 7 * 24 * 60,
+            RateWindowKind::Monthly => 30 * 24 * 60,
+            RateWindowKind::Model | RateWindowKind::Credits => return None,
+        };
+        Self::weekly(&named.window, now, default_minutes)
+    }
+
     /// Format the pace as a status line
     pub fn format_status(&self) -> String {
         let stage_text = self.stage.label();
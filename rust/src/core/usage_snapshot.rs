@@ -3,61 +3,82 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use super::RateWindow;
+use super::{format_currency, CurrencyConverter, NamedRateWindow, ProviderFetchAttempt, RateWindow, RateWindowKind};
+
+/// Returned by [`UsageSnapshot::primary`] if every window was removed
+static EMPTY_WINDOW: RateWindow = RateWindow {
+    used_percent: 0.0,
+    window_minutes: None,
+    resets_at: None,
+    reset_description: None,
+};
 
 /// A snapshot of usage data for a provider at a point in time
+///
+/// Rate windows are kept as an ordered list of [`NamedRateWindow`]s. The
+/// `primary`, `secondary` and `model_specific` fields are still written to
+/// (and read from) JSON so older consumers keep working.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "UsageSnapshotRepr", into = "UsageSnapshotRepr")]
 pub struct UsageSnapshot {
-    /// Primary rate window (usually session-based, e.g., 5-hour for Claude)
-    pub primary: RateWindow,
-
-    /// Secondary rate window (usually weekly/monthly)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub secondary: Option<RateWindow>,
-
-    /// Model-specific rate window (e.g., Opus quota for Claude)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub model_specific: Option<RateWindow>,
+    /// All rate windows, the primary window first
+    pub windows: Vec<NamedRateWindow>,
 
     /// When this snapshot was captured
     pub updated_at: DateTime<Utc>,
 
     /// Account email if available
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub account_email: Option<String>,
 
     /// Account organization if available
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub account_organization: Option<String>,
 
     /// Login method/plan info (e.g., "Claude Pro", "Claude Max")
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub login_method: Option<String>,
+
+    /// The data is older than the source normally provides (e.g. read from
+    /// local logs long after the last event); `updated_at` says how old
+    pub stale: bool,
 }
 
 impl UsageSnapshot {
     /// Create a new usage snapshot with just primary window
     pub fn new(primary: RateWindow) -> Self {
         Self {
-            primary,
-            secondary: None,
-            model_specific: None,
+            windows: vec![NamedRateWindow::primary(primary)],
             updated_at: Utc::now(),
             account_email: None,
             account_organization: None,
             login_method: None,
+            stale: false,
+        }
+    }
+
+    /// Create a new usage snapshot whose primary window has its own label/kind
+    pub fn from_window(primary: NamedRateWindow) -> Self {
+        Self {
+            windows: vec![primary],
+            ..Self::new(RateWindow::default())
         }
     }
 
     /// Builder pattern: set secondary window
-    pub fn with_secondary(mut self, secondary: RateWindow) -> Self {
-        self.secondary = Some(secondary);
-        self
+    pub fn with_secondary(self, secondary: RateWindow) -> Self {
+        self.with_window(NamedRateWindow::secondary(secondary))
     }
 
     /// Builder pattern: set model-specific window
-    pub fn with_model_specific(mut self, model_specific: RateWindow) -> Self {
-        self.model_specific = Some(model_specific);
+    pub fn with_model_specific(self, model_specific: RateWindow) -> Self {
+        self.with_window(NamedRateWindow::model_specific(model_specific))
+    }
+
+    /// Builder pattern: add a named window, replacing any window with the same id
+    pub fn with_window(mut self, window: NamedRateWindow) -> Self {
+        if let Some(existing) = self.windows.iter_mut().find(|w| w.id == window.id) {
+            *existing = window;
+        } else {
+            self.windows.push(window);
+        }
         self
     }
 
@@ -79,30 +100,124 @@ impl UsageSnapshot {
         self
     }
 
-    /// Get the most restrictive (highest used) rate window
-    pub fn most_restrictive(&self) -> &RateWindow {
-        let mut most = &self.primary;
+    /// Builder pattern: set when the data was captured at the source
+    pub fn with_updated_at(mut self, updated_at: DateTime<Utc>) -> Self {
+        self.updated_at = updated_at;
+        self
+    }
 
-        if let Some(ref secondary) = self.secondary {
-            if secondary.used_percent > most.used_percent {
-                most = secondary;
-            }
-        }
+    /// Builder pattern: mark the data as stale
+    pub fn with_stale(mut self, stale: bool) -> Self {
+        self.stale = stale;
+        self
+    }
 
-        if let Some(ref model_specific) = self.model_specific {
-            if model_specific.used_percent > most.used_percent {
-                most = model_specific;
+    /// Look up a window by id
+    pub fn window(&self, id: &str) -> Option<&NamedRateWindow> {
+        self.windows.iter().find(|w| w.id == id)
+    }
+
+    /// Primary rate window (usually session-based, e.g., 5-hour for Claude)
+    pub fn primary(&self) -> &RateWindow {
+        self.window(NamedRateWindow::PRIMARY_ID)
+            .or_else(|| self.windows.first())
+            .map(|w| &w.window)
+            .unwrap_or(&EMPTY_WINDOW)
+    }
+
+    /// Secondary rate window (usually weekly/monthly)
+    pub fn secondary(&self) -> Option<&RateWindow> {
+        self.window(NamedRateWindow::SECONDARY_ID).map(|w| &w.window)
+    }
+
+    /// Model-specific rate window (e.g., Opus quota for Claude), falling back
+    /// to the first window of kind `model`
+    pub fn model_specific(&self) -> Option<&RateWindow> {
+        self.window(NamedRateWindow::MODEL_SPECIFIC_ID)
+            .or_else(|| self.windows.iter().find(|w| w.kind == RateWindowKind::Model))
+            .map(|w| &w.window)
+    }
+
+    /// Get the most restrictive (highest used) rate window
+    pub fn most_restrictive(&self) -> &RateWindow {
+        let mut most = self.primary();
+        for named in &self.windows {
+            if named.window.used_percent > most.used_percent {
+                most = &named.window;
             }
         }
-
         most
     }
 
     /// Check if any rate window is exhausted
     pub fn any_exhausted(&self) -> bool {
-        self.primary.is_exhausted()
-            || self.secondary.as_ref().is_some_and(|w| w.is_exhausted())
-            || self.model_specific.as_ref().is_some_and(|w| w.is_exhausted())
+        self.windows.iter().any(|w| w.window.is_exhausted())
+    }
+}
+
+/// JSON shape of [`UsageSnapshot`]: the legacy fixed fields plus `windows`
+#[derive(Serialize, Deserialize)]
+struct UsageSnapshotRepr {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    primary: Option<RateWindow>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    secondary: Option<RateWindow>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    model_specific: Option<RateWindow>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    windows: Vec<NamedRateWindow>,
+    updated_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    account_email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    account_organization: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    login_method: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    stale: bool,
+}
+
+impl From<UsageSnapshot> for UsageSnapshotRepr {
+    fn from(snapshot: UsageSnapshot) -> Self {
+        Self {
+            primary: Some(snapshot.primary().clone()),
+            secondary: snapshot.secondary().cloned(),
+            model_specific: snapshot.model_specific().cloned(),
+            windows: snapshot.windows,
+            updated_at: snapshot.updated_at,
+            account_email: snapshot.account_email,
+            account_organization: snapshot.account_organization,
+            login_method: snapshot.login_method,
+            stale: snapshot.stale,
+        }
+    }
+}
+
+impl TryFrom<UsageSnapshotRepr> for UsageSnapshot {
+    type Error = String;
+
+    fn try_from(repr: UsageSnapshotRepr) -> Result<Self, Self::Error> {
+        // Snapshots written before `windows` existed only carry the fixed fields
+        let windows = if repr.windows.is_empty() {
+            let primary = repr
+                .primary
+                .ok_or_else(|| "usage snapshot has no rate windows".to_string())?;
+            let mut windows = vec![NamedRateWindow::primary(primary)];
+            windows.extend(repr.secondary.map(NamedRateWindow::secondary));
+            windows.extend(repr.model_specific.map(NamedRateWindow::model_specific));
+            windows
+        } else {
+            repr.windows
+        };
+
+        Ok(Self {
+            windows,
+            updated_at: repr.updated_at,
+            account_email: repr.account_email,
+            account_organization: repr.account_organization,
+            login_method: repr.login_method,
+            stale: repr.stale,
+        })
     }
 }
 
@@ -180,15 +295,26 @@ impl CostSnapshot {
     pub fn format_limit(&self) -> Option<String> {
         self.limit.map(|l| format_currency(l, &self.currency_code))
     }
-}
 
-/// Format a value as currency
-fn format_currency(value: f64, currency_code: &str) -> String {
-    match currency_code.to_uppercase().as_str() {
-        "USD" => format!("${:.2}", value),
-        "EUR" => format!("€{:.2}", value),
-        "GBP" => format!("£{:.2}", value),
-        _ => format!("{:.2} {}", value, currency_code),
+    /// Whether this snapshot is billed in a currency other than the reporting one
+    pub fn needs_conversion(&self, converter: &CurrencyConverter) -> bool {
+        !self.currency_code.trim().eq_ignore_ascii_case(converter.reporting_currency())
+    }
+
+    /// Copy of this snapshot converted into the reporting currency, or
+    /// `None` if no exchange rate is known for its currency
+    pub fn converted(&self, converter: &CurrencyConverter) -> Option<CostSnapshot> {
+        let used = converter.convert(self.used, &self.currency_code)?;
+        let limit = match self.limit {
+            Some(limit) => Some(converter.convert(limit, &self.currency_code)?),
+            None => None,
+        };
+        Some(CostSnapshot {
+            used,
+            limit,
+            currency_code: converter.reporting_currency().to_string(),
+            ..self.clone()
+        })
     }
 }
 
@@ -204,6 +330,10 @@ pub struct ProviderFetchResult {
 
     /// Label describing the data source (e.g., "oauth", "web", "cli")
     pub source_label: String,
+
+    /// Fetch strategies tried to produce this result, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attempts: Vec<ProviderFetchAttempt>,
 }
 
 impl ProviderFetchResult {
@@ -213,6 +343,7 @@ impl ProviderFetchResult {
             usage,
             cost: None,
             source_label: source_label.into(),
+            attempts: Vec::new(),
         }
     }
 
@@ -221,4 +352,122 @@ impl ProviderFetchResult {
         self.cost = Some(cost);
         self
     }
+
+    /// Builder pattern: set fetch attempts
+    pub fn with_attempts(mut self, attempts: Vec<ProviderFetchAttempt>) -> Self {
+        self.attempts = attempts;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot() -> UsageSnapshot {
+        UsageSnapshot::new(RateWindow::new(20.0))
+            .with_secondary(RateWindow::new(55.0))
+            .with_window(NamedRateWindow::new("opus", "Opus", RateWindowKind::Model, RateWindow::new(70.0)))
+            .with_window(NamedRateWindow::new("sonnet", "Sonnet", RateWindowKind::Model, RateWindow::new(30.0)))
+    }
+
+    #[test]
+    fn test_cost_snapshot_converted() {
+        let cost = CostSnapshot::new(72.0, "CNY", "Monthly").with_limit(144.0);
+        assert_eq!(cost.format_used(), "CN¥72.00");
+
+        let converter = CurrencyConverter::default();
+        assert!(cost.needs_conversion(&converter));
+        let usd = cost.converted(&converter).unwrap();
+        assert_eq!(usd.currency_code, "USD");
+        assert!((usd.used - 10.0).abs() < 1e-9);
+        assert!((usd.limit.unwrap() - 20.0).abs() < 1e-9);
+        assert_eq!(usd.period, "Monthly");
+
+        let unknown = CostSnapshot::new(1.0, "XYZ", "Monthly");
+        assert!(unknown.converted(&converter).is_none());
+    }
+
+    #[test]
+    fn test_legacy_accessors_map_onto_windows() {
+        let usage = snapshot();
+        assert_eq!(usage.windows.len(), 4);
+        assert_eq!(usage.primary().used_percent, 20.0);
+        assert_eq!(usage.secondary().unwrap().used_percent, 55.0);
+        // No explicit model_specific window: first model-kind window is used
+        assert_eq!(usage.model_specific().unwrap().used_percent, 70.0);
+    }
+
+    #[test]
+    fn test_with_window_replaces_same_id() {
+        let usage = snapshot().with_secondary(RateWindow::new(60.0));
+        assert_eq!(usage.windows.len(), 4);
+        assert_eq!(usage.secondary().unwrap().used_percent, 60.0);
+    }
+
+    #[test]
+    fn test_most_restrictive_iterates_all_windows() {
+        let usage = snapshot().with_window(NamedRateWindow::new(
+            "premium",
+            "Premium",
+            RateWindowKind::Credits,
+            RateWindow::new(95.0),
+        ));
+        assert_eq!(usage.most_restrictive().used_percent, 95.0);
+        assert!(!usage.any_exhausted());
+
+        let usage = usage.with_window(NamedRateWindow::new(
+            "sonnet",
+            "Sonnet",
+            RateWindowKind::Model,
+            RateWindow::new(100.0),
+        ));
+        assert!(usage.any_exhausted());
+    }
+
+    #[test]
+    fn test_json_keeps_legacy_fields() {
+        let json = serde_json::to_value(snapshot()).unwrap();
+        assert_eq!(json["primary"]["used_percent"], 20.0);
+        assert_eq!(json["secondary"]["used_percent"], 55.0);
+        assert_eq!(json["model_specific"]["used_percent"], 70.0);
+        assert_eq!(json["windows"].as_array().unwrap().len(), 4);
+        assert_eq!(json["windows"][2]["id"], "opus");
+        assert_eq!(json["windows"][2]["label"], "Opus");
+        assert_eq!(json["windows"][2]["kind"], "model");
+    }
+
+    #[test]
+    fn test_deserialize_round_trip() {
+        let json = serde_json::to_string(&snapshot()).unwrap();
+        let usage: UsageSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(usage.windows.len(), 4);
+        assert_eq!(usage.window("sonnet").unwrap().label, "Sonnet");
+        assert!(!usage.stale);
+
+        // `stale` is only written when set
+        assert!(!json.contains("stale"));
+        let stale = serde_json::to_string(&snapshot().with_stale(true)).unwrap();
+        assert!(serde_json::from_str::<UsageSnapshot>(&stale).unwrap().stale);
+    }
+
+    #[test]
+    fn test_deserialize_legacy_json() {
+        let json = r#"{
+            "primary": {"used_percent": 12.0, "window_minutes": 300},
+            "secondary": {"used_percent": 34.0},
+            "model_specific": {"used_percent": 56.0},
+            "updated_at": "2026-01-01T00:00:00Z",
+            "login_method": "Claude Max"
+        }"#;
+        let usage: UsageSnapshot = serde_json::from_str(json).unwrap();
+        assert_eq!(usage.windows.len(), 3);
+        assert_eq!(usage.primary().window_minutes, Some(300));
+        assert_eq!(usage.secondary().unwrap().used_percent, 34.0);
+        assert_eq!(usage.model_specific().unwrap().used_percent, 56.0);
+        assert_eq!(usage.login_method.as_deref(), Some("Claude Max"));
+
+        let missing = r#"{"updated_at": "2026-01-01T00:00:00Z"}"#;
+        assert!(serde_json::from_str::<UsageSnapshot>(missing).is_err());
+    }
 }
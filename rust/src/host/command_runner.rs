@@ -7,11 +7,11 @@
 #![allow(dead_code)]
 
 use std::collections::HashMap;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Read};
 use std::path::PathBuf;
 use std::process::{Child, Command, Stdio};
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 /// Command runner configuration
@@ -67,12 +67,22 @@ impl Default for CommandOptions {
 pub struct CommandResult {
     /// Captured output text
     pub text: String,
+    /// Captured standard error
+    pub stderr: String,
     /// Whether the command timed out
     pub timed_out: bool,
     /// Exit code if available
     pub exit_code: Option<i32>,
 }
 
+/// Output captured from a running process
+struct CapturedOutput {
+    text: String,
+    /// Stdout reached end-of-file (the process closed it or exited)
+    finished: bool,
+    timed_out: bool,
+}
+
 /// Command runner errors
 #[derive(Debug, Clone)]
 pub enum CommandError {
@@ -125,6 +135,31 @@ impl CommandRunner {
         which::which(binary).ok()
     }
 
+    /// A path as given, else the binary found in PATH
+    fn resolve(binary: &str) -> Result<PathBuf, CommandError> {
+        if std::path::Path::new(binary).exists() {
+            Ok(PathBuf::from(binary))
+        } else {
+            Self::which(binary).ok_or_else(|| CommandError::BinaryNotFound(binary.to_string()))
+        }
+    }
+
+    /// Start a command attached to this process's terminal (stdin, stdout and
+    /// stderr inherited) for interactive use; the caller waits on the child.
+    /// Only `extra_args` and `working_directory` of `options` apply.
+    pub fn spawn_attached(&self, binary: &str, options: &CommandOptions) -> Result<Child, CommandError> {
+        let mut cmd = Command::new(Self::resolve(binary)?);
+        cmd.args(&options.extra_args);
+        if let Some(dir) = &options.working_directory {
+            cmd.current_dir(dir);
+        }
+        cmd.envs(&self.env_additions);
+        cmd.stdin(Stdio::inherit());
+        cmd.stdout(Stdio::inherit());
+        cmd.stderr(Stdio::inherit());
+        cmd.spawn().map_err(|e| CommandError::LaunchFailed(e.to_string()))
+    }
+
     /// Run a command and capture output
     pub fn run(
         &self,
@@ -133,11 +168,7 @@ impl CommandRunner {
         options: &CommandOptions,
     ) -> Result<CommandResult, CommandError> {
         // Find the binary
-        let binary_path = if std::path::Path::new(binary).exists() {
-            PathBuf::from(binary)
-        } else {
-            Self::which(binary).ok_or_else(|| CommandError::BinaryNotFound(binary.to_string()))?
-        };
+        let binary_path = Self::resolve(binary)?;
 
         // Build the command
         let mut cmd = Command::new(&binary_path);
@@ -191,93 +222,132 @@ impl CommandRunner {
                 let _ = stdin.write_all(b"\n");
                 let _ = stdin.flush();
             }
+        } else {
+            // Close stdin so a process that reads it sees end-of-file
+            drop(child.stdin.take());
         }
 
+        // Drain stderr in the background so a chatty process never blocks
+        let stderr_buf = Arc::new(Mutex::new(Vec::new()));
+        let stderr_done = child.stderr.take().map(|mut stderr| {
+            let buf = Arc::clone(&stderr_buf);
+            let (tx, rx) = mpsc::channel::<()>();
+            std::thread::spawn(move || {
+                let mut chunk = [0u8; 4096];
+                while let Ok(n) = stderr.read(&mut chunk) {
+                    if n == 0 {
+                        break;
+                    }
+                    if let Ok(mut buf) = buf.lock() {
+                        buf.extend_from_slice(&chunk[..n]);
+                    }
+                }
+                let _ = tx.send(());
+            });
+            rx
+        });
+
         // Capture output
         let output = self.capture_output(&mut child, options, deadline)?;
 
-        // Wait for process to finish (with timeout)
-        let exit_code = match child.try_wait() {
-            Ok(Some(status)) => status.code(),
-            Ok(None) => {
-                // Process still running, kill it
-                let _ = child.kill();
-                let _ = child.wait();
-                None
+        // Once stdout closes, give the process until the deadline to exit
+        let mut status = child.try_wait().ok().flatten();
+        if output.finished {
+            while status.is_none() && Instant::now() < deadline {
+                std::thread::sleep(Duration::from_millis(10));
+                status = child.try_wait().ok().flatten();
             }
-            Err(_) => None,
-        };
+        }
+        if status.is_none() {
+            // Process still running, kill it
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+        let exit_code = status.and_then(|s| s.code());
+        let timed_out = output.timed_out || (output.finished && status.is_none());
 
-        let timed_out = Instant::now() >= deadline && output.is_empty();
+        // A surviving grandchild can hold stderr open, so only wait briefly
+        if let Some(done) = stderr_done {
+            let _ = done.recv_timeout(Duration::from_millis(200));
+        }
+        let stderr = stderr_buf
+            .lock()
+            .map(|buf| String::from_utf8_lossy(&buf).into_owned())
+            .unwrap_or_default();
 
         Ok(CommandResult {
-            text: output,
+            text: output.text,
+            stderr,
             timed_out,
             exit_code,
         })
     }
 
-    /// Capture output from a running process
+    /// Capture output from a running process until it closes stdout, a stop
+    /// condition matches, or the deadline passes
     fn capture_output(
         &self,
         child: &mut Child,
         options: &CommandOptions,
         deadline: Instant,
-    ) -> Result<String, CommandError> {
-        let stop_flag = Arc::new(AtomicBool::new(false));
+    ) -> Result<CapturedOutput, CommandError> {
         let mut output = String::new();
-        #[allow(unused_assignments)]
         let mut last_output_time = Instant::now();
 
-        // Read stdout in a separate thread
+        // Read stdout in a separate thread so the deadline is enforced even
+        // when the process stays silent
         let stdout = child
             .stdout
             .take()
             .ok_or_else(|| CommandError::IoError("Failed to capture stdout".to_string()))?;
 
-        let reader = BufReader::new(stdout);
-
-        for line_result in reader.lines() {
-            if stop_flag.load(Ordering::SeqCst) {
-                break;
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                if tx.send(line).is_err() {
+                    break;
+                }
             }
+        });
 
-            if Instant::now() >= deadline {
-                break;
+        loop {
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(CapturedOutput { text: output, finished: false, timed_out: true });
+            }
+            let mut wait = deadline - now;
+            if let Some(idle_timeout) = options.idle_timeout {
+                wait = wait.min(idle_timeout.saturating_sub(last_output_time.elapsed()));
             }
 
-            match line_result {
+            match rx.recv_timeout(wait) {
                 Ok(line) => {
                     output.push_str(&line);
                     output.push('\n');
                     last_output_time = Instant::now();
 
                     // Check stop conditions
-                    if options.stop_on_url && (line.contains("https://") || line.contains("http://")) {
+                    let stop = (options.stop_on_url && (line.contains("https://") || line.contains("http://")))
+                        || options.stop_on_substrings.iter().any(|s| line.contains(s));
+                    if stop {
                         std::thread::sleep(options.settle_after_stop);
-                        break;
-                    }
-
-                    for stop_substr in &options.stop_on_substrings {
-                        if line.contains(stop_substr) {
-                            std::thread::sleep(options.settle_after_stop);
-                            stop_flag.store(true, Ordering::SeqCst);
-                            break;
-                        }
+                        return Ok(CapturedOutput { text: output, finished: false, timed_out: false });
                     }
                 }
-                Err(_) => break,
-            }
-
-            // Check idle timeout
-            if let Some(idle_timeout) = options.idle_timeout {
-                if last_output_time.elapsed() > idle_timeout {
-                    break;
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Ok(CapturedOutput { text: output, finished: true, timed_out: false });
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    let idle = options
+                        .idle_timeout
+                        .is_some_and(|idle| last_output_time.elapsed() >= idle);
+                    if idle {
+                        return Ok(CapturedOutput { text: output, finished: false, timed_out: false });
+                    }
                 }
             }
         }
-
-        Ok(output)
     }
 
     /// Run a command asynchronously
@@ -389,6 +459,53 @@ mod tests {
         assert_eq!(runner.env_additions.get("BAZ"), Some(&"qux".to_string()));
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_run_captures_output_and_exit_code() {
+        let options = CommandOptions {
+            extra_args: vec!["-c".to_string(), "echo out; echo err >&2; exit 3".to_string()],
+            initial_delay: Duration::ZERO,
+            ..CommandOptions::default()
+        };
+        let result = CommandRunner::new().run("sh", None, &options).unwrap();
+        assert_eq!(result.text, "out\n");
+        assert_eq!(result.stderr, "err\n");
+        assert_eq!(result.exit_code, Some(3));
+        assert!(!result.timed_out);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_times_out_silent_process() {
+        let options = CommandOptions {
+            extra_args: vec!["-c".to_string(), "sleep 5".to_string()],
+            timeout: Duration::from_millis(200),
+            ..CommandOptions::default()
+        };
+        let start = Instant::now();
+        let result = CommandRunner::new().run("sh", None, &options).unwrap();
+        assert!(result.timed_out);
+        assert_eq!(result.exit_code, None);
+        assert!(start.elapsed() < Duration::from_secs(3));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_spawn_attached() {
+        let options = CommandOptions {
+            extra_args: vec!["-c".to_string(), "exit $CODE".to_string()],
+            ..CommandOptions::default()
+        };
+        let mut child = CommandRunner::new()
+            .with_env("CODE", "4")
+            .spawn_attached("sh", &options)
+            .unwrap();
+        assert_eq!(child.wait().unwrap().code(), Some(4));
+
+        let missing = CommandRunner::new().spawn_attached("codexbar-no-such-binary", &options);
+        assert!(matches!(missing, Err(CommandError::BinaryNotFound(_))));
+    }
+
     #[test]
     fn test_error_display() {
         let err = CommandError::BinaryNotFound("codex".to_string());
@@ -1,7 +1,26 @@
 //! Logging configuration using tracing
 
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+
 use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 
+/// Whether log output to stderr is suppressed (while a full-screen view owns the terminal)
+static STDERR_MUTED: AtomicBool = AtomicBool::new(false);
+
+/// Suppress or resume log output to stderr
+pub fn set_stderr_muted(muted: bool) {
+    STDERR_MUTED.store(muted, Ordering::Relaxed);
+}
+
+fn stderr_writer() -> Box<dyn Write> {
+    if STDERR_MUTED.load(Ordering::Relaxed) {
+        Box::new(std::io::sink())
+    } else {
+        Box::new(std::io::stderr())
+    }
+}
+
 /// Initialize the logging system
 pub fn init(verbose: bool, json: bool) -> anyhow::Result<()> {
     let filter = if verbose {
@@ -13,12 +32,12 @@ pub fn init(verbose: bool, json: bool) -> anyhow::Result<()> {
     if json {
         tracing_subscriber::registry()
             .with(filter)
-            .with(fmt::layer().json().with_writer(std::io::stderr))
+            .with(fmt::layer().json().with_writer(stderr_writer))
             .init();
     } else {
         tracing_subscriber::registry()
             .with(filter)
-            .with(fmt::layer().with_writer(std::io::stderr))
+            .with(fmt::layer().with_writer(stderr_writer))
             .init();
     }
 
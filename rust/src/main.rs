@@ -11,7 +11,6 @@
 mod browser;
 mod cli;
 mod core;
-mod cost_scanner;
 mod host;
 mod logging;
 mod login;
@@ -93,6 +92,9 @@ fn run() -> i32 {
         return exit_codes::UNEXPECTED_FAILURE;
     }
 
+    // Apply proxy, CA and user-agent settings to every HTTP client
+    core::set_http_client_config(settings::Settings::load().http_client_config());
+
     // Create tokio runtime for async commands
     let rt = match tokio::runtime::Runtime::new() {
         Ok(rt) => rt,
@@ -125,6 +127,83 @@ fn run() -> i32 {
                 }
             })
         }
+        Some(Commands::Budget(args)) => {
+            rt.block_on(async {
+                match cli::budget::run(args).await {
+                    Ok(()) => exit_codes::SUCCESS,
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        categorize_error(&e)
+                    }
+                }
+            })
+        }
+        Some(Commands::History(args)) => {
+            rt.block_on(async {
+                match cli::history::run(args).await {
+                    Ok(()) => exit_codes::SUCCESS,
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        categorize_error(&e)
+                    }
+                }
+            })
+        }
+        Some(Commands::Watch(args)) => {
+            rt.block_on(async {
+                match cli::watch::run(args).await {
+                    Ok(()) => exit_codes::SUCCESS,
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        categorize_error(&e)
+                    }
+                }
+            })
+        }
+        Some(Commands::Serve(args)) => {
+            rt.block_on(async {
+                match cli::serve::run(args).await {
+                    Ok(()) => exit_codes::SUCCESS,
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        categorize_error(&e)
+                    }
+                }
+            })
+        }
+        Some(Commands::Mcp(args)) => {
+            rt.block_on(async {
+                match cli::mcp::run(args).await {
+                    Ok(()) => exit_codes::SUCCESS,
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        categorize_error(&e)
+                    }
+                }
+            })
+        }
+        Some(Commands::Exec(args)) => {
+            rt.block_on(async {
+                match cli::exec::run(args).await {
+                    Ok(code) => code,
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        categorize_error(&e)
+                    }
+                }
+            })
+        }
+        Some(Commands::Pick(args)) => {
+            rt.block_on(async {
+                match cli::pick::run(args).await {
+                    Ok(()) => exit_codes::SUCCESS,
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        categorize_error(&e)
+                    }
+                }
+            })
+        }
         Some(Commands::Menubar) => {
             // Hide the console window for GUI mode
             #[cfg(windows)]
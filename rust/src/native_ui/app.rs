@@ -2,24 +2,26 @@
 //! Clean, spacious design with rich visual hierarchy
 
 use eframe::egui::{self, Color32, FontData, FontDefinitions, FontFamily, Rect, RichText, Rounding, Stroke, Vec2};
+use std::collections::HashMap;
 use std::sync::mpsc::{self, Receiver};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use super::charts::{
-    ChartPoint, CostHistoryChart, CreditsHistoryChart, ServiceUsage, UsageBreakdownChart,
+    CacheEfficiencyChart, CacheEfficiencyPoint, ChartPoint, CostHistoryChart, CreditsHistoryChart, ServiceUsage, UsageBreakdownChart,
     UsageBreakdownPoint,
 };
 use super::preferences::PreferencesWindow;
 use super::provider_icons::ProviderIconCache;
 use super::theme::{provider_color, status_color, FontSize, Radius, Spacing, Theme};
 use crate::core::{
-    FetchContext, OpenAIDashboardCacheStore, PersonalInfoRedactor, Provider, ProviderId,
-    ProviderFetchResult, RateWindow,
+    budget_spend_since, evaluate_budgets, format_currency, BudgetScope, BudgetStatus, CostAnalytics, CostUsageDayRange, CostWatcher, CurrencyConverter, FetchContext, JsonlScanner, local_log_providers, local_log_scanner, OpenAIDashboardCacheStore, PersonalInfoRedactor, ProviderFetchAttempt,
+    NamedRateWindow, ProviderId, ProviderFetchResult, RateWindow, RateWindowKind, RefreshScheduler,
+    SpendHistory, UsageHistoryStore,
 };
 use crate::core::{TokenAccountStore, TokenAccountSupport};
-use crate::cost_scanner::get_daily_cost_history;
 use crate::login::LoginPhase;
+use crate::notifications::NotificationManager;
 use crate::providers::*;
 use crate::settings::{ApiKeys, ManualCookies, Settings};
 use crate::browser::cookies::get_cookie_header;
@@ -52,17 +54,18 @@ fn restore_main_window() {
 #[cfg(not(windows))]
 fn restore_main_window() {}
 
+/// Days of local log cost shown in the cost chart
+const COST_HISTORY_DAYS: u32 = 30;
+
 #[derive(Clone, Debug)]
 pub struct ProviderData {
     pub name: String,
     pub display_name: String,
     pub account: Option<String>,  // Account email for display
     pub session_percent: Option<f64>,
-    pub session_reset: Option<String>,
     pub weekly_percent: Option<f64>,
-    pub weekly_reset: Option<String>,
     pub model_percent: Option<f64>,
-    pub model_name: Option<String>,
+    pub windows: Vec<MetricWindow>,
     pub plan: Option<String>,
     pub error: Option<String>,
     pub dashboard_url: Option<String>,
@@ -74,8 +77,17 @@ pub struct ProviderData {
     pub status_level: StatusLevel,
     pub status_description: Option<String>,
     pub cost_history: Vec<(String, f64)>,
+    /// Currency of `cost_history` values (the reporting currency once converted)
+    pub cost_currency: String,
+    /// Daily cache efficiency over the cost history days (costs in `cost_currency`)
+    pub cache_history: Vec<CacheEfficiencyPoint>,
     pub credits_history: Vec<(String, f64)>,
     pub usage_breakdown: Vec<UsageBreakdownPoint>,
+    pub fetch_attempts: Vec<ProviderFetchAttempt>,
+    /// Local time of the data when the snapshot is stale (e.g. read from local logs)
+    pub stale_since: Option<String>,
+    /// This provider's budgets followed by the total budgets
+    pub budgets: Vec<BudgetStatus>,
 }
 
 impl ProviderData {
@@ -85,11 +97,9 @@ impl ProviderData {
             display_name: id.display_name().to_string(),
             account: None,
             session_percent: None,
-            session_reset: None,
             weekly_percent: None,
-            weekly_reset: None,
             model_percent: None,
-            model_name: None,
+            windows: Vec::new(),
             plan: None,
             error: None,
             dashboard_url: None,
@@ -101,14 +111,38 @@ impl ProviderData {
             status_level: StatusLevel::Unknown,
             status_description: None,
             cost_history: Vec::new(),
+            cost_currency: "USD".to_string(),
+            cache_history: Vec::new(),
             credits_history: Vec::new(),
             usage_breakdown: Vec::new(),
+            fetch_attempts: Vec::new(),
+            stale_since: None,
+            budgets: Vec::new(),
         }
     }
 
-    fn from_result(id: ProviderId, result: &ProviderFetchResult, metadata: &crate::core::ProviderMetadata, reset_time_relative: bool) -> Self {
+    fn from_result(
+        id: ProviderId,
+        result: &ProviderFetchResult,
+        metadata: &crate::core::ProviderMetadata,
+        reset_time_relative: bool,
+        converter: &CurrencyConverter,
+    ) -> Self {
         let snapshot = &result.usage;
-        let (pace_percent, pace_lasts) = calculate_pace(&snapshot.primary);
+        let (pace_percent, pace_lasts) = calculate_pace(snapshot.primary());
+        let windows: Vec<MetricWindow> = snapshot
+            .windows
+            .iter()
+            .map(|w| MetricWindow {
+                id: w.id.clone(),
+                label: w.label.clone(),
+                percent: w.window.used_percent,
+                reset: w.window.resets_at.map(|t| format_reset_time(t, reset_time_relative)),
+            })
+            .collect();
+        let model_window = snapshot
+            .window(NamedRateWindow::MODEL_SPECIFIC_ID)
+            .or_else(|| snapshot.windows.iter().find(|w| w.kind == RateWindowKind::Model));
 
         let (cost_used, credits_remaining, credits_percent) = if let Some(ref cost) = result.cost {
             if cost.period == "Credits" {
@@ -122,11 +156,12 @@ impl ProviderData {
                 };
                 (None, Some(remaining), Some(percent))
             } else {
-                (
-                    Some(cost.format_used()),
-                    None,
-                    None,
-                )
+                // Show the amount in the reporting currency alongside the billed one
+                let used = match cost.converted(converter).filter(|_| cost.needs_conversion(converter)) {
+                    Some(converted) => format!("{} (≈ {})", cost.format_used(), converted.format_used()),
+                    None => cost.format_used(),
+                };
+                (Some(used), None, None)
             }
         } else {
             (None, None, None)
@@ -136,12 +171,10 @@ impl ProviderData {
             name: id.cli_name().to_string(),
             display_name: id.display_name().to_string(),
             account: snapshot.account_email.clone(),  // Account email if available
-            session_percent: Some(snapshot.primary.used_percent),
-            session_reset: snapshot.primary.resets_at.map(|t| format_reset_time(t, reset_time_relative)),
-            weekly_percent: snapshot.secondary.as_ref().map(|s| s.used_percent),
-            weekly_reset: snapshot.secondary.as_ref().and_then(|s| s.resets_at.map(|t| format_reset_time(t, reset_time_relative))),
-            model_percent: snapshot.model_specific.as_ref().map(|m| m.used_percent),
-            model_name: snapshot.model_specific.as_ref().and_then(|m| m.reset_description.clone()),
+            session_percent: Some(snapshot.primary().used_percent),
+            weekly_percent: snapshot.secondary().map(|s| s.used_percent),
+            model_percent: model_window.map(|m| m.window.used_percent),
+            windows,
             plan: snapshot.login_method.clone(),
             error: None,
             dashboard_url: metadata.dashboard_url.map(|s| s.to_string()),
@@ -153,8 +186,15 @@ impl ProviderData {
             status_level: StatusLevel::Unknown,
             status_description: None,
             cost_history: Vec::new(),
+            cost_currency: "USD".to_string(),
+            cache_history: Vec::new(),
             credits_history: Vec::new(),
             usage_breakdown: Vec::new(),
+            fetch_attempts: result.attempts.clone(),
+            stale_since: snapshot
+                .stale
+                .then(|| snapshot.updated_at.with_timezone(&chrono::Local).format("%b %-d %H:%M").to_string()),
+            budgets: Vec::new(),
         }
     }
 
@@ -164,11 +204,9 @@ impl ProviderData {
             display_name: id.display_name().to_string(),
             account: None,
             session_percent: None,
-            session_reset: None,
             weekly_percent: None,
-            weekly_reset: None,
             model_percent: None,
-            model_name: None,
+            windows: Vec::new(),
             plan: None,
             error: Some(error),
             dashboard_url: None,
@@ -180,8 +218,13 @@ impl ProviderData {
             status_level: StatusLevel::Unknown,
             status_description: None,
             cost_history: Vec::new(),
+            cost_currency: "USD".to_string(),
+            cache_history: Vec::new(),
             credits_history: Vec::new(),
             usage_breakdown: Vec::new(),
+            fetch_attempts: Vec::new(),
+            stale_since: None,
+            budgets: Vec::new(),
         }
     }
 
@@ -202,25 +245,30 @@ impl ProviderData {
                 self.credits_percent.unwrap_or_else(|| self.session_percent.unwrap_or(0.0))
             }
             crate::settings::MetricPreference::Average => {
-                // Average of all available metrics
-                let mut sum = 0.0;
-                let mut count = 0;
-                if let Some(v) = self.session_percent { sum += v; count += 1; }
-                if let Some(v) = self.weekly_percent { sum += v; count += 1; }
-                if let Some(v) = self.model_percent { sum += v; count += 1; }
-                if count > 0 { sum / count as f64 } else { 0.0 }
+                // Average of all rate windows
+                if self.windows.is_empty() {
+                    0.0
+                } else {
+                    self.windows.iter().map(|w| w.percent).sum::<f64>() / self.windows.len() as f64
+                }
             }
             crate::settings::MetricPreference::Automatic => {
-                // Automatic: prefer the highest available metric (most concerning)
-                let session = self.session_percent.unwrap_or(0.0);
-                let weekly = self.weekly_percent.unwrap_or(0.0);
-                let model = self.model_percent.unwrap_or(0.0);
-                session.max(weekly).max(model)
+                // Automatic: prefer the highest rate window (most concerning)
+                self.windows.iter().map(|w| w.percent).fold(0.0, f64::max)
             }
         }
     }
 }
 
+/// A rate window as shown in the detail card
+#[derive(Clone, Debug)]
+pub struct MetricWindow {
+    pub id: String,
+    pub label: String,
+    pub percent: f64,
+    pub reset: Option<String>,
+}
+
 fn format_reset_time(reset: chrono::DateTime<chrono::Utc>, relative: bool) -> String {
     if relative {
         let now = chrono::Utc::now();
@@ -351,11 +399,121 @@ fn random_surprise_delay() -> Duration {
     Duration::from_secs(secs)
 }
 
+/// Replace a provider's entry in the grid, matched by name
+fn store_provider_data(state: &mut SharedState, mut data: ProviderData) {
+    if let Some(slot) = state.providers.iter_mut().find(|p| p.name == data.name) {
+        // Budgets are re-evaluated once the whole refresh is done
+        data.budgets = std::mem::take(&mut slot.budgets);
+        *slot = data;
+    }
+}
+
+/// Show each provider its own budgets followed by the total budgets
+fn apply_budgets(state: &mut SharedState, budgets: &[BudgetStatus]) {
+    for slot in &mut state.providers {
+        let id = ProviderId::from_cli_name(&slot.name);
+        let mut own: Vec<BudgetStatus> = budgets
+            .iter()
+            .filter(|b| id.is_some_and(|id| b.scope == BudgetScope::Provider(id)))
+            .cloned()
+            .collect();
+        own.extend(budgets.iter().filter(|b| b.scope == BudgetScope::Total).cloned());
+        slot.budgets = own;
+    }
+}
+
+/// Set the cost chart from daily USD costs, in the reporting currency when it has a rate
+fn apply_cost_history(data: &mut ProviderData, history: Vec<(String, f64)>, converter: &CurrencyConverter) {
+    if converter.rate(converter.reporting_currency()).is_some() {
+        data.cost_history = history
+            .into_iter()
+            .filter_map(|(date, usd)| converter.convert(usd, "USD").map(|v| (date, v)))
+            .collect();
+        data.cost_currency = converter.reporting_currency().to_string();
+    } else {
+        data.cost_history = history;
+        data.cost_currency = "USD".to_string();
+    }
+}
+
+/// Set the cache efficiency chart from the cost history days, in the cost chart's currency
+fn apply_cache_history(
+    data: &mut ProviderData,
+    analytics: &CostAnalytics,
+    range: &CostUsageDayRange,
+    converter: &CurrencyConverter,
+) {
+    let money = |usd: f64| {
+        if data.cost_currency == "USD" {
+            usd
+        } else {
+            converter.convert(usd, "USD").unwrap_or(usd)
+        }
+    };
+    let (Some(mut date), Some(until)) = (
+        CostUsageDayRange::parse_day_key(&range.since_key),
+        CostUsageDayRange::parse_day_key(&range.until_key),
+    ) else {
+        return;
+    };
+    let mut points = Vec::new();
+    while date <= until {
+        let key = CostUsageDayRange::day_key(date);
+        let day = analytics.by_day.iter().find(|(day, _)| *day == key).map(|(_, e)| e);
+        points.push(CacheEfficiencyPoint {
+            hit_ratio: day.and_then(|e| e.cache_hit_ratio()),
+            cache_read_cost: day.map_or(0.0, |e| money(e.cache_read_usd)),
+            cache_write_cost: day.map_or(0.0, |e| money(e.cache_write_usd)),
+            saved: day.map_or(0.0, |e| money(e.savings_usd())),
+            output_input_ratio: day.and_then(|e| e.output_input_ratio()),
+            date: key,
+        });
+        date += chrono::Duration::days(1);
+    }
+    data.cache_history = points;
+}
+
+/// Keep the local-log cost charts current by watching the session logs
+fn watch_local_costs(state: Arc<Mutex<SharedState>>, repaint_ctx: egui::Context) {
+    let range = CostUsageDayRange::last_days(COST_HISTORY_DAYS);
+    let mut watcher = match CostWatcher::new(&local_log_providers(), &range) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            tracing::warn!("Live cost updates unavailable: {}", e);
+            return;
+        }
+    };
+
+    let mut updated = watcher.providers();
+    if updated.is_empty() {
+        return;
+    }
+    loop {
+        if !updated.is_empty() {
+            let histories: Vec<(ProviderId, Vec<(String, f64)>)> = updated
+                .iter()
+                .map(|&id| (id, watcher.daily_costs(id, COST_HISTORY_DAYS)))
+                .collect();
+            if let Ok(mut guard) = state.lock() {
+                let s = &mut *guard;
+                for (id, history) in histories {
+                    if let Some(slot) = s.providers.iter_mut().find(|p| p.name == id.cli_name()) {
+                        apply_cost_history(slot, history.clone(), &s.cost_converter);
+                    }
+                    s.live_costs.insert(id, history);
+                }
+            }
+            repaint_ctx.request_repaint();
+        }
+        updated = watcher.wait(Duration::from_secs(60));
+    }
+}
+
 struct SharedState {
     providers: Vec<ProviderData>,
     selected_provider_idx: usize,  // Index of selected provider in grid
-    last_refresh: Instant,
     is_refreshing: bool,
+    scheduler: RefreshScheduler,
     loading_pattern: LoadingPattern,
     loading_phase: f64,
     surprise_animation: Option<SurpriseAnimation>,
@@ -368,6 +526,12 @@ struct SharedState {
     login_provider: Option<String>,
     login_phase: LoginPhase,
     login_message: Option<String>,
+    /// Daily USD costs kept current by the log watcher
+    live_costs: HashMap<ProviderId, Vec<(String, f64)>>,
+    /// Converter of the last refresh, for live cost updates
+    cost_converter: CurrencyConverter,
+    /// Budget alerts already shown
+    notifications: NotificationManager,
 }
 
 pub struct CodexBarApp {
@@ -409,11 +573,17 @@ impl CodexBarApp {
             .map(|&id| ProviderData::placeholder(id))
             .collect();
 
+        let mut scheduler = RefreshScheduler::new();
+        scheduler.configure(
+            enabled_ids.iter().map(|&id| (id, settings.refresh_policy(id))),
+            chrono::Utc::now(),
+        );
+
         let state = Arc::new(Mutex::new(SharedState {
             providers: placeholders,
             selected_provider_idx: 0,  // Select first provider by default
-            last_refresh: Instant::now() - Duration::from_secs(999),
             is_refreshing: false,
+            scheduler,
             loading_pattern: LoadingPattern::random(),
             loading_phase: 0.0,
             surprise_animation: None,
@@ -426,8 +596,18 @@ impl CodexBarApp {
             login_provider: None,
             login_phase: LoginPhase::Idle,
             login_message: None,
+            live_costs: HashMap::new(),
+            cost_converter: settings.currency_converter(),
+            notifications: NotificationManager::new(),
         }));
 
+        // Update the cost charts as the agents write their logs
+        {
+            let state = Arc::clone(&state);
+            let repaint_ctx = cc.egui_ctx.clone();
+            std::thread::spawn(move || watch_local_costs(state, repaint_ctx));
+        }
+
         // Initialize system tray based on settings
         let tray_manager = match UnifiedTrayManager::new(&settings) {
             Ok(tm) => Some(tm),
@@ -643,27 +823,68 @@ impl CodexBarApp {
         self.anchor_main_window_to_pointer = false;
     }
 
+    /// Apply per-provider refresh policies from settings to the scheduler
+    fn configure_scheduler(&self) {
+        if let Ok(mut s) = self.state.lock() {
+            s.scheduler.configure(
+                self.settings
+                    .get_enabled_provider_ids()
+                    .into_iter()
+                    .map(|id| (id, self.settings.refresh_policy(id))),
+                chrono::Utc::now(),
+            );
+        }
+    }
+
+    /// Refresh every enabled provider that is not paused
     fn refresh_providers(&self) {
+        self.configure_scheduler();
+        let ids = self
+            .state
+            .lock()
+            .map(|s| s.scheduler.active())
+            .unwrap_or_default();
+        self.refresh_provider_ids(ids, true);
+    }
+
+    /// Refresh the given providers. A manual refresh shows placeholders while
+    /// loading; a scheduled one keeps the previous data on screen.
+    fn refresh_provider_ids(&self, ids: Vec<ProviderId>, manual: bool) {
         let state = Arc::clone(&self.state);
         let enabled_ids = self.settings.get_enabled_provider_ids();
         let manual_cookies = ManualCookies::load();
         let api_keys = ApiKeys::load();
         let reset_time_relative = self.settings.reset_time_relative;
+        let converter = self.settings.currency_converter();
+        let settings = self.settings.clone();
+        let base_urls: HashMap<ProviderId, String> = enabled_ids
+            .iter()
+            .filter_map(|&id| self.settings.base_url_override(id).map(|url| (id, url)))
+            .collect();
         // Load token accounts for account switching support
         let token_accounts = TokenAccountStore::new().load().unwrap_or_default();
 
         std::thread::spawn(move || {
             if let Ok(mut s) = state.lock() {
                 s.is_refreshing = true;
+                s.cost_converter = converter.clone();
                 s.loading_pattern = LoadingPattern::random();
                 s.loading_phase = 0.0;
+                let mut previous = std::mem::take(&mut s.providers);
                 s.providers = enabled_ids
                     .iter()
-                    .map(|&id| ProviderData::placeholder(id))
+                    .map(|&id| {
+                        let existing = previous.iter().position(|p| p.name == id.cli_name());
+                        match existing {
+                            Some(pos) if !(manual && ids.contains(&id)) => previous.swap_remove(pos),
+                            _ => ProviderData::placeholder(id),
+                        }
+                    })
                     .collect();
                 if s.selected_provider_idx >= s.providers.len() {
                     s.selected_provider_idx = 0;
                 }
+                s.scheduler.mark_started(&ids);
             }
 
             let rt = match tokio::runtime::Runtime::new() {
@@ -676,6 +897,23 @@ impl CodexBarApp {
                     return;
                 }
             };
+            // Every successful snapshot is appended to the local usage history,
+            // which is kept within the retention setting
+            let history = match UsageHistoryStore::open_default() {
+                Ok(store) => {
+                    if let Some(cutoff) = settings.history_cutoff(chrono::Utc::now()) {
+                        if let Err(e) = store.prune_before(cutoff) {
+                            tracing::warn!("Failed to prune usage history: {}", e);
+                        }
+                    }
+                    Some(Arc::new(Mutex::new(store)))
+                }
+                Err(e) => {
+                    tracing::warn!("Usage history unavailable: {}", e);
+                    None
+                }
+            };
+
             rt.block_on(async {
                 // Clear any stale OAuth env vars at the start of refresh
                 // This ensures account switches take effect immediately
@@ -688,10 +926,9 @@ impl CodexBarApp {
                     std::env::remove_var(key);
                 }
 
-                let handles: Vec<_> = enabled_ids
+                let handles: Vec<_> = ids
                     .iter()
-                    .enumerate()
-                    .map(|(idx, &id)| {
+                    .map(|&id| {
                         // Check for active token account first
                         let active_token = token_accounts.get(&id)
                             .and_then(|data| data.active_account())
@@ -738,11 +975,21 @@ impl CodexBarApp {
                         let ctx = FetchContext {
                             manual_cookie_header: cookie_header,
                             api_key,
+                            base_url: base_urls.get(&id).cloned(),
                             ..FetchContext::default()
                         };
                         let state = Arc::clone(&state);
+                        let history = history.clone();
+                        let converter = converter.clone();
+                        let provider = REGISTRY.get(id);
                         tokio::spawn(async move {
-                            let provider = create_provider(id);
+                            let Some(provider) = provider else {
+                                if let Ok(mut s) = state.lock() {
+                                    s.scheduler.record_failure(id, false, chrono::Utc::now());
+                                    store_provider_data(&mut s, ProviderData::from_error(id, "Provider not registered".to_string()));
+                                }
+                                return;
+                            };
                             let metadata = provider.metadata().clone();
                             let provider_name = id.cli_name().to_string();
 
@@ -750,7 +997,7 @@ impl CodexBarApp {
                                 async {
                                     tokio::time::timeout(
                                         std::time::Duration::from_secs(5),
-                                        provider.fetch_usage(&ctx)
+                                        provider.fetch_outcome(&ctx)
                                     ).await
                                 },
                                 async {
@@ -761,10 +1008,34 @@ impl CodexBarApp {
                                 }
                             );
 
+                            // For the scheduler: the usage on success, otherwise
+                            // whether the failure is worth backing off for
+                            let schedule: Result<crate::core::UsageSnapshot, bool>;
                             let mut result = match usage_result {
-                                Ok(Ok(result)) => ProviderData::from_result(id, &result, &metadata, reset_time_relative),
-                                Ok(Err(e)) => ProviderData::from_error(id, e.to_string()),
-                                Err(_) => ProviderData::from_error(id, "Timeout".to_string()),
+                                Ok(outcome) => {
+                                    let attempts = outcome.attempts.clone();
+                                    match outcome.into_result() {
+                                        Ok(result) => {
+                                            schedule = Ok(result.usage.clone());
+                                            if let Some(store) = history.as_ref().and_then(|h| h.lock().ok()) {
+                                                if let Err(e) = store.record(id, &result) {
+                                                    tracing::warn!("Failed to record usage history for {}: {}", id.cli_name(), e);
+                                                }
+                                            }
+                                            ProviderData::from_result(id, &result, &metadata, reset_time_relative, &converter)
+                                        }
+                                        Err(e) => {
+                                            schedule = Err(e.is_transient());
+                                            let mut data = ProviderData::from_error(id, e.to_string());
+                                            data.fetch_attempts = attempts;
+                                            data
+                                        }
+                                    }
+                                }
+                                Err(_) => {
+                                    schedule = Err(true);
+                                    ProviderData::from_error(id, "Timeout".to_string())
+                                }
                             };
 
                             if let Ok(Some(status)) = status_result {
@@ -776,15 +1047,30 @@ impl CodexBarApp {
                                 result.usage_breakdown = load_usage_breakdown_points(id, result.account.as_deref());
                             }
 
-                            let provider_name_lower = provider_name.to_lowercase();
-                            if provider_name_lower == "codex" || provider_name_lower == "claude" {
-                                result.cost_history = get_daily_cost_history(&provider_name_lower, 30);
+                            if local_log_scanner(id).is_some() {
+                                // The log watcher keeps these current; scan only without it
+                                let live = state.lock().ok().and_then(|s| s.live_costs.get(&id).cloned());
+                                let history = live.unwrap_or_else(|| JsonlScanner::daily_cost_history(id, COST_HISTORY_DAYS));
+                                apply_cost_history(&mut result, history, &converter);
+                                // The refresh above (or the watcher) keeps the cache on disk current
+                                let range = CostUsageDayRange::last_days(COST_HISTORY_DAYS);
+                                let analytics = CostAnalytics::from_cache(&JsonlScanner::load_cache(id, None), id, &range);
+                                apply_cache_history(&mut result, &analytics, &range, &converter);
+                            }
+
+                            if let Some(store) = history.as_ref().and_then(|h| h.lock().ok()) {
+                                if let Err(e) = store.record_fetch_outcome(id, schedule.is_ok(), chrono::Utc::now()) {
+                                    tracing::warn!("Failed to record fetch outcome for {}: {}", id.cli_name(), e);
+                                }
                             }
 
                             if let Ok(mut s) = state.lock() {
-                                if idx < s.providers.len() {
-                                    s.providers[idx] = result;
+                                let now = chrono::Utc::now();
+                                match schedule {
+                                    Ok(usage) => s.scheduler.record_success(id, &usage, now),
+                                    Err(transient) => s.scheduler.record_failure(id, transient, now),
                                 }
+                                store_provider_data(&mut s, result);
                             }
                         })
                     })
@@ -795,8 +1081,28 @@ impl CodexBarApp {
                 }
             });
 
+            // Budgets cover every enabled provider, not only the refreshed ones
+            if !settings.budgets.is_empty() {
+                let today = chrono::Utc::now().date_naive();
+                let store = history.as_ref().and_then(|h| h.lock().ok());
+                let spend = SpendHistory::collect(
+                    &settings.budgets.spend_providers(&enabled_ids),
+                    store.as_deref(),
+                    &converter,
+                    budget_spend_since(today),
+                    today,
+                );
+                drop(store);
+                let budgets = evaluate_budgets(&settings.budgets, &spend, today);
+                if let Ok(mut s) = state.lock() {
+                    for status in &budgets {
+                        s.notifications.check_budget(status, &settings.budgets.alert_thresholds, &settings);
+                    }
+                    apply_budgets(&mut s, &budgets);
+                }
+            }
+
             if let Ok(mut s) = state.lock() {
-                s.last_refresh = Instant::now();
                 s.is_refreshing = false;
             }
         });
@@ -855,32 +1161,6 @@ fn work_area_rect(ctx: &egui::Context) -> Option<Rect> {
     })
 }
 
-fn create_provider(id: ProviderId) -> Box<dyn Provider> {
-    match id {
-        ProviderId::Claude => Box::new(ClaudeProvider::new()),
-        ProviderId::Codex => Box::new(CodexProvider::new()),
-        ProviderId::Cursor => Box::new(CursorProvider::new()),
-        ProviderId::Gemini => Box::new(GeminiProvider::new()),
-        ProviderId::Copilot => Box::new(CopilotProvider::new()),
-        ProviderId::Antigravity => Box::new(AntigravityProvider::new()),
-        ProviderId::Factory => Box::new(FactoryProvider::new()),
-        ProviderId::Zai => Box::new(ZaiProvider::new()),
-        ProviderId::Kiro => Box::new(KiroProvider::new()),
-        ProviderId::VertexAI => Box::new(VertexAIProvider::new()),
-        ProviderId::Augment => Box::new(AugmentProvider::new()),
-        ProviderId::MiniMax => Box::new(MiniMaxProvider::new()),
-        ProviderId::OpenCode => Box::new(OpenCodeProvider::new()),
-        ProviderId::Kimi => Box::new(KimiProvider::new()),
-        ProviderId::KimiK2 => Box::new(KimiK2Provider::new()),
-        ProviderId::Amp => Box::new(AmpProvider::new()),
-        ProviderId::Warp => Box::new(WarpProvider::new()),
-        ProviderId::Ollama => Box::new(OllamaProvider::new()),
-        ProviderId::OpenRouter => Box::new(OpenRouterProvider::new()),
-        ProviderId::Synthetic => Box::new(SyntheticProvider::new()),
-        ProviderId::JetBrains => Box::new(JetBrainsProvider::new()),
-    }
-}
-
 impl eframe::App for CodexBarApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         if self.pending_main_window_layout {
@@ -979,18 +1259,13 @@ impl eframe::App for CodexBarApp {
             }
         }
 
-        // Auto-refresh check
-        let should_refresh = {
-            if self.settings.refresh_interval_secs == 0 {
-                false
-            } else if let Ok(state) = self.state.lock() {
-                !state.is_refreshing && state.last_refresh.elapsed() > Duration::from_secs(self.settings.refresh_interval_secs)
-            } else {
-                false
-            }
+        // Auto-refresh check: each provider is refreshed on its own schedule
+        let due_providers = match self.state.lock() {
+            Ok(state) if !state.is_refreshing => state.scheduler.due(chrono::Utc::now()),
+            _ => Vec::new(),
         };
-        if should_refresh {
-            self.refresh_providers();
+        if !due_providers.is_empty() {
+            self.refresh_provider_ids(due_providers, false);
         }
 
         // Get state
@@ -1069,6 +1344,7 @@ impl eframe::App for CodexBarApp {
             } else {
                 // Respect menu_bar_display_mode setting
                 // Use per-provider metric preferences from settings
+                let today_key = CostUsageDayRange::day_key(chrono::Utc::now().date_naive());
                 let provider_usages: Vec<ProviderUsage> = providers
                     .iter()
                     .filter(|p| p.session_percent.is_some())
@@ -1085,21 +1361,24 @@ impl eframe::App for CodexBarApp {
                         } else {
                             preferred_percent // Already used %
                         };
-                        // Weekly percent is always usage-based (not credits)
-                        let weekly_percent = p.weekly_percent.unwrap_or(used_percent);
-                        ProviderUsage {
-                            name: p.display_name.clone(),
-                            session_percent: used_percent, // Always use "used %" for tray severity
-                            weekly_percent,
-                        }
+                        // Weekly, model and credit windows are always usage-based
+                        ProviderUsage::from_windows(
+                            p.display_name.clone(),
+                            used_percent,
+                            p.windows.iter().map(|w| (w.id.as_str(), w.percent)),
+                            p.cost_history
+                                .last()
+                                .filter(|(day, _)| *day == today_key)
+                                .map(|(_, cost)| format_currency(*cost, &p.cost_currency)),
+                        )
                     })
                     .collect();
 
                 match self.settings.menu_bar_display_mode.as_str() {
                     "minimal" => {
-                        // Minimal: show only the highest-usage provider's session bar
+                        // Minimal: show only the provider closest to a limit
                         if let Some(p) = provider_usages.iter().max_by(|a, b| {
-                            a.session_percent.partial_cmp(&b.session_percent).unwrap_or(std::cmp::Ordering::Equal)
+                            a.max_percent.partial_cmp(&b.max_percent).unwrap_or(std::cmp::Ordering::Equal)
                         }) {
                             tray.update_usage(p.session_percent, p.weekly_percent, &p.name);
                         }
@@ -1651,6 +1930,8 @@ impl eframe::App for CodexBarApp {
             if let Err(e) = self.settings.save() {
                 tracing::error!("Failed to save settings: {}", e);
             }
+            crate::core::set_http_client_config(self.settings.http_client_config());
+            self.configure_scheduler();
             if previous_enabled_provider_ids != self.settings.get_enabled_provider_ids() {
                 refresh_requested = true;
             }
@@ -1741,6 +2022,12 @@ fn draw_provider_detail_card(
                                 .size(FontSize::XS) // 11px footnote
                                 .color(Theme::RED),
                         );
+                    } else if let Some(since) = &provider.stale_since {
+                        ui.label(
+                            RichText::new(format!("Stale · data from {}", since))
+                                .size(FontSize::XS)
+                                .color(Theme::YELLOW),
+                        );
                     } else {
                         ui.label(
                             RichText::new("Updated just now")
@@ -1782,12 +2069,13 @@ fn draw_provider_detail_card(
         // ═══════════════════════════════════════════════════════════════════
         // DIVIDER - only if we have metrics
         // ═══════════════════════════════════════════════════════════════════
-        let has_metrics = provider.session_percent.is_some() || provider.weekly_percent.is_some();
+        let has_metrics = !provider.windows.is_empty();
         let has_credits = provider.credits_remaining.is_some();
         let has_cost = provider.cost_used.is_some();
         let has_usage_breakdown = !provider.usage_breakdown.is_empty();
+        let has_budgets = !provider.budgets.is_empty();
 
-        if has_metrics || provider.error.is_some() || has_credits || has_cost || has_usage_breakdown {
+        if has_metrics || provider.error.is_some() || has_credits || has_cost || has_usage_breakdown || has_budgets {
             ui.add_space(4.0);
             draw_horizontal_separator(ui, 0.0);
         }
@@ -1798,53 +2086,23 @@ fn draw_provider_detail_card(
         if has_metrics {
             ui.add_space(10.0);
 
-            // Session metric (primary) - no pace indicator for session
-            if let Some(session_pct) = provider.session_percent {
-                draw_metric_row(
-                    ui,
-                    "Session",
-                    session_pct,
-                    show_as_used,
-                    provider.session_reset.as_deref(),
-                    brand_color,
-                    content_width,
-                    None,  // No pace for session
-                    false,
-                );
-            }
-
-            // Weekly metric (secondary) - includes pace indicator
-            if let Some(weekly_pct) = provider.weekly_percent {
-                ui.add_space(12.0);
-
-                draw_metric_row(
-                    ui,
-                    "Weekly",
-                    weekly_pct,
-                    show_as_used,
-                    provider.weekly_reset.as_deref(),
-                    brand_color,
-                    content_width,
-                    provider.pace_percent,
-                    provider.pace_lasts_to_reset,
-                );
-            }
-
-            // Model-specific metric (tertiary) - no pace indicator
-            if let Some(model_pct) = provider.model_percent {
-                ui.add_space(12.0);
+            for (i, window) in provider.windows.iter().enumerate() {
+                if i > 0 {
+                    ui.add_space(12.0);
+                }
 
-                let model_label = provider.model_name.as_deref().unwrap_or("Model");
+                // Pace indicator only on the weekly (secondary) window
+                let is_secondary = window.id == NamedRateWindow::SECONDARY_ID;
                 draw_metric_row(
                     ui,
-                    model_label,
-                    model_pct,
+                    &window.label,
+                    window.percent,
                     show_as_used,
-                    None,
+                    window.reset.as_deref(),
                     brand_color,
                     content_width,
-                    None,  // No pace for model
-                    false,
+                    if is_secondary { provider.pace_percent } else { None },
+                    is_secondary && provider.pace_lasts_to_reset,
                 );
             }
 
@@ -1862,6 +2120,38 @@ fn draw_provider_detail_card(
             ui.add_space(2.0);
         }
 
+        // ═══════════════════════════════════════════════════════════════════
+        // FETCH ATTEMPTS - which sources were tried, shown when any failed
+        // ═══════════════════════════════════════════════════════════════════
+        let any_attempt_failed = provider.fetch_attempts.iter().any(|a| !a.succeeded());
+        if any_attempt_failed {
+            ui.add_space(4.0);
+            for attempt in &provider.fetch_attempts {
+                let (symbol, detail, color) = if !attempt.was_available {
+                    ("–", "unavailable".to_string(), Theme::TEXT_MUTED)
+                } else if let Some(ref error) = attempt.error_description {
+                    ("✗", error.clone(), Theme::RED)
+                } else {
+                    ("✓", "ok".to_string(), Theme::GREEN)
+                };
+                ui.horizontal(|ui| {
+                    ui.add_space(16.0);
+                    ui.label(RichText::new(symbol).size(FontSize::XS).color(color));
+                    ui.label(
+                        RichText::new(format!("{} ({})", attempt.strategy_id, attempt.kind))
+                            .size(FontSize::XS)
+                            .color(Theme::TEXT_PRIMARY),
+                    );
+                    ui.label(
+                        RichText::new(detail)
+                            .size(FontSize::XS)
+                            .color(Theme::TEXT_SECONDARY),
+                    );
+                });
+            }
+            ui.add_space(2.0);
+        }
+
         // ═══════════════════════════════════════════════════════════════════
         // CREDITS SECTION - macOS CreditsBarContent style
         // ═══════════════════════════════════════════════════════════════════
@@ -1981,12 +2271,12 @@ fn draw_provider_detail_card(
                 let today_cost: f64 = provider.cost_history.last().map(|(_, c)| *c).unwrap_or(0.0);
 
                 ui.label(
-                    RichText::new(format!("Today: ${:.2}", today_cost))
+                    RichText::new(format!("Today: {}", format_currency(today_cost, &provider.cost_currency)))
                         .size(FontSize::XS)
                         .color(Theme::TEXT_PRIMARY)
                 );
                 ui.label(
-                    RichText::new(format!("Last 30 days: ${:.2}", total_30d))
+                    RichText::new(format!("Last 30 days: {}", format_currency(total_30d, &provider.cost_currency)))
                         .size(FontSize::XS)
                         .color(Theme::TEXT_PRIMARY)
                 );
@@ -2005,11 +2295,53 @@ fn draw_provider_detail_card(
                     .iter()
                     .map(|(date, cost)| ChartPoint::new(date.clone(), *cost))
                     .collect();
-                let mut chart = CostHistoryChart::new(chart_points, brand_color);
+                let mut chart = CostHistoryChart::new(chart_points, brand_color)
+                    .with_currency(&provider.cost_currency);
+                chart.show(ui);
+            }
+
+            // Cache hit ratio per day
+            if provider.cache_history.iter().any(|p| p.hit_ratio.is_some()) {
+                ui.add_space(8.0);
+                let total_read: f64 = provider.cache_history.iter().map(|p| p.cache_read_cost).sum();
+                let total_write: f64 = provider.cache_history.iter().map(|p| p.cache_write_cost).sum();
+                let total_saved: f64 = provider.cache_history.iter().map(|p| p.saved).sum();
+                ui.label(
+                    RichText::new(format!(
+                        "Cache: saved {} (reads {}, writes {})",
+                        format_currency(total_saved, &provider.cost_currency),
+                        format_currency(total_read, &provider.cost_currency),
+                        format_currency(total_write, &provider.cost_currency)
+                    ))
+                    .size(FontSize::XS)
+                    .color(Theme::TEXT_PRIMARY)
+                );
+                ui.add_space(4.0);
+                let mut chart = CacheEfficiencyChart::new(provider.cache_history.clone(), brand_color)
+                    .with_currency(&provider.cost_currency);
                 chart.show(ui);
             }
         }
 
+        // ═══════════════════════════════════════════════════════════════════
+        // BUDGETS SECTION - spend against the configured budgets
+        // ═══════════════════════════════════════════════════════════════════
+        if has_budgets {
+            if has_metrics || has_credits || has_cost || has_usage_breakdown {
+                draw_horizontal_separator(ui, 0.0);
+            }
+            ui.add_space(10.0);
+
+            for (i, budget) in provider.budgets.iter().enumerate() {
+                if i > 0 {
+                    ui.add_space(12.0);
+                }
+                draw_budget_row(ui, budget, brand_color);
+            }
+
+            ui.add_space(2.0);
+        }
+
         // ═══════════════════════════════════════════════════════════════════
         // ACTION LINKS SECTION - macOS style vertical list
         // ═══════════════════════════════════════════════════════════════════
@@ -2018,7 +2350,7 @@ fn draw_provider_detail_card(
         let has_error = provider.error.is_some();
 
         if has_dashboard || has_status_issue || has_error {
-            if has_metrics || has_credits || has_cost || has_usage_breakdown {
+            if has_metrics || has_credits || has_cost || has_usage_breakdown || has_budgets {
                 draw_horizontal_separator(ui, 0.0);
             }
             ui.add_space(6.0);
@@ -2216,6 +2548,78 @@ fn draw_metric_row(
     });
 }
 
+/// Draw a budget: spend bar with a forecast marker, spend (left) and
+/// month/week-end forecast (right)
+fn draw_budget_row(ui: &mut egui::Ui, budget: &BudgetStatus, color: Color32) {
+    let title = match budget.scope {
+        BudgetScope::Total => format!("{} budget · {}", budget.period.display_name(), budget.scope.display_name()),
+        BudgetScope::Provider(_) => format!("{} budget", budget.period.display_name()),
+    };
+    ui.label(
+        RichText::new(title)
+            .size(FontSize::BASE)
+            .color(Theme::TEXT_PRIMARY)
+            .strong(),
+    );
+
+    ui.add_space(6.0);
+
+    let bar_width = ui.available_width();
+    let bar_height = 8.0;
+    let (rect, _) = ui.allocate_exact_size(Vec2::new(bar_width, bar_height), egui::Sense::hover());
+    ui.painter().rect_filled(rect, Rounding::same(4.0), Theme::progress_track());
+
+    let percent = budget.percent();
+    let fill_color = if percent >= 100.0 { Theme::RED } else { color };
+    let fill_w = rect.width() * (percent as f32 / 100.0).clamp(0.0, 1.0);
+    if fill_w > 0.0 {
+        let fill_rect = Rect::from_min_size(rect.min, Vec2::new(fill_w, bar_height));
+        ui.painter().rect_filled(fill_rect, Rounding::same(4.0), fill_color);
+    }
+
+    // Forecast marker - where spend is heading by the end of the period
+    let forecast_x = rect.min.x + rect.width() * (budget.forecast_percent() as f32 / 100.0).clamp(0.0, 1.0);
+    let marker_width = 2.0;
+    let marker_rect = Rect::from_min_size(
+        egui::pos2(forecast_x - marker_width / 2.0, rect.min.y),
+        Vec2::new(marker_width, bar_height),
+    );
+    ui.painter().rect_filled(
+        marker_rect,
+        Rounding::same(1.0),
+        Color32::from_rgba_unmultiplied(255, 255, 255, 180),
+    );
+
+    ui.add_space(6.0);
+
+    ui.horizontal(|ui| {
+        ui.label(
+            RichText::new(format!(
+                "{} of {} ({:.0}%)",
+                format_currency(budget.spent_usd, "USD"),
+                format_currency(budget.limit_usd, "USD"),
+                percent
+            ))
+            .size(FontSize::XS)
+            .color(Theme::TEXT_PRIMARY),
+        );
+
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            let forecast = budget.forecast.high_usd();
+            let forecast_color = if forecast > budget.limit_usd {
+                Theme::YELLOW
+            } else {
+                Theme::TEXT_SECONDARY
+            };
+            ui.label(
+                RichText::new(format!("Forecast {}", format_currency(forecast, "USD")))
+                    .size(FontSize::XS)
+                    .color(forecast_color),
+            );
+        });
+    });
+}
+
 /// Draw a menu item button - macOS style compact
 fn draw_menu_item(ui: &mut egui::Ui, icon: &str, label: &str) -> bool {
     let available_width = ui.available_width();
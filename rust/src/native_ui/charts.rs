@@ -1,4 +1,4 @@
-//! Charts module for cost and credits history visualization
+//! Charts module for cost, credits and cache efficiency history visualization
 //!
 //! Provides bar charts similar to the macOS SwiftUI Charts
 
@@ -6,6 +6,8 @@
 
 use eframe::egui::{self, Color32, RichText, Rounding, Vec2};
 
+use crate::core::format_currency;
+
 /// Model cost breakdown for a single day
 #[derive(Clone, Debug)]
 pub struct ModelBreakdown {
@@ -17,7 +19,7 @@ pub struct ModelBreakdown {
 #[derive(Clone, Debug)]
 pub struct ChartPoint {
     pub date: String,      // "2025-01-15" format
-    pub value: f64,        // Cost in USD or credits used
+    pub value: f64,        // Cost (see CostHistoryChart::with_currency) or credits used
     pub tokens: Option<i64>, // Optional token count
     pub model_breakdowns: Option<Vec<ModelBreakdown>>, // Optional model-level breakdown
 }
@@ -52,6 +54,7 @@ pub struct CostHistoryChart {
     selected_index: Option<usize>,
     bar_color: Color32,
     total_cost: Option<f64>,
+    currency_code: String,
     animation_start: Option<std::time::Instant>,
     is_animated: bool,
 }
@@ -69,11 +72,18 @@ impl CostHistoryChart {
             selected_index: None,
             bar_color,
             total_cost,
+            currency_code: "USD".to_string(),
             animation_start: None,
             is_animated: false,
         }
     }
 
+    /// Builder: currency the point values are in (defaults to USD)
+    pub fn with_currency(mut self, currency_code: &str) -> Self {
+        self.currency_code = currency_code.to_string();
+        self
+    }
+
     /// Start the entrance animation
     pub fn animate_entrance(&mut self) {
         self.animation_start = Some(std::time::Instant::now());
@@ -237,7 +247,7 @@ impl CostHistoryChart {
         if let Some(idx) = self.selected_index {
             if let Some(point) = self.points.get(idx) {
                 let date_display = format_date_display(&point.date);
-                let cost_display = format!("${:.2}", point.value);
+                let cost_display = format_currency(point.value, &self.currency_code);
 
                 let detail = if let Some(tokens) = point.tokens {
                     format!("{}: {} · {} tokens", date_display, cost_display, format_tokens(tokens))
@@ -395,6 +405,131 @@ impl CreditsHistoryChart {
     }
 }
 
+/// Cache efficiency for a single day
+#[derive(Clone, Debug)]
+pub struct CacheEfficiencyPoint {
+    pub date: String,              // "2025-01-15" format
+    pub hit_ratio: Option<f64>,    // Share of input served from the cache (0-1)
+    pub cache_read_cost: f64,      // Amounts in the chart's currency
+    pub cache_write_cost: f64,
+    pub saved: f64,                // Versus uncached pricing (negative = cache cost more)
+    pub output_input_ratio: Option<f64>,
+}
+
+/// Cache hit ratio chart widget (one bar per day on a 0-100% scale)
+pub struct CacheEfficiencyChart {
+    points: Vec<CacheEfficiencyPoint>,
+    selected_index: Option<usize>,
+    bar_color: Color32,
+    currency_code: String,
+}
+
+impl CacheEfficiencyChart {
+    pub fn new(points: Vec<CacheEfficiencyPoint>, bar_color: Color32) -> Self {
+        Self {
+            points,
+            selected_index: None,
+            bar_color,
+            currency_code: "USD".to_string(),
+        }
+    }
+
+    /// Builder: currency the cost values are in (defaults to USD)
+    pub fn with_currency(mut self, currency_code: &str) -> Self {
+        self.currency_code = currency_code.to_string();
+        self
+    }
+
+    /// Render the chart
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        if self.points.iter().all(|p| p.hit_ratio.is_none()) {
+            ui.label(
+                RichText::new("No cache usage recorded yet")
+                    .size(11.0)
+                    .color(Color32::GRAY),
+            );
+            return;
+        }
+
+        // Chart area
+        let chart_height = 30.0;
+        let available_width = ui.available_width();
+        let bar_width = (available_width / self.points.len() as f32) * 0.8;
+        let bar_spacing = (available_width / self.points.len() as f32) * 0.2;
+
+        let (response, painter) = ui.allocate_painter(
+            Vec2::new(available_width, chart_height),
+            egui::Sense::hover(),
+        );
+
+        let rect = response.rect;
+
+        // Faint track per day so the fixed 100% scale is visible
+        for (i, point) in self.points.iter().enumerate() {
+            let x = rect.left() + (i as f32 * (bar_width + bar_spacing)) + bar_spacing / 2.0;
+            let track_rect = egui::Rect::from_min_size(
+                egui::pos2(x, rect.top()),
+                Vec2::new(bar_width, chart_height),
+            );
+            painter.rect_filled(track_rect, Rounding::same(2.0), Color32::from_rgba_unmultiplied(255, 255, 255, 12));
+
+            let is_hovered = response.hover_pos().is_some_and(|pos| {
+                pos.x >= x && pos.x <= x + bar_width
+            });
+            if is_hovered {
+                self.selected_index = Some(i);
+            }
+
+            let Some(ratio) = point.hit_ratio else {
+                continue;
+            };
+            // Minimum 1px so days with usage but no cache hits still show
+            let bar_height = (ratio as f32 * chart_height).max(1.0);
+            let bar_rect = egui::Rect::from_min_size(
+                egui::pos2(x, rect.bottom() - bar_height),
+                Vec2::new(bar_width, bar_height),
+            );
+            let color = if is_hovered {
+                self.bar_color.gamma_multiply(1.2)
+            } else {
+                self.bar_color
+            };
+            painter.rect_filled(bar_rect, Rounding::same(2.0), color);
+        }
+
+        // Reset selection if not hovering
+        if !response.hovered() {
+            self.selected_index = None;
+        }
+
+        // Compact: Only show detail on hover
+        if let Some(point) = self.selected_index.and_then(|idx| self.points.get(idx)) {
+            let date_display = format_date_display(&point.date);
+            let detail = match point.hit_ratio {
+                Some(ratio) => format!(
+                    "{}: {:.0}% from cache · saved {}",
+                    date_display,
+                    ratio * 100.0,
+                    format_currency(point.saved, &self.currency_code)
+                ),
+                None => format!("{}: no usage", date_display),
+            };
+            ui.label(RichText::new(detail).size(10.0).color(Color32::GRAY));
+            if point.hit_ratio.is_some() {
+                let mut costs = format!(
+                    "Reads {} · writes {}",
+                    format_currency(point.cache_read_cost, &self.currency_code),
+                    format_currency(point.cache_write_cost, &self.currency_code)
+                );
+                if let Some(ratio) = point.output_input_ratio {
+                    costs.push_str(&format!(" · out/in {:.3}", ratio));
+                }
+                ui.label(RichText::new(costs).size(10.0).color(Color32::GRAY));
+            }
+        }
+    }
+}
+
 /// Format date from "2025-01-15" to "Jan 15"
 fn format_date_display(date_key: &str) -> String {
     let parts: Vec<&str> = date_key.split('-').collect();
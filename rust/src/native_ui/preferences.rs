@@ -12,7 +12,7 @@ use eframe::egui::{self, Color32, RichText, Rounding, Stroke, Vec2, Rect};
 use super::provider_icons::ProviderIconCache;
 use super::theme::{provider_color, provider_icon, FontSize, Radius, Spacing, Theme};
 use crate::settings::{ApiKeys, ManualCookies, Settings, TrayIconMode, get_api_key_providers};
-use crate::core::{PersonalInfoRedactor, ProviderId, WidgetSnapshot, WidgetSnapshotStore};
+use crate::core::{CurrencyConverter, PersonalInfoRedactor, ProviderId, WidgetSnapshot, WidgetSnapshotStore, CURRENCIES};
 use crate::core::{TokenAccountStore, TokenAccount, TokenAccountSupport, ProviderAccountData};
 use crate::browser::detection::{BrowserDetector, BrowserType};
 use crate::browser::cookies::get_cookie_header_from_browser;
@@ -1912,7 +1912,7 @@ fn render_providers_tab_macos(ui: &mut egui::Ui, available_height: f32, shared_s
         None
     };
 
-    let providers = ProviderId::all();
+    let providers = ProviderId::all_with_custom();
     let selected = selected_provider.unwrap_or(providers[0]);
 
     // Create a horizontal layout with two fixed regions
@@ -1934,7 +1934,7 @@ fn render_providers_tab_macos(ui: &mut egui::Ui, available_height: f32, shared_s
                         .max_height(available_height - Spacing::LG * 2.0)
                         .auto_shrink([false, false])
                         .show(ui, |ui| {
-                            for provider_id in providers {
+                            for provider_id in &providers {
                                 let is_selected = *provider_id == selected;
                                 let is_enabled = if let Ok(state) = shared_state.lock() {
                                     state.settings.enabled_providers.contains(provider_id.cli_name())
@@ -2524,6 +2524,68 @@ fn render_provider_detail_panel(ui: &mut egui::Ui, provider_id: ProviderId, shar
             .color(Theme::TEXT_MUTED)
     );
 
+    ui.add_space(Spacing::MD);
+
+    // Refresh interval dropdown (None = global interval)
+    let (interval_override, global_interval, mut paused) = if let Ok(state) = shared_state.lock() {
+        (
+            state.settings.provider_refresh_intervals.get(provider_id.cli_name()).copied(),
+            state.settings.refresh_interval_secs,
+            state.settings.is_provider_paused(provider_id),
+        )
+    } else {
+        (None, 300, false)
+    };
+    let interval_label = |secs: u64| match secs {
+        0 => "Manual".to_string(),
+        s if s % 60 == 0 => format!("{} min", s / 60),
+        s => format!("{} sec", s),
+    };
+
+    ui.horizontal(|ui| {
+        ui.label(
+            RichText::new("Refresh interval")
+                .size(FontSize::SM)
+                .color(Theme::TEXT_SECONDARY)
+        );
+
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            let intervals: [Option<u64>; 7] = [None, Some(60), Some(300), Some(900), Some(1800), Some(3600), Some(0)];
+            let selected = match interval_override {
+                Some(secs) => interval_label(secs),
+                None => format!("Default ({})", interval_label(global_interval)),
+            };
+            egui::ComboBox::from_id_salt(format!("refresh_{}", provider_id.cli_name()))
+                .selected_text(selected)
+                .width(120.0)
+                .show_ui(ui, |ui| {
+                    for option in intervals {
+                        let label = option.map_or_else(|| "Default".to_string(), interval_label);
+                        if ui.selectable_label(interval_override == option, label).clicked() {
+                            if let Ok(mut state) = shared_state.lock() {
+                                state.settings.set_provider_refresh_interval(provider_id, option);
+                                state.settings_changed = true;
+                            }
+                        }
+                    }
+                });
+        });
+    });
+
+    ui.add_space(Spacing::SM);
+
+    if setting_toggle(
+        ui,
+        "Pause refreshing",
+        "Skip this provider in automatic and manual refreshes",
+        &mut paused,
+    ) {
+        if let Ok(mut state) = shared_state.lock() {
+            state.settings.set_provider_paused(provider_id, paused);
+            state.settings_changed = true;
+        }
+    }
+
     // ═══════════════════════════════════════════════════════════
     // ACCOUNTS SECTION - Token account switching (only for supported providers)
     // ═══════════════════════════════════════════════════════════
@@ -3211,6 +3273,126 @@ fn render_display_tab(ui: &mut egui::Ui, shared_state: &Arc<Mutex<PreferencesSha
             }
         }
     });
+
+    ui.add_space(Spacing::LG);
+
+    section_header(ui, "Currency");
+
+    let (reporting, overrides) = if let Ok(state) = shared_state.lock() {
+        (state.settings.reporting_currency.clone(), state.settings.exchange_rates.clone())
+    } else {
+        return;
+    };
+    let converter = CurrencyConverter::new(&reporting, &overrides);
+
+    settings_card(ui, |ui| {
+        ui.horizontal(|ui| {
+            ui.vertical(|ui| {
+                ui.label(
+                    RichText::new("Reporting currency")
+                        .size(FontSize::MD)
+                        .color(Theme::TEXT_PRIMARY)
+                );
+                ui.label(
+                    RichText::new("Costs from all providers are converted to this for totals and charts")
+                        .size(FontSize::SM)
+                        .color(Theme::TEXT_MUTED)
+                );
+            });
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                // Style combobox to match theme
+                ui.style_mut().visuals.widgets.inactive.bg_fill = Theme::BG_TERTIARY;
+                ui.style_mut().visuals.widgets.inactive.weak_bg_fill = Theme::BG_TERTIARY;
+                ui.style_mut().visuals.widgets.hovered.bg_fill = Theme::CARD_BG_HOVER;
+                ui.style_mut().visuals.widgets.active.bg_fill = Theme::CARD_BG;
+                ui.style_mut().visuals.widgets.inactive.rounding = Rounding::same(Radius::SM);
+                ui.style_mut().visuals.widgets.hovered.rounding = Rounding::same(Radius::SM);
+                ui.style_mut().visuals.widgets.active.rounding = Rounding::same(Radius::SM);
+
+                egui::ComboBox::from_id_salt("reporting_currency")
+                    .selected_text(converter.reporting_currency())
+                    .width(90.0)
+                    .show_ui(ui, |ui| {
+                        for currency in CURRENCIES {
+                            let selected = currency.code == converter.reporting_currency();
+                            if ui.selectable_label(selected, currency.code).clicked() {
+                                if let Ok(mut state) = shared_state.lock() {
+                                    state.settings.reporting_currency = currency.code.to_string();
+                                    state.settings_changed = true;
+                                }
+                            }
+                        }
+                    });
+            });
+        });
+
+        setting_divider(ui);
+
+        ui.label(
+            RichText::new("Exchange rates (units per 1 USD)")
+                .size(FontSize::MD)
+                .color(Theme::TEXT_PRIMARY)
+        );
+        ui.add_space(Spacing::XS);
+
+        // Built-in currencies first, then any custom codes from settings.json
+        let mut codes: Vec<String> = CURRENCIES
+            .iter()
+            .filter(|c| c.code != "USD")
+            .map(|c| c.code.to_string())
+            .collect();
+        let mut custom: Vec<String> = overrides
+            .keys()
+            .map(|c| c.to_uppercase())
+            .filter(|c| c != "USD" && !codes.contains(c))
+            .collect();
+        custom.sort();
+        codes.extend(custom);
+
+        egui::Grid::new("exchange_rates")
+            .num_columns(3)
+            .spacing([Spacing::MD, Spacing::XS])
+            .show(ui, |ui| {
+                for code in &codes {
+                    ui.label(
+                        RichText::new(code)
+                            .size(FontSize::SM)
+                            .color(Theme::TEXT_SECONDARY)
+                    );
+
+                    let mut rate = converter.rate(code).unwrap_or(1.0);
+                    let speed = rate * 0.001;
+                    let drag = egui::DragValue::new(&mut rate)
+                        .speed(speed)
+                        .range(0.0001..=1_000_000.0)
+                        .max_decimals(4);
+                    if ui.add(drag).changed() {
+                        if let Ok(mut state) = shared_state.lock() {
+                            state.settings.set_exchange_rate(code, Some(rate));
+                            state.settings_changed = true;
+                        }
+                    }
+
+                    let overridden = overrides.keys().any(|k| k.eq_ignore_ascii_case(code));
+                    if overridden {
+                        if ui.small_button("Reset").clicked() {
+                            if let Ok(mut state) = shared_state.lock() {
+                                state.settings.set_exchange_rate(code, None);
+                                state.settings_changed = true;
+                            }
+                        }
+                    } else {
+                        ui.label(
+                            RichText::new("default")
+                                .size(FontSize::XS)
+                                .color(Theme::TEXT_MUTED)
+                        );
+                    }
+                    ui.end_row();
+                }
+            });
+    });
 }
 
 /// Render API Keys tab for viewport
@@ -3748,6 +3930,92 @@ fn render_advanced_tab(ui: &mut egui::Ui, shared_state: &Arc<Mutex<PreferencesSh
                     });
             });
         });
+
+        setting_divider(ui);
+
+        let mut adaptive = shared_state.lock().map(|s| s.settings.adaptive_refresh).unwrap_or(true);
+        if setting_toggle(
+            ui,
+            "Adaptive refresh",
+            "Poll faster near a limit or reset; back off after network errors",
+            &mut adaptive,
+        ) {
+            if let Ok(mut state) = shared_state.lock() {
+                state.settings.adaptive_refresh = adaptive;
+                state.settings_changed = true;
+            }
+        }
+    });
+
+    ui.add_space(Spacing::LG);
+    section_header(ui, "Network");
+
+    settings_card(ui, |ui| {
+        let (mut proxy, mut no_proxy, mut ca_bundle, mut user_agent, mut timeout) =
+            if let Ok(state) = shared_state.lock() {
+                let settings = &state.settings;
+                (
+                    settings.http_proxy.clone().unwrap_or_default(),
+                    settings.http_no_proxy.clone().unwrap_or_default(),
+                    settings
+                        .http_ca_bundle
+                        .as_ref()
+                        .map(|p| p.display().to_string())
+                        .unwrap_or_default(),
+                    settings.http_user_agent.clone().unwrap_or_default(),
+                    settings.http_timeout_secs.unwrap_or(0),
+                )
+            } else {
+                Default::default()
+            };
+
+        let mut changed = false;
+        let fields: [(&str, &mut String, &str); 4] = [
+            ("Proxy", &mut proxy, "http://proxy:8080 or socks5h://host:1080"),
+            ("No proxy", &mut no_proxy, "localhost,.corp.example,10.0.0.0/8"),
+            ("CA bundle", &mut ca_bundle, "C:\\certs\\corp-root.pem"),
+            ("User-agent", &mut user_agent, crate::core::DEFAULT_USER_AGENT),
+        ];
+        for (label, value, hint) in fields {
+            ui.label(RichText::new(label).size(FontSize::SM).color(Theme::TEXT_SECONDARY));
+            let edit = egui::TextEdit::singleline(value)
+                .desired_width(ui.available_width())
+                .hint_text(hint);
+            changed |= ui.add(edit).changed();
+            ui.add_space(Spacing::XS);
+        }
+
+        ui.horizontal(|ui| {
+            ui.label(
+                RichText::new("Request timeout")
+                    .size(FontSize::MD)
+                    .color(Theme::TEXT_PRIMARY)
+            );
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                let drag = egui::DragValue::new(&mut timeout)
+                    .range(0..=300)
+                    .custom_formatter(|v, _| if v == 0.0 { "default".to_string() } else { format!("{} s", v) });
+                changed |= ui.add(drag).changed();
+            });
+        });
+
+        ui.label(
+            RichText::new("Applies to providers, status checks, updates and token refresh. Run `codexbar config validate` to test.")
+                .size(FontSize::XS)
+                .color(Theme::TEXT_MUTED)
+        );
+
+        if changed {
+            let non_empty = |value: String| Some(value.trim().to_string()).filter(|v| !v.is_empty());
+            if let Ok(mut state) = shared_state.lock() {
+                state.settings.http_proxy = non_empty(proxy);
+                state.settings.http_no_proxy = non_empty(no_proxy);
+                state.settings.http_ca_bundle = non_empty(ca_bundle).map(std::path::PathBuf::from);
+                state.settings.http_user_agent = non_empty(user_agent);
+                state.settings.http_timeout_secs = Some(timeout).filter(|secs| *secs > 0);
+                state.settings_changed = true;
+            }
+        }
     });
 }
 
@@ -3873,9 +4141,9 @@ fn render_about_tab(ui: &mut egui::Ui) {
 fn render_providers_tab(ui: &mut egui::Ui, _available_height: f32, shared_state: &Arc<Mutex<PreferencesSharedState>>) {
     section_header(ui, "Enabled Providers");
 
-    let providers = ProviderId::all();
+    let providers = ProviderId::all_with_custom();
 
-    for provider_id in providers {
+    for provider_id in &providers {
         let is_enabled = if let Ok(state) = shared_state.lock() {
             state.settings.enabled_providers.contains(provider_id.cli_name())
         } else { true };
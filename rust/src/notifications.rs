@@ -4,7 +4,9 @@
 
 #![allow(dead_code)]
 
-use crate::core::ProviderId;
+use chrono::NaiveDate;
+
+use crate::core::{BudgetPeriod, BudgetScope, BudgetStatus, ProviderId};
 use crate::settings::Settings;
 use crate::sound::{play_alert, AlertSound};
 
@@ -23,6 +25,8 @@ pub enum NotificationType {
     SessionDepleted,
     /// Session quota restored (back from 100%)
     SessionRestored,
+    /// Spend crossed a budget alert percentage
+    BudgetAlert,
 }
 
 impl NotificationType {
@@ -34,6 +38,7 @@ impl NotificationType {
             NotificationType::StatusIssue => "Provider Status Issue",
             NotificationType::SessionDepleted => "Session Depleted",
             NotificationType::SessionRestored => "Session Restored",
+            NotificationType::BudgetAlert => "Budget Alert",
         }
     }
 
@@ -45,6 +50,7 @@ impl NotificationType {
             NotificationType::StatusIssue => "⚡",
             NotificationType::SessionDepleted => "🔴",
             NotificationType::SessionRestored => "✅",
+            NotificationType::BudgetAlert => "💰",
         }
     }
 }
@@ -55,6 +61,8 @@ pub struct NotificationManager {
     sent_notifications: std::collections::HashSet<(ProviderId, NotificationType)>,
     /// Track previous session percent for depleted/restored transitions
     previous_session_percent: std::collections::HashMap<ProviderId, f64>,
+    /// Highest budget percentage notified, per budget and period start
+    budget_alerts: std::collections::HashMap<(BudgetScope, BudgetPeriod), (NaiveDate, f64)>,
 }
 
 impl NotificationManager {
@@ -62,6 +70,7 @@ impl NotificationManager {
         Self {
             sent_notifications: std::collections::HashSet::new(),
             previous_session_percent: std::collections::HashMap::new(),
+            budget_alerts: std::collections::HashMap::new(),
         }
     }
 
@@ -157,6 +166,43 @@ impl NotificationManager {
         self.previous_session_percent.insert(provider, current_percent);
     }
 
+    /// Check a budget and notify once for each alert percentage it crosses
+    /// within a period. Returns true if a notification was sent.
+    pub fn check_budget(&mut self, status: &BudgetStatus, thresholds: &[f64], settings: &Settings) -> bool {
+        if !settings.show_notifications {
+            return false;
+        }
+        let Some(threshold) = status.reached_threshold(thresholds) else {
+            return false;
+        };
+
+        let key = (status.scope, status.period);
+        if let Some((start, notified)) = self.budget_alerts.get(&key) {
+            if *start == status.start && *notified >= threshold {
+                return false;
+            }
+        }
+        self.budget_alerts.insert(key, (status.start, threshold));
+
+        let body = format!(
+            "{} {} budget at {:.0}% (${:.2} of ${:.2}), forecast ${:.2}",
+            status.scope.display_name(),
+            status.period.as_str(),
+            status.percent(),
+            status.spent_usd,
+            status.limit_usd,
+            status.forecast.high_usd()
+        );
+        self.show_toast(NotificationType::BudgetAlert.title(), &body);
+        let sound = if status.percent() >= 100.0 {
+            AlertSound::Critical
+        } else {
+            AlertSound::Warning
+        };
+        play_alert(sound, settings);
+        true
+    }
+
     /// Send a Windows toast notification with sound
     fn send_notification(&self, provider: ProviderId, used_percent: f64, notif_type: NotificationType, settings: &Settings) {
         let title = notif_type.title();
@@ -179,6 +225,9 @@ impl NotificationManager {
             NotificationType::SessionRestored => {
                 format!("{} session restored. Quota available again.", provider.display_name())
             }
+            NotificationType::BudgetAlert => {
+                format!("{} budget at {:.0}%", provider.display_name(), used_percent)
+            }
         };
 
         self.show_toast(title, &body);
@@ -191,6 +240,7 @@ impl NotificationManager {
             NotificationType::StatusIssue => AlertSound::Error,
             NotificationType::SessionDepleted => AlertSound::Error,
             NotificationType::SessionRestored => AlertSound::Success,
+            NotificationType::BudgetAlert => AlertSound::Warning,
         };
         play_alert(alert_sound, settings);
     }
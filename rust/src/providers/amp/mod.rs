@@ -7,10 +7,14 @@ use async_trait::async_trait;
 use std::path::PathBuf;
 
 use crate::core::{
-    FetchContext, Provider, ProviderId, ProviderError, ProviderFetchResult,
-    ProviderMetadata, RateWindow, SourceMode, UsageSnapshot,
+    FetchContext, Provider, ProviderError, ProviderFetchKind, ProviderFetchPipeline,
+    ProviderFetchResult, ProviderFetchStrategy, ProviderId, ProviderMetadata, RateWindow,
+    UsageSnapshot,
 };
 
+/// Sourcegraph API base URL
+const AMP_API_BASE: &str = "https://sourcegraph.com";
+
 /// Amp provider (Sourcegraph)
 pub struct AmpProvider {
     metadata: ProviderMetadata,
@@ -110,20 +114,18 @@ impl AmpProvider {
     async fn fetch_via_web(&self, ctx: &FetchContext) -> Result<UsageSnapshot, ProviderError> {
         let token = self.read_access_token(ctx).await?;
 
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
+        let client = crate::core::http_client(std::time::Duration::from_secs(30))
             .map_err(|e| ProviderError::Other(e.to_string()))?;
 
         // Sourcegraph Cody usage API
         let resp = client
-            .get("https://sourcegraph.com/.api/cody/current-user/usage")
+            .get(ctx.endpoint(AMP_API_BASE, "/.api/cody/current-user/usage"))
             .header("Authorization", format!("token {}", token))
             .send()
             .await?;
 
         if !resp.status().is_success() {
-            return Err(ProviderError::AuthRequired);
+            return Err(ProviderError::from_http_status(resp.status(), "Sourcegraph API"));
         }
 
         let json: serde_json::Value = resp.json().await
@@ -211,40 +213,51 @@ impl Provider for AmpProvider {
         &self.metadata
     }
 
-    async fn fetch_usage(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
-        tracing::debug!("Fetching Amp usage");
+    fn fetch_pipeline(&self) -> ProviderFetchPipeline<'_> {
+        ProviderFetchPipeline::empty()
+            .with_strategy(AmpWebStrategy { provider: self })
+            .with_strategy(AmpLocalStrategy { provider: self })
+    }
+}
 
-        match ctx.source_mode {
-            SourceMode::Auto => {
-                if let Ok(usage) = self.fetch_via_web(ctx).await {
-                    return Ok(ProviderFetchResult::new(usage, "web"));
-                }
-                let usage = self.probe_cli(ctx).await?;
-                Ok(ProviderFetchResult::new(usage, "cli"))
-            }
-            SourceMode::Web => {
-                let usage = self.fetch_via_web(ctx).await?;
-                Ok(ProviderFetchResult::new(usage, "web"))
-            }
-            SourceMode::Cli => {
-                let usage = self.probe_cli(ctx).await?;
-                Ok(ProviderFetchResult::new(usage, "cli"))
-            }
-            SourceMode::OAuth => {
-                Err(ProviderError::UnsupportedSource(SourceMode::OAuth))
-            }
-        }
+/// Sourcegraph Cody usage API strategy
+struct AmpWebStrategy<'a> {
+    provider: &'a AmpProvider,
+}
+
+#[async_trait]
+impl ProviderFetchStrategy for AmpWebStrategy<'_> {
+    fn id(&self) -> &str {
+        "amp.web"
+    }
+
+    fn kind(&self) -> ProviderFetchKind {
+        ProviderFetchKind::Web
+    }
+
+    async fn fetch(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        let usage = self.provider.fetch_via_web(ctx).await?;
+        Ok(ProviderFetchResult::new(usage, "web"))
     }
+}
 
-    fn available_sources(&self) -> Vec<SourceMode> {
-        vec![SourceMode::Auto, SourceMode::Web, SourceMode::Cli]
+/// Local Amp/Cody configuration probe strategy
+struct AmpLocalStrategy<'a> {
+    provider: &'a AmpProvider,
+}
+
+#[async_trait]
+impl ProviderFetchStrategy for AmpLocalStrategy<'_> {
+    fn id(&self) -> &str {
+        "amp.local"
     }
 
-    fn supports_web(&self) -> bool {
-        true
+    fn kind(&self) -> ProviderFetchKind {
+        ProviderFetchKind::LocalProbe
     }
 
-    fn supports_cli(&self) -> bool {
-        true
+    async fn fetch(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        let usage = self.provider.probe_cli(ctx).await?;
+        Ok(ProviderFetchResult::new(usage, "cli"))
     }
 }
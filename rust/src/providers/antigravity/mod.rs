@@ -11,8 +11,9 @@ use std::os::windows::process::CommandExt;
 use regex_lite::Regex;
 
 use crate::core::{
-    FetchContext, Provider, ProviderId, ProviderError, ProviderFetchResult,
-    ProviderMetadata, RateWindow, SourceMode, UsageSnapshot,
+    FetchContext, Provider, ProviderError, ProviderFetchKind, ProviderFetchPipeline,
+    ProviderFetchResult, ProviderFetchStrategy, ProviderId, ProviderMetadata, RateWindow,
+    UsageSnapshot,
 };
 
 /// Antigravity provider
@@ -101,9 +102,10 @@ impl AntigravityProvider {
         // SECURITY: TLS verification is disabled because the local language server uses
         // self-signed certificates. This is scoped to 127.0.0.1 only and the port range
         // is limited. We verify the server responds with the expected gRPC endpoint.
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(2))
+        let client = crate::core::http_client_builder(std::time::Duration::from_secs(2))
+            .map_err(|e| ProviderError::Other(e.to_string()))?
             .danger_accept_invalid_certs(true)
+            .no_proxy() // loopback only; never route through the configured proxy
             .redirect(reqwest::redirect::Policy::none())
             .build()
             .map_err(|e| ProviderError::Other(e.to_string()))?;
@@ -152,9 +154,10 @@ impl AntigravityProvider {
         let api_port = Self::find_api_port(process_info.extension_port).await?;
 
         // SECURITY: TLS verification disabled for local language server (see find_api_port)
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(8))
+        let client = crate::core::http_client_builder(std::time::Duration::from_secs(8))
+            .map_err(|e| ProviderError::Other(e.to_string()))?
             .danger_accept_invalid_certs(true)
+            .no_proxy() // loopback only; never route through the configured proxy
             .redirect(reqwest::redirect::Policy::none())
             .build()
             .map_err(|e| ProviderError::Other(e.to_string()))?;
@@ -301,10 +304,28 @@ impl Provider for AntigravityProvider {
         &self.metadata
     }
 
-    async fn fetch_usage(&self, _ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
-        tracing::debug!("Fetching Antigravity usage via local probe");
+    fn fetch_pipeline(&self) -> ProviderFetchPipeline<'_> {
+        ProviderFetchPipeline::empty().with_strategy(AntigravityLocalStrategy { provider: self })
+    }
+}
+
+/// Local language-server probe strategy
+struct AntigravityLocalStrategy<'a> {
+    provider: &'a AntigravityProvider,
+}
+
+#[async_trait]
+impl ProviderFetchStrategy for AntigravityLocalStrategy<'_> {
+    fn id(&self) -> &str {
+        "antigravity.local"
+    }
 
-        match self.fetch_user_status().await {
+    fn kind(&self) -> ProviderFetchKind {
+        ProviderFetchKind::LocalProbe
+    }
+
+    async fn fetch(&self, _ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        match self.provider.fetch_user_status().await {
             Ok(usage) => Ok(ProviderFetchResult::new(usage, "local")),
             Err(e) => {
                 tracing::warn!("Antigravity probe failed: {}", e);
@@ -312,14 +333,6 @@ impl Provider for AntigravityProvider {
             }
         }
     }
-
-    fn available_sources(&self) -> Vec<SourceMode> {
-        vec![SourceMode::Auto, SourceMode::Cli]
-    }
-
-    fn supports_cli(&self) -> bool {
-        true
-    }
 }
 
 struct ProcessInfo {
@@ -213,9 +213,7 @@ impl AugmentSessionKeepalive {
 
     /// Ping Augment's session endpoint to trigger cookie refresh
     async fn ping_session_endpoint(config: &KeepaliveConfig) -> Result<bool, String> {
-        let client = reqwest::Client::builder()
-            .timeout(config.refresh_timeout)
-            .build()
+        let client = crate::core::http_client(config.refresh_timeout)
             .map_err(|e| e.to_string())?;
 
         // Session endpoints to try
@@ -13,10 +13,14 @@ use async_trait::async_trait;
 use std::path::PathBuf;
 
 use crate::core::{
-    FetchContext, Provider, ProviderId, ProviderError, ProviderFetchResult,
-    ProviderMetadata, RateWindow, SourceMode, UsageSnapshot,
+    FetchContext, Provider, ProviderError, ProviderFetchKind, ProviderFetchPipeline,
+    ProviderFetchResult, ProviderFetchStrategy, ProviderId, ProviderMetadata, RateWindow,
+    UsageSnapshot,
 };
 
+/// Augment API base URL
+const AUGMENT_API_BASE: &str = "https://api.augmentcode.com";
+
 /// Augment provider
 pub struct AugmentProvider {
     metadata: ProviderMetadata,
@@ -66,7 +70,12 @@ impl AugmentProvider {
     }
 
     /// Read Augment auth token
-    async fn read_auth_token(&self) -> Result<String, ProviderError> {
+    async fn read_auth_token(&self, api_key: Option<&str>) -> Result<String, ProviderError> {
+        // Check api_key first (from settings/ctx)
+        if let Some(token) = api_key.map(str::trim).filter(|token| !token.is_empty()) {
+            return Ok(token.to_string());
+        }
+
         let config_path = Self::get_augment_config_path()
             .ok_or_else(|| ProviderError::NotInstalled("Augment config not found".to_string()))?;
 
@@ -117,24 +126,28 @@ impl AugmentProvider {
     }
 
     /// Fetch usage via Augment API
-    async fn fetch_via_web(&self) -> Result<UsageSnapshot, ProviderError> {
-        let token = self.read_auth_token().await?;
+    async fn fetch_via_web(&self, ctx: &FetchContext) -> Result<UsageSnapshot, ProviderError> {
+        let token = self.read_auth_token(ctx.api_key.as_deref()).await?;
 
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
+        let client = crate::core::http_client(std::time::Duration::from_secs(30))
             .map_err(|e| ProviderError::Other(e.to_string()))?;
 
         let resp = client
-            .get("https://api.augmentcode.com/v1/user/usage")
+            .get(ctx.endpoint(AUGMENT_API_BASE, "/v1/user/usage"))
             .header("Authorization", format!("Bearer {}", token))
             .send()
             .await?;
 
-        if !resp.status().is_success() {
+        if resp.status() == reqwest::StatusCode::UNAUTHORIZED
+            || resp.status() == reqwest::StatusCode::FORBIDDEN
+        {
             return Err(ProviderError::AuthRequired);
         }
 
+        if !resp.status().is_success() {
+            return Err(ProviderError::from_http_status(resp.status(), "Augment API"));
+        }
+
         let json: serde_json::Value = resp.json().await
             .map_err(|e| ProviderError::Parse(e.to_string()))?;
 
@@ -211,40 +224,51 @@ impl Provider for AugmentProvider {
         &self.metadata
     }
 
-    async fn fetch_usage(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
-        tracing::debug!("Fetching Augment usage");
+    fn fetch_pipeline(&self) -> ProviderFetchPipeline<'_> {
+        ProviderFetchPipeline::empty()
+            .with_strategy(AugmentWebStrategy { provider: self })
+            .with_strategy(AugmentLocalStrategy { provider: self })
+    }
+}
 
-        match ctx.source_mode {
-            SourceMode::Auto => {
-                if let Ok(usage) = self.fetch_via_web().await {
-                    return Ok(ProviderFetchResult::new(usage, "web"));
-                }
-                let usage = self.probe_cli().await?;
-                Ok(ProviderFetchResult::new(usage, "cli"))
-            }
-            SourceMode::Web => {
-                let usage = self.fetch_via_web().await?;
-                Ok(ProviderFetchResult::new(usage, "web"))
-            }
-            SourceMode::Cli => {
-                let usage = self.probe_cli().await?;
-                Ok(ProviderFetchResult::new(usage, "cli"))
-            }
-            SourceMode::OAuth => {
-                Err(ProviderError::UnsupportedSource(SourceMode::OAuth))
-            }
-        }
+/// Augment usage API strategy
+struct AugmentWebStrategy<'a> {
+    provider: &'a AugmentProvider,
+}
+
+#[async_trait]
+impl ProviderFetchStrategy for AugmentWebStrategy<'_> {
+    fn id(&self) -> &str {
+        "augment.web"
+    }
+
+    fn kind(&self) -> ProviderFetchKind {
+        ProviderFetchKind::Web
     }
 
-    fn available_sources(&self) -> Vec<SourceMode> {
-        vec![SourceMode::Auto, SourceMode::Web, SourceMode::Cli]
+    async fn fetch(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        let usage = self.provider.fetch_via_web(ctx).await?;
+        Ok(ProviderFetchResult::new(usage, "web"))
+    }
+}
+
+/// Augment installation probe strategy
+struct AugmentLocalStrategy<'a> {
+    provider: &'a AugmentProvider,
+}
+
+#[async_trait]
+impl ProviderFetchStrategy for AugmentLocalStrategy<'_> {
+    fn id(&self) -> &str {
+        "augment.local"
     }
 
-    fn supports_web(&self) -> bool {
-        true
+    fn kind(&self) -> ProviderFetchKind {
+        ProviderFetchKind::LocalProbe
     }
 
-    fn supports_cli(&self) -> bool {
-        true
+    async fn fetch(&self, _ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        let usage = self.provider.probe_cli().await?;
+        Ok(ProviderFetchResult::new(usage, "cli"))
     }
 }
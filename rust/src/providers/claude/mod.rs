@@ -10,8 +10,11 @@ use tokio::io::AsyncWriteExt;
 use regex_lite::Regex;
 
 use crate::core::{
-    FetchContext, Provider, ProviderError, ProviderFetchResult, ProviderId, ProviderMetadata,
-    RateWindow, SourceMode, UsageSnapshot,
+    build_session_blocks, estimate_session_window, max_completed_block_tokens,
+    session_token_limit_from_env, FetchContext, JsonlScanner, NamedRateWindow, Provider,
+    ProviderError, ProviderFetchKind, ProviderFetchPipeline, ProviderFetchResult,
+    ProviderFetchStrategy, ProviderId, ProviderMetadata, RateWindow, RateWindowKind,
+    UsageSnapshot, SESSION_LIMIT_LOOKBACK_DAYS,
 };
 
 pub use web_api::ClaudeWebApiFetcher;
@@ -61,54 +64,114 @@ impl Provider for ClaudeProvider {
         &self.metadata
     }
 
-    async fn fetch_usage(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
-        match ctx.source_mode {
-            SourceMode::Auto => {
-                // Try OAuth first, then Web, then CLI
-                if let Ok(result) = self.fetch_via_oauth(ctx).await {
-                    return Ok(result);
-                }
-                if let Ok(result) = self.fetch_via_web(ctx).await {
-                    return Ok(result);
-                }
-                self.fetch_via_cli(ctx).await
-            }
-            SourceMode::OAuth => self.fetch_via_oauth(ctx).await,
-            SourceMode::Web => self.fetch_via_web(ctx).await,
-            SourceMode::Cli => self.fetch_via_cli(ctx).await,
-        }
+    fn fetch_pipeline(&self) -> ProviderFetchPipeline<'_> {
+        // Try OAuth first, then Web, then CLI, then estimate from local logs
+        ProviderFetchPipeline::empty()
+            .with_strategy(ClaudeOAuthStrategy { provider: self })
+            .with_strategy(ClaudeWebStrategy { provider: self })
+            .with_strategy(ClaudeCliStrategy { provider: self })
+            .with_strategy(ClaudeLocalStrategy { provider: self })
     }
 
-    fn available_sources(&self) -> Vec<SourceMode> {
-        vec![
-            SourceMode::Auto,
-            SourceMode::OAuth,
-            SourceMode::Web,
-            SourceMode::Cli,
-        ]
+    fn detect_version(&self) -> Option<String> {
+        detect_claude_version()
     }
+}
 
-    fn supports_oauth(&self) -> bool {
-        true
+/// Claude OAuth API strategy
+struct ClaudeOAuthStrategy<'a> {
+    provider: &'a ClaudeProvider,
+}
+
+#[async_trait]
+impl ProviderFetchStrategy for ClaudeOAuthStrategy<'_> {
+    fn id(&self) -> &str {
+        "claude.oauth"
     }
 
-    fn supports_web(&self) -> bool {
-        true
+    fn kind(&self) -> ProviderFetchKind {
+        ProviderFetchKind::OAuth
     }
 
-    fn supports_cli(&self) -> bool {
-        true
+    async fn fetch(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        self.provider.fetch_via_oauth(ctx).await
     }
+}
 
-    fn detect_version(&self) -> Option<String> {
-        detect_claude_version()
+/// claude.ai web API strategy (browser or manual cookies)
+struct ClaudeWebStrategy<'a> {
+    provider: &'a ClaudeProvider,
+}
+
+#[async_trait]
+impl ProviderFetchStrategy for ClaudeWebStrategy<'_> {
+    fn id(&self) -> &str {
+        "claude.web"
+    }
+
+    fn kind(&self) -> ProviderFetchKind {
+        ProviderFetchKind::Web
+    }
+
+    async fn fetch(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        self.provider.fetch_via_web(ctx).await
+    }
+}
+
+/// Claude CLI `/usage` probe strategy
+struct ClaudeCliStrategy<'a> {
+    provider: &'a ClaudeProvider,
+}
+
+#[async_trait]
+impl ProviderFetchStrategy for ClaudeCliStrategy<'_> {
+    fn id(&self) -> &str {
+        "claude.cli"
+    }
+
+    fn kind(&self) -> ProviderFetchKind {
+        ProviderFetchKind::Cli
+    }
+
+    fn is_available(&self, _ctx: &FetchContext) -> bool {
+        which_claude().is_some()
+    }
+
+    async fn fetch(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        self.provider.fetch_via_cli(ctx).await
+    }
+}
+
+/// Session window estimated from 5-hour blocks in the local Claude logs
+struct ClaudeLocalStrategy<'a> {
+    provider: &'a ClaudeProvider,
+}
+
+#[async_trait]
+impl ProviderFetchStrategy for ClaudeLocalStrategy<'_> {
+    fn id(&self) -> &str {
+        "claude.local"
+    }
+
+    fn kind(&self) -> ProviderFetchKind {
+        ProviderFetchKind::LocalProbe
+    }
+
+    fn is_available(&self, _ctx: &FetchContext) -> bool {
+        JsonlScanner::default_claude_projects_roots()
+            .iter()
+            .any(|root| root.is_dir())
+    }
+
+    async fn fetch(&self, _ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        self.provider.fetch_via_local_logs().await
     }
 }
 
 impl ClaudeProvider {
-    async fn fetch_via_oauth(&self, _ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+    async fn fetch_via_oauth(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
         tracing::debug!("Attempting OAuth fetch for Claude");
-        self.oauth_fetcher.fetch().await
+        self.oauth_fetcher.fetch(ctx).await
     }
 
     async fn fetch_via_web(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
@@ -117,11 +180,33 @@ impl ClaudeProvider {
         // Check for manual cookie header first
         if let Some(ref cookie_header) = ctx.manual_cookie_header {
             tracing::debug!("Using manual cookie header");
-            return self.web_fetcher.fetch_with_cookie_header(cookie_header).await;
+            return self.web_fetcher.fetch_with_cookie_header(ctx, cookie_header).await;
         }
 
         // Otherwise, try to extract cookies from browser
-        self.web_fetcher.fetch_with_cookies().await
+        self.web_fetcher.fetch_with_cookies(ctx).await
+    }
+
+    async fn fetch_via_local_logs(&self) -> Result<ProviderFetchResult, ProviderError> {
+        tracing::debug!("Estimating Claude session window from local logs");
+
+        let now = chrono::Utc::now();
+        let since = now - chrono::Duration::days(SESSION_LIMIT_LOOKBACK_DAYS);
+        let entries = tokio::task::spawn_blocking(move || {
+            JsonlScanner::claude_usage_entries(&JsonlScanner::default_claude_projects_roots(), since)
+        })
+        .await
+        .map_err(|e| ProviderError::Other(format!("Failed to scan Claude logs: {}", e)))?;
+
+        let blocks = build_session_blocks(&entries, now);
+        let limit = session_token_limit_from_env()
+            .or_else(|| max_completed_block_tokens(&blocks))
+            .ok_or_else(|| {
+                ProviderError::Other("No Claude usage in the local logs to estimate the session limit from".to_string())
+            })?;
+
+        let usage = UsageSnapshot::new(estimate_session_window(&blocks, limit));
+        Ok(ProviderFetchResult::new(usage, "local estimate"))
     }
 
     async fn fetch_via_cli(&self, _ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
@@ -255,13 +340,13 @@ impl ClaudeProvider {
         }
 
         if let Some(opus_used) = opus_percent {
-            let model_specific = RateWindow::with_details(
+            let opus = RateWindow::with_details(
                 opus_used,
                 Some(10080),
                 None,
                 None,
             );
-            usage = usage.with_model_specific(model_specific);
+            usage = usage.with_window(NamedRateWindow::new("opus", "Opus", RateWindowKind::Model, opus));
         }
 
         if let Some(method) = login_method {
@@ -3,11 +3,13 @@
 //! Loads OAuth credentials from Claude CLI and fetches usage from the API.
 
 use chrono::{DateTime, Utc};
-use reqwest::Client;
 use serde::Deserialize;
 use std::path::PathBuf;
 
-use crate::core::{ProviderError, RateWindow, UsageSnapshot, ProviderFetchResult};
+use crate::core::{
+    FetchContext, NamedRateWindow, ProviderError, ProviderFetchResult, RateWindow, RateWindowKind,
+    UsageSnapshot,
+};
 
 /// OAuth credentials from Claude CLI
 #[derive(Debug, Clone)]
@@ -101,33 +103,35 @@ pub struct ExtraUsage {
 }
 
 /// Claude OAuth fetcher
-pub struct ClaudeOAuthFetcher {
-    client: Client,
-}
+pub struct ClaudeOAuthFetcher;
 
 impl ClaudeOAuthFetcher {
-    const USAGE_URL: &'static str = "https://api.claude.ai/api/usage";
+    const API_BASE: &'static str = "https://api.claude.ai";
+    const USAGE_PATH: &'static str = "/api/usage";
     const CREDENTIALS_PATH: &'static str = ".claude/.credentials.json";
     const ENV_TOKEN_KEY: &'static str = "CODEXBAR_CLAUDE_OAUTH_TOKEN";
     const ENV_SCOPES_KEY: &'static str = "CODEXBAR_CLAUDE_OAUTH_SCOPES";
 
     pub fn new() -> Self {
-        Self {
-            client: Client::new(),
-        }
+        Self
     }
 
     /// Load credentials and fetch usage
-    pub async fn fetch(&self) -> Result<ProviderFetchResult, ProviderError> {
-        let credentials = self.load_credentials()?;
-        let usage_response = self.fetch_usage(&credentials).await?;
+    pub async fn fetch(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        let credentials = self.load_credentials(ctx.api_key.as_deref())?;
+        let usage_response = self.fetch_usage(ctx, &credentials).await?;
         let usage = self.build_usage_snapshot(&usage_response, &credentials);
         Ok(ProviderFetchResult::new(usage, "oauth"))
     }
 
-    /// Load OAuth credentials from environment or file
-    pub fn load_credentials(&self) -> Result<ClaudeOAuthCredentials, ProviderError> {
-        // Try environment variables first
+    /// Load OAuth credentials from the context token, environment or file
+    pub fn load_credentials(&self, api_key: Option<&str>) -> Result<ClaudeOAuthCredentials, ProviderError> {
+        // Check api_key first (from a token account or settings)
+        if let Some(creds) = api_key.and_then(|token| self.credentials_from_token(token)) {
+            return Ok(creds);
+        }
+
+        // Try environment variables next
         if let Some(creds) = self.load_from_environment() {
             return Ok(creds);
         }
@@ -139,6 +143,11 @@ impl ClaudeOAuthFetcher {
     /// Load credentials from environment variables
     fn load_from_environment(&self) -> Option<ClaudeOAuthCredentials> {
         let token = std::env::var(Self::ENV_TOKEN_KEY).ok()?;
+        self.credentials_from_token(&token)
+    }
+
+    /// Credentials for a bare access token; scopes come from the environment
+    fn credentials_from_token(&self, token: &str) -> Option<ClaudeOAuthCredentials> {
         let token = token.trim();
         if token.is_empty() {
             return None;
@@ -225,6 +234,7 @@ impl ClaudeOAuthFetcher {
     /// Fetch usage data using OAuth credentials
     pub async fn fetch_usage(
         &self,
+        ctx: &FetchContext,
         credentials: &ClaudeOAuthCredentials,
     ) -> Result<OAuthUsageResponse, ProviderError> {
         if credentials.is_expired() {
@@ -241,11 +251,10 @@ impl ClaudeOAuthFetcher {
             )));
         }
 
-        let response = self
-            .client
-            .get(Self::USAGE_URL)
+        let response = crate::core::http_client(std::time::Duration::from_secs(10))
+            .map_err(|e| ProviderError::Other(e.to_string()))?
+            .get(ctx.endpoint(Self::API_BASE, Self::USAGE_PATH))
             .header("Authorization", format!("Bearer {}", credentials.access_token))
-            .timeout(std::time::Duration::from_secs(10))
             .send()
             .await?;
 
@@ -254,9 +263,11 @@ impl ClaudeOAuthFetcher {
             let body = response.text().await.unwrap_or_default();
 
             if status.as_u16() == 401 {
-                return Err(ProviderError::OAuth(
-                    "OAuth token invalid or expired. Run `claude` to re-authenticate.".to_string(),
-                ));
+                return Err(ProviderError::AuthRequired);
+            }
+
+            if status.as_u16() == 429 {
+                return Err(ProviderError::RateLimited);
             }
 
             if status.as_u16() == 403 && body.contains("user:profile") {
@@ -299,11 +310,12 @@ impl ClaudeOAuthFetcher {
             usage = usage.with_secondary(weekly);
         }
 
-        // Model-specific: Opus or Sonnet
+        // Per-model weekly caps: Opus and Sonnet
         if let Some(opus) = response.seven_day_opus.as_ref().and_then(|w| Self::to_rate_window(w, Some(10080))) {
-            usage = usage.with_model_specific(opus);
-        } else if let Some(sonnet) = response.seven_day_sonnet.as_ref().and_then(|w| Self::to_rate_window(w, Some(10080))) {
-            usage = usage.with_model_specific(sonnet);
+            usage = usage.with_window(NamedRateWindow::new("opus", "Opus", RateWindowKind::Model, opus));
+        }
+        if let Some(sonnet) = response.seven_day_sonnet.as_ref().and_then(|w| Self::to_rate_window(w, Some(10080))) {
+            usage = usage.with_window(NamedRateWindow::new("sonnet", "Sonnet", RateWindowKind::Model, sonnet));
         }
 
         // Login method from rate limit tier or default
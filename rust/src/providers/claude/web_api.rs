@@ -1,18 +1,17 @@
 //! Claude Web API fetcher - uses browser cookies to fetch usage from claude.ai
 
 use chrono::{DateTime, Utc};
-use reqwest::{Client, header};
+use reqwest::header;
 use serde::Deserialize;
 
 use crate::browser::cookies::get_cookie_header;
 use crate::core::{
-    CostSnapshot, ProviderError, ProviderFetchResult, RateWindow, UsageSnapshot,
+    CostSnapshot, FetchContext, NamedRateWindow, ProviderError, ProviderFetchResult, RateWindow,
+    RateWindowKind, UsageSnapshot,
 };
 
 /// Claude Web API fetcher
-pub struct ClaudeWebApiFetcher {
-    client: Client,
-}
+pub struct ClaudeWebApiFetcher;
 
 /// Organization info from Claude API
 #[derive(Debug, Deserialize)]
@@ -72,20 +71,15 @@ struct AccountResponse {
 }
 
 impl ClaudeWebApiFetcher {
-    const BASE_URL: &'static str = "https://claude.ai/api";
+    const BASE_URL: &'static str = "https://claude.ai";
 
     /// Create a new fetcher
     pub fn new() -> Self {
-        Self {
-            client: Client::builder()
-                .timeout(std::time::Duration::from_secs(30))
-                .build()
-                .expect("Failed to create HTTP client"),
-        }
+        Self
     }
 
     /// Fetch usage using browser cookies
-    pub async fn fetch_with_cookies(&self) -> Result<ProviderFetchResult, ProviderError> {
+    pub async fn fetch_with_cookies(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
         // Try multiple domains - Claude uses different domains for different services
         // console.anthropic.com has the sessionKey for API access
         let domains = ["claude.ai", "claude.com", "console.anthropic.com", "anthropic.com"];
@@ -94,7 +88,7 @@ impl ClaudeWebApiFetcher {
             match get_cookie_header(domain) {
                 Ok(cookie_header) if !cookie_header.is_empty() => {
                     tracing::debug!("Found cookies for {}", domain);
-                    return self.fetch_with_cookie_header(&cookie_header).await;
+                    return self.fetch_with_cookie_header(ctx, &cookie_header).await;
                 }
                 Ok(_) => {
                     tracing::debug!("No cookies found for {}", domain);
@@ -111,22 +105,24 @@ impl ClaudeWebApiFetcher {
     /// Fetch usage with a provided cookie header
     pub async fn fetch_with_cookie_header(
         &self,
+        ctx: &FetchContext,
         cookie_header: &str,
     ) -> Result<ProviderFetchResult, ProviderError> {
         tracing::debug!("Fetching Claude usage via web API");
+        let api_base = ctx.endpoint(Self::BASE_URL, "/api");
 
         // Step 1: Get organization ID
-        let org_id = self.get_organization_id(cookie_header).await?;
+        let org_id = self.get_organization_id(&api_base, cookie_header).await?;
         tracing::debug!("Got organization ID: {}", org_id);
 
         // Step 2: Fetch usage data
-        let usage = self.get_usage(&org_id, cookie_header).await?;
+        let usage = self.get_usage(&api_base, &org_id, cookie_header).await?;
 
         // Step 3: Fetch extra usage (credits) - optional
-        let extra_usage = self.get_extra_usage(&org_id, cookie_header).await.ok();
+        let extra_usage = self.get_extra_usage(&api_base, &org_id, cookie_header).await.ok();
 
         // Step 4: Fetch account info - optional
-        let account = self.get_account_info(cookie_header).await.ok();
+        let account = self.get_account_info(&api_base, cookie_header).await.ok();
 
         // Build the result
         let primary = usage
@@ -140,19 +136,28 @@ impl ClaudeWebApiFetcher {
             .as_ref()
             .map(|w| self.to_rate_window(w, Some(10080))); // 7 days = 10080 minutes
 
-        let model_specific = usage
+        let opus = usage
             .seven_day_opus
             .as_ref()
             .map(|w| self.to_rate_window(w, Some(10080)));
 
+        let sonnet = usage
+            .seven_day_sonnet
+            .as_ref()
+            .map(|w| self.to_rate_window(w, Some(10080)));
+
         let mut snapshot = UsageSnapshot::new(primary);
 
         if let Some(s) = secondary {
             snapshot = snapshot.with_secondary(s);
         }
 
-        if let Some(m) = model_specific {
-            snapshot = snapshot.with_model_specific(m);
+        if let Some(m) = opus {
+            snapshot = snapshot.with_window(NamedRateWindow::new("opus", "Opus", RateWindowKind::Model, m));
+        }
+
+        if let Some(m) = sonnet {
+            snapshot = snapshot.with_window(NamedRateWindow::new("sonnet", "Sonnet", RateWindowKind::Model, m));
         }
 
         if let Some(ref acc) = account {
@@ -191,11 +196,11 @@ impl ClaudeWebApiFetcher {
     }
 
     /// Get the organization ID
-    async fn get_organization_id(&self, cookie_header: &str) -> Result<String, ProviderError> {
-        let url = format!("{}/organizations", Self::BASE_URL);
+    async fn get_organization_id(&self, api_base: &str, cookie_header: &str) -> Result<String, ProviderError> {
+        let url = format!("{}/organizations", api_base);
 
-        let response = self
-            .client
+        let response = crate::core::http_client(std::time::Duration::from_secs(30))
+            .map_err(|e| ProviderError::Other(e.to_string()))?
             .get(&url)
             .header(header::COOKIE, cookie_header)
             .header(header::ACCEPT, "application/json")
@@ -203,10 +208,7 @@ impl ClaudeWebApiFetcher {
             .await?;
 
         if !response.status().is_success() {
-            return Err(ProviderError::Other(format!(
-                "Failed to get organizations: {}",
-                response.status()
-            )));
+            return Err(Self::status_error(response.status(), "claude.ai organizations API"));
         }
 
         let orgs: Vec<Organization> = response.json().await.map_err(|e| {
@@ -222,13 +224,14 @@ impl ClaudeWebApiFetcher {
     /// Get usage data
     async fn get_usage(
         &self,
+        api_base: &str,
         org_id: &str,
         cookie_header: &str,
     ) -> Result<UsageResponse, ProviderError> {
-        let url = format!("{}/organizations/{}/usage", Self::BASE_URL, org_id);
+        let url = format!("{}/organizations/{}/usage", api_base, org_id);
 
-        let response = self
-            .client
+        let response = crate::core::http_client(std::time::Duration::from_secs(30))
+            .map_err(|e| ProviderError::Other(e.to_string()))?
             .get(&url)
             .header(header::COOKIE, cookie_header)
             .header(header::ACCEPT, "application/json")
@@ -236,10 +239,7 @@ impl ClaudeWebApiFetcher {
             .await?;
 
         if !response.status().is_success() {
-            return Err(ProviderError::Other(format!(
-                "Failed to get usage: {}",
-                response.status()
-            )));
+            return Err(Self::status_error(response.status(), "claude.ai usage API"));
         }
 
         response.json().await.map_err(|e| {
@@ -250,16 +250,17 @@ impl ClaudeWebApiFetcher {
     /// Get extra usage (credits)
     async fn get_extra_usage(
         &self,
+        api_base: &str,
         org_id: &str,
         cookie_header: &str,
     ) -> Result<ExtraUsageResponse, ProviderError> {
         let url = format!(
             "{}/organizations/{}/overage_spend_limit",
-            Self::BASE_URL, org_id
+            api_base, org_id
         );
 
-        let response = self
-            .client
+        let response = crate::core::http_client(std::time::Duration::from_secs(30))
+            .map_err(|e| ProviderError::Other(e.to_string()))?
             .get(&url)
             .header(header::COOKIE, cookie_header)
             .header(header::ACCEPT, "application/json")
@@ -267,10 +268,7 @@ impl ClaudeWebApiFetcher {
             .await?;
 
         if !response.status().is_success() {
-            return Err(ProviderError::Other(format!(
-                "Failed to get extra usage: {}",
-                response.status()
-            )));
+            return Err(Self::status_error(response.status(), "claude.ai overage API"));
         }
 
         response.json().await.map_err(|e| {
@@ -279,11 +277,11 @@ impl ClaudeWebApiFetcher {
     }
 
     /// Get account info
-    async fn get_account_info(&self, cookie_header: &str) -> Result<AccountResponse, ProviderError> {
-        let url = format!("{}/account", Self::BASE_URL);
+    async fn get_account_info(&self, api_base: &str, cookie_header: &str) -> Result<AccountResponse, ProviderError> {
+        let url = format!("{}/account", api_base);
 
-        let response = self
-            .client
+        let response = crate::core::http_client(std::time::Duration::from_secs(30))
+            .map_err(|e| ProviderError::Other(e.to_string()))?
             .get(&url)
             .header(header::COOKIE, cookie_header)
             .header(header::ACCEPT, "application/json")
@@ -291,10 +289,7 @@ impl ClaudeWebApiFetcher {
             .await?;
 
         if !response.status().is_success() {
-            return Err(ProviderError::Other(format!(
-                "Failed to get account: {}",
-                response.status()
-            )));
+            return Err(Self::status_error(response.status(), "claude.ai account API"));
         }
 
         response.json().await.map_err(|e| {
@@ -302,6 +297,14 @@ impl ClaudeWebApiFetcher {
         })
     }
 
+    /// Map an unsuccessful status; claude.ai answers 403 to an expired session
+    fn status_error(status: reqwest::StatusCode, label: &str) -> ProviderError {
+        match status {
+            reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => ProviderError::AuthRequired,
+            _ => ProviderError::from_http_status(status, label),
+        }
+    }
+
     /// Convert a usage window to a RateWindow
     fn to_rate_window(&self, window: &UsageWindow, window_minutes: Option<u32>) -> RateWindow {
         let used_percent = window.utilization.unwrap_or(0.0);
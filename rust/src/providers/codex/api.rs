@@ -2,7 +2,7 @@
 //!
 //! Uses OAuth tokens stored by the Codex CLI in ~/.codex/auth.json
 
-use crate::core::{CostSnapshot, ProviderError, RateWindow, UsageSnapshot};
+use crate::core::{CostSnapshot, FetchContext, ProviderError, RateWindow, UsageSnapshot};
 use chrono::{DateTime, TimeZone, Utc};
 use serde::Deserialize;
 use std::path::PathBuf;
@@ -12,42 +12,32 @@ const USAGE_PATH: &str = "/wham/usage";
 
 /// Codex API client
 pub struct CodexApi {
-    client: reqwest::Client,
     home_dir: PathBuf,
 }
 
 impl CodexApi {
     pub fn new() -> Self {
-        // Build client with proper TLS settings
-        let client = reqwest::Client::builder()
-            .use_rustls_tls()
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
-            .unwrap_or_else(|_| reqwest::Client::new());
-
         Self {
-            client,
             home_dir: dirs::home_dir().unwrap_or_else(|| PathBuf::from(".")),
         }
     }
 
     /// Fetch usage information from Codex API
     /// Returns (UsageSnapshot, optional CostSnapshot)
-    pub async fn fetch_usage(&self) -> Result<(UsageSnapshot, Option<CostSnapshot>), ProviderError> {
+    pub async fn fetch_usage(&self, ctx: &FetchContext) -> Result<(UsageSnapshot, Option<CostSnapshot>), ProviderError> {
         // Load credentials
-        let creds = self.load_credentials()?;
+        let creds = self.load_credentials(ctx.api_key.as_deref())?;
 
-        // Build request URL
+        // Build request URL (a CodexBar base URL override wins over config.toml)
         let base_url = self.resolve_base_url();
-        let url = format!("{}{}", base_url, USAGE_PATH);
+        let url = ctx.endpoint(&base_url, USAGE_PATH);
 
         // Build request
-        let mut request = self.client
+        let mut request = crate::core::http_client(std::time::Duration::from_secs(30))
+            .map_err(|e| ProviderError::Other(e.to_string()))?
             .get(&url)
             .header("Authorization", format!("Bearer {}", creds.access_token))
-            .header("User-Agent", "CodexBar")
-            .header("Accept", "application/json")
-            .timeout(std::time::Duration::from_secs(30));
+            .header("Accept", "application/json");
 
         if let Some(account_id) = &creds.account_id {
             if !account_id.is_empty() {
@@ -62,10 +52,7 @@ impl CodexApi {
         }
 
         if !response.status().is_success() {
-            return Err(ProviderError::Other(format!(
-                "Codex API returned {}",
-                response.status()
-            )));
+            return Err(ProviderError::from_http_status(response.status(), "Codex API"));
         }
 
         // Parse as raw JSON first for flexibility
@@ -77,7 +64,16 @@ impl CodexApi {
         self.build_result_from_json(&json)
     }
 
-    fn load_credentials(&self) -> Result<CodexCredentials, ProviderError> {
+    fn load_credentials(&self, api_key: Option<&str>) -> Result<CodexCredentials, ProviderError> {
+        // Check api_key first (from settings/ctx)
+        if let Some(key) = api_key.map(str::trim).filter(|key| !key.is_empty()) {
+            return Ok(CodexCredentials {
+                access_token: key.to_string(),
+                refresh_token: None,
+                account_id: None,
+            });
+        }
+
         let auth_path = self.get_auth_path();
 
         if !auth_path.exists() {
@@ -181,20 +177,7 @@ impl CodexApi {
         let (primary, secondary) = self.extract_rate_limits(json);
 
         // Build login method string
-        let login_method = plan_type.as_ref().map(|pt| {
-            match pt.as_str() {
-                "guest" => "Guest".to_string(),
-                "free" => "ChatGPT Free".to_string(),
-                "go" => "ChatGPT Go".to_string(),
-                "plus" => "ChatGPT Plus".to_string(),
-                "pro" => "ChatGPT Pro".to_string(),
-                "team" => "ChatGPT Team".to_string(),
-                "business" => "ChatGPT Business".to_string(),
-                "enterprise" => "ChatGPT Enterprise".to_string(),
-                "education" | "edu" => "ChatGPT Education".to_string(),
-                other => format!("ChatGPT {}", capitalize(other)),
-            }
-        });
+        let login_method = plan_type.as_deref().map(plan_display_name);
 
         let mut usage = UsageSnapshot::new(primary);
         if let Some(sec) = secondary {
@@ -315,20 +298,7 @@ impl CodexApi {
             });
 
         // Build usage snapshot
-        let login_method = response.plan_type.as_ref().map(|pt| {
-            match pt.as_str() {
-                "guest" => "Guest".to_string(),
-                "free" => "ChatGPT Free".to_string(),
-                "go" => "ChatGPT Go".to_string(),
-                "plus" => "ChatGPT Plus".to_string(),
-                "pro" => "ChatGPT Pro".to_string(),
-                "team" => "ChatGPT Team".to_string(),
-                "business" => "ChatGPT Business".to_string(),
-                "enterprise" => "ChatGPT Enterprise".to_string(),
-                "education" | "edu" => "ChatGPT Education".to_string(),
-                other => format!("ChatGPT {}", capitalize(other)),
-            }
-        });
+        let login_method = response.plan_type.as_deref().map(plan_display_name);
 
         let mut usage = UsageSnapshot::new(primary);
         if let Some(sec) = secondary {
@@ -460,6 +430,22 @@ fn normalize_base_url(url: &str) -> String {
     trimmed
 }
 
+/// Display name of a ChatGPT plan type ("plus" -> "ChatGPT Plus")
+pub(super) fn plan_display_name(plan_type: &str) -> String {
+    match plan_type {
+        "guest" => "Guest".to_string(),
+        "free" => "ChatGPT Free".to_string(),
+        "go" => "ChatGPT Go".to_string(),
+        "plus" => "ChatGPT Plus".to_string(),
+        "pro" => "ChatGPT Pro".to_string(),
+        "team" => "ChatGPT Team".to_string(),
+        "business" => "ChatGPT Business".to_string(),
+        "enterprise" => "ChatGPT Enterprise".to_string(),
+        "education" | "edu" => "ChatGPT Education".to_string(),
+        other => format!("ChatGPT {}", capitalize(other)),
+    }
+}
+
 fn capitalize(s: &str) -> String {
     let mut chars = s.chars();
     match chars.next() {
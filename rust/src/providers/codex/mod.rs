@@ -1,17 +1,23 @@
 //! Codex (OpenAI/ChatGPT) provider implementation
 //!
 //! Fetches usage data from ChatGPT's backend API using OAuth credentials
-//! stored by the Codex CLI in ~/.codex/auth.json
+//! stored by the Codex CLI in ~/.codex/auth.json. Without working credentials
+//! or network, the rate limits logged in the latest Codex session are used.
 
 mod api;
 
 use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
 
 use crate::core::{
-    FetchContext, Provider, ProviderId, ProviderError, ProviderFetchResult,
-    ProviderMetadata, SourceMode,
+    CodexRateLimits, FetchContext, JsonlScanner, Provider, ProviderId, ProviderError,
+    ProviderFetchKind, ProviderFetchPipeline, ProviderFetchResult, ProviderFetchStrategy,
+    ProviderMetadata, RateWindow, UsageSnapshot,
 };
 
+/// Local rate-limit snapshots older than this are marked stale
+const LOCAL_SNAPSHOT_STALE_MINUTES: i64 = 15;
+
 pub use api::CodexApi;
 
 /// Codex provider for fetching AI usage limits
@@ -56,10 +62,37 @@ impl Provider for CodexProvider {
         &self.metadata
     }
 
-    async fn fetch_usage(&self, _ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+    fn fetch_pipeline(&self) -> ProviderFetchPipeline<'_> {
+        // Try the API first, then the rate limits logged by the Codex CLI
+        ProviderFetchPipeline::empty()
+            .with_strategy(CodexOAuthStrategy { provider: self })
+            .with_strategy(CodexLocalStrategy)
+    }
+
+    fn detect_version(&self) -> Option<String> {
+        detect_codex_version()
+    }
+}
+
+/// ChatGPT backend API strategy using the Codex CLI's OAuth credentials
+struct CodexOAuthStrategy<'a> {
+    provider: &'a CodexProvider,
+}
+
+#[async_trait]
+impl ProviderFetchStrategy for CodexOAuthStrategy<'_> {
+    fn id(&self) -> &str {
+        "codex.oauth"
+    }
+
+    fn kind(&self) -> ProviderFetchKind {
+        ProviderFetchKind::OAuth
+    }
+
+    async fn fetch(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
         tracing::debug!("Fetching Codex usage via OAuth API");
 
-        match self.api.fetch_usage().await {
+        match self.provider.api.fetch_usage(ctx).await {
             Ok((usage, cost)) => {
                 let mut result = ProviderFetchResult::new(usage, "oauth");
                 if let Some(c) = cost {
@@ -73,22 +106,62 @@ impl Provider for CodexProvider {
             }
         }
     }
+}
 
-    fn available_sources(&self) -> Vec<SourceMode> {
-        vec![SourceMode::Auto, SourceMode::OAuth, SourceMode::Cli]
+/// Rate limits from the newest `token_count` event in `CODEX_HOME/sessions`
+struct CodexLocalStrategy;
+
+#[async_trait]
+impl ProviderFetchStrategy for CodexLocalStrategy {
+    fn id(&self) -> &str {
+        "codex.local"
     }
 
-    fn supports_oauth(&self) -> bool {
-        true
+    fn kind(&self) -> ProviderFetchKind {
+        ProviderFetchKind::LocalProbe
     }
 
-    fn supports_cli(&self) -> bool {
-        true
+    fn is_available(&self, _ctx: &FetchContext) -> bool {
+        JsonlScanner::default_codex_sessions_root().is_some_and(|root| root.is_dir())
     }
 
-    fn detect_version(&self) -> Option<String> {
-        detect_codex_version()
+    async fn fetch(&self, _ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        tracing::debug!("Reading Codex rate limits from local session logs");
+
+        let root = JsonlScanner::default_codex_sessions_root()
+            .ok_or_else(|| ProviderError::NotInstalled("Codex sessions directory not found".to_string()))?;
+        let now = Utc::now();
+        let limits = tokio::task::spawn_blocking(move || JsonlScanner::latest_codex_rate_limits(&root, now))
+            .await
+            .map_err(|e| ProviderError::Other(format!("Failed to scan Codex sessions: {}", e)))?
+            .ok_or_else(|| ProviderError::Other("No rate limits in recent Codex session logs".to_string()))?;
+
+        Ok(ProviderFetchResult::new(local_snapshot(&limits, now), "local"))
+    }
+}
+
+/// Usage snapshot from logged rate limits. Windows that have reset since the
+/// event are reported as unused; the snapshot is stale once the event is old.
+fn local_snapshot(limits: &CodexRateLimits, now: DateTime<Utc>) -> UsageSnapshot {
+    let current = |window: &RateWindow| {
+        if window.resets_at.is_some_and(|resets_at| resets_at <= now) {
+            RateWindow::with_details(0.0, window.window_minutes, None, None)
+        } else {
+            window.clone()
+        }
+    };
+
+    let primary = limits.primary.as_ref().map(current).unwrap_or_default();
+    let mut usage = UsageSnapshot::new(primary)
+        .with_updated_at(limits.timestamp)
+        .with_stale(now - limits.timestamp > Duration::minutes(LOCAL_SNAPSHOT_STALE_MINUTES));
+    if let Some(secondary) = limits.secondary.as_ref() {
+        usage = usage.with_secondary(current(secondary));
+    }
+    if let Some(plan) = limits.plan_type.as_deref() {
+        usage = usage.with_login_method(api::plan_display_name(plan));
     }
+    usage
 }
 
 /// Try to find the codex CLI binary
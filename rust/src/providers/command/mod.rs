@@ -0,0 +1,237 @@
+//! External-command provider implementation
+//!
+//! Runs the executable named in the custom providers file and parses the
+//! versioned JSON document it prints on stdout.
+
+use async_trait::async_trait;
+use std::time::Duration;
+
+use crate::core::{
+    parse_command_output, CommandProviderConfig, FetchContext, Provider, ProviderError,
+    ProviderFetchKind, ProviderFetchPipeline, ProviderFetchResult, ProviderFetchStrategy,
+    ProviderId, ProviderMetadata, ProviderRegistry, COMMAND_PROVIDER_ENV_API_KEY,
+    COMMAND_PROVIDER_ENV_ID, COMMAND_PROVIDER_ENV_VERSION, COMMAND_PROVIDER_PROTOCOL_VERSION,
+    CUSTOM_PROVIDERS,
+};
+use crate::host::{CommandError, CommandOptions, CommandResult, CommandRunner};
+
+/// Provider backed by an external executable
+pub struct CommandProvider {
+    config: &'static CommandProviderConfig,
+    metadata: ProviderMetadata,
+}
+
+impl CommandProvider {
+    pub fn new(config: &'static CommandProviderConfig) -> Self {
+        Self {
+            config,
+            metadata: ProviderMetadata {
+                id: config.provider_id(),
+                display_name: config.name.as_str(),
+                session_label: "Session",
+                weekly_label: "Weekly",
+                supports_opus: false,
+                supports_credits: true,
+                default_enabled: false,
+                is_primary: false,
+                dashboard_url: config.dashboard_url.as_deref(),
+                status_page_url: None,
+            },
+        }
+    }
+
+    /// Run the command and map its output
+    async fn run_command(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        let config = self.config;
+
+        let mut runner = CommandRunner::new()
+            .with_env(COMMAND_PROVIDER_ENV_ID, config.id.as_str())
+            .with_env(COMMAND_PROVIDER_ENV_VERSION, COMMAND_PROVIDER_PROTOCOL_VERSION.to_string());
+        if let Some(key) = ctx.api_key.as_deref().filter(|k| !k.is_empty()) {
+            runner = runner.with_env(COMMAND_PROVIDER_ENV_API_KEY, key);
+        }
+        for (key, value) in &config.env {
+            runner = runner.with_env(key.as_str(), value.as_str());
+        }
+
+        let options = CommandOptions {
+            timeout: Duration::from_secs(config.timeout_secs),
+            working_directory: config.working_directory.clone(),
+            extra_args: config.args.clone(),
+            initial_delay: Duration::ZERO,
+            ..CommandOptions::default()
+        };
+
+        let outcome = runner.run_async(&config.command, None, &options).await;
+        let mut result = map_command_outcome(&config.name, outcome)?;
+        if !ctx.include_credits {
+            result.cost = None;
+        }
+        Ok(result)
+    }
+}
+
+/// Map a finished (or failed) command run onto a fetch result
+///
+/// An error document on stdout wins over the exit code, so commands can
+/// report `auth_required` and still exit non-zero. Otherwise a non-zero exit
+/// is reported with the last line of stderr.
+fn map_command_outcome(
+    name: &str,
+    outcome: Result<CommandResult, CommandError>,
+) -> Result<ProviderFetchResult, ProviderError> {
+    let result = match outcome {
+        Ok(result) => result,
+        Err(CommandError::BinaryNotFound(binary)) => {
+            return Err(ProviderError::NotInstalled(format!("{}: '{}' not found", name, binary)))
+        }
+        Err(CommandError::TimedOut) => return Err(ProviderError::Timeout),
+        Err(e) => return Err(ProviderError::Other(format!("{}: {}", name, e))),
+    };
+
+    if result.timed_out {
+        return Err(ProviderError::Timeout);
+    }
+
+    let parsed = parse_command_output(name, &result.text);
+    match (result.exit_code, parsed) {
+        (_, Err(error)) if !matches!(error, ProviderError::Parse(_)) => Err(error),
+        (Some(0), parsed) => parsed,
+        (code, _) => {
+            let status = code.map_or_else(|| "was terminated".to_string(), |c| format!("exited with code {}", c));
+            let detail = result
+                .stderr
+                .lines()
+                .rev()
+                .map(str::trim)
+                .find(|line| !line.is_empty())
+                .map(|line| format!(": {}", line))
+                .unwrap_or_default();
+            Err(ProviderError::Other(format!("{} command {}{}", name, status, detail)))
+        }
+    }
+}
+
+#[async_trait]
+impl Provider for CommandProvider {
+    fn id(&self) -> ProviderId {
+        self.metadata.id
+    }
+
+    fn metadata(&self) -> &ProviderMetadata {
+        &self.metadata
+    }
+
+    fn fetch_pipeline(&self) -> ProviderFetchPipeline<'_> {
+        ProviderFetchPipeline::empty().with_strategy(CommandStrategy { provider: self })
+    }
+}
+
+/// External-command strategy
+struct CommandStrategy<'a> {
+    provider: &'a CommandProvider,
+}
+
+#[async_trait]
+impl ProviderFetchStrategy for CommandStrategy<'_> {
+    fn id(&self) -> &str {
+        "command.exec"
+    }
+
+    fn kind(&self) -> ProviderFetchKind {
+        ProviderFetchKind::Cli
+    }
+
+    async fn fetch(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        self.provider.run_command(ctx).await
+    }
+}
+
+/// Register a provider for every `[[command]]` in the custom providers file
+pub fn register_command_providers(registry: &ProviderRegistry) {
+    for config in CUSTOM_PROVIDERS.commands.iter() {
+        registry.register(std::sync::Arc::new(CommandProvider::new(config)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUCCESS: &str = r#"{ "version": 1, "usage": { "primary": { "used_percent": 25.0 } } }"#;
+
+    fn finished(text: &str, stderr: &str, exit_code: Option<i32>) -> Result<CommandResult, CommandError> {
+        Ok(CommandResult {
+            text: text.to_string(),
+            stderr: stderr.to_string(),
+            timed_out: false,
+            exit_code,
+        })
+    }
+
+    #[test]
+    fn test_map_success() {
+        let result = map_command_outcome("Gateway", finished(SUCCESS, "", Some(0))).unwrap();
+        assert_eq!(result.usage.primary().used_percent, 25.0);
+    }
+
+    #[test]
+    fn test_map_runner_errors() {
+        let missing = map_command_outcome("Gateway", Err(CommandError::BinaryNotFound("gw".into())));
+        assert!(matches!(missing, Err(ProviderError::NotInstalled(_))));
+
+        let launch = map_command_outcome("Gateway", Err(CommandError::LaunchFailed("denied".into())));
+        assert!(matches!(launch, Err(ProviderError::Other(_))));
+
+        let mut slow = finished("", "", None).unwrap();
+        slow.timed_out = true;
+        assert!(matches!(map_command_outcome("Gateway", Ok(slow)), Err(ProviderError::Timeout)));
+    }
+
+    #[test]
+    fn test_map_exit_codes() {
+        let auth = r#"{ "version": 1, "error": { "kind": "auth_required" } }"#;
+        let result = map_command_outcome("Gateway", finished(auth, "", Some(2)));
+        assert!(matches!(result, Err(ProviderError::AuthRequired)));
+
+        match map_command_outcome("Gateway", finished("", "warming up\nno token found\n", Some(1))) {
+            Err(ProviderError::Other(message)) => {
+                assert_eq!(message, "Gateway command exited with code 1: no token found")
+            }
+            other => panic!("unexpected {:?}", other.map(|r| r.source_label)),
+        }
+
+        let garbage = map_command_outcome("Gateway", finished("hello", "", Some(0)));
+        assert!(matches!(garbage, Err(ProviderError::Parse(_))));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_command_end_to_end() {
+        let config: &'static CommandProviderConfig = Box::leak(Box::new(CommandProviderConfig {
+            id: "gateway".to_string(),
+            name: "Gateway".to_string(),
+            command: "sh".to_string(),
+            args: vec![
+                "-c".to_string(),
+                format!(
+                    r#"test "$CODEXBAR_PROVIDER_ID" = gateway && test "$CODEXBAR_API_KEY" = secret && echo '{}'"#,
+                    SUCCESS
+                ),
+            ],
+            env: Default::default(),
+            working_directory: None,
+            timeout_secs: 10,
+            dashboard_url: None,
+        }));
+        let provider = CommandProvider::new(config);
+        let ctx = FetchContext {
+            api_key: Some("secret".to_string()),
+            ..FetchContext::default()
+        };
+
+        let result = provider.run_command(&ctx).await.unwrap();
+        assert_eq!(result.usage.primary().used_percent, 25.0);
+        assert_eq!(result.source_label, "command");
+    }
+}
@@ -2,11 +2,12 @@
 //!
 //! Uses GitHub OAuth token stored in Windows Credential Manager
 
-use crate::core::{ProviderError, RateWindow, UsageSnapshot};
+use crate::core::{FetchContext, NamedRateWindow, ProviderError, RateWindow, RateWindowKind, UsageSnapshot};
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
 
-const API_URL: &str = "https://api.github.com/copilot_internal/user";
+const API_BASE: &str = "https://api.github.com";
+const USAGE_PATH: &str = "/copilot_internal/user";
 
 // Credential Manager targets to try
 const CREDENTIAL_TARGETS: &[&str] = &[
@@ -16,34 +17,27 @@ const CREDENTIAL_TARGETS: &[&str] = &[
 ];
 
 /// Copilot API client
-pub struct CopilotApi {
-    client: reqwest::Client,
-}
+pub struct CopilotApi;
 
 impl CopilotApi {
     pub fn new() -> Self {
-        // Build client with proper settings to avoid "builder error"
-        let client = reqwest::Client::builder()
-            .use_rustls_tls()
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
-            .unwrap_or_else(|_| reqwest::Client::new());
-
-        Self { client }
+        Self
     }
 
     /// Fetch usage information from Copilot API
-    pub async fn fetch_usage(&self, api_key: Option<&str>) -> Result<UsageSnapshot, ProviderError> {
+    pub async fn fetch_usage(&self, ctx: &FetchContext) -> Result<UsageSnapshot, ProviderError> {
         // Load token from provided api_key or credential store
-        let token = self.load_token(api_key)?;
+        let token = self.load_token(ctx.api_key.as_deref())?;
 
         // Build request with required headers
-        let response = self.client
-            .get(API_URL)
+        let response = crate::core::http_client(std::time::Duration::from_secs(30))
+            .map_err(|e| ProviderError::Other(e.to_string()))?
+            .get(ctx.endpoint(API_BASE, USAGE_PATH))
             .header("Authorization", format!("token {}", token))
             .header("Accept", "application/json")
             .header("Editor-Version", "vscode/1.96.2")
             .header("Editor-Plugin-Version", "copilot-chat/0.26.7")
+            // The Copilot API only answers clients that identify as the extension
             .header("User-Agent", "GitHubCopilotChat/0.26.7")
             .header("X-Github-Api-Version", "2025-04-01")
             .send()
@@ -55,10 +49,7 @@ impl CopilotApi {
         }
 
         if !response.status().is_success() {
-            return Err(ProviderError::Other(format!(
-                "GitHub Copilot API returned {}",
-                response.status()
-            )));
+            return Err(ProviderError::from_http_status(response.status(), "GitHub Copilot API"));
         }
 
         let usage_response: CopilotUsageResponse = response
@@ -160,39 +151,39 @@ impl CopilotApi {
     }
 
     fn build_snapshot(&self, response: CopilotUsageResponse) -> Result<UsageSnapshot, ProviderError> {
-        // Build primary rate window from premium_interactions
-        let primary = response.quota_snapshots.premium_interactions
-            .as_ref()
-            .map(|snapshot| {
-                let used_percent = (100.0 - snapshot.percent_remaining).max(0.0);
-                RateWindow::with_details(
-                    used_percent,
-                    None, // Window not provided
-                    parse_iso_date(&response.quota_reset_date),
-                    None,
-                )
-            })
-            .unwrap_or_else(|| RateWindow::new(0.0));
+        let reset_at = parse_iso_date(&response.quota_reset_date);
+        let to_window = |snapshot: &QuotaSnapshot| {
+            let used_percent = (100.0 - snapshot.percent_remaining).max(0.0);
+            RateWindow::with_details(
+                used_percent,
+                None, // Window not provided
+                reset_at,
+                None,
+            )
+        };
 
-        // Build secondary rate window from chat
-        let secondary = response.quota_snapshots.chat
+        // Primary: premium requests, secondary: chat, plus completions when reported
+        let snapshots = &response.quota_snapshots;
+        let primary = snapshots.premium_interactions
             .as_ref()
-            .map(|snapshot| {
-                let used_percent = (100.0 - snapshot.percent_remaining).max(0.0);
-                RateWindow::with_details(
-                    used_percent,
-                    None,
-                    parse_iso_date(&response.quota_reset_date),
-                    None,
-                )
-            });
+            .map(to_window)
+            .unwrap_or_else(|| RateWindow::new(0.0));
 
         // Format plan type
         let plan_type = format!("Copilot {}", capitalize(&response.copilot_plan));
 
-        let mut usage = UsageSnapshot::new(primary);
-        if let Some(sec) = secondary {
-            usage = usage.with_secondary(sec);
+        let mut usage = UsageSnapshot::from_window(
+            NamedRateWindow::new(NamedRateWindow::PRIMARY_ID, "Premium", RateWindowKind::Credits, primary),
+        );
+        if let Some(chat) = snapshots.chat.as_ref().map(to_window) {
+            usage = usage.with_window(
+                NamedRateWindow::new(NamedRateWindow::SECONDARY_ID, "Chat", RateWindowKind::Monthly, chat),
+            );
+        }
+        if let Some(completions) = snapshots.completions.as_ref().map(to_window) {
+            usage = usage.with_window(
+                NamedRateWindow::new("completions", "Completions", RateWindowKind::Monthly, completions),
+            );
         }
         usage = usage.with_login_method(plan_type);
 
@@ -220,6 +211,7 @@ struct CopilotUsageResponse {
 struct QuotaSnapshots {
     premium_interactions: Option<QuotaSnapshot>,
     chat: Option<QuotaSnapshot>,
+    completions: Option<QuotaSnapshot>,
 }
 
 #[derive(Debug, Deserialize)]
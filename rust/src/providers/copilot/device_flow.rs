@@ -81,15 +81,11 @@ impl std::fmt::Display for DeviceFlowError {
 impl std::error::Error for DeviceFlowError {}
 
 /// Copilot Device Flow OAuth client
-pub struct CopilotDeviceFlow {
-    client: reqwest::Client,
-}
+pub struct CopilotDeviceFlow;
 
 impl CopilotDeviceFlow {
     pub fn new() -> Self {
-        Self {
-            client: reqwest::Client::new(),
-        }
+        Self
     }
 
     /// Request a device code from GitHub
@@ -104,8 +100,8 @@ impl CopilotDeviceFlow {
         params.insert("client_id", CLIENT_ID);
         params.insert("scope", SCOPES);
 
-        let response = self
-            .client
+        let response = crate::core::http_client(std::time::Duration::from_secs(30))
+            .map_err(|e| DeviceFlowError::Network(e.to_string()))?
             .post("https://github.com/login/device/code")
             .header("Accept", "application/json")
             .form(&params)
@@ -137,8 +133,8 @@ impl CopilotDeviceFlow {
         params.insert("device_code", device_code);
         params.insert("grant_type", "urn:ietf:params:oauth:grant-type:device_code");
 
-        let response = self
-            .client
+        let response = crate::core::http_client(std::time::Duration::from_secs(30))
+            .map_err(|e| DeviceFlowError::Network(e.to_string()))?
             .post("https://github.com/login/oauth/access_token")
             .header("Accept", "application/json")
             .form(&params)
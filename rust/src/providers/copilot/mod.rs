@@ -8,8 +8,8 @@ pub mod device_flow;
 use async_trait::async_trait;
 
 use crate::core::{
-    FetchContext, Provider, ProviderId, ProviderError, ProviderFetchResult,
-    ProviderMetadata, SourceMode,
+    FetchContext, Provider, ProviderError, ProviderFetchKind, ProviderFetchPipeline,
+    ProviderFetchResult, ProviderFetchStrategy, ProviderId, ProviderMetadata,
 };
 
 pub use api::CopilotApi;
@@ -56,10 +56,28 @@ impl Provider for CopilotProvider {
         &self.metadata
     }
 
-    async fn fetch_usage(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
-        tracing::debug!("Fetching GitHub Copilot usage via API");
+    fn fetch_pipeline(&self) -> ProviderFetchPipeline<'_> {
+        ProviderFetchPipeline::empty().with_strategy(CopilotOAuthStrategy { provider: self })
+    }
+}
+
+/// GitHub Copilot internal API strategy
+struct CopilotOAuthStrategy<'a> {
+    provider: &'a CopilotProvider,
+}
+
+#[async_trait]
+impl ProviderFetchStrategy for CopilotOAuthStrategy<'_> {
+    fn id(&self) -> &str {
+        "copilot.oauth"
+    }
 
-        match self.api.fetch_usage(ctx.api_key.as_deref()).await {
+    fn kind(&self) -> ProviderFetchKind {
+        ProviderFetchKind::OAuth
+    }
+
+    async fn fetch(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        match self.provider.api.fetch_usage(ctx).await {
             Ok(usage) => Ok(ProviderFetchResult::new(usage, "oauth")),
             Err(e) => {
                 tracing::warn!("Copilot API fetch failed: {}", e);
@@ -67,12 +85,4 @@ impl Provider for CopilotProvider {
             }
         }
     }
-
-    fn available_sources(&self) -> Vec<SourceMode> {
-        vec![SourceMode::Auto, SourceMode::OAuth]
-    }
-
-    fn supports_oauth(&self) -> bool {
-        true
-    }
 }
@@ -3,7 +3,7 @@
 //! Uses browser cookies to authenticate with cursor.com API
 
 use crate::browser::cookies::get_cookie_header;
-use crate::core::{CostSnapshot, ProviderError, RateWindow};
+use crate::core::{CostSnapshot, FetchContext, ProviderError, RateWindow};
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
 
@@ -11,27 +11,26 @@ const BASE_URL: &str = "https://cursor.com";
 const COOKIE_DOMAINS: [&str; 2] = ["cursor.com", "cursor.sh"];
 
 /// Cursor API client
-pub struct CursorApi {
-    client: reqwest::Client,
-}
+pub struct CursorApi;
 
 impl CursorApi {
     pub fn new() -> Self {
-        Self {
-            client: reqwest::Client::new(),
-        }
+        Self
     }
 
     /// Fetch usage information from Cursor API
     /// Returns (primary RateWindow, optional CostSnapshot, optional email, optional plan_type)
-    pub async fn fetch_usage(&self) -> Result<(RateWindow, Option<CostSnapshot>, Option<String>, Option<String>), ProviderError> {
-        // Try to get cookies from browser
-        let cookie_header = self.get_cookie_header()?;
+    pub async fn fetch_usage(&self, ctx: &FetchContext) -> Result<(RateWindow, Option<CostSnapshot>, Option<String>, Option<String>), ProviderError> {
+        // Prefer a manual cookie header, then try browser cookies
+        let cookie_header = match ctx.manual_cookie_header {
+            Some(ref header) if !header.is_empty() => header.clone(),
+            _ => self.get_cookie_header()?,
+        };
 
         // Fetch usage summary and user info in parallel
         let (usage_result, user_result) = tokio::join!(
-            self.fetch_usage_summary(&cookie_header),
-            self.fetch_user_info(&cookie_header)
+            self.fetch_usage_summary(ctx, &cookie_header),
+            self.fetch_user_info(ctx, &cookie_header)
         );
 
         let usage_summary = usage_result?;
@@ -61,16 +60,16 @@ impl CursorApi {
 
     async fn fetch_usage_summary(
         &self,
+        ctx: &FetchContext,
         cookie_header: &str,
     ) -> Result<UsageSummary, ProviderError> {
-        let url = format!("{}/api/usage-summary", BASE_URL);
+        let url = ctx.endpoint(BASE_URL, "/api/usage-summary");
 
-        let response = self
-            .client
+        let response = crate::core::http_client(std::time::Duration::from_secs(15))
+            .map_err(|e| ProviderError::Other(e.to_string()))?
             .get(&url)
             .header("Cookie", cookie_header)
             .header("Accept", "application/json")
-            .timeout(std::time::Duration::from_secs(15))
             .send()
             .await?;
 
@@ -79,10 +78,7 @@ impl CursorApi {
         }
 
         if !response.status().is_success() {
-            return Err(ProviderError::Other(format!(
-                "Cursor API returned {}",
-                response.status()
-            )));
+            return Err(ProviderError::from_http_status(response.status(), "Cursor API"));
         }
 
         response
@@ -91,15 +87,14 @@ impl CursorApi {
             .map_err(|e| ProviderError::Parse(e.to_string()))
     }
 
-    async fn fetch_user_info(&self, cookie_header: &str) -> Result<UserInfo, ProviderError> {
-        let url = format!("{}/api/auth/me", BASE_URL);
+    async fn fetch_user_info(&self, ctx: &FetchContext, cookie_header: &str) -> Result<UserInfo, ProviderError> {
+        let url = ctx.endpoint(BASE_URL, "/api/auth/me");
 
-        let response = self
-            .client
+        let response = crate::core::http_client(std::time::Duration::from_secs(15))
+            .map_err(|e| ProviderError::Other(e.to_string()))?
             .get(&url)
             .header("Cookie", cookie_header)
             .header("Accept", "application/json")
-            .timeout(std::time::Duration::from_secs(15))
             .send()
             .await?;
 
@@ -7,8 +7,8 @@ mod api;
 use async_trait::async_trait;
 
 use crate::core::{
-    FetchContext, Provider, ProviderId, ProviderError, ProviderFetchResult,
-    ProviderMetadata, SourceMode, UsageSnapshot,
+    FetchContext, Provider, ProviderError, ProviderFetchKind, ProviderFetchPipeline,
+    ProviderFetchResult, ProviderFetchStrategy, ProviderId, ProviderMetadata, UsageSnapshot,
 };
 
 pub use api::CursorApi;
@@ -55,10 +55,28 @@ impl Provider for CursorProvider {
         &self.metadata
     }
 
-    async fn fetch_usage(&self, _ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
-        tracing::debug!("Fetching Cursor usage via web API");
+    fn fetch_pipeline(&self) -> ProviderFetchPipeline<'_> {
+        ProviderFetchPipeline::empty().with_strategy(CursorWebStrategy { provider: self })
+    }
+}
+
+/// Cursor web API strategy (browser cookies)
+struct CursorWebStrategy<'a> {
+    provider: &'a CursorProvider,
+}
+
+#[async_trait]
+impl ProviderFetchStrategy for CursorWebStrategy<'_> {
+    fn id(&self) -> &str {
+        "cursor.web"
+    }
 
-        match self.api.fetch_usage().await {
+    fn kind(&self) -> ProviderFetchKind {
+        ProviderFetchKind::Web
+    }
+
+    async fn fetch(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        match self.provider.api.fetch_usage(ctx).await {
             Ok((primary, cost, email, plan_type)) => {
                 let mut usage = UsageSnapshot::new(primary);
                 if let Some(e) = email {
@@ -80,12 +98,4 @@ impl Provider for CursorProvider {
             }
         }
     }
-
-    fn available_sources(&self) -> Vec<SourceMode> {
-        vec![SourceMode::Auto, SourceMode::Web]
-    }
-
-    fn supports_web(&self) -> bool {
-        true
-    }
 }
@@ -0,0 +1,127 @@
+//! Custom HTTP provider implementation
+//!
+//! Fetches a JSON usage endpoint described in the custom providers file and
+//! maps the response through the configured JSON paths.
+
+use async_trait::async_trait;
+
+use crate::core::{
+    CustomProviderConfig, FetchContext, Provider, ProviderError, ProviderFetchKind,
+    ProviderFetchPipeline, ProviderFetchResult, ProviderFetchStrategy, ProviderId,
+    ProviderMetadata, ProviderRegistry, CUSTOM_PROVIDERS,
+};
+
+/// Provider backed by a declarative custom provider definition
+pub struct CustomHttpProvider {
+    config: &'static CustomProviderConfig,
+    metadata: ProviderMetadata,
+}
+
+impl CustomHttpProvider {
+    pub fn new(config: &'static CustomProviderConfig) -> Self {
+        Self {
+            config,
+            metadata: ProviderMetadata {
+                id: config.provider_id(),
+                display_name: config.name.as_str(),
+                session_label: config.mapping.window_label.as_str(),
+                weekly_label: "Weekly",
+                supports_opus: false,
+                supports_credits: config.mapping.cost_used.is_some(),
+                default_enabled: false,
+                is_primary: false,
+                dashboard_url: config.dashboard_url.as_deref(),
+                status_page_url: None,
+            },
+        }
+    }
+
+    /// Fetch and map the configured endpoint
+    async fn fetch_endpoint(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        let config = self.config;
+        let url = config.request_url(ctx.base_url.as_deref());
+
+        let client = crate::core::http_client(std::time::Duration::from_secs(config.timeout_secs))
+            .map_err(|e| ProviderError::Other(e.to_string()))?;
+        let mut request = client.get(&url).header("Accept", "application/json");
+        for (name, value) in &config.headers {
+            request = request.header(name, value);
+        }
+        if let Some(ref auth) = config.auth {
+            let key = config
+                .api_key(ctx.api_key.as_deref())
+                .ok_or(ProviderError::AuthRequired)?;
+            request = request.header(&auth.header, format!("{}{}", auth.prefix, key));
+        }
+
+        let response = request.send().await.map_err(|e| {
+            if e.is_timeout() {
+                ProviderError::Timeout
+            } else {
+                ProviderError::Network(e.to_string())
+            }
+        })?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::FORBIDDEN {
+            return Err(ProviderError::AuthRequired);
+        }
+        if !status.is_success() {
+            return Err(ProviderError::from_http_status(status, &config.name));
+        }
+
+        let json: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| ProviderError::Parse(e.to_string()))?;
+
+        let (usage, cost) = config.parse_response(&json)?;
+        let mut result = ProviderFetchResult::new(usage, "api");
+        if let Some(cost) = cost.filter(|_| ctx.include_credits) {
+            result = result.with_cost(cost);
+        }
+        Ok(result)
+    }
+}
+
+#[async_trait]
+impl Provider for CustomHttpProvider {
+    fn id(&self) -> ProviderId {
+        self.metadata.id
+    }
+
+    fn metadata(&self) -> &ProviderMetadata {
+        &self.metadata
+    }
+
+    fn fetch_pipeline(&self) -> ProviderFetchPipeline<'_> {
+        ProviderFetchPipeline::empty().with_strategy(CustomHttpStrategy { provider: self })
+    }
+}
+
+/// Custom JSON endpoint strategy
+struct CustomHttpStrategy<'a> {
+    provider: &'a CustomHttpProvider,
+}
+
+#[async_trait]
+impl ProviderFetchStrategy for CustomHttpStrategy<'_> {
+    fn id(&self) -> &str {
+        "custom.http"
+    }
+
+    fn kind(&self) -> ProviderFetchKind {
+        ProviderFetchKind::ApiToken
+    }
+
+    async fn fetch(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        self.provider.fetch_endpoint(ctx).await
+    }
+}
+
+/// Register a provider for every `[[provider]]` in the custom providers file
+pub fn register_custom_providers(registry: &ProviderRegistry) {
+    for config in CUSTOM_PROVIDERS.providers.iter() {
+        registry.register(std::sync::Arc::new(CustomHttpProvider::new(config)));
+    }
+}
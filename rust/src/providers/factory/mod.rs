@@ -9,13 +9,15 @@ use serde::Deserialize;
 use crate::browser::cookies::CookieExtractor;
 use crate::browser::detection::BrowserDetector;
 use crate::core::{
-    FetchContext, Provider, ProviderId, ProviderError, ProviderFetchResult,
-    ProviderMetadata, RateWindow, SourceMode, UsageSnapshot,
+    FetchContext, Provider, ProviderError, ProviderFetchKind, ProviderFetchPipeline,
+    ProviderFetchResult, ProviderFetchStrategy, ProviderId, ProviderMetadata, RateWindow,
+    UsageSnapshot,
 };
 
-/// Factory.ai API endpoints
-const FACTORY_AUTH_URL: &str = "https://app.factory.ai/api/app/auth/me";
-const FACTORY_USAGE_URL: &str = "https://app.factory.ai/api/organization/subscription/usage";
+/// Factory.ai API base URL and endpoint paths
+const FACTORY_API_BASE: &str = "https://app.factory.ai";
+const FACTORY_AUTH_PATH: &str = "/api/app/auth/me";
+const FACTORY_USAGE_PATH: &str = "/api/organization/subscription/usage";
 
 /// Factory usage response
 #[derive(Debug, Deserialize)]
@@ -75,8 +77,14 @@ impl FactoryProvider {
         }
     }
 
-    /// Get cookies for Factory.ai from browser
-    fn get_cookies(&self) -> Result<String, ProviderError> {
+    /// Get cookies for Factory.ai (manual cookie header first, then browser)
+    fn get_cookies(&self, ctx: &FetchContext) -> Result<String, ProviderError> {
+        if let Some(ref cookie) = ctx.manual_cookie_header {
+            if !cookie.is_empty() {
+                return Ok(cookie.clone());
+            }
+        }
+
         let browsers = BrowserDetector::detect_all();
 
         if browsers.is_empty() {
@@ -101,28 +109,19 @@ impl FactoryProvider {
     }
 
     /// Fetch auth info from Factory API
-    async fn fetch_auth_info(&self, cookies: &str) -> Result<FactoryAuthResponse, ProviderError> {
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
+    async fn fetch_auth_info(&self, ctx: &FetchContext, cookies: &str) -> Result<FactoryAuthResponse, ProviderError> {
+        let client = crate::core::http_client(std::time::Duration::from_secs(30))
             .map_err(|e| ProviderError::Other(e.to_string()))?;
 
         let resp = client
-            .get(FACTORY_AUTH_URL)
+            .get(ctx.endpoint(FACTORY_API_BASE, FACTORY_AUTH_PATH))
             .header("Cookie", cookies)
             .header("Accept", "application/json")
             .send()
             .await?;
 
-        if resp.status() == reqwest::StatusCode::UNAUTHORIZED {
-            return Err(ProviderError::AuthRequired);
-        }
-
         if !resp.status().is_success() {
-            return Err(ProviderError::Other(format!(
-                "Factory auth API returned status {}",
-                resp.status()
-            )));
+            return Err(ProviderError::from_http_status(resp.status(), "Factory auth API"));
         }
 
         resp.json().await
@@ -130,28 +129,19 @@ impl FactoryProvider {
     }
 
     /// Fetch usage from Factory API
-    async fn fetch_usage_api(&self, cookies: &str) -> Result<FactoryUsageResponse, ProviderError> {
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
+    async fn fetch_usage_api(&self, ctx: &FetchContext, cookies: &str) -> Result<FactoryUsageResponse, ProviderError> {
+        let client = crate::core::http_client(std::time::Duration::from_secs(30))
             .map_err(|e| ProviderError::Other(e.to_string()))?;
 
         let resp = client
-            .get(FACTORY_USAGE_URL)
+            .get(ctx.endpoint(FACTORY_API_BASE, FACTORY_USAGE_PATH))
             .header("Cookie", cookies)
             .header("Accept", "application/json")
             .send()
             .await?;
 
-        if resp.status() == reqwest::StatusCode::UNAUTHORIZED {
-            return Err(ProviderError::AuthRequired);
-        }
-
         if !resp.status().is_success() {
-            return Err(ProviderError::Other(format!(
-                "Factory usage API returned status {}",
-                resp.status()
-            )));
+            return Err(ProviderError::from_http_status(resp.status(), "Factory usage API"));
         }
 
         resp.json().await
@@ -159,12 +149,12 @@ impl FactoryProvider {
     }
 
     /// Fetch usage via web cookies
-    async fn fetch_via_web(&self) -> Result<UsageSnapshot, ProviderError> {
-        let cookies = self.get_cookies()?;
+    async fn fetch_via_web(&self, ctx: &FetchContext) -> Result<UsageSnapshot, ProviderError> {
+        let cookies = self.get_cookies(ctx)?;
 
         // Fetch auth info and usage in parallel conceptually, but sequentially here
-        let auth_info = self.fetch_auth_info(&cookies).await.ok();
-        let usage_data = self.fetch_usage_api(&cookies).await?;
+        let auth_info = self.fetch_auth_info(ctx, &cookies).await.ok();
+        let usage_data = self.fetch_usage_api(ctx, &cookies).await?;
 
         // Calculate standard tokens usage
         let standard_percent = if let Some(ref standard) = usage_data.standard {
@@ -245,30 +235,28 @@ impl Provider for FactoryProvider {
         &self.metadata
     }
 
-    async fn fetch_usage(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
-        tracing::debug!("Fetching Droid (Factory) usage");
-
-        match ctx.source_mode {
-            SourceMode::Auto | SourceMode::Web => {
-                let usage = self.fetch_via_web().await?;
-                Ok(ProviderFetchResult::new(usage, "web"))
-            }
-            SourceMode::Cli | SourceMode::OAuth => {
-                // Droid doesn't have CLI or OAuth support
-                Err(ProviderError::UnsupportedSource(ctx.source_mode))
-            }
-        }
+    fn fetch_pipeline(&self) -> ProviderFetchPipeline<'_> {
+        ProviderFetchPipeline::empty().with_strategy(FactoryWebStrategy { provider: self })
     }
+}
 
-    fn available_sources(&self) -> Vec<SourceMode> {
-        vec![SourceMode::Auto, SourceMode::Web]
+/// Factory web API strategy (browser cookies)
+struct FactoryWebStrategy<'a> {
+    provider: &'a FactoryProvider,
+}
+
+#[async_trait]
+impl ProviderFetchStrategy for FactoryWebStrategy<'_> {
+    fn id(&self) -> &str {
+        "factory.web"
     }
 
-    fn supports_web(&self) -> bool {
-        true
+    fn kind(&self) -> ProviderFetchKind {
+        ProviderFetchKind::Web
     }
 
-    fn supports_cli(&self) -> bool {
-        false
+    async fn fetch(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        let usage = self.provider.fetch_via_web(ctx).await?;
+        Ok(ProviderFetchResult::new(usage, "web"))
     }
 }
@@ -2,35 +2,34 @@
 //!
 //! Uses Google Cloud Code Private API with OAuth tokens from ~/.gemini/oauth_creds.json
 
-use crate::core::{FetchContext, ProviderError, RateWindow};
+use crate::core::{FetchContext, NamedRateWindow, ProviderError, RateWindow, RateWindowKind};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
-const QUOTA_ENDPOINT: &str = "https://cloudcode-pa.googleapis.com/v1internal:retrieveUserQuota";
+const QUOTA_API_BASE: &str = "https://cloudcode-pa.googleapis.com";
+const QUOTA_PATH: &str = "/v1internal:retrieveUserQuota";
 const TOKEN_REFRESH_ENDPOINT: &str = "https://oauth2.googleapis.com/token";
 
 /// Gemini API client
 pub struct GeminiApi {
-    client: reqwest::Client,
     home_dir: PathBuf,
 }
 
 impl GeminiApi {
     pub fn new() -> Self {
         Self {
-            client: reqwest::Client::new(),
             home_dir: dirs::home_dir().unwrap_or_else(|| PathBuf::from(".")),
         }
     }
 
     /// Fetch quota information from the Gemini API
-    /// Returns (primary RateWindow, optional model-specific RateWindow, optional email)
+    /// Returns (primary RateWindow, one window per model quota bucket, optional email)
     /// Note: Gemini quota API requires OAuth tokens, not API keys
-    pub async fn fetch_quota(&self, _ctx: &FetchContext) -> Result<(RateWindow, Option<RateWindow>, Option<String>), ProviderError> {
-        // Gemini quota endpoint requires OAuth credentials (not API keys)
-        // Always load OAuth credentials from ~/.gemini/oauth_creds.json
-        let mut creds = self.load_credentials()?;
+    pub async fn fetch_quota(&self, ctx: &FetchContext) -> Result<(RateWindow, Vec<NamedRateWindow>, Option<String>), ProviderError> {
+        // Gemini quota endpoint requires OAuth credentials (not API keys):
+        // an access token from the context, else ~/.gemini/oauth_creds.json
+        let mut creds = self.load_credentials(ctx.api_key.as_deref())?;
 
         // Check if token needs refresh
         if creds.is_expired() {
@@ -39,15 +38,15 @@ impl GeminiApi {
         }
 
         let access_token = creds.access_token.clone()
-            .ok_or_else(|| ProviderError::AuthRequired)?;
+            .ok_or(ProviderError::AuthRequired)?;
 
         // Fetch quota
-        let response = self.client
-            .post(QUOTA_ENDPOINT)
+        let response = crate::core::http_client(std::time::Duration::from_secs(10))
+            .map_err(|e| ProviderError::Other(e.to_string()))?
+            .post(ctx.endpoint(QUOTA_API_BASE, QUOTA_PATH))
             .header("Authorization", format!("Bearer {}", access_token))
             .header("Content-Type", "application/json")
             .body("{}")
-            .timeout(std::time::Duration::from_secs(10))
             .send()
             .await?;
 
@@ -56,10 +55,7 @@ impl GeminiApi {
         }
 
         if !response.status().is_success() {
-            return Err(ProviderError::Other(format!(
-                "Gemini API returned {}",
-                response.status()
-            )));
+            return Err(ProviderError::from_http_status(response.status(), "Gemini API"));
         }
 
         let quota_response: QuotaResponse = response
@@ -71,7 +67,17 @@ impl GeminiApi {
         self.parse_quota_response(quota_response, Some(&creds))
     }
 
-    fn load_credentials(&self) -> Result<OAuthCredentials, ProviderError> {
+    fn load_credentials(&self, access_token: Option<&str>) -> Result<OAuthCredentials, ProviderError> {
+        // Check access_token first (from settings/ctx)
+        if let Some(token) = access_token.map(str::trim).filter(|token| !token.is_empty()) {
+            return Ok(OAuthCredentials {
+                access_token: Some(token.to_string()),
+                id_token: None,
+                refresh_token: None,
+                expiry_date: None,
+            });
+        }
+
         let creds_path = self.home_dir.join(".gemini").join("oauth_creds.json");
 
         if !creds_path.exists() {
@@ -89,7 +95,7 @@ impl GeminiApi {
 
     async fn refresh_token(&self, creds: &OAuthCredentials) -> Result<OAuthCredentials, ProviderError> {
         let refresh_token = creds.refresh_token.as_ref()
-            .ok_or_else(|| ProviderError::AuthRequired)?;
+            .ok_or(ProviderError::AuthRequired)?;
 
         // Get OAuth client credentials from Gemini CLI
         let client_creds = self.extract_oauth_client_credentials()?;
@@ -101,10 +107,10 @@ impl GeminiApi {
             ("grant_type", "refresh_token"),
         ];
 
-        let response = self.client
+        let response = crate::core::http_client(std::time::Duration::from_secs(10))
+            .map_err(|e| ProviderError::Other(e.to_string()))?
             .post(TOKEN_REFRESH_ENDPOINT)
             .form(&params)
-            .timeout(std::time::Duration::from_secs(10))
             .send()
             .await?;
 
@@ -183,7 +189,7 @@ impl GeminiApi {
         &self,
         response: QuotaResponse,
         creds: Option<&OAuthCredentials>,
-    ) -> Result<(RateWindow, Option<RateWindow>, Option<String>), ProviderError> {
+    ) -> Result<(RateWindow, Vec<NamedRateWindow>, Option<String>), ProviderError> {
         let buckets = response.buckets.ok_or_else(|| {
             ProviderError::Parse("No quota buckets in response".to_string())
         })?;
@@ -235,23 +241,30 @@ impl GeminiApi {
             None,
         );
 
-        // Model-specific window for Flash if Pro is primary
-        let model_specific = if pro_quota.is_some() {
-            flash_quota.map(|(_, (frac, reset))| {
+        // One window per model bucket, Flash models first so the legacy
+        // model-specific field keeps reporting Flash
+        let mut models: Vec<(&String, &(f64, Option<String>))> = model_quotas.iter().collect();
+        models.sort_by_key(|(model_id, _)| (!model_id.to_lowercase().contains("flash"), model_id.to_string()));
+        let model_windows = models
+            .into_iter()
+            .map(|(model_id, (frac, reset))| {
                 let percent_used = (1.0 - frac) * 100.0;
                 let reset_at = reset.as_ref().and_then(|s| parse_iso_date(s));
-                RateWindow::with_details(percent_used, Some(1440), reset_at, None)
+                NamedRateWindow::new(
+                    model_id.clone(),
+                    model_id.clone(),
+                    RateWindowKind::Model,
+                    RateWindow::with_details(percent_used, Some(1440), reset_at, None),
+                )
             })
-        } else {
-            None
-        };
+            .collect();
 
         // Extract email from ID token
         let email = creds
             .and_then(|c| c.id_token.as_ref())
             .and_then(|token| extract_email_from_jwt(token));
 
-        Ok((primary, model_specific, email))
+        Ok((primary, model_windows, email))
     }
 }
 
@@ -8,8 +8,8 @@ mod api;
 use async_trait::async_trait;
 
 use crate::core::{
-    FetchContext, Provider, ProviderId, ProviderError, ProviderFetchResult,
-    ProviderMetadata, SourceMode, UsageSnapshot,
+    FetchContext, Provider, ProviderError, ProviderFetchKind, ProviderFetchPipeline,
+    ProviderFetchResult, ProviderFetchStrategy, ProviderId, ProviderMetadata, UsageSnapshot,
 };
 
 pub use api::GeminiApi;
@@ -56,14 +56,32 @@ impl Provider for GeminiProvider {
         &self.metadata
     }
 
-    async fn fetch_usage(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
-        tracing::debug!("Fetching Gemini usage via API");
+    fn fetch_pipeline(&self) -> ProviderFetchPipeline<'_> {
+        ProviderFetchPipeline::empty().with_strategy(GeminiCliStrategy { provider: self })
+    }
+}
+
+/// Quota API strategy using the Gemini CLI's OAuth credentials
+struct GeminiCliStrategy<'a> {
+    provider: &'a GeminiProvider,
+}
+
+#[async_trait]
+impl ProviderFetchStrategy for GeminiCliStrategy<'_> {
+    fn id(&self) -> &str {
+        "gemini.cli"
+    }
 
-        match self.api.fetch_quota(ctx).await {
-            Ok((primary, model_specific, email)) => {
+    fn kind(&self) -> ProviderFetchKind {
+        ProviderFetchKind::Cli
+    }
+
+    async fn fetch(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        match self.provider.api.fetch_quota(ctx).await {
+            Ok((primary, model_windows, email)) => {
                 let mut usage = UsageSnapshot::new(primary);
-                if let Some(ms) = model_specific {
-                    usage = usage.with_model_specific(ms);
+                for window in model_windows {
+                    usage = usage.with_window(window);
                 }
                 if let Some(e) = email {
                     usage = usage.with_email(e);
@@ -78,12 +96,4 @@ impl Provider for GeminiProvider {
             }
         }
     }
-
-    fn available_sources(&self) -> Vec<SourceMode> {
-        vec![SourceMode::Auto, SourceMode::Cli]
-    }
-
-    fn supports_cli(&self) -> bool {
-        true
-    }
 }
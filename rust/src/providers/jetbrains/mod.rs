@@ -9,8 +9,9 @@ use async_trait::async_trait;
 use std::path::PathBuf;
 
 use crate::core::{
-    FetchContext, Provider, ProviderId, ProviderError, ProviderFetchResult,
-    ProviderMetadata, RateWindow, SourceMode, UsageSnapshot,
+    FetchContext, Provider, ProviderError, ProviderFetchKind, ProviderFetchPipeline,
+    ProviderFetchResult, ProviderFetchStrategy, ProviderId, ProviderMetadata, RateWindow,
+    UsageSnapshot,
 };
 
 /// JetBrains AI provider
@@ -190,30 +191,28 @@ impl Provider for JetBrainsProvider {
         &self.metadata
     }
 
-    async fn fetch_usage(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
-        tracing::debug!("Fetching JetBrains AI usage");
-
-        match ctx.source_mode {
-            SourceMode::Auto | SourceMode::Cli => {
-                let usage = self.read_local_config().await?;
-                Ok(ProviderFetchResult::new(usage, "local"))
-            }
-            SourceMode::Web | SourceMode::OAuth => {
-                // JetBrains AI doesn't have web API access
-                Err(ProviderError::UnsupportedSource(ctx.source_mode))
-            }
-        }
+    fn fetch_pipeline(&self) -> ProviderFetchPipeline<'_> {
+        ProviderFetchPipeline::empty().with_strategy(JetBrainsLocalStrategy { provider: self })
     }
+}
+
+/// Local IDE quota file strategy
+struct JetBrainsLocalStrategy<'a> {
+    provider: &'a JetBrainsProvider,
+}
 
-    fn available_sources(&self) -> Vec<SourceMode> {
-        vec![SourceMode::Auto, SourceMode::Cli]
+#[async_trait]
+impl ProviderFetchStrategy for JetBrainsLocalStrategy<'_> {
+    fn id(&self) -> &str {
+        "jetbrains.local"
     }
 
-    fn supports_web(&self) -> bool {
-        false
+    fn kind(&self) -> ProviderFetchKind {
+        ProviderFetchKind::LocalProbe
     }
 
-    fn supports_cli(&self) -> bool {
-        true
+    async fn fetch(&self, _ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        let usage = self.provider.read_local_config().await?;
+        Ok(ProviderFetchResult::new(usage, "local"))
     }
 }
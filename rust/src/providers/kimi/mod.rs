@@ -8,8 +8,9 @@ use async_trait::async_trait;
 
 use crate::browser::cookies::get_cookie_header;
 use crate::core::{
-    FetchContext, Provider, ProviderId, ProviderError, ProviderFetchResult,
-    ProviderMetadata, RateWindow, SourceMode, UsageSnapshot,
+    FetchContext, Provider, ProviderError, ProviderFetchKind, ProviderFetchPipeline,
+    ProviderFetchResult, ProviderFetchStrategy, ProviderId, ProviderMetadata, RateWindow,
+    UsageSnapshot,
 };
 
 const KIMI_API_BASE: &str = "https://kimi.moonshot.cn";
@@ -39,10 +40,13 @@ impl KimiProvider {
     }
 
     /// Extract JWT token from kimi-auth cookie
-    fn get_auth_token(&self) -> Result<String, ProviderError> {
-        // Try to get cookies from browser
-        let cookies = get_cookie_header(KIMI_COOKIE_DOMAIN)
-            .map_err(|e| ProviderError::Other(format!("Failed to get cookies: {}", e)))?;
+    fn get_auth_token(&self, ctx: &FetchContext) -> Result<String, ProviderError> {
+        // Prefer a manual cookie header, then fall back to the browser
+        let cookies = match ctx.manual_cookie_header {
+            Some(ref cookie) if !cookie.is_empty() => cookie.clone(),
+            _ => get_cookie_header(KIMI_COOKIE_DOMAIN)
+                .map_err(|e| ProviderError::Other(format!("Failed to get cookies: {}", e)))?,
+        };
 
         if cookies.is_empty() {
             return Err(ProviderError::AuthRequired);
@@ -74,20 +78,19 @@ impl KimiProvider {
     }
 
     /// Fetch usage via Kimi web API
-    async fn fetch_via_web(&self) -> Result<UsageSnapshot, ProviderError> {
-        let token = self.get_auth_token()?;
+    async fn fetch_via_web(&self, ctx: &FetchContext) -> Result<UsageSnapshot, ProviderError> {
+        let token = self.get_auth_token(ctx)?;
 
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
+        let client = crate::core::http_client(std::time::Duration::from_secs(30))
             .map_err(|e| ProviderError::Other(e.to_string()))?;
 
         // Fetch user profile/quota info
         let resp = client
-            .get(format!("{}/api/user", KIMI_API_BASE))
+            .get(ctx.endpoint(KIMI_API_BASE, "/api/user"))
             .header("Authorization", format!("Bearer {}", token))
             .header("Cookie", format!("kimi-auth={}", token))
             .header("Accept", "application/json")
+            // A browser user-agent, as the web session cookie was issued to one
             .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36")
             .send()
             .await?;
@@ -97,7 +100,7 @@ impl KimiProvider {
             if status.as_u16() == 401 || status.as_u16() == 403 {
                 return Err(ProviderError::AuthRequired);
             }
-            return Err(ProviderError::Other(format!("API error: {}", status)));
+            return Err(ProviderError::from_http_status(status, "Kimi API"));
         }
 
         let json: serde_json::Value = resp.json().await
@@ -200,32 +203,28 @@ impl Provider for KimiProvider {
         &self.metadata
     }
 
-    async fn fetch_usage(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
-        tracing::debug!("Fetching Kimi usage");
-
-        match ctx.source_mode {
-            SourceMode::Auto | SourceMode::Web => {
-                let usage = self.fetch_via_web().await?;
-                Ok(ProviderFetchResult::new(usage, "web"))
-            }
-            SourceMode::Cli => {
-                Err(ProviderError::UnsupportedSource(SourceMode::Cli))
-            }
-            SourceMode::OAuth => {
-                Err(ProviderError::UnsupportedSource(SourceMode::OAuth))
-            }
-        }
+    fn fetch_pipeline(&self) -> ProviderFetchPipeline<'_> {
+        ProviderFetchPipeline::empty().with_strategy(KimiWebStrategy { provider: self })
     }
+}
 
-    fn available_sources(&self) -> Vec<SourceMode> {
-        vec![SourceMode::Auto, SourceMode::Web]
+/// Kimi web API strategy
+struct KimiWebStrategy<'a> {
+    provider: &'a KimiProvider,
+}
+
+#[async_trait]
+impl ProviderFetchStrategy for KimiWebStrategy<'_> {
+    fn id(&self) -> &str {
+        "kimi.web"
     }
 
-    fn supports_web(&self) -> bool {
-        true
+    fn kind(&self) -> ProviderFetchKind {
+        ProviderFetchKind::Web
     }
 
-    fn supports_cli(&self) -> bool {
-        false
+    async fn fetch(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        let usage = self.provider.fetch_via_web(ctx).await?;
+        Ok(ProviderFetchResult::new(usage, "web"))
     }
 }
@@ -6,8 +6,9 @@
 use async_trait::async_trait;
 
 use crate::core::{
-    FetchContext, Provider, ProviderId, ProviderError, ProviderFetchResult,
-    ProviderMetadata, RateWindow, SourceMode, UsageSnapshot,
+    FetchContext, Provider, ProviderError, ProviderFetchKind, ProviderFetchPipeline,
+    ProviderFetchResult, ProviderFetchStrategy, ProviderId, ProviderMetadata, RateWindow,
+    UsageSnapshot,
 };
 
 const KIMIK2_API_BASE: &str = "https://api.moonshot.cn";
@@ -35,9 +36,16 @@ impl KimiK2Provider {
         }
     }
 
-    /// Get API key from environment or config
-    fn get_api_key() -> Option<String> {
-        // Check environment variable first
+    /// Get API key from settings, environment or config
+    fn get_api_key(api_key: Option<&str>) -> Option<String> {
+        // Check ctx.api_key first (from settings)
+        if let Some(key) = api_key {
+            if !key.is_empty() {
+                return Some(key.to_string());
+            }
+        }
+
+        // Check environment variable
         if let Ok(key) = std::env::var("MOONSHOT_API_KEY") {
             if !key.is_empty() {
                 return Some(key);
@@ -69,21 +77,19 @@ impl KimiK2Provider {
     }
 
     /// Fetch usage via Moonshot API
-    async fn fetch_via_api(&self) -> Result<UsageSnapshot, ProviderError> {
-        let api_key = Self::get_api_key().ok_or_else(|| {
+    async fn fetch_via_api(&self, ctx: &FetchContext) -> Result<UsageSnapshot, ProviderError> {
+        let api_key = Self::get_api_key(ctx.api_key.as_deref()).ok_or_else(|| {
             ProviderError::NotInstalled(
                 "Moonshot API key not found. Set MOONSHOT_API_KEY environment variable.".to_string()
             )
         })?;
 
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
+        let client = crate::core::http_client(std::time::Duration::from_secs(30))
             .map_err(|e| ProviderError::Other(e.to_string()))?;
 
         // Fetch account/billing info
         let resp = client
-            .get(format!("{}/v1/users/me/balance", KIMIK2_API_BASE))
+            .get(ctx.endpoint(KIMIK2_API_BASE, "/v1/users/me/balance"))
             .header("Authorization", format!("Bearer {}", api_key))
             .header("Accept", "application/json")
             .send()
@@ -94,7 +100,7 @@ impl KimiK2Provider {
             if status.as_u16() == 401 || status.as_u16() == 403 {
                 return Err(ProviderError::AuthRequired);
             }
-            return Err(ProviderError::Other(format!("API error: {}", status)));
+            return Err(ProviderError::from_http_status(status, "Moonshot API"));
         }
 
         let json: serde_json::Value = resp.json().await
@@ -172,29 +178,28 @@ impl Provider for KimiK2Provider {
         &self.metadata
     }
 
-    async fn fetch_usage(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
-        tracing::debug!("Fetching Kimi K2 usage");
-
-        match ctx.source_mode {
-            SourceMode::Auto | SourceMode::Web | SourceMode::OAuth => {
-                let usage = self.fetch_via_api().await?;
-                Ok(ProviderFetchResult::new(usage, "api"))
-            }
-            SourceMode::Cli => {
-                Err(ProviderError::UnsupportedSource(SourceMode::Cli))
-            }
-        }
+    fn fetch_pipeline(&self) -> ProviderFetchPipeline<'_> {
+        ProviderFetchPipeline::empty().with_strategy(KimiK2ApiStrategy { provider: self })
     }
+}
 
-    fn available_sources(&self) -> Vec<SourceMode> {
-        vec![SourceMode::Auto, SourceMode::Web]
+/// Moonshot platform API key strategy
+struct KimiK2ApiStrategy<'a> {
+    provider: &'a KimiK2Provider,
+}
+
+#[async_trait]
+impl ProviderFetchStrategy for KimiK2ApiStrategy<'_> {
+    fn id(&self) -> &str {
+        "kimik2.api"
     }
 
-    fn supports_web(&self) -> bool {
-        true
+    fn kind(&self) -> ProviderFetchKind {
+        ProviderFetchKind::ApiToken
     }
 
-    fn supports_cli(&self) -> bool {
-        false
+    async fn fetch(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        let usage = self.provider.fetch_via_api(ctx).await?;
+        Ok(ProviderFetchResult::new(usage, "api"))
     }
 }
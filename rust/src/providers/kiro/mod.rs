@@ -21,8 +21,9 @@ use regex_lite::Regex;
 use std::os::windows::process::CommandExt;
 
 use crate::core::{
-    FetchContext, Provider, ProviderId, ProviderError, ProviderFetchResult,
-    ProviderMetadata, RateWindow, SourceMode, UsageSnapshot,
+    FetchContext, Provider, ProviderError, ProviderFetchKind, ProviderFetchPipeline,
+    ProviderFetchResult, ProviderFetchStrategy, ProviderId, ProviderMetadata, RateWindow,
+    UsageSnapshot,
 };
 
 /// Kiro provider (AWS AI assistant)
@@ -402,34 +403,28 @@ impl Provider for KiroProvider {
         &self.metadata
     }
 
-    async fn fetch_usage(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
-        tracing::debug!("Fetching Kiro usage");
-
-        match ctx.source_mode {
-            SourceMode::Auto | SourceMode::Cli => {
-                let usage = self.fetch_via_cli().await?;
-                Ok(ProviderFetchResult::new(usage, "cli"))
-            }
-            SourceMode::Web => {
-                // Kiro doesn't have a direct web API, use CLI
-                let usage = self.fetch_via_cli().await?;
-                Ok(ProviderFetchResult::new(usage, "cli"))
-            }
-            SourceMode::OAuth => {
-                Err(ProviderError::UnsupportedSource(SourceMode::OAuth))
-            }
-        }
+    fn fetch_pipeline(&self) -> ProviderFetchPipeline<'_> {
+        ProviderFetchPipeline::empty().with_strategy(KiroCliStrategy { provider: self })
     }
+}
+
+/// kiro-cli usage probe strategy
+struct KiroCliStrategy<'a> {
+    provider: &'a KiroProvider,
+}
 
-    fn available_sources(&self) -> Vec<SourceMode> {
-        vec![SourceMode::Auto, SourceMode::Cli]
+#[async_trait]
+impl ProviderFetchStrategy for KiroCliStrategy<'_> {
+    fn id(&self) -> &str {
+        "kiro.cli"
     }
 
-    fn supports_web(&self) -> bool {
-        false
+    fn kind(&self) -> ProviderFetchKind {
+        ProviderFetchKind::Cli
     }
 
-    fn supports_cli(&self) -> bool {
-        true
+    async fn fetch(&self, _ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        let usage = self.provider.fetch_via_cli().await?;
+        Ok(ProviderFetchResult::new(usage, "cli"))
     }
 }
@@ -13,8 +13,9 @@ use async_trait::async_trait;
 use std::path::PathBuf;
 
 use crate::core::{
-    FetchContext, Provider, ProviderId, ProviderError, ProviderFetchResult,
-    ProviderMetadata, RateWindow, SourceMode, UsageSnapshot,
+    FetchContext, Provider, ProviderError, ProviderFetchKind, ProviderFetchPipeline,
+    ProviderFetchResult, ProviderFetchStrategy, ProviderId, ProviderMetadata, RateWindow,
+    UsageSnapshot,
 };
 
 /// MiniMax API region
@@ -59,14 +60,18 @@ impl MiniMaxProvider {
         }
     }
 
-    /// Read MiniMax API key
-    async fn read_api_key(&self) -> Result<(String, String), ProviderError> {
-        // Check environment variables first
-        if let (Ok(group_id), Ok(api_key)) = (
-            std::env::var("MINIMAX_GROUP_ID"),
-            std::env::var("MINIMAX_API_KEY")
-        ) {
-            return Ok((group_id, api_key));
+    /// Read MiniMax group ID and API key
+    async fn read_api_key(&self, api_key: Option<&str>) -> Result<(Option<String>, String), ProviderError> {
+        let env_group_id = std::env::var("MINIMAX_GROUP_ID").ok();
+
+        // Check api_key first (from settings/ctx); the group ID is optional then
+        if let Some(key) = api_key.map(str::trim).filter(|key| !key.is_empty()) {
+            return Ok((env_group_id, key.to_string()));
+        }
+
+        // Check environment variables next
+        if let (Some(group_id), Ok(api_key)) = (env_group_id, std::env::var("MINIMAX_API_KEY")) {
+            return Ok((Some(group_id), api_key));
         }
 
         // Check config file
@@ -90,7 +95,7 @@ impl MiniMaxProvider {
                 .map(|s| s.to_string());
 
             if let (Some(gid), Some(key)) = (group_id, api_key) {
-                return Ok((gid, key));
+                return Ok((Some(gid), key));
             }
         }
 
@@ -106,38 +111,44 @@ impl MiniMaxProvider {
     }
 
     /// Fetch usage via MiniMax API with region fallback
-    async fn fetch_via_web(&self) -> Result<UsageSnapshot, ProviderError> {
-        let (group_id, api_key) = self.read_api_key().await?;
+    async fn fetch_via_web(&self, ctx: &FetchContext) -> Result<UsageSnapshot, ProviderError> {
+        let (group_id, api_key) = self.read_api_key(ctx.api_key.as_deref()).await?;
+        let group_id = group_id.as_deref();
+
+        // An explicit base URL override replaces the region endpoints
+        if let Some(ref base_url) = ctx.base_url {
+            return self.fetch_from_base(group_id, &api_key, base_url).await;
+        }
 
         // Try global endpoint first, fall back to China mainland on 401/403
-        match self.fetch_from_region(&group_id, &api_key, MiniMaxRegion::Global).await {
+        let global = Self::api_base_url(MiniMaxRegion::Global);
+        match self.fetch_from_base(group_id, &api_key, global).await {
             Ok(usage) => Ok(usage),
             Err(ProviderError::AuthRequired) => {
                 // Retry with China mainland endpoint
-                self.fetch_from_region(&group_id, &api_key, MiniMaxRegion::ChinaMainland).await
+                let china = Self::api_base_url(MiniMaxRegion::ChinaMainland);
+                self.fetch_from_base(group_id, &api_key, china).await
             }
             Err(e) => Err(e),
         }
     }
 
-    /// Fetch from a specific region endpoint
-    async fn fetch_from_region(
+    /// Fetch from a specific API base URL
+    async fn fetch_from_base(
         &self,
-        group_id: &str,
+        group_id: Option<&str>,
         api_key: &str,
-        region: MiniMaxRegion,
+        base_url: &str,
     ) -> Result<UsageSnapshot, ProviderError> {
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
+        let client = crate::core::http_client(std::time::Duration::from_secs(30))
             .map_err(|e| ProviderError::Other(e.to_string()))?;
 
-        let base_url = Self::api_base_url(region);
-        let resp = client
-            .get(format!(
-                "{}/v1/billing/usage?group_id={}",
-                base_url, group_id
-            ))
+        let base_url = base_url.trim_end_matches('/');
+        let mut request = client.get(format!("{}/v1/billing/usage", base_url));
+        if let Some(group_id) = group_id {
+            request = request.query(&[("group_id", group_id)]);
+        }
+        let resp = request
             .header("Authorization", format!("Bearer {}", api_key))
             .header("MM-API-Source", "CodexBar")
             .send()
@@ -150,10 +161,7 @@ impl MiniMaxProvider {
         }
 
         if !resp.status().is_success() {
-            return Err(ProviderError::Other(format!(
-                "MiniMax API returned status {}",
-                resp.status()
-            )));
+            return Err(ProviderError::from_http_status(resp.status(), "MiniMax API"));
         }
 
         let json: serde_json::Value = resp.json().await
@@ -240,40 +248,51 @@ impl Provider for MiniMaxProvider {
         &self.metadata
     }
 
-    async fn fetch_usage(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
-        tracing::debug!("Fetching MiniMax usage");
+    fn fetch_pipeline(&self) -> ProviderFetchPipeline<'_> {
+        ProviderFetchPipeline::empty()
+            .with_strategy(MiniMaxWebStrategy { provider: self })
+            .with_strategy(MiniMaxLocalStrategy { provider: self })
+    }
+}
 
-        match ctx.source_mode {
-            SourceMode::Auto => {
-                if let Ok(usage) = self.fetch_via_web().await {
-                    return Ok(ProviderFetchResult::new(usage, "web"));
-                }
-                let usage = self.probe_cli().await?;
-                Ok(ProviderFetchResult::new(usage, "cli"))
-            }
-            SourceMode::Web => {
-                let usage = self.fetch_via_web().await?;
-                Ok(ProviderFetchResult::new(usage, "web"))
-            }
-            SourceMode::Cli => {
-                let usage = self.probe_cli().await?;
-                Ok(ProviderFetchResult::new(usage, "cli"))
-            }
-            SourceMode::OAuth => {
-                Err(ProviderError::UnsupportedSource(SourceMode::OAuth))
-            }
-        }
+/// MiniMax usage API strategy (tries each region)
+struct MiniMaxWebStrategy<'a> {
+    provider: &'a MiniMaxProvider,
+}
+
+#[async_trait]
+impl ProviderFetchStrategy for MiniMaxWebStrategy<'_> {
+    fn id(&self) -> &str {
+        "minimax.web"
+    }
+
+    fn kind(&self) -> ProviderFetchKind {
+        ProviderFetchKind::Web
     }
 
-    fn available_sources(&self) -> Vec<SourceMode> {
-        vec![SourceMode::Auto, SourceMode::Web, SourceMode::Cli]
+    async fn fetch(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        let usage = self.provider.fetch_via_web(ctx).await?;
+        Ok(ProviderFetchResult::new(usage, "web"))
+    }
+}
+
+/// MiniMax API key configuration probe strategy
+struct MiniMaxLocalStrategy<'a> {
+    provider: &'a MiniMaxProvider,
+}
+
+#[async_trait]
+impl ProviderFetchStrategy for MiniMaxLocalStrategy<'_> {
+    fn id(&self) -> &str {
+        "minimax.local"
     }
 
-    fn supports_web(&self) -> bool {
-        true
+    fn kind(&self) -> ProviderFetchKind {
+        ProviderFetchKind::LocalProbe
     }
 
-    fn supports_cli(&self) -> bool {
-        true
+    async fn fetch(&self, _ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        let usage = self.provider.probe_cli().await?;
+        Ok(ProviderFetchResult::new(usage, "cli"))
     }
 }
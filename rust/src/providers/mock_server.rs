@@ -0,0 +1,583 @@
+//! Provider integration tests against a local HTTP stand-in
+//!
+//! Each provider's `fetch_usage` is pointed at a loopback server through the
+//! base URL override and replayed against recorded fixtures in
+//! `tests/fixtures/providers/<provider>/<scenario>.json`. A fixture maps a
+//! request path to the recorded response (`status`, optional `headers` and a
+//! JSON `body`; string bodies are sent verbatim).
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use serde::Deserialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::core::{
+    CustomProvidersFile, FetchContext, Provider, ProviderError, ProviderFetchResult, SourceMode,
+};
+
+use super::custom::CustomHttpProvider;
+use super::{
+    AmpProvider, AugmentProvider, ClaudeProvider, CodexProvider, CopilotProvider, CursorProvider,
+    FactoryProvider, GeminiProvider, KimiK2Provider, KimiProvider, MiniMaxProvider, OllamaProvider,
+    OpenCodeProvider, OpenRouterProvider, SyntheticProvider, VertexAIProvider, WarpProvider, ZaiProvider,
+};
+
+/// A recorded HTTP response
+#[derive(Debug, Clone, Deserialize)]
+struct RecordedResponse {
+    status: u16,
+    #[serde(default)]
+    headers: HashMap<String, String>,
+    body: serde_json::Value,
+}
+
+impl RecordedResponse {
+    fn not_found() -> Self {
+        Self {
+            status: 404,
+            headers: HashMap::new(),
+            body: serde_json::json!({ "error": "not found" }),
+        }
+    }
+
+    fn to_http(&self) -> Vec<u8> {
+        let body = match self.body {
+            serde_json::Value::String(ref raw) => raw.clone(),
+            ref value => value.to_string(),
+        };
+        let reason = reqwest::StatusCode::from_u16(self.status)
+            .ok()
+            .and_then(|s| s.canonical_reason())
+            .unwrap_or("Unknown");
+
+        let mut head = format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+            self.status,
+            reason,
+            body.len()
+        );
+        for (name, value) in &self.headers {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        head.push_str("\r\n");
+
+        let mut bytes = head.into_bytes();
+        bytes.extend_from_slice(body.as_bytes());
+        bytes
+    }
+}
+
+/// A request seen by the mock server
+#[derive(Debug, Clone)]
+struct RecordedRequest {
+    method: String,
+    path: String,
+    headers: HashMap<String, String>,
+}
+
+/// Minimal HTTP/1.1 server replaying one fixture
+struct MockServer {
+    base_url: String,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl MockServer {
+    async fn start(provider: &str, scenario: &str) -> Self {
+        let routes = Arc::new(load_fixture(provider, scenario));
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind mock server");
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+
+        let seen = Arc::clone(&requests);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let routes = Arc::clone(&routes);
+                let seen = Arc::clone(&seen);
+                tokio::spawn(async move {
+                    let _ = serve_connection(stream, &routes, &seen).await;
+                });
+            }
+        });
+
+        Self { base_url, requests }
+    }
+
+    fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    fn context(&self) -> FetchContext {
+        FetchContext {
+            base_url: Some(self.base_url.clone()),
+            web_timeout: 5,
+            ..FetchContext::default()
+        }
+    }
+}
+
+fn load_fixture(provider: &str, scenario: &str) -> HashMap<String, RecordedResponse> {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/providers")
+        .join(provider)
+        .join(format!("{}.json", scenario));
+    let content = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("read fixture {}: {}", path.display(), e));
+    serde_json::from_str(&content)
+        .unwrap_or_else(|e| panic!("parse fixture {}: {}", path.display(), e))
+}
+
+async fn serve_connection(
+    mut stream: TcpStream,
+    routes: &HashMap<String, RecordedResponse>,
+    seen: &Mutex<Vec<RecordedRequest>>,
+) -> std::io::Result<()> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_string();
+    let target = request_line.next().unwrap_or_default().to_string();
+    let headers: HashMap<String, String> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+        .collect();
+
+    // Drain the request body so the client never sees a reset
+    let content_length = headers
+        .get("content-length")
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut body_read = buf.len() - header_end;
+    while body_read < content_length {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            break;
+        }
+        body_read += n;
+    }
+
+    let path = target.split('?').next().unwrap_or_default().to_string();
+    seen.lock().unwrap().push(RecordedRequest {
+        method,
+        path: path.clone(),
+        headers,
+    });
+
+    let response = routes.get(&path).cloned().unwrap_or_else(RecordedResponse::not_found);
+    stream.write_all(&response.to_http()).await?;
+    stream.shutdown().await
+}
+
+/// Custom provider definition exercised against the `custom` fixtures
+const CUSTOM_PROVIDER_TOML: &str = r#"
+[[provider]]
+id = "acme"
+name = "Acme AI"
+url = "https://usage.acme.example/v1/usage"
+
+[provider.auth]
+header = "Authorization"
+
+[provider.mapping]
+used_percent = "$.quota.used_percent"
+resets_at = "$.quota.resets_at"
+window_minutes = "$.quota.window_minutes"
+cost_used = "$.billing.spent"
+cost_limit = "$.billing.budget"
+currency = "CNY"
+account_email = "$.user.email"
+"#;
+
+fn custom_provider() -> CustomHttpProvider {
+    let file = CustomProvidersFile::from_toml(CUSTOM_PROVIDER_TOML).unwrap();
+    file.validate().unwrap();
+    let config = Box::leak(Box::new(file.providers.into_iter().next().unwrap()));
+    CustomHttpProvider::new(config)
+}
+
+/// How a provider under test is authenticated and routed
+struct Case {
+    name: &'static str,
+    provider: Arc<dyn Provider>,
+    source_mode: SourceMode,
+    api_key: Option<&'static str>,
+    cookie: Option<&'static str>,
+    /// Path of the request that decides success or failure
+    primary_path: &'static str,
+    /// Whether a successful fetch reports a usage percentage
+    reports_usage: bool,
+}
+
+fn cases() -> Vec<Case> {
+    vec![
+        Case {
+            name: "amp",
+            provider: Arc::new(AmpProvider::new()),
+            // Skip the local config probe, which would mask HTTP failures
+            source_mode: SourceMode::Web,
+            api_key: Some("sgp_test"),
+            cookie: None,
+            primary_path: "/.api/cody/current-user/usage",
+            reports_usage: true,
+        },
+        Case {
+            name: "synthetic",
+            provider: Arc::new(SyntheticProvider::new()),
+            source_mode: SourceMode::Web,
+            api_key: Some("syn_test"),
+            cookie: None,
+            primary_path: "/v1/usage",
+            reports_usage: true,
+        },
+        Case {
+            name: "warp",
+            provider: Arc::new(WarpProvider::new()),
+            source_mode: SourceMode::Auto,
+            api_key: Some("wk-test"),
+            cookie: None,
+            primary_path: "/graphql/v2",
+            reports_usage: true,
+        },
+        Case {
+            name: "zai",
+            provider: Arc::new(ZaiProvider::new()),
+            source_mode: SourceMode::Auto,
+            api_key: Some("zai-test"),
+            cookie: None,
+            primary_path: "/api/monitor/usage/quota/limit",
+            reports_usage: true,
+        },
+        Case {
+            name: "openrouter",
+            provider: Arc::new(OpenRouterProvider::new()),
+            source_mode: SourceMode::Auto,
+            api_key: Some("sk-or-test"),
+            cookie: None,
+            primary_path: "/api/v1/auth/credits",
+            reports_usage: true,
+        },
+        Case {
+            name: "kimik2",
+            provider: Arc::new(KimiK2Provider::new()),
+            source_mode: SourceMode::Auto,
+            api_key: Some("sk-moonshot-test"),
+            cookie: None,
+            primary_path: "/v1/users/me/balance",
+            reports_usage: true,
+        },
+        Case {
+            name: "kimi",
+            provider: Arc::new(KimiProvider::new()),
+            source_mode: SourceMode::Auto,
+            api_key: None,
+            cookie: Some("kimi-auth=kimi-test"),
+            primary_path: "/api/user",
+            reports_usage: true,
+        },
+        Case {
+            name: "factory",
+            provider: Arc::new(FactoryProvider::new()),
+            source_mode: SourceMode::Auto,
+            api_key: None,
+            cookie: Some("session=factory-test"),
+            primary_path: "/api/organization/subscription/usage",
+            reports_usage: true,
+        },
+        Case {
+            name: "copilot",
+            provider: Arc::new(CopilotProvider::new()),
+            source_mode: SourceMode::Auto,
+            api_key: Some("gho_test"),
+            cookie: None,
+            primary_path: "/copilot_internal/user",
+            reports_usage: true,
+        },
+        Case {
+            name: "cursor",
+            provider: Arc::new(CursorProvider::new()),
+            source_mode: SourceMode::Auto,
+            api_key: None,
+            cookie: Some("WorkosCursorSessionToken=cursor-test"),
+            primary_path: "/api/usage-summary",
+            reports_usage: true,
+        },
+        Case {
+            name: "claude",
+            provider: Arc::new(ClaudeProvider::new()),
+            // OAuth only, so the web, CLI and local log strategies stay out of the way
+            source_mode: SourceMode::OAuth,
+            api_key: Some("sk-ant-oat01-test"),
+            cookie: None,
+            primary_path: "/api/usage",
+            reports_usage: true,
+        },
+        Case {
+            name: "claude_web",
+            provider: Arc::new(ClaudeProvider::new()),
+            source_mode: SourceMode::Web,
+            api_key: None,
+            cookie: Some("sessionKey=sk-ant-sid01-test"),
+            primary_path: "/api/organizations",
+            reports_usage: true,
+        },
+        Case {
+            name: "codex",
+            provider: Arc::new(CodexProvider::new()),
+            // Skip the session log fallback, which would mask HTTP failures
+            source_mode: SourceMode::OAuth,
+            api_key: Some("codex-test"),
+            cookie: None,
+            primary_path: "/wham/usage",
+            reports_usage: true,
+        },
+        Case {
+            name: "gemini",
+            provider: Arc::new(GeminiProvider::new()),
+            source_mode: SourceMode::Auto,
+            api_key: Some("ya29.gemini-test"),
+            cookie: None,
+            primary_path: "/v1internal:retrieveUserQuota",
+            reports_usage: true,
+        },
+        Case {
+            name: "minimax",
+            provider: Arc::new(MiniMaxProvider::new()),
+            source_mode: SourceMode::Web,
+            api_key: Some("mm-test"),
+            cookie: None,
+            primary_path: "/v1/billing/usage",
+            reports_usage: true,
+        },
+        Case {
+            name: "augment",
+            provider: Arc::new(AugmentProvider::new()),
+            source_mode: SourceMode::Web,
+            api_key: Some("aug-test"),
+            cookie: None,
+            primary_path: "/v1/user/usage",
+            reports_usage: true,
+        },
+        Case {
+            name: "vertexai",
+            provider: Arc::new(VertexAIProvider::new()),
+            source_mode: SourceMode::Web,
+            api_key: Some("ya29.vertex-test"),
+            cookie: None,
+            // No GOOGLE_CLOUD_PROJECT or gcloud properties in the test environment
+            primary_path: "/v1/projects/unknown",
+            // Only the project is looked up; usage is not reported yet
+            reports_usage: false,
+        },
+        Case {
+            name: "ollama",
+            provider: Arc::new(OllamaProvider::new()),
+            source_mode: SourceMode::Auto,
+            api_key: None,
+            cookie: Some("__Secure-session=ollama-test"),
+            primary_path: "/settings",
+            reports_usage: true,
+        },
+        Case {
+            name: "opencode",
+            provider: Arc::new(OpenCodeProvider::new()),
+            source_mode: SourceMode::Auto,
+            api_key: None,
+            cookie: Some("auth=opencode-test"),
+            primary_path: "/_server",
+            reports_usage: true,
+        },
+        Case {
+            name: "custom",
+            provider: Arc::new(custom_provider()),
+            source_mode: SourceMode::Auto,
+            api_key: Some("acme-test"),
+            cookie: None,
+            primary_path: "/v1/usage",
+            reports_usage: true,
+        },
+    ]
+}
+
+async fn run_case(case: &Case, scenario: &str) -> (Result<ProviderFetchResult, ProviderError>, MockServer) {
+    let server = MockServer::start(case.name, scenario).await;
+    let ctx = FetchContext {
+        source_mode: case.source_mode,
+        api_key: case.api_key.map(str::to_string),
+        manual_cookie_header: case.cookie.map(str::to_string),
+        ..server.context()
+    };
+    let result = case.provider.fetch_usage(&ctx).await;
+
+    let requests = server.requests();
+    assert!(
+        requests.iter().any(|r| r.path == case.primary_path),
+        "{}/{}: expected a request to {}, saw {:?}",
+        case.name,
+        scenario,
+        case.primary_path,
+        requests.iter().map(|r| format!("{} {}", r.method, r.path)).collect::<Vec<_>>()
+    );
+
+    (result, server)
+}
+
+#[tokio::test]
+async fn test_providers_success_fixture() {
+    for case in cases() {
+        let (result, server) = run_case(&case, "success").await;
+        let result = result.unwrap_or_else(|e| panic!("{}: expected success, got {}", case.name, e));
+
+        if case.reports_usage {
+            assert!(
+                result.usage.primary().used_percent > 0.0,
+                "{}: expected non-zero usage",
+                case.name
+            );
+        }
+        assert!(!result.attempts.is_empty(), "{}: missing fetch attempts", case.name);
+
+        // Credentials reach the overridden endpoint
+        let primary = server
+            .requests()
+            .into_iter()
+            .find(|r| r.path == case.primary_path)
+            .unwrap();
+        if let Some(key) = case.api_key {
+            let auth = primary.headers.get("authorization").cloned().unwrap_or_default();
+            assert!(auth.ends_with(key), "{}: unexpected authorization {:?}", case.name, auth);
+        }
+        if case.cookie.is_some() {
+            assert!(primary.headers.contains_key("cookie"), "{}: missing cookie", case.name);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_providers_success_values() {
+    let by_name: HashMap<&str, Case> = cases().into_iter().map(|c| (c.name, c)).collect();
+
+    let (result, _server) = run_case(&by_name["openrouter"], "success").await;
+    let result = result.unwrap();
+    assert_eq!(result.usage.primary().used_percent, 25.0);
+    assert_eq!(result.usage.secondary().unwrap().used_percent, 25.0);
+
+    let (result, _server) = run_case(&by_name["zai"], "success").await;
+    let result = result.unwrap();
+    assert_eq!(result.usage.primary().used_percent, 20.0);
+    assert_eq!(result.usage.secondary().unwrap().used_percent, 30.0);
+    let search = result.usage.window("web_search").unwrap();
+    assert_eq!(search.label, "Web search");
+    assert_eq!(search.window.used_percent, 10.0);
+
+    let (result, _server) = run_case(&by_name["copilot"], "success").await;
+    let usage = result.unwrap().usage;
+    let ids: Vec<&str> = usage.windows.iter().map(|w| w.id.as_str()).collect();
+    assert_eq!(ids, ["primary", "secondary", "completions"]);
+    assert_eq!(usage.primary().used_percent, 40.0);
+    assert_eq!(usage.window("completions").unwrap().window.used_percent, 25.0);
+
+    let (result, _server) = run_case(&by_name["factory"], "success").await;
+    let usage = result.unwrap().usage;
+    assert_eq!(usage.primary().used_percent, 40.0);
+    assert_eq!(usage.account_email.as_deref(), Some("dev@example.com"));
+    assert_eq!(usage.login_method.as_deref(), Some("Pro (Team)"));
+
+    let (result, _server) = run_case(&by_name["cursor"], "success").await;
+    let result = result.unwrap();
+    assert_eq!(result.usage.primary().used_percent, 75.0);
+    assert_eq!(result.cost.as_ref().unwrap().used, 15.0);
+    assert_eq!(result.usage.login_method.as_deref(), Some("Cursor Pro"));
+
+    let (result, _server) = run_case(&by_name["claude_web"], "success").await;
+    let result = result.unwrap();
+    assert_eq!(result.usage.primary().used_percent, 48.0);
+    assert_eq!(result.usage.secondary().unwrap().used_percent, 71.0);
+    assert_eq!(result.usage.window("sonnet").unwrap().window.used_percent, 20.0);
+    assert_eq!(result.usage.account_email.as_deref(), Some("dev@example.com"));
+    let cost = result.cost.unwrap();
+    assert_eq!((cost.used, cost.limit), (12.5, Some(50.0)));
+
+    let (result, _server) = run_case(&by_name["codex"], "success").await;
+    let usage = result.unwrap().usage;
+    assert_eq!(usage.primary().used_percent, 27.0);
+    assert_eq!(usage.primary().window_minutes, Some(300));
+    assert_eq!(usage.secondary().unwrap().used_percent, 54.0);
+
+    let (result, _server) = run_case(&by_name["gemini"], "success").await;
+    let usage = result.unwrap().usage;
+    assert!((usage.primary().used_percent - 40.0).abs() < 1e-9);
+    assert!((usage.window("gemini-2.5-flash").unwrap().window.used_percent - 10.0).abs() < 1e-9);
+
+    let (result, _server) = run_case(&by_name["augment"], "success").await;
+    let usage = result.unwrap().usage;
+    assert_eq!(usage.primary().used_percent, 75.0);
+    assert_eq!(usage.account_email.as_deref(), Some("dev@example.com"));
+
+    let (result, _server) = run_case(&by_name["opencode"], "success").await;
+    let usage = result.unwrap().usage;
+    assert_eq!(usage.primary().used_percent, 38.0);
+    assert_eq!(usage.secondary().unwrap().used_percent, 57.0);
+
+    let (result, _server) = run_case(&by_name["custom"], "success").await;
+    let result = result.unwrap();
+    assert_eq!(result.usage.primary().used_percent, 42.5);
+    assert_eq!(result.usage.primary().window_minutes, Some(43200));
+    assert_eq!(result.usage.account_email.as_deref(), Some("dev@acme.example"));
+    let cost = result.cost.unwrap();
+    assert_eq!((cost.used, cost.limit), (88.0, Some(200.0)));
+    assert_eq!(cost.currency_code, "CNY");
+}
+
+#[tokio::test]
+async fn test_providers_auth_expired_fixture() {
+    for case in cases() {
+        let (result, _server) = run_case(&case, "auth_expired").await;
+        assert!(
+            matches!(result, Err(ProviderError::AuthRequired)),
+            "{}: expected AuthRequired, got {:?}",
+            case.name,
+            result.map(|r| r.source_label)
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_providers_rate_limited_fixture() {
+    for case in cases() {
+        let (result, _server) = run_case(&case, "rate_limited").await;
+        assert!(
+            matches!(result, Err(ProviderError::RateLimited)),
+            "{}: expected RateLimited, got {:?}",
+            case.name,
+            result.map(|r| r.source_label)
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_providers_malformed_fixture() {
+    for case in cases() {
+        let (result, _server) = run_case(&case, "malformed").await;
+        assert!(
+            matches!(result, Err(ProviderError::Parse(_))),
+            "{}: expected Parse error, got {:?}",
+            case.name,
+            result.map(|r| r.source_label)
+        );
+    }
+}
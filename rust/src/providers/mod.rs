@@ -2,13 +2,19 @@
 
 #![allow(dead_code)]
 
+use std::sync::{Arc, LazyLock};
+
+use crate::core::ProviderRegistry;
+
 pub mod amp;
 pub mod antigravity;
 pub mod augment;
 pub mod claude;
 pub mod codex;
+pub mod command;
 pub mod copilot;
 pub mod cursor;
+pub mod custom;
 pub mod factory;
 pub mod gemini;
 pub mod jetbrains;
@@ -25,14 +31,19 @@ pub mod vertexai;
 pub mod warp;
 pub mod zai;
 
+#[cfg(test)]
+mod mock_server;
+
 // Re-export provider implementations
 pub use amp::AmpProvider;
 pub use antigravity::AntigravityProvider;
 pub use augment::AugmentProvider;
 pub use claude::ClaudeProvider;
 pub use codex::CodexProvider;
+pub use command::register_command_providers;
 pub use copilot::CopilotProvider;
 pub use cursor::CursorProvider;
+pub use custom::register_custom_providers;
 pub use factory::FactoryProvider;
 pub use gemini::GeminiProvider;
 pub use jetbrains::JetBrainsProvider;
@@ -47,3 +58,40 @@ pub use synthetic::SyntheticProvider;
 pub use vertexai::VertexAIProvider;
 pub use warp::WarpProvider;
 pub use zai::ZaiProvider;
+
+/// Global provider registry, populated with every built-in provider and the
+/// custom providers file on first use
+///
+/// The CLI, the tray app and embedders all resolve providers from here.
+pub static REGISTRY: LazyLock<ProviderRegistry> = LazyLock::new(|| {
+    let registry = ProviderRegistry::new();
+    register_builtin_providers(&registry);
+    register_custom_providers(&registry);
+    register_command_providers(&registry);
+    registry
+});
+
+/// Register every built-in provider implementation, in `ProviderId::all()` order
+pub fn register_builtin_providers(registry: &ProviderRegistry) {
+    registry.register(Arc::new(CodexProvider::new()));
+    registry.register(Arc::new(ClaudeProvider::new()));
+    registry.register(Arc::new(CursorProvider::new()));
+    registry.register(Arc::new(FactoryProvider::new()));
+    registry.register(Arc::new(GeminiProvider::new()));
+    registry.register(Arc::new(AntigravityProvider::new()));
+    registry.register(Arc::new(CopilotProvider::new()));
+    registry.register(Arc::new(ZaiProvider::new()));
+    registry.register(Arc::new(MiniMaxProvider::new()));
+    registry.register(Arc::new(KiroProvider::new()));
+    registry.register(Arc::new(VertexAIProvider::new()));
+    registry.register(Arc::new(AugmentProvider::new()));
+    registry.register(Arc::new(OpenCodeProvider::new()));
+    registry.register(Arc::new(KimiProvider::new()));
+    registry.register(Arc::new(KimiK2Provider::new()));
+    registry.register(Arc::new(AmpProvider::new()));
+    registry.register(Arc::new(WarpProvider::new()));
+    registry.register(Arc::new(OllamaProvider::new()));
+    registry.register(Arc::new(OpenRouterProvider::new()));
+    registry.register(Arc::new(SyntheticProvider::new()));
+    registry.register(Arc::new(JetBrainsProvider::new()));
+}
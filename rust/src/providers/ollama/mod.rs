@@ -7,12 +7,13 @@ use async_trait::async_trait;
 use regex_lite::Regex;
 
 use crate::core::{
-    FetchContext, Provider, ProviderId, ProviderError, ProviderFetchResult,
-    ProviderMetadata, RateWindow, SourceMode, UsageSnapshot,
+    FetchContext, Provider, ProviderError, ProviderFetchKind, ProviderFetchPipeline,
+    ProviderFetchResult, ProviderFetchStrategy, ProviderId, ProviderMetadata, RateWindow,
+    UsageSnapshot,
 };
 
-/// Ollama settings page URL
-const OLLAMA_SETTINGS_URL: &str = "https://ollama.com/settings";
+/// Ollama base URL
+const OLLAMA_BASE_URL: &str = "https://ollama.com";
 
 /// Ollama provider
 pub struct OllamaProvider {
@@ -41,19 +42,20 @@ impl OllamaProvider {
     async fn fetch_usage_web(&self, ctx: &FetchContext) -> Result<UsageSnapshot, ProviderError> {
         let cookie_header = self.resolve_cookie_header(ctx)?;
 
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(ctx.web_timeout))
+        let client = crate::core::http_client_builder(std::time::Duration::from_secs(ctx.web_timeout))
+            .map_err(|e| ProviderError::Other(e.to_string()))?
             .redirect(reqwest::redirect::Policy::limited(5))
             .build()
             .map_err(|e| ProviderError::Other(e.to_string()))?;
 
         let resp = client
-            .get(OLLAMA_SETTINGS_URL)
+            .get(ctx.endpoint(OLLAMA_BASE_URL, "/settings"))
             .header("Cookie", &cookie_header)
             .header(
                 "Accept",
                 "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8",
             )
+            // The settings page is scraped, so look like the browser that owns the cookie
             .header(
                 "User-Agent",
                 "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/143.0.0.0 Safari/537.36",
@@ -74,10 +76,7 @@ impl OllamaProvider {
         }
 
         if !resp.status().is_success() {
-            return Err(ProviderError::Other(format!(
-                "Ollama returned status {}",
-                resp.status()
-            )));
+            return Err(ProviderError::from_http_status(resp.status(), "Ollama"));
         }
 
         let html = resp
@@ -199,29 +198,28 @@ impl Provider for OllamaProvider {
         &self.metadata
     }
 
-    async fn fetch_usage(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
-        tracing::debug!("Fetching Ollama usage");
-
-        match ctx.source_mode {
-            SourceMode::Auto | SourceMode::Web => {
-                let usage = self.fetch_usage_web(ctx).await?;
-                Ok(ProviderFetchResult::new(usage, "web"))
-            }
-            SourceMode::OAuth | SourceMode::Cli => {
-                Err(ProviderError::UnsupportedSource(ctx.source_mode))
-            }
-        }
+    fn fetch_pipeline(&self) -> ProviderFetchPipeline<'_> {
+        ProviderFetchPipeline::empty().with_strategy(OllamaWebStrategy { provider: self })
     }
+}
+
+/// ollama.com settings page strategy (browser cookies)
+struct OllamaWebStrategy<'a> {
+    provider: &'a OllamaProvider,
+}
 
-    fn available_sources(&self) -> Vec<SourceMode> {
-        vec![SourceMode::Auto, SourceMode::Web]
+#[async_trait]
+impl ProviderFetchStrategy for OllamaWebStrategy<'_> {
+    fn id(&self) -> &str {
+        "ollama.web"
     }
 
-    fn supports_web(&self) -> bool {
-        true
+    fn kind(&self) -> ProviderFetchKind {
+        ProviderFetchKind::Web
     }
 
-    fn supports_cli(&self) -> bool {
-        false
+    async fn fetch(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        let usage = self.provider.fetch_usage_web(ctx).await?;
+        Ok(ProviderFetchResult::new(usage, "web"))
     }
 }
@@ -11,24 +11,23 @@ pub use scraper::{OpenCodeError, OpenCodeUsageFetcher, OpenCodeUsageSnapshot};
 
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use reqwest::Client;
 use serde_json::Value;
 use uuid::Uuid;
 
 use crate::core::{
-    FetchContext, Provider, ProviderId, ProviderError, ProviderFetchResult,
-    ProviderMetadata, RateWindow, SourceMode, UsageSnapshot,
+    FetchContext, Provider, ProviderError, ProviderFetchKind, ProviderFetchPipeline,
+    ProviderFetchResult, ProviderFetchStrategy, ProviderId, ProviderMetadata, RateWindow,
+    UsageSnapshot,
 };
 
 const BASE_URL: &str = "https://opencode.ai";
-const SERVER_URL: &str = "https://opencode.ai/_server";
+const SERVER_PATH: &str = "/_server";
 const WORKSPACES_SERVER_ID: &str = "def39973159c7f0483d8793a822b8dbb10d067e12c65455fcb4608459ba0234f";
 const SUBSCRIPTION_SERVER_ID: &str = "7abeebee372f304e050aaaf92be863f4a86490e382f8c79db68fd94040d691b4";
 
 /// OpenCode provider
 pub struct OpenCodeProvider {
     metadata: ProviderMetadata,
-    client: Client,
 }
 
 impl OpenCodeProvider {
@@ -46,34 +45,32 @@ impl OpenCodeProvider {
                 dashboard_url: Some("https://opencode.ai"),
                 status_page_url: None,
             },
-            client: Client::builder()
-                .timeout(std::time::Duration::from_secs(30))
-                .build()
-                .unwrap_or_else(|_| Client::new()),
         }
     }
 
     /// Fetch usage with cookie header
-    async fn fetch_with_cookies(&self, cookie_header: &str) -> Result<UsageSnapshot, ProviderError> {
+    async fn fetch_with_cookies(&self, ctx: &FetchContext, cookie_header: &str) -> Result<UsageSnapshot, ProviderError> {
         // First get workspace ID
-        let workspace_id = self.fetch_workspace_id(cookie_header).await?;
+        let workspace_id = self.fetch_workspace_id(ctx, cookie_header).await?;
 
         // Then fetch subscription info
-        let subscription = self.fetch_subscription(&workspace_id, cookie_header).await?;
+        let subscription = self.fetch_subscription(ctx, &workspace_id, cookie_header).await?;
 
         // Parse the response
         self.parse_subscription(&subscription)
     }
 
     /// Fetch workspace ID from server
-    async fn fetch_workspace_id(&self, cookie_header: &str) -> Result<String, ProviderError> {
-        let url = format!("{}?id={}", SERVER_URL, WORKSPACES_SERVER_ID);
+    async fn fetch_workspace_id(&self, ctx: &FetchContext, cookie_header: &str) -> Result<String, ProviderError> {
+        let url = format!("{}?id={}", ctx.endpoint(BASE_URL, SERVER_PATH), WORKSPACES_SERVER_ID);
 
-        let response = self.client
+        let response = crate::core::http_client(std::time::Duration::from_secs(30))
+            .map_err(|e| ProviderError::Other(e.to_string()))?
             .get(&url)
             .header("Cookie", cookie_header)
             .header("X-Server-Id", WORKSPACES_SERVER_ID)
             .header("X-Server-Instance", format!("server-fn:{}", Uuid::new_v4()))
+            // Server functions of the web app: send the browser user-agent the cookie belongs to
             .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36")
             .header("Origin", BASE_URL)
             .header("Referer", BASE_URL)
@@ -85,10 +82,7 @@ impl OpenCodeProvider {
             if response.status().as_u16() == 401 || response.status().as_u16() == 403 {
                 return Err(ProviderError::AuthRequired);
             }
-            return Err(ProviderError::Other(format!(
-                "OpenCode API returned {}",
-                response.status()
-            )));
+            return Err(ProviderError::from_http_status(response.status(), "OpenCode API"));
         }
 
         let text = response.text().await?;
@@ -108,17 +102,24 @@ impl OpenCodeProvider {
     }
 
     /// Fetch subscription info for a workspace
-    async fn fetch_subscription(&self, workspace_id: &str, cookie_header: &str) -> Result<String, ProviderError> {
+    async fn fetch_subscription(&self, ctx: &FetchContext, workspace_id: &str, cookie_header: &str) -> Result<String, ProviderError> {
         let referer = format!("https://opencode.ai/workspace/{}/billing", workspace_id);
         let args = serde_json::json!([workspace_id]);
         let encoded_args = Self::url_encode(&args.to_string());
-        let url = format!("{}?id={}&args={}", SERVER_URL, SUBSCRIPTION_SERVER_ID, encoded_args);
+        let url = format!(
+            "{}?id={}&args={}",
+            ctx.endpoint(BASE_URL, SERVER_PATH),
+            SUBSCRIPTION_SERVER_ID,
+            encoded_args
+        );
 
-        let response = self.client
+        let response = crate::core::http_client(std::time::Duration::from_secs(30))
+            .map_err(|e| ProviderError::Other(e.to_string()))?
             .get(&url)
             .header("Cookie", cookie_header)
             .header("X-Server-Id", SUBSCRIPTION_SERVER_ID)
             .header("X-Server-Instance", format!("server-fn:{}", Uuid::new_v4()))
+            // Server functions of the web app: send the browser user-agent the cookie belongs to
             .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36")
             .header("Origin", BASE_URL)
             .header("Referer", referer)
@@ -130,10 +131,7 @@ impl OpenCodeProvider {
             if response.status().as_u16() == 401 || response.status().as_u16() == 403 {
                 return Err(ProviderError::AuthRequired);
             }
-            return Err(ProviderError::Other(format!(
-                "OpenCode subscription API returned {}",
-                response.status()
-            )));
+            return Err(ProviderError::from_http_status(response.status(), "OpenCode subscription API"));
         }
 
         let text = response.text().await?;
@@ -345,64 +343,60 @@ impl Provider for OpenCodeProvider {
         &self.metadata
     }
 
-    async fn fetch_usage(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
-        tracing::debug!("Fetching OpenCode usage");
+    fn fetch_pipeline(&self) -> ProviderFetchPipeline<'_> {
+        ProviderFetchPipeline::empty().with_strategy(OpenCodeWebStrategy { provider: self })
+    }
+}
 
-        match ctx.source_mode {
-            SourceMode::Auto | SourceMode::Web => {
-                // Check for manual cookie header first
-                if let Some(ref cookie_header) = ctx.manual_cookie_header {
-                    let usage = self.fetch_with_cookies(cookie_header).await?;
-                    return Ok(ProviderFetchResult::new(usage, "web"));
-                }
+/// opencode.ai web strategy (manual or browser cookies)
+struct OpenCodeWebStrategy<'a> {
+    provider: &'a OpenCodeProvider,
+}
+
+#[async_trait]
+impl ProviderFetchStrategy for OpenCodeWebStrategy<'_> {
+    fn id(&self) -> &str {
+        "opencode.web"
+    }
+
+    fn kind(&self) -> ProviderFetchKind {
+        ProviderFetchKind::Web
+    }
 
-                // Try to get cookies from browser
-                #[cfg(windows)]
-                {
-                    use crate::browser::detection::BrowserDetector;
-                    use crate::browser::cookies::{Cookie, CookieExtractor};
-
-                    let browsers = BrowserDetector::detect_all();
-
-                    for browser in browsers {
-                        if let Ok(cookies) = CookieExtractor::extract_for_domain(&browser, "opencode.ai") {
-                            // Build cookie header
-                            let cookie_header: String = cookies.iter()
-                                .map(|c: &Cookie| format!("{}={}", c.name, c.value))
-                                .collect::<Vec<_>>()
-                                .join("; ");
-
-                            if !cookie_header.is_empty() {
-                                match self.fetch_with_cookies(&cookie_header).await {
-                                    Ok(usage) => return Ok(ProviderFetchResult::new(usage, "web")),
-                                    Err(ProviderError::AuthRequired) => continue,
-                                    Err(e) => return Err(e),
-                                }
-                            }
+    async fn fetch(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        // Check for manual cookie header first
+        if let Some(ref cookie_header) = ctx.manual_cookie_header {
+            let usage = self.provider.fetch_with_cookies(ctx, cookie_header).await?;
+            return Ok(ProviderFetchResult::new(usage, "web"));
+        }
+
+        // Try to get cookies from browser
+        #[cfg(windows)]
+        {
+            use crate::browser::detection::BrowserDetector;
+            use crate::browser::cookies::{Cookie, CookieExtractor};
+
+            let browsers = BrowserDetector::detect_all();
+
+            for browser in browsers {
+                if let Ok(cookies) = CookieExtractor::extract_for_domain(&browser, "opencode.ai") {
+                    // Build cookie header
+                    let cookie_header: String = cookies.iter()
+                        .map(|c: &Cookie| format!("{}={}", c.name, c.value))
+                        .collect::<Vec<_>>()
+                        .join("; ");
+
+                    if !cookie_header.is_empty() {
+                        match self.provider.fetch_with_cookies(ctx, &cookie_header).await {
+                            Ok(usage) => return Ok(ProviderFetchResult::new(usage, "web")),
+                            Err(ProviderError::AuthRequired) => continue,
+                            Err(e) => return Err(e),
                         }
                     }
                 }
-
-                Err(ProviderError::AuthRequired)
-            }
-            SourceMode::Cli => {
-                Err(ProviderError::UnsupportedSource(SourceMode::Cli))
-            }
-            SourceMode::OAuth => {
-                Err(ProviderError::UnsupportedSource(SourceMode::OAuth))
             }
         }
-    }
-
-    fn available_sources(&self) -> Vec<SourceMode> {
-        vec![SourceMode::Auto, SourceMode::Web]
-    }
-
-    fn supports_web(&self) -> bool {
-        true
-    }
 
-    fn supports_cli(&self) -> bool {
-        false
+        Err(ProviderError::AuthRequired)
     }
 }
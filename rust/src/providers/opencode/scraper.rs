@@ -284,9 +284,7 @@ impl OpenCodeUsageFetcher {
     ) -> Result<String, OpenCodeError> {
         let url = Self::build_server_url(&request.server_id, &request.args, &request.method);
 
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(timeout_secs))
-            .build()
+        let client = crate::core::http_client(std::time::Duration::from_secs(timeout_secs))
             .map_err(|e| OpenCodeError::NetworkError(e.to_string()))?;
 
         let mut req = if request.method == "GET" {
@@ -299,6 +297,7 @@ impl OpenCodeUsageFetcher {
             .header("Cookie", cookie_header)
             .header("X-Server-Id", &request.server_id)
             .header("X-Server-Instance", format!("server-fn:{}", uuid::Uuid::new_v4()))
+            // Scraped page: send a browser user-agent
             .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 Chrome/120.0.0.0 Safari/537.36")
             .header("Origin", BASE_URL)
             .header("Referer", &request.referer)
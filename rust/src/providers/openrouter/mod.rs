@@ -7,12 +7,15 @@ use async_trait::async_trait;
 use serde::Deserialize;
 
 use crate::core::{
-    FetchContext, Provider, ProviderId, ProviderError, ProviderFetchResult,
-    ProviderMetadata, RateWindow, SourceMode, UsageSnapshot,
+    CostSnapshot, FetchContext, Provider, ProviderError, ProviderFetchKind, ProviderFetchPipeline,
+    ProviderFetchResult, ProviderFetchStrategy, ProviderId, ProviderMetadata, RateWindow,
+    UsageSnapshot,
 };
 
-/// OpenRouter API base URL
-const OPENROUTER_API_BASE: &str = "https://openrouter.ai/api/v1/auth";
+/// OpenRouter API base URL and endpoint paths
+const OPENROUTER_API_BASE: &str = "https://openrouter.ai";
+const OPENROUTER_CREDITS_PATH: &str = "/api/v1/auth/credits";
+const OPENROUTER_KEY_PATH: &str = "/api/v1/auth/key";
 
 /// Windows Credential Manager target for OpenRouter API token
 const OPENROUTER_CREDENTIAL_TARGET: &str = "codexbar-openrouter";
@@ -111,16 +114,17 @@ impl OpenRouterProvider {
     }
 
     /// Fetch usage from OpenRouter API
-    async fn fetch_usage_api(&self, ctx: &FetchContext) -> Result<UsageSnapshot, ProviderError> {
+    async fn fetch_usage_api(
+        &self,
+        ctx: &FetchContext,
+    ) -> Result<(UsageSnapshot, CostSnapshot), ProviderError> {
         let api_key = Self::get_api_token(ctx.api_key.as_deref())?;
 
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
+        let client = crate::core::http_client(std::time::Duration::from_secs(30))
             .map_err(|e| ProviderError::Other(e.to_string()))?;
 
         // Fetch credits (primary endpoint)
-        let credits_url = format!("{}/credits", OPENROUTER_API_BASE);
+        let credits_url = ctx.endpoint(OPENROUTER_API_BASE, OPENROUTER_CREDITS_PATH);
         let resp = client
             .get(&credits_url)
             .header("Authorization", format!("Bearer {}", api_key))
@@ -128,15 +132,8 @@ impl OpenRouterProvider {
             .send()
             .await?;
 
-        if resp.status() == reqwest::StatusCode::UNAUTHORIZED {
-            return Err(ProviderError::AuthRequired);
-        }
-
         if !resp.status().is_success() {
-            return Err(ProviderError::Other(format!(
-                "OpenRouter API returned status {}",
-                resp.status()
-            )));
+            return Err(ProviderError::from_http_status(resp.status(), "OpenRouter API"));
         }
 
         let credits: CreditsResponse = resp
@@ -152,12 +149,11 @@ impl OpenRouterProvider {
 
         let mut usage = UsageSnapshot::new(primary)
             .with_login_method(&format!("${:.2} balance", balance));
+        let cost = CostSnapshot::new(balance, "USD", "Credits").with_limit(credits.data.total_credits);
 
         // Try to enrich with /key endpoint data (optional, short timeout)
-        let key_url = format!("{}/key", OPENROUTER_API_BASE);
-        let key_client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(3))
-            .build()
+        let key_url = ctx.endpoint(OPENROUTER_API_BASE, OPENROUTER_KEY_PATH);
+        let key_client = crate::core::http_client(std::time::Duration::from_secs(3))
             .map_err(|e| ProviderError::Other(e.to_string()))?;
 
         if let Ok(key_resp) = key_client
@@ -187,7 +183,7 @@ impl OpenRouterProvider {
             }
         }
 
-        Ok(usage)
+        Ok((usage, cost))
     }
 }
 
@@ -207,29 +203,28 @@ impl Provider for OpenRouterProvider {
         &self.metadata
     }
 
-    async fn fetch_usage(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
-        tracing::debug!("Fetching OpenRouter usage");
-
-        match ctx.source_mode {
-            SourceMode::Auto | SourceMode::OAuth => {
-                let usage = self.fetch_usage_api(ctx).await?;
-                Ok(ProviderFetchResult::new(usage, "api"))
-            }
-            SourceMode::Web | SourceMode::Cli => {
-                Err(ProviderError::UnsupportedSource(ctx.source_mode))
-            }
-        }
+    fn fetch_pipeline(&self) -> ProviderFetchPipeline<'_> {
+        ProviderFetchPipeline::empty().with_strategy(OpenRouterApiStrategy { provider: self })
     }
+}
 
-    fn available_sources(&self) -> Vec<SourceMode> {
-        vec![SourceMode::Auto, SourceMode::OAuth]
+/// OpenRouter credits API key strategy
+struct OpenRouterApiStrategy<'a> {
+    provider: &'a OpenRouterProvider,
+}
+
+#[async_trait]
+impl ProviderFetchStrategy for OpenRouterApiStrategy<'_> {
+    fn id(&self) -> &str {
+        "openrouter.api"
     }
 
-    fn supports_web(&self) -> bool {
-        false
+    fn kind(&self) -> ProviderFetchKind {
+        ProviderFetchKind::ApiToken
     }
 
-    fn supports_cli(&self) -> bool {
-        false
+    async fn fetch(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        let (usage, cost) = self.provider.fetch_usage_api(ctx).await?;
+        Ok(ProviderFetchResult::new(usage, "api").with_cost(cost))
     }
 }
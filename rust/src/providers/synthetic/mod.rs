@@ -7,10 +7,14 @@ use async_trait::async_trait;
 use std::path::PathBuf;
 
 use crate::core::{
-    FetchContext, Provider, ProviderId, ProviderError, ProviderFetchResult,
-    ProviderMetadata, RateWindow, SourceMode, UsageSnapshot,
+    FetchContext, Provider, ProviderError, ProviderFetchKind, ProviderFetchPipeline,
+    ProviderFetchResult, ProviderFetchStrategy, ProviderId, ProviderMetadata, RateWindow,
+    UsageSnapshot,
 };
 
+/// Synthetic API base URL
+const SYNTHETIC_API_BASE: &str = "https://api.synthetic.computer";
+
 /// Synthetic provider
 pub struct SyntheticProvider {
     metadata: ProviderMetadata,
@@ -102,20 +106,18 @@ impl SyntheticProvider {
     async fn fetch_via_web(&self, ctx: &FetchContext) -> Result<UsageSnapshot, ProviderError> {
         let token = self.read_access_token(ctx).await?;
 
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
+        let client = crate::core::http_client(std::time::Duration::from_secs(30))
             .map_err(|e| ProviderError::Other(e.to_string()))?;
 
         // Synthetic usage API (hypothetical - adjust based on actual API)
         let resp = client
-            .get("https://api.synthetic.computer/v1/usage")
+            .get(ctx.endpoint(SYNTHETIC_API_BASE, "/v1/usage"))
             .header("Authorization", format!("Bearer {}", token))
             .send()
             .await?;
 
         if !resp.status().is_success() {
-            return Err(ProviderError::AuthRequired);
+            return Err(ProviderError::from_http_status(resp.status(), "Synthetic API"));
         }
 
         let json: serde_json::Value = resp.json().await
@@ -203,40 +205,51 @@ impl Provider for SyntheticProvider {
         &self.metadata
     }
 
-    async fn fetch_usage(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
-        tracing::debug!("Fetching Synthetic usage");
+    fn fetch_pipeline(&self) -> ProviderFetchPipeline<'_> {
+        ProviderFetchPipeline::empty()
+            .with_strategy(SyntheticWebStrategy { provider: self })
+            .with_strategy(SyntheticLocalStrategy { provider: self })
+    }
+}
 
-        match ctx.source_mode {
-            SourceMode::Auto => {
-                if let Ok(usage) = self.fetch_via_web(ctx).await {
-                    return Ok(ProviderFetchResult::new(usage, "web"));
-                }
-                let usage = self.probe_cli(ctx).await?;
-                Ok(ProviderFetchResult::new(usage, "cli"))
-            }
-            SourceMode::Web => {
-                let usage = self.fetch_via_web(ctx).await?;
-                Ok(ProviderFetchResult::new(usage, "web"))
-            }
-            SourceMode::Cli => {
-                let usage = self.probe_cli(ctx).await?;
-                Ok(ProviderFetchResult::new(usage, "cli"))
-            }
-            SourceMode::OAuth => {
-                Err(ProviderError::UnsupportedSource(SourceMode::OAuth))
-            }
-        }
+/// Synthetic usage API strategy
+struct SyntheticWebStrategy<'a> {
+    provider: &'a SyntheticProvider,
+}
+
+#[async_trait]
+impl ProviderFetchStrategy for SyntheticWebStrategy<'_> {
+    fn id(&self) -> &str {
+        "synthetic.web"
+    }
+
+    fn kind(&self) -> ProviderFetchKind {
+        ProviderFetchKind::Web
+    }
+
+    async fn fetch(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        let usage = self.provider.fetch_via_web(ctx).await?;
+        Ok(ProviderFetchResult::new(usage, "web"))
     }
+}
 
-    fn available_sources(&self) -> Vec<SourceMode> {
-        vec![SourceMode::Auto, SourceMode::Web, SourceMode::Cli]
+/// Synthetic configuration probe strategy
+struct SyntheticLocalStrategy<'a> {
+    provider: &'a SyntheticProvider,
+}
+
+#[async_trait]
+impl ProviderFetchStrategy for SyntheticLocalStrategy<'_> {
+    fn id(&self) -> &str {
+        "synthetic.local"
     }
 
-    fn supports_web(&self) -> bool {
-        true
+    fn kind(&self) -> ProviderFetchKind {
+        ProviderFetchKind::LocalProbe
     }
 
-    fn supports_cli(&self) -> bool {
-        true
+    async fn fetch(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        let usage = self.provider.probe_cli(ctx).await?;
+        Ok(ProviderFetchResult::new(usage, "cli"))
     }
 }
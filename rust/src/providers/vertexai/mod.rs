@@ -13,10 +13,14 @@ use async_trait::async_trait;
 use std::path::PathBuf;
 
 use crate::core::{
-    FetchContext, Provider, ProviderId, ProviderError, ProviderFetchResult,
-    ProviderMetadata, RateWindow, SourceMode, UsageSnapshot,
+    FetchContext, Provider, ProviderError, ProviderFetchKind, ProviderFetchPipeline,
+    ProviderFetchResult, ProviderFetchStrategy, ProviderId, ProviderMetadata, RateWindow,
+    UsageSnapshot,
 };
 
+/// Cloud Resource Manager API base URL
+const RESOURCE_MANAGER_API_BASE: &str = "https://cloudresourcemanager.googleapis.com";
+
 /// Vertex AI provider
 pub struct VertexAIProvider {
     metadata: ProviderMetadata,
@@ -73,8 +77,13 @@ impl VertexAIProvider {
         possible_paths.into_iter().flatten().find(|p| p.exists())
     }
 
-    /// Read access token from gcloud config
-    async fn get_access_token(&self) -> Result<String, ProviderError> {
+    /// Read access token from the context or gcloud config
+    async fn get_access_token(&self, api_key: Option<&str>) -> Result<String, ProviderError> {
+        // Check api_key first (from settings/ctx)
+        if let Some(token) = api_key.map(str::trim).filter(|token| !token.is_empty()) {
+            return Ok(token.to_string());
+        }
+
         let creds_path = Self::get_gcloud_config_path()
             .ok_or_else(|| ProviderError::NotInstalled("Google Cloud credentials not found".to_string()))?;
 
@@ -114,7 +123,8 @@ impl VertexAIProvider {
     }
 
     async fn refresh_access_token(&self, refresh_token: &str, client_id: &str, client_secret: &str) -> Result<String, ProviderError> {
-        let client = reqwest::Client::new();
+        let client = crate::core::http_client(std::time::Duration::from_secs(30))
+            .map_err(|e| ProviderError::Other(e.to_string()))?;
 
         let resp = client
             .post("https://oauth2.googleapis.com/token")
@@ -141,12 +151,10 @@ impl VertexAIProvider {
     }
 
     /// Fetch usage via Vertex AI API
-    async fn fetch_via_web(&self) -> Result<UsageSnapshot, ProviderError> {
-        let token = self.get_access_token().await?;
+    async fn fetch_via_web(&self, ctx: &FetchContext) -> Result<UsageSnapshot, ProviderError> {
+        let token = self.get_access_token(ctx.api_key.as_deref()).await?;
 
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
+        let client = crate::core::http_client(std::time::Duration::from_secs(30))
             .map_err(|e| ProviderError::Other(e.to_string()))?;
 
         // Get project ID from config
@@ -154,27 +162,27 @@ impl VertexAIProvider {
 
         // Vertex AI billing/quota API
         let resp = client
-            .get(format!(
-                "https://cloudresourcemanager.googleapis.com/v1/projects/{}",
-                project_id
+            .get(ctx.endpoint(
+                RESOURCE_MANAGER_API_BASE,
+                &format!("/v1/projects/{}", project_id),
             ))
             .header("Authorization", format!("Bearer {}", token))
             .send()
-            .await;
+            .await?;
 
-        match resp {
-            Ok(r) if r.status().is_success() => {
-                let json: serde_json::Value = r.json().await
-                    .map_err(|e| ProviderError::Parse(e.to_string()))?;
-                self.parse_usage_response(&json, &project_id)
-            }
-            _ => {
-                // Return placeholder with project info
-                let usage = UsageSnapshot::new(RateWindow::new(0.0))
-                    .with_login_method(&format!("Vertex AI ({})", project_id));
-                Ok(usage)
-            }
+        if resp.status() == reqwest::StatusCode::UNAUTHORIZED
+            || resp.status() == reqwest::StatusCode::FORBIDDEN
+        {
+            return Err(ProviderError::AuthRequired);
         }
+
+        if !resp.status().is_success() {
+            return Err(ProviderError::from_http_status(resp.status(), "Cloud Resource Manager API"));
+        }
+
+        let json: serde_json::Value = resp.json().await
+            .map_err(|e| ProviderError::Parse(e.to_string()))?;
+        self.parse_usage_response(&json, &project_id)
     }
 
     async fn get_project_id(&self) -> Result<String, ProviderError> {
@@ -258,40 +266,55 @@ impl Provider for VertexAIProvider {
         &self.metadata
     }
 
-    async fn fetch_usage(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
-        tracing::debug!("Fetching Vertex AI usage");
+    fn fetch_pipeline(&self) -> ProviderFetchPipeline<'_> {
+        ProviderFetchPipeline::empty()
+            .with_strategy(VertexAIWebStrategy { provider: self })
+            .with_strategy(VertexAICliStrategy { provider: self })
+    }
+}
 
-        match ctx.source_mode {
-            SourceMode::Auto => {
-                if let Ok(usage) = self.fetch_via_web().await {
-                    return Ok(ProviderFetchResult::new(usage, "web"));
-                }
-                let usage = self.probe_cli().await?;
-                Ok(ProviderFetchResult::new(usage, "cli"))
-            }
-            SourceMode::Web => {
-                let usage = self.fetch_via_web().await?;
-                Ok(ProviderFetchResult::new(usage, "web"))
-            }
-            SourceMode::Cli => {
-                let usage = self.probe_cli().await?;
-                Ok(ProviderFetchResult::new(usage, "cli"))
-            }
-            SourceMode::OAuth => {
-                Err(ProviderError::UnsupportedSource(SourceMode::OAuth))
-            }
-        }
+/// Cloud Monitoring quota API strategy
+struct VertexAIWebStrategy<'a> {
+    provider: &'a VertexAIProvider,
+}
+
+#[async_trait]
+impl ProviderFetchStrategy for VertexAIWebStrategy<'_> {
+    fn id(&self) -> &str {
+        "vertexai.web"
+    }
+
+    fn kind(&self) -> ProviderFetchKind {
+        ProviderFetchKind::Web
+    }
+
+    async fn fetch(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        let usage = self.provider.fetch_via_web(ctx).await?;
+        Ok(ProviderFetchResult::new(usage, "web"))
+    }
+}
+
+/// gcloud CLI probe strategy
+struct VertexAICliStrategy<'a> {
+    provider: &'a VertexAIProvider,
+}
+
+#[async_trait]
+impl ProviderFetchStrategy for VertexAICliStrategy<'_> {
+    fn id(&self) -> &str {
+        "vertexai.cli"
     }
 
-    fn available_sources(&self) -> Vec<SourceMode> {
-        vec![SourceMode::Auto, SourceMode::Web, SourceMode::Cli]
+    fn kind(&self) -> ProviderFetchKind {
+        ProviderFetchKind::Cli
     }
 
-    fn supports_web(&self) -> bool {
-        true
+    fn is_available(&self, _ctx: &FetchContext) -> bool {
+        VertexAIProvider::which_gcloud().is_some()
     }
 
-    fn supports_cli(&self) -> bool {
-        true
+    async fn fetch(&self, _ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        let usage = self.provider.probe_cli().await?;
+        Ok(ProviderFetchResult::new(usage, "cli"))
     }
 }
@@ -84,9 +84,7 @@ impl VertexAITokenRefresher {
             return Err(RefreshError::InvalidResponse("No refresh token available".to_string()));
         }
 
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
+        let client = crate::core::http_client(std::time::Duration::from_secs(30))
             .map_err(|e| RefreshError::NetworkError(e.to_string()))?;
 
         let body = [
@@ -8,12 +8,14 @@ use serde::Deserialize;
 use serde_json::json;
 
 use crate::core::{
-    FetchContext, Provider, ProviderId, ProviderError, ProviderFetchResult,
-    ProviderMetadata, RateWindow, SourceMode, UsageSnapshot,
+    FetchContext, Provider, ProviderError, ProviderFetchKind, ProviderFetchPipeline,
+    ProviderFetchResult, ProviderFetchStrategy, ProviderId, ProviderMetadata, RateWindow,
+    UsageSnapshot,
 };
 
-/// Warp GraphQL API endpoint
-const WARP_API_URL: &str = "https://app.warp.dev/graphql/v2?op=GetRequestLimitInfo";
+/// Warp GraphQL API base URL and request path
+const WARP_API_BASE: &str = "https://app.warp.dev";
+const WARP_API_PATH: &str = "/graphql/v2?op=GetRequestLimitInfo";
 
 /// Windows Credential Manager target for Warp API token
 const WARP_CREDENTIAL_TARGET: &str = "codexbar-warp";
@@ -166,9 +168,7 @@ impl WarpProvider {
     async fn fetch_usage_api(&self, ctx: &FetchContext) -> Result<UsageSnapshot, ProviderError> {
         let api_key = Self::get_api_token(ctx.api_key.as_deref())?;
 
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(15))
-            .build()
+        let client = crate::core::http_client(std::time::Duration::from_secs(15))
             .map_err(|e| ProviderError::Other(e.to_string()))?;
 
         let os_version = "10.0";
@@ -188,7 +188,7 @@ impl WarpProvider {
         });
 
         let resp = client
-            .post(WARP_API_URL)
+            .post(ctx.endpoint(WARP_API_BASE, WARP_API_PATH))
             .header("Content-Type", "application/json")
             .header("Accept", "application/json")
             .header("x-warp-client-id", "warp-app")
@@ -196,16 +196,14 @@ impl WarpProvider {
             .header("x-warp-os-name", "Windows")
             .header("x-warp-os-version", os_version)
             .header("Authorization", format!("Bearer {}", api_key))
+            // The Warp API expects its own client's user-agent
             .header("User-Agent", "Warp/1.0")
             .json(&body)
             .send()
             .await?;
 
         if !resp.status().is_success() {
-            return Err(ProviderError::Other(format!(
-                "Warp API returned status {}",
-                resp.status()
-            )));
+            return Err(ProviderError::from_http_status(resp.status(), "Warp API"));
         }
 
         let gql_response: GraphQLResponse = resp
@@ -328,29 +326,28 @@ impl Provider for WarpProvider {
         &self.metadata
     }
 
-    async fn fetch_usage(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
-        tracing::debug!("Fetching Warp usage");
-
-        match ctx.source_mode {
-            SourceMode::Auto | SourceMode::OAuth => {
-                let usage = self.fetch_usage_api(ctx).await?;
-                Ok(ProviderFetchResult::new(usage, "api"))
-            }
-            SourceMode::Web | SourceMode::Cli => {
-                Err(ProviderError::UnsupportedSource(ctx.source_mode))
-            }
-        }
+    fn fetch_pipeline(&self) -> ProviderFetchPipeline<'_> {
+        ProviderFetchPipeline::empty().with_strategy(WarpApiStrategy { provider: self })
     }
+}
+
+/// Warp GraphQL API key strategy
+struct WarpApiStrategy<'a> {
+    provider: &'a WarpProvider,
+}
 
-    fn available_sources(&self) -> Vec<SourceMode> {
-        vec![SourceMode::Auto, SourceMode::OAuth]
+#[async_trait]
+impl ProviderFetchStrategy for WarpApiStrategy<'_> {
+    fn id(&self) -> &str {
+        "warp.api"
     }
 
-    fn supports_web(&self) -> bool {
-        false
+    fn kind(&self) -> ProviderFetchKind {
+        ProviderFetchKind::ApiToken
     }
 
-    fn supports_cli(&self) -> bool {
-        false
+    async fn fetch(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        let usage = self.provider.fetch_usage_api(ctx).await?;
+        Ok(ProviderFetchResult::new(usage, "api"))
     }
 }
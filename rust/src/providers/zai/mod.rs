@@ -13,12 +13,14 @@ use async_trait::async_trait;
 use serde::Deserialize;
 
 use crate::core::{
-    FetchContext, Provider, ProviderId, ProviderError, ProviderFetchResult,
-    ProviderMetadata, RateWindow, SourceMode, UsageSnapshot,
+    FetchContext, NamedRateWindow, Provider, ProviderError, ProviderFetchKind,
+    ProviderFetchPipeline, ProviderFetchResult, ProviderFetchStrategy, ProviderId,
+    ProviderMetadata, RateWindow, RateWindowKind, UsageSnapshot,
 };
 
-/// z.ai API endpoint for quota/usage
-const ZAI_API_URL: &str = "https://api.z.ai/api/monitor/usage/quota/limit";
+/// z.ai API base URL and quota/usage path
+const ZAI_API_BASE: &str = "https://api.z.ai";
+const ZAI_QUOTA_PATH: &str = "/api/monitor/usage/quota/limit";
 
 /// Windows Credential Manager target for z.ai API token
 const ZAI_CREDENTIAL_TARGET: &str = "codexbar-zai";
@@ -105,27 +107,18 @@ impl ZaiProvider {
     async fn fetch_usage_api(&self, ctx: &FetchContext) -> Result<UsageSnapshot, ProviderError> {
         let api_token = Self::get_api_token(ctx.api_key.as_deref())?;
 
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
+        let client = crate::core::http_client(std::time::Duration::from_secs(30))
             .map_err(|e| ProviderError::Other(e.to_string()))?;
 
         let resp = client
-            .get(ZAI_API_URL)
+            .get(ctx.endpoint(ZAI_API_BASE, ZAI_QUOTA_PATH))
             .header("Authorization", format!("Bearer {}", api_token))
             .header("Accept", "application/json")
             .send()
             .await?;
 
-        if resp.status() == reqwest::StatusCode::UNAUTHORIZED {
-            return Err(ProviderError::AuthRequired);
-        }
-
         if !resp.status().is_success() {
-            return Err(ProviderError::Other(format!(
-                "z.ai API returned status {}",
-                resp.status()
-            )));
+            return Err(ProviderError::from_http_status(resp.status(), "z.ai API"));
         }
 
         let resp_bytes = resp.bytes().await
@@ -145,54 +138,65 @@ impl ZaiProvider {
     }
 
     fn parse_quota_response(&self, quota: &ZaiQuotaResponse) -> Result<UsageSnapshot, ProviderError> {
-        // Find tokens limit (primary/session)
+        // Tokens limit is the primary (session) window, MCP the secondary one
         let tokens_limit = quota.limits.iter()
             .find(|l| l.limit_type.as_deref() == Some("tokens"));
-
-        // Find MCP limit (weekly/secondary)
         let mcp_limit = quota.limits.iter()
             .find(|l| l.limit_type.as_deref() == Some("mcp"));
 
-        // Calculate session (tokens) usage percentage
-        let session_percent = if let Some(tokens) = tokens_limit {
-            let used = tokens.used.unwrap_or(0.0);
-            let limit = tokens.limit.unwrap_or(0.0);
-            if limit > 0.0 {
-                (used / limit) * 100.0
-            } else if used > 0.0 {
-                // No limit field but usage exists - don't report 0%
-                100.0
-            } else {
-                0.0
-            }
-        } else {
-            0.0
-        };
+        let session = tokens_limit
+            .map(Self::limit_window)
+            .unwrap_or_else(|| RateWindow::new(0.0));
 
-        // Calculate MCP usage percentage
-        let mcp_percent = if let Some(mcp) = mcp_limit {
-            let used = mcp.used.unwrap_or(0.0);
-            let limit = mcp.limit.unwrap_or(0.0);
-            if limit > 0.0 {
-                (used / limit) * 100.0
-            } else if used > 0.0 {
-                100.0
-            } else {
-                0.0
+        let mut usage = UsageSnapshot::from_window(NamedRateWindow::primary(session).with_label("Tokens"))
+            .with_login_method("z.ai");
+
+        if let Some(mcp) = mcp_limit {
+            usage = usage.with_window(
+                NamedRateWindow::secondary(Self::limit_window(mcp)).with_label("MCP"),
+            );
+        }
+
+        // Any other limit types are kept as their own windows
+        for limit in &quota.limits {
+            let Some(limit_type) = limit.limit_type.as_deref() else { continue };
+            if limit_type == "tokens" || limit_type == "mcp" {
+                continue;
+            }
+            let id = limit_type.to_lowercase();
+            let mut label = id.replace(['_', '-'], " ");
+            if let Some(first) = label.get_mut(0..1) {
+                first.make_ascii_uppercase();
             }
+            usage = usage.with_window(NamedRateWindow::new(
+                id,
+                label,
+                RateWindowKind::Credits,
+                Self::limit_window(limit),
+            ));
+        }
+
+        Ok(usage)
+    }
+
+    /// Convert a quota limit into a rate window
+    fn limit_window(limit: &ZaiLimit) -> RateWindow {
+        let used = limit.used.unwrap_or(0.0);
+        let total = limit.limit.unwrap_or(0.0);
+        let percent = if total > 0.0 {
+            (used / total) * 100.0
+        } else if used > 0.0 {
+            // No limit field but usage exists - don't report 0%
+            100.0
         } else {
             0.0
         };
 
-        let mut usage = UsageSnapshot::new(RateWindow::new(session_percent))
-            .with_login_method("z.ai");
-
-        // Add secondary (MCP) usage if available
-        if mcp_limit.is_some() {
-            usage = usage.with_secondary(RateWindow::new(mcp_percent));
-        }
+        let resets_at = limit.reset_at.as_deref()
+            .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+            .map(|dt| dt.with_timezone(&chrono::Utc));
 
-        Ok(usage)
+        RateWindow::with_details(percent, None, resets_at, None)
     }
 }
 
@@ -212,31 +216,28 @@ impl Provider for ZaiProvider {
         &self.metadata
     }
 
-    async fn fetch_usage(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
-        tracing::debug!("Fetching z.ai usage");
-
-        // z.ai only supports OAuth/API token - no CLI or web cookie fallback
-        match ctx.source_mode {
-            SourceMode::Auto | SourceMode::OAuth => {
-                let usage = self.fetch_usage_api(ctx).await?;
-                Ok(ProviderFetchResult::new(usage, "oauth"))
-            }
-            SourceMode::Web | SourceMode::Cli => {
-                // z.ai doesn't support web cookies or CLI
-                Err(ProviderError::UnsupportedSource(ctx.source_mode))
-            }
-        }
+    fn fetch_pipeline(&self) -> ProviderFetchPipeline<'_> {
+        ProviderFetchPipeline::empty().with_strategy(ZaiApiStrategy { provider: self })
     }
+}
 
-    fn available_sources(&self) -> Vec<SourceMode> {
-        vec![SourceMode::Auto, SourceMode::OAuth]
+/// z.ai quota API token strategy
+struct ZaiApiStrategy<'a> {
+    provider: &'a ZaiProvider,
+}
+
+#[async_trait]
+impl ProviderFetchStrategy for ZaiApiStrategy<'_> {
+    fn id(&self) -> &str {
+        "zai.api"
     }
 
-    fn supports_web(&self) -> bool {
-        false
+    fn kind(&self) -> ProviderFetchKind {
+        ProviderFetchKind::ApiToken
     }
 
-    fn supports_cli(&self) -> bool {
-        false
+    async fn fetch(&self, ctx: &FetchContext) -> Result<ProviderFetchResult, ProviderError> {
+        let usage = self.provider.fetch_usage_api(ctx).await?;
+        Ok(ProviderFetchResult::new(usage, "oauth"))
     }
 }
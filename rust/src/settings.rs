@@ -12,7 +12,10 @@ use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 
-use crate::core::ProviderId;
+use crate::core::{
+    normalize_base_url_override, BudgetSettings, CurrencyConverter, HttpClientConfig, ProviderId,
+    RefreshPolicy,
+};
 
 /// Update channel for receiving updates
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -131,6 +134,18 @@ pub struct Settings {
     /// Refresh interval in seconds (0 = manual only)
     pub refresh_interval_secs: u64,
 
+    /// Per-provider refresh interval overrides in seconds (0 = manual only)
+    #[serde(default)]
+    pub provider_refresh_intervals: HashMap<String, u64>,
+
+    /// Providers excluded from automatic and manual refreshes
+    #[serde(default)]
+    pub paused_providers: HashSet<String>,
+
+    /// Poll faster when a window is nearly exhausted or about to reset
+    #[serde(default = "default_true")]
+    pub adaptive_refresh: bool,
+
     /// Whether to start minimized
     pub start_minimized: bool,
 
@@ -198,6 +213,46 @@ pub struct Settings {
     /// Install pending updates when quitting the application
     #[serde(default)]
     pub install_updates_on_quit: bool,
+
+    /// Per-provider API base URL overrides (e.g. corporate egress proxy)
+    #[serde(default)]
+    pub provider_base_urls: HashMap<String, String>,
+
+    /// Currency that costs from all providers are converted to for totals and charts
+    #[serde(default = "default_reporting_currency")]
+    pub reporting_currency: String,
+
+    /// Exchange-rate overrides in units per 1 USD (e.g. "CNY": 7.2)
+    #[serde(default)]
+    pub exchange_rates: HashMap<String, f64>,
+
+    /// Monthly/weekly spend budgets in USD, in total and per provider
+    #[serde(default)]
+    pub budgets: BudgetSettings,
+
+    /// Days of usage history to keep (0 = keep everything)
+    #[serde(default = "default_history_retention_days")]
+    pub history_retention_days: u32,
+
+    /// Proxy for all HTTP requests (http://, https://, socks5:// or socks5h://)
+    #[serde(default)]
+    pub http_proxy: Option<String>,
+
+    /// Comma-separated hosts that bypass the proxy
+    #[serde(default)]
+    pub http_no_proxy: Option<String>,
+
+    /// PEM file with extra root certificates
+    #[serde(default)]
+    pub http_ca_bundle: Option<PathBuf>,
+
+    /// HTTP request timeout in seconds (None = per-request defaults)
+    #[serde(default)]
+    pub http_timeout_secs: Option<u64>,
+
+    /// User-agent for HTTP requests (None = CodexBar/<version>)
+    #[serde(default)]
+    pub http_user_agent: Option<String>,
 }
 
 fn default_true() -> bool {
@@ -208,6 +263,14 @@ fn default_global_shortcut() -> String {
     "Ctrl+Shift+U".to_string()
 }
 
+fn default_reporting_currency() -> String {
+    "USD".to_string()
+}
+
+fn default_history_retention_days() -> u32 {
+    90
+}
+
 impl Default for Settings {
     fn default() -> Self {
         let mut enabled = HashSet::new();
@@ -218,6 +281,9 @@ impl Default for Settings {
         Self {
             enabled_providers: enabled,
             refresh_interval_secs: 300, // 5 minutes
+            provider_refresh_intervals: HashMap::new(), // Empty = global interval for all
+            paused_providers: HashSet::new(),
+            adaptive_refresh: true,
             start_minimized: false,
             start_at_login: false,
             show_notifications: true,
@@ -239,6 +305,16 @@ impl Default for Settings {
             global_shortcut: default_global_shortcut(), // Ctrl+Shift+U by default
             auto_download_updates: true, // Auto-download updates by default
             install_updates_on_quit: false, // Don't auto-install on quit by default
+            provider_base_urls: HashMap::new(), // Empty = built-in endpoints
+            reporting_currency: default_reporting_currency(), // Report in USD by default
+            exchange_rates: HashMap::new(), // Empty = built-in rates
+            budgets: BudgetSettings::default(), // No budgets
+            history_retention_days: default_history_retention_days(), // Three months of history
+            http_proxy: None,            // System proxy environment variables
+            http_no_proxy: None,
+            http_ca_bundle: None,        // Built-in root certificates only
+            http_timeout_secs: None,     // Per-request defaults
+            http_user_agent: None,       // CodexBar/<version>
         }
     }
 }
@@ -373,21 +449,20 @@ impl Settings {
 
     /// Get list of enabled provider IDs
     pub fn get_enabled_provider_ids(&self) -> Vec<ProviderId> {
-        ProviderId::all()
-            .iter()
-            .filter(|id| self.is_provider_enabled(**id))
-            .copied()
+        ProviderId::all_with_custom()
+            .into_iter()
+            .filter(|id| self.is_provider_enabled(*id))
             .collect()
     }
 
     /// Get all available providers with their enabled status
     pub fn get_all_providers_status(&self) -> Vec<ProviderStatus> {
-        ProviderId::all()
-            .iter()
+        ProviderId::all_with_custom()
+            .into_iter()
             .map(|id| ProviderStatus {
                 id: id.cli_name().to_string(),
                 name: id.display_name().to_string(),
-                enabled: self.is_provider_enabled(*id),
+                enabled: self.is_provider_enabled(id),
             })
             .collect()
     }
@@ -404,6 +479,110 @@ impl Settings {
     pub fn set_provider_metric(&mut self, id: ProviderId, metric: MetricPreference) {
         self.provider_metrics.insert(id.cli_name().to_string(), metric);
     }
+
+    /// Refresh interval for a provider in seconds (0 = manual only)
+    pub fn provider_refresh_interval(&self, id: ProviderId) -> u64 {
+        self.provider_refresh_intervals
+            .get(id.cli_name())
+            .copied()
+            .unwrap_or(self.refresh_interval_secs)
+    }
+
+    /// Set (or clear, with `None`) the refresh interval override for a provider
+    pub fn set_provider_refresh_interval(&mut self, id: ProviderId, secs: Option<u64>) {
+        match secs {
+            Some(secs) => {
+                self.provider_refresh_intervals.insert(id.cli_name().to_string(), secs);
+            }
+            None => {
+                self.provider_refresh_intervals.remove(id.cli_name());
+            }
+        }
+    }
+
+    /// Check if a provider is paused
+    pub fn is_provider_paused(&self, id: ProviderId) -> bool {
+        self.paused_providers.contains(id.cli_name())
+    }
+
+    /// Pause or resume a provider
+    pub fn set_provider_paused(&mut self, id: ProviderId, paused: bool) {
+        if paused {
+            self.paused_providers.insert(id.cli_name().to_string());
+        } else {
+            self.paused_providers.remove(id.cli_name());
+        }
+    }
+
+    /// Usage history older than this is pruned (None = keep everything)
+    pub fn history_cutoff(&self, now: chrono::DateTime<chrono::Utc>) -> Option<chrono::DateTime<chrono::Utc>> {
+        (self.history_retention_days > 0).then(|| now - chrono::Duration::days(i64::from(self.history_retention_days)))
+    }
+
+    /// Refresh policy for the scheduler
+    pub fn refresh_policy(&self, id: ProviderId) -> RefreshPolicy {
+        RefreshPolicy {
+            interval_secs: self.provider_refresh_interval(id),
+            adaptive: self.adaptive_refresh,
+            paused: self.is_provider_paused(id),
+        }
+    }
+
+    /// Resolve the API base URL override for a provider.
+    /// The `CODEXBAR_<PROVIDER>_BASE_URL` env var wins over settings.
+    pub fn base_url_override(&self, id: ProviderId) -> Option<String> {
+        if let Ok(value) = std::env::var(id.base_url_env_var()) {
+            if let Some(url) = normalize_base_url_override(&value) {
+                return Some(url);
+            }
+        }
+
+        self.provider_base_urls
+            .get(id.cli_name())
+            .and_then(|value| normalize_base_url_override(value))
+    }
+
+    /// Set (or clear, with an empty string) the base URL override for a provider
+    pub fn set_provider_base_url(&mut self, id: ProviderId, url: &str) {
+        let url = url.trim();
+        if url.is_empty() {
+            self.provider_base_urls.remove(id.cli_name());
+        } else {
+            self.provider_base_urls.insert(id.cli_name().to_string(), url.to_string());
+        }
+    }
+
+    /// Converter into the reporting currency, using built-in rates plus overrides
+    pub fn currency_converter(&self) -> CurrencyConverter {
+        CurrencyConverter::new(&self.reporting_currency, &self.exchange_rates)
+    }
+
+    /// Network settings for the shared HTTP client factory
+    pub fn http_client_config(&self) -> HttpClientConfig {
+        let non_empty = |value: &Option<String>| {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+        };
+        HttpClientConfig {
+            proxy: non_empty(&self.http_proxy),
+            no_proxy: non_empty(&self.http_no_proxy),
+            ca_bundle: self.http_ca_bundle.clone().filter(|p| !p.as_os_str().is_empty()),
+            timeout_secs: self.http_timeout_secs.filter(|secs| *secs > 0),
+            user_agent: non_empty(&self.http_user_agent),
+        }
+    }
+
+    /// Set (or clear, with `None`) the exchange-rate override for a currency
+    pub fn set_exchange_rate(&mut self, code: &str, units_per_usd: Option<f64>) {
+        let code = code.trim().to_uppercase();
+        self.exchange_rates.retain(|key, _| !key.eq_ignore_ascii_case(&code));
+        if let Some(rate) = units_per_usd.filter(|r| r.is_finite() && *r > 0.0) {
+            self.exchange_rates.insert(code, rate);
+        }
+    }
 }
 
 /// Provider status for settings UI
@@ -742,6 +921,89 @@ mod tests {
         assert_eq!(settings.critical_usage_threshold, 90.0);
     }
 
+    #[test]
+    fn test_settings_provider_base_url() {
+        let mut settings = Settings::default();
+        assert!(settings.base_url_override(ProviderId::Synthetic).is_none());
+
+        settings.set_provider_base_url(ProviderId::Synthetic, "https://egress.corp.example/synthetic/");
+        assert_eq!(
+            settings.base_url_override(ProviderId::Synthetic),
+            Some("https://egress.corp.example/synthetic".to_string())
+        );
+
+        // Insecure overrides are ignored
+        settings.set_provider_base_url(ProviderId::Synthetic, "http://egress.corp.example");
+        assert!(settings.base_url_override(ProviderId::Synthetic).is_none());
+
+        settings.set_provider_base_url(ProviderId::Synthetic, "");
+        assert!(settings.provider_base_urls.is_empty());
+    }
+
+    #[test]
+    fn test_settings_currency_converter() {
+        let mut settings = Settings::default();
+        assert_eq!(settings.reporting_currency, "USD");
+        assert!(settings.currency_converter().convert(7.2, "CNY").is_some());
+
+        settings.reporting_currency = "CNY".to_string();
+        settings.set_exchange_rate("cny", Some(7.0));
+        let converter = settings.currency_converter();
+        assert!((converter.convert(10.0, "USD").unwrap() - 70.0).abs() < 1e-9);
+
+        settings.set_exchange_rate("CNY", None);
+        assert!(settings.exchange_rates.is_empty());
+    }
+
+    #[test]
+    fn test_settings_http_client_config() {
+        let mut settings = Settings::default();
+        assert_eq!(settings.http_client_config(), HttpClientConfig::default());
+
+        settings.http_proxy = Some(" http://proxy.corp:8080 ".to_string());
+        settings.http_no_proxy = Some("".to_string());
+        settings.http_timeout_secs = Some(0);
+        settings.http_user_agent = Some("CorpBar/1.0".to_string());
+        let config = settings.http_client_config();
+        assert_eq!(config.proxy.as_deref(), Some("http://proxy.corp:8080"));
+        assert_eq!(config.no_proxy, None);
+        assert_eq!(config.timeout_secs, None);
+        assert_eq!(config.user_agent.as_deref(), Some("CorpBar/1.0"));
+    }
+
+    #[test]
+    fn test_settings_refresh_policy() {
+        let mut settings = Settings::default();
+        let policy = settings.refresh_policy(ProviderId::Claude);
+        assert_eq!(policy.interval_secs, 300);
+        assert!(policy.adaptive);
+        assert!(!policy.paused);
+
+        settings.set_provider_refresh_interval(ProviderId::Claude, Some(60));
+        settings.set_provider_paused(ProviderId::Codex, true);
+        assert_eq!(settings.refresh_policy(ProviderId::Claude).interval_secs, 60);
+        assert!(settings.refresh_policy(ProviderId::Codex).paused);
+
+        settings.set_provider_refresh_interval(ProviderId::Claude, None);
+        settings.set_provider_paused(ProviderId::Codex, false);
+        assert_eq!(settings.refresh_policy(ProviderId::Claude).interval_secs, 300);
+        assert!(!settings.is_provider_paused(ProviderId::Codex));
+    }
+
+    #[test]
+    fn test_settings_history_cutoff() {
+        let mut settings = Settings::default();
+        let now = chrono::Utc::now();
+        assert_eq!(settings.history_cutoff(now), Some(now - chrono::Duration::days(90)));
+
+        settings.history_retention_days = 0;
+        assert_eq!(settings.history_cutoff(now), None);
+
+        // Older settings files without the field keep the default
+        let loaded: Settings = serde_json::from_str(r#"{"refresh_interval_secs": 60}"#).unwrap();
+        assert_eq!(loaded.history_retention_days, 90);
+    }
+
     #[test]
     fn test_settings_provider_enabled() {
         let settings = Settings::default();
@@ -96,9 +96,7 @@ pub fn get_status_page_url(provider: &str) -> Option<&'static str> {
 
 /// Fetch status from a Statuspage.io-based status page
 pub async fn fetch_statuspage_io(url: &str) -> Result<ProviderStatus, String> {
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(10))
-        .build()
+    let client = crate::core::http_client(std::time::Duration::from_secs(10))
         .map_err(|e| e.to_string())?;
 
     // Statuspage.io API endpoint
@@ -148,9 +146,7 @@ pub async fn fetch_statuspage_io(url: &str) -> Result<ProviderStatus, String> {
 
 /// Fetch status with components from a Statuspage.io-based status page
 pub async fn fetch_statuspage_io_components(url: &str) -> Result<ProviderStatus, String> {
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(10))
-        .build()
+    let client = crate::core::http_client(std::time::Duration::from_secs(10))
         .map_err(|e| e.to_string())?;
 
     // Statuspage.io components endpoint
@@ -14,7 +14,7 @@ use tray_icon::{
 };
 
 use super::icon::{LoadingPattern, UsageLevel};
-use crate::core::ProviderId;
+use crate::core::{NamedRateWindow, ProviderId};
 use crate::settings::{Settings, TrayIconMode};
 use crate::status::IndicatorStatusLevel;
 
@@ -89,6 +89,46 @@ pub struct ProviderUsage {
     pub session_percent: f64,
     #[allow(dead_code)]
     pub weekly_percent: f64,
+    /// Used percent of the most restrictive window, which sets the bar colour
+    pub max_percent: f64,
+    /// Today's cost from the local logs, formatted
+    pub cost_today: Option<String>,
+}
+
+impl ProviderUsage {
+    /// Bars for a provider's rate windows (id, used percent): the upper bar shows
+    /// `session_percent`, the lower one the tightest window besides the primary
+    /// one, and the colour follows the most restrictive window
+    pub fn from_windows<'a>(
+        name: String,
+        session_percent: f64,
+        windows: impl IntoIterator<Item = (&'a str, f64)>,
+        cost_today: Option<String>,
+    ) -> Self {
+        let mut weekly_percent: Option<f64> = None;
+        let mut max_percent = session_percent;
+        for (id, percent) in windows {
+            if id != NamedRateWindow::PRIMARY_ID {
+                weekly_percent = Some(weekly_percent.map_or(percent, |w| w.max(percent)));
+            }
+            max_percent = max_percent.max(percent);
+        }
+        Self {
+            name,
+            session_percent,
+            weekly_percent: weekly_percent.unwrap_or(session_percent),
+            max_percent,
+            cost_today,
+        }
+    }
+
+    /// One line of the merged tooltip
+    fn tooltip_line(&self) -> String {
+        match &self.cost_today {
+            Some(cost) => format!("{}: {}% · {} today", self.name, self.session_percent as i32, cost),
+            None => format!("{}: {}%", self.name, self.session_percent as i32),
+        }
+    }
 }
 
 /// System tray manager
@@ -124,14 +164,14 @@ impl TrayManager {
         // Build submenu items first, then add to parent menu to avoid Windows duplication bug
         let providers_submenu = Submenu::new("Providers", true);
         let mut provider_menu_items = HashMap::new();
-        for provider_id in ProviderId::all() {
+        for provider_id in ProviderId::all_with_custom() {
             let cli_name = provider_id.cli_name();
             let display_name = provider_id.display_name();
-            let is_enabled = settings.is_provider_enabled(*provider_id);
+            let is_enabled = settings.is_provider_enabled(provider_id);
             let item_id = format!("provider_{}", cli_name);
             let check_item = CheckMenuItem::with_id(&item_id, display_name, true, is_enabled, None);
             providers_submenu.append(&check_item)?;
-            provider_menu_items.insert(*provider_id, check_item);
+            provider_menu_items.insert(provider_id, check_item);
         }
         menu.append(&providers_submenu)?;
 
@@ -268,7 +308,7 @@ impl TrayManager {
             let tooltip_lines: Vec<String> = providers
                 .iter()
                 .take(4)
-                .map(|p| format!("{}: {}%", p.name, p.session_percent as i32))
+                .map(ProviderUsage::tooltip_line)
                 .collect();
             let tooltip = format!("CodexBar\n{}", tooltip_lines.join("\n"));
             let _ = self.tray_icon.set_tooltip(Some(&tooltip));
@@ -282,7 +322,7 @@ impl TrayManager {
         let tooltip_lines: Vec<String> = providers
             .iter()
             .take(4) // Limit tooltip length
-            .map(|p| format!("{}: {}%", p.name, p.session_percent as i32))
+            .map(ProviderUsage::tooltip_line)
             .collect();
         let tooltip = format!("CodexBar\n{}", tooltip_lines.join("\n"));
         let _ = self.tray_icon.set_tooltip(Some(&tooltip));
@@ -403,8 +443,10 @@ impl TrayManager {
             p.name.hash(&mut hasher);
             let session_tenths = (p.session_percent * 10.0).round() as i32;
             let weekly_tenths = (p.weekly_percent * 10.0).round() as i32;
+            let max_tenths = (p.max_percent * 10.0).round() as i32;
             session_tenths.hash(&mut hasher);
             weekly_tenths.hash(&mut hasher);
+            max_tenths.hash(&mut hasher);
         }
         hasher.finish()
     }
@@ -930,7 +972,7 @@ fn create_merged_icon(providers: &[ProviderUsage]) -> Icon {
         let y_start = 4 + (i as u32 * (bar_height + spacing));
         let y_end = (y_start + bar_height).min(ICON_SIZE - 4);
 
-        let level = UsageLevel::from_percent(provider.session_percent);
+        let level = UsageLevel::from_percent(provider.max_percent);
         let (r, g, b) = level.color();
         let fill_width = ((provider.session_percent / 100.0) * bar_width as f64) as u32;
 
@@ -1365,15 +1407,15 @@ mod tests {
     #[test]
     fn test_merged_signature_tracks_list_content() {
         let providers_a = vec![
-            ProviderUsage { name: "Claude".into(), session_percent: 10.0, weekly_percent: 20.0 },
-            ProviderUsage { name: "Codex".into(), session_percent: 30.0, weekly_percent: 40.0 },
+            ProviderUsage { name: "Claude".into(), session_percent: 10.0, weekly_percent: 20.0, max_percent: 20.0, cost_today: None },
+            ProviderUsage { name: "Codex".into(), session_percent: 30.0, weekly_percent: 40.0, max_percent: 40.0, cost_today: None },
         ];
         let providers_b = vec![
-            ProviderUsage { name: "Claude".into(), session_percent: 10.0, weekly_percent: 20.0 },
-            ProviderUsage { name: "Codex".into(), session_percent: 30.0, weekly_percent: 50.0 },
+            ProviderUsage { name: "Claude".into(), session_percent: 10.0, weekly_percent: 20.0, max_percent: 20.0, cost_today: None },
+            ProviderUsage { name: "Codex".into(), session_percent: 30.0, weekly_percent: 50.0, max_percent: 50.0, cost_today: None },
         ];
         let providers_c = vec![
-            ProviderUsage { name: "Claude".into(), session_percent: 10.0, weekly_percent: 20.0 },
+            ProviderUsage { name: "Claude".into(), session_percent: 10.0, weekly_percent: 20.0, max_percent: 20.0, cost_today: None },
         ];
 
         let sig_a1 = TrayManager::merged_signature(&providers_a);
@@ -1385,4 +1427,39 @@ mod tests {
         assert_ne!(sig_a1, sig_b, "value change should alter signature");
         assert_ne!(sig_a1, sig_c, "length/content change should alter signature");
     }
+
+    #[test]
+    fn test_provider_usage_from_windows() {
+        // Claude: the Opus cap binds before the weekly window
+        let usage = ProviderUsage::from_windows(
+            "Claude".into(),
+            30.0,
+            [("primary", 30.0), ("secondary", 45.0), ("opus", 92.0)],
+            None,
+        );
+        assert_eq!(usage.session_percent, 30.0);
+        assert_eq!(usage.weekly_percent, 92.0);
+        assert_eq!(usage.max_percent, 92.0);
+
+        // Gemini: model buckets only, the primary one is not repeated below
+        let usage = ProviderUsage::from_windows(
+            "Gemini".into(),
+            40.0,
+            [("primary", 40.0), ("gemini-2.5-flash", 10.0), ("gemini-2.5-pro", 40.0)],
+            None,
+        );
+        assert_eq!((usage.weekly_percent, usage.max_percent), (40.0, 40.0));
+
+        // A single window fills both bars; a preferred metric above it sets the colour
+        let usage = ProviderUsage::from_windows("Cursor".into(), 80.0, [("primary", 75.0)], None);
+        assert_eq!((usage.weekly_percent, usage.max_percent), (80.0, 80.0));
+
+        let sig_low = TrayManager::merged_signature(&[
+            ProviderUsage { name: "Claude".into(), session_percent: 30.0, weekly_percent: 45.0, max_percent: 45.0, cost_today: None },
+        ]);
+        let sig_high = TrayManager::merged_signature(&[
+            ProviderUsage { name: "Claude".into(), session_percent: 30.0, weekly_percent: 45.0, max_percent: 92.0, cost_today: None },
+        ]);
+        assert_ne!(sig_low, sig_high, "severity changes should change signature");
+    }
 }
@@ -106,6 +106,7 @@ impl WeeklyIndicatorColors {
             ProviderId::Warp => (1, 217, 166, 255),       // Warp teal
             ProviderId::Ollama => (255, 255, 255, 255),    // Ollama white
             ProviderId::OpenRouter => (110, 65, 226, 255), // OpenRouter purple
+            ProviderId::Custom(_) => (158, 158, 158, 255), // Gray
         }
     }
 
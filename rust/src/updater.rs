@@ -9,6 +9,7 @@ use crate::settings::UpdateChannel;
 
 const GITHUB_REPO: &str = "Finesssee/Win-CodexBar";
 const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+const DOWNLOAD_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30 * 60);
 
 /// State of the update download process
 #[derive(Debug, Clone, PartialEq)]
@@ -91,9 +92,7 @@ pub async fn check_for_updates_with_channel(channel: UpdateChannel) -> Option<Up
         }
     };
 
-    let client = reqwest::Client::builder()
-        .user_agent("CodexBar")
-        .build()
+    let client = crate::core::http_client(std::time::Duration::from_secs(30))
         .ok()?;
 
     let response = client.get(&url).send().await.ok()?;
@@ -202,8 +201,10 @@ pub async fn download_update(
     let file_path = download_dir.join(&filename);
 
     // Start download
-    let client = reqwest::Client::builder()
-        .user_agent("CodexBar")
+    // The shared timeout suits API calls, not installer downloads
+    let client = crate::core::http_client_builder(DOWNLOAD_TIMEOUT)
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?
+        .timeout(DOWNLOAD_TIMEOUT)
         .build()
         .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
 